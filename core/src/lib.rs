@@ -0,0 +1,38 @@
+//! The renderer of `TrayRacer` without the GUI, to embed it in other tools.
+//!
+//! - [`scene`] loads, edits and saves scene files and bundles
+//! - [`raytracer`] traces rays through a scene, [`raytracer::Raytracer`] renders single pixels
+//! - [`raytracer::render::Render`] renders whole images on a background thread
+//!   or, on the web, step by step with [`raytracer::render::RenderThread::step`],
+//!   the pixels are passed to a [`raytracer::render::RenderOutput`] as they are done
+//! - [`raytracer::export::ExportOptions`] writes finished renders to PNG, JPEG and EXR files
+//! - [`profiler`] times scopes of loading and rendering while it is enabled
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use trayracer_core::raytracer::{export::ExportOptions, render::Render};
+//! use trayracer_core::scene::Scene;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let scene = Scene::open("scene.yaml")?;
+//!
+//! // nothing to show the pixels in while rendering
+//! let mut render = Render::new(Arc::new(()));
+//! render.render(&scene);
+//! if let Some(thread) = render.thread.take() {
+//!     thread.join();
+//! }
+//!
+//! ExportOptions::default().export(
+//!     &render.raw.lock(),
+//!     &render.post.read(),
+//!     &render.metadata(Some(&scene)),
+//!     "render.png".as_ref(),
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod profiler;
+pub mod raytracer;
+pub mod scene;
@@ -0,0 +1,91 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, PoisonError,
+    },
+    time::Duration,
+};
+use web_time::Instant;
+
+/// Scopes are only timed while the profiler is enabled, so it costs nothing otherwise
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TIMINGS: Mutex<BTreeMap<&'static str, Timing>> = Mutex::new(BTreeMap::new());
+
+/// Time spent in all calls of a scope since the profiler was reset
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timing {
+    pub calls: u64,
+    pub total: Duration,
+    /// Duration of the slowest call
+    pub max: Duration,
+    /// Duration of the latest call
+    pub last: Duration,
+}
+
+impl Timing {
+    /// Average duration of a call
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        u32::try_from(self.calls)
+            .ok()
+            .filter(|&calls| calls > 0)
+            .map_or(Duration::ZERO, |calls| self.total / calls)
+    }
+}
+
+/// Measures the time until it is dropped and adds it to the timing of its name
+#[must_use = "the scope is measured until it is dropped"]
+pub struct Scope {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            let elapsed = start.elapsed();
+            let mut timings = TIMINGS.lock().unwrap_or_else(PoisonError::into_inner);
+            let timing = timings.entry(self.name).or_default();
+            timing.calls += 1;
+            timing.total += elapsed;
+            timing.max = timing.max.max(elapsed);
+            timing.last = elapsed;
+            drop(timings);
+        }
+    }
+}
+
+/// Time the rest of the enclosing block, `let _scope = profiler::scope("name");`
+pub fn scope(name: &'static str) -> Scope {
+    Scope {
+        name,
+        start: ENABLED.load(Ordering::Relaxed).then(Instant::now),
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Timings of all scopes that have been measured, by name
+pub fn timings() -> Vec<(&'static str, Timing)> {
+    TIMINGS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|(&name, &timing)| (name, timing))
+        .collect()
+}
+
+/// Forget all measured timings
+pub fn reset() {
+    TIMINGS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+}
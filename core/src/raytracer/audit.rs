@@ -0,0 +1,278 @@
+use super::{integrator::Integrator, registry::Registry, Hit, Ray, Raytracer};
+use crate::scene::{Camera, Color, Material, Object, Scene, Settings, Skybox};
+use nalgebra::{Point3, Vector3};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::{
+    collections::BTreeMap,
+    f32::consts::PI,
+    fmt::{self, Display},
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Resolution of the audit renders
+const RESOLUTION: u32 = 64;
+
+/// Tolerance for rounding errors before a material counts as gaining energy
+const TOLERANCE: f32 = 0.01;
+
+/// Hemisphere directions per axis the furnace integrates over at a hit
+const HEMISPHERE_SAMPLES: u32 = 16;
+
+/// Energy a single material reflects in a white furnace
+#[derive(Debug, Clone)]
+pub struct MaterialAudit {
+    pub object: String,
+    pub material: String,
+    /// reflected energy per channel in a uniform white environment,
+    /// above 1 the material gains energy, below 1 it absorbs some
+    pub energy: Color,
+    pub warnings: Vec<String>,
+}
+
+impl MaterialAudit {
+    /// Energy gained (positive) or lost (negative) by the brightest channel in percent
+    #[must_use]
+    pub fn gain(&self) -> f32 {
+        // adding zero turns a rounded -0 into 0
+        ((self.energy.max() - 1.0) * 100.0).round() + 0.0
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    pub materials: Vec<MaterialAudit>,
+}
+
+impl AuditReport {
+    /// Run a furnace test on every material in the scene
+    #[must_use]
+    pub fn run(scene: &Scene) -> Self {
+        let materials = scene
+            .objects
+            .iter()
+            .flat_map(|o| o.materials.iter().map(move |m| (o, m)))
+            .map(|(object, material)| {
+                let energy = render_sphere(material);
+                let warnings = parameter_warnings(material);
+
+                let mut audit = MaterialAudit {
+                    object: object.name.clone(),
+                    material: material.name.clone(),
+                    energy,
+                    warnings,
+                };
+                if energy.max() > 1.0 + TOLERANCE {
+                    audit.warnings.push(format!(
+                        "reflects {:.0}% more energy than it receives",
+                        audit.gain()
+                    ));
+                }
+
+                audit
+            })
+            .collect();
+
+        Self { materials }
+    }
+
+    #[must_use]
+    pub fn warnings(&self) -> usize {
+        self.materials.iter().map(|m| m.warnings.len()).sum()
+    }
+}
+
+/// Environment-only integrator of the furnace test: no lights,
+/// the skybox is integrated over the hemisphere of every hit with the BSDF of the scene.
+///
+/// The BSDFs leave out the 1/π of a Lambertian reflector, so the integral is divided by π
+/// and a white Lambert surface reflects exactly the radiance of the environment.
+struct Furnace;
+
+impl Integrator for Furnace {
+    fn name(&self) -> &'static str {
+        "furnace"
+    }
+
+    fn shade(&self, raytracer: &Raytracer, ray: Ray, hit: &Hit, depth: u32) -> Color {
+        if depth >= raytracer.max_depth() {
+            return raytracer.skybox(ray.direction);
+        }
+
+        let surface = raytracer.surface(hit);
+        // the hemisphere faces the viewer, even on the back of a surface
+        let normal = hit.normal.normalize() * -hit.normal.dot(&ray.direction).signum();
+        let (tangent, bitangent) = basis(normal);
+
+        // cosine weighted and stratified, so every sample is weighted by 1 / cos
+        let mut reflected = Color::zeros();
+        for i in 0..HEMISPHERE_SAMPLES * HEMISPHERE_SAMPLES {
+            let u = ((i % HEMISPHERE_SAMPLES) as f32 + 0.5) / HEMISPHERE_SAMPLES as f32;
+            let v = ((i / HEMISPHERE_SAMPLES) as f32 + 0.5) / HEMISPHERE_SAMPLES as f32;
+            let (sin, cos) = (2.0 * PI * v).sin_cos();
+            let radius = u.sqrt();
+            let cos_theta = (1.0 - u).sqrt();
+            let direction = tangent * radius * cos + bitangent * radius * sin + normal * cos_theta;
+
+            let incoming = raytracer.shade(
+                Ray {
+                    origin: hit.point + normal * raytracer.delta(),
+                    direction,
+                },
+                depth + 1,
+            );
+            reflected += raytracer
+                .bsdf()
+                .eval(&surface, direction, ray.direction)
+                .component_mul(&incoming)
+                / cos_theta;
+        }
+        reflected /= (HEMISPHERE_SAMPLES * HEMISPHERE_SAMPLES) as f32;
+
+        // mirror reflection of the environment, weighted by the specular color
+        if hit
+            .material
+            .is_some_and(|m| m.illumination_model.reflection())
+        {
+            let reflection_ray = Ray {
+                origin: hit.point + normal * raytracer.delta(),
+                direction: Raytracer::reflect(ray.direction, normal),
+            };
+            reflected += surface
+                .specular
+                .component_mul(&raytracer.shade(reflection_ray, depth + 1));
+        }
+
+        // the dissolved part lets the environment behind the surface through
+        let dissolve = hit.material.and_then(|m| m.dissolve).unwrap_or(1.0);
+        reflected * dissolve + raytracer.skybox(ray.direction) * (1.0 - dissolve)
+    }
+}
+
+/// Two unit vectors perpendicular to the normal and to each other
+fn basis(normal: Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    let helper = if normal.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let tangent = normal.cross(&helper).normalize();
+
+    (tangent, normal.cross(&tangent))
+}
+
+/// Render a sphere with the given material in a uniform white environment without lights
+/// and average the radiance leaving it, 1 for a material that conserves energy exactly
+fn render_sphere(material: &Material) -> Color {
+    let camera = Camera {
+        name: None,
+        position: Point3::new(0.0, 0.0, 3.0),
+        look_at: Point3::origin(),
+        up: Vector3::y(),
+        fov: 45.0_f32.to_radians(),
+        resolution: (RESOLUTION, RESOLUTION),
+        ..Camera::default()
+    };
+
+    let scene = Scene {
+        path: PathBuf::new(),
+        objects: vec![Object::sphere("audit", Some(material.clone()))],
+        groups: vec![],
+        lights: vec![],
+        cameras: vec![camera],
+        active_camera: 0,
+        settings: Settings {
+            ambient_intensity: 0.0,
+            skybox: Skybox::Color(Color::from_element(1.0)),
+            anti_aliasing: false,
+            integrator: String::from(Furnace.name()),
+            ..Settings::default()
+        },
+        materials: BTreeMap::new(),
+        material_library: None,
+        library_materials: BTreeMap::new(),
+        solo_light: None,
+    };
+
+    let mut registry = Registry::default();
+    registry.register_integrator(Arc::new(Furnace));
+    let raytracer = Raytracer::new(scene, &registry, 1e-5, 2);
+
+    let (sum, pixels) = (0..RESOLUTION * RESOLUTION)
+        .into_par_iter()
+        .filter_map(|i| {
+            let x = ((i % RESOLUTION) as f32 / RESOLUTION as f32).mul_add(2.0, -1.0);
+            let y = ((i / RESOLUTION) as f32 / RESOLUTION as f32).mul_add(2.0, -1.0);
+            let ray = raytracer.scene.camera().ray(x, y);
+
+            // only count pixels covered by the sphere
+            raytracer
+                .raycast(ray)
+                .map(|hit| (Furnace.shade(&raytracer, ray, &hit, 0), 1))
+        })
+        .reduce(|| (Color::zeros(), 0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+    sum / pixels.max(1) as f32
+}
+
+/// Check material parameters against physically plausible bounds
+fn parameter_warnings(material: &Material) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let diffuse = material.diffuse_color.unwrap_or_default();
+    let specular = material.specular_color.unwrap_or_default();
+
+    if diffuse.max() > 1.0 {
+        warnings.push(format!("diffuse color exceeds 1 ({:.2})", diffuse.max()));
+    }
+
+    if specular.max() > 1.0 {
+        warnings.push(format!("specular color exceeds 1 ({:.2})", specular.max()));
+    }
+
+    if material.illumination_model.specular() && (diffuse + specular).max() > 1.0 {
+        warnings.push(format!(
+            "diffuse + specular exceeds 1 ({:.2})",
+            (diffuse + specular).max()
+        ));
+    }
+
+    if let Some(dissolve) = material.dissolve.filter(|d| !(0.0..=1.0).contains(d)) {
+        warnings.push(format!("dissolve outside of [0, 1] ({dissolve:.2})"));
+    }
+
+    if let Some(ior) = material.refraction_index.filter(|n| *n < 1.0) {
+        warnings.push(format!("refraction index below 1 ({ior:.2})"));
+    }
+
+    warnings
+}
+
+impl Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} materials, {} warnings",
+            self.materials.len(),
+            self.warnings()
+        )?;
+
+        for m in &self.materials {
+            write!(
+                f,
+                "\n{} / {}: energy r {:.2} g {:.2} b {:.2}, brightest channel {:+.0}%",
+                m.object,
+                m.material,
+                m.energy.x,
+                m.energy.y,
+                m.energy.z,
+                m.gain()
+            )?;
+
+            for warning in &m.warnings {
+                write!(f, "\n  ⚠ {warning}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use super::{registry::Registry, render, stats::Stats, Raytracer};
+use crate::scene::Scene;
+use log::info;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::{
+    fmt::{self, Display},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Renders a scene several times with a fixed seed and resolution
+/// to compare the performance of the raytracer across changes
+#[derive(Debug, Clone, Copy)]
+pub struct Benchmark {
+    pub resolution: (u32, u32),
+    pub iterations: u32,
+    pub seed: u64,
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Self {
+            resolution: (640, 360),
+            iterations: 3,
+            seed: 0,
+        }
+    }
+}
+
+/// Timings and counters of a single benchmark iteration
+#[derive(Debug, Clone, Copy)]
+pub struct BenchmarkRun {
+    /// time to clone the scene and create the raytracer
+    pub setup: Duration,
+    /// time to render all pixels
+    pub render: Duration,
+    pub rays: u64,
+    pub shadow_rays: u64,
+    pub bvh_nodes: u64,
+    pub triangle_tests: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchmarkReport {
+    pub resolution: (u32, u32),
+    pub samples: u32,
+    pub triangles: usize,
+    pub runs: Vec<BenchmarkRun>,
+}
+
+impl Benchmark {
+    #[must_use]
+    pub fn run(&self, scene: &Scene) -> BenchmarkReport {
+        let (width, height) = self.resolution;
+        let anti_aliasing = scene.settings.anti_aliasing;
+
+        let runs = (0..self.iterations)
+            .map(|i| {
+                info!("benchmark iteration {}/{}", i + 1, self.iterations);
+
+                let start = Instant::now();
+                let stats = Arc::new(Stats::default());
+                let raytracer = Raytracer::new(scene.clone(), &Registry::default(), 1e-5, 5)
+                    .with_seed(self.seed)
+                    .with_stats(stats.clone());
+                let setup = start.elapsed();
+
+                // the same blocks and pixels in parallel as a render in the app
+                let start = Instant::now();
+                let (_, blocks) = render::blocks((width, height));
+                blocks.into_par_iter().for_each(|region| {
+                    (0..region.width * region.height)
+                        .into_par_iter()
+                        .for_each(|i| {
+                            let x = i % region.width + region.x;
+                            let y = i / region.width + region.y;
+                            raytracer.render((x, y), (width, height), anti_aliasing);
+                        });
+                });
+                let render = start.elapsed();
+
+                BenchmarkRun {
+                    setup,
+                    render,
+                    rays: stats.rays(),
+                    shadow_rays: stats.shadow_rays(),
+                    bvh_nodes: stats.bvh_nodes(),
+                    triangle_tests: stats.triangle_tests(),
+                }
+            })
+            .collect();
+
+        BenchmarkReport {
+            resolution: self.resolution,
+            samples: if anti_aliasing {
+                scene.settings.samples
+            } else {
+                1
+            },
+            triangles: scene.objects.iter().map(|o| o.mesh.triangles.len()).sum(),
+            runs,
+        }
+    }
+}
+
+impl BenchmarkReport {
+    #[must_use]
+    pub fn average_render(&self) -> Duration {
+        self.runs
+            .iter()
+            .map(|r| r.render)
+            .sum::<Duration>()
+            .checked_div(self.runs.len() as u32)
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn rays_per_second(&self) -> f64 {
+        let rays = self.runs.iter().map(|r| r.rays).sum::<u64>();
+        let seconds = self
+            .runs
+            .iter()
+            .map(|r| r.render.as_secs_f64())
+            .sum::<f64>();
+
+        if seconds > 0.0 {
+            rays as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    #[must_use]
+    pub fn bvh_nodes_per_ray(&self) -> f64 {
+        self.per_ray(|r| r.bvh_nodes)
+    }
+
+    #[must_use]
+    pub fn triangle_tests_per_ray(&self) -> f64 {
+        self.per_ray(|r| r.triangle_tests)
+    }
+
+    fn per_ray(&self, count: impl Fn(&BenchmarkRun) -> u64) -> f64 {
+        let rays = self.runs.iter().map(|r| r.rays).sum::<u64>();
+        let count = self.runs.iter().map(count).sum::<u64>();
+
+        if rays > 0 {
+            count as f64 / rays as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "resolution: {}x{}, samples: {}, triangles: {}",
+            self.resolution.0, self.resolution.1, self.samples, self.triangles
+        )?;
+
+        for (i, run) in self.runs.iter().enumerate() {
+            writeln!(
+                f,
+                "run {}: setup {:.2?}, render {:.2?}, {} rays ({} shadow), {} BVH nodes, {} triangle tests",
+                i + 1,
+                run.setup,
+                run.render,
+                run.rays,
+                run.shadow_rays,
+                run.bvh_nodes,
+                run.triangle_tests
+            )?;
+        }
+
+        writeln!(f, "average render: {:.2?}", self.average_render())?;
+        writeln!(f, "rays/sec: {:.0}", self.rays_per_second())?;
+        writeln!(f, "BVH nodes/ray: {:.2}", self.bvh_nodes_per_ray())?;
+        write!(
+            f,
+            "triangle tests/ray: {:.2}",
+            self.triangle_tests_per_ray()
+        )
+    }
+}
@@ -0,0 +1,58 @@
+use super::{Hit, Raytracer};
+use crate::scene::Color;
+use nalgebra::Vector3;
+
+/// Colors of the material at a hit, with textures and the clay override applied
+#[derive(Debug, Clone)]
+pub struct Surface<'a> {
+    pub hit: &'a Hit<'a>,
+    pub diffuse: Color,
+    pub specular: Color,
+}
+
+/// Shading model of the materials, how much light is reflected towards the viewer
+pub trait Bsdf: Send + Sync {
+    /// Name in scene files and the render settings
+    fn name(&self) -> &'static str;
+
+    /// Light reflected towards the viewer per intensity of a light,
+    /// `light` points from the hit to the light and `view` is the direction of the incoming ray
+    fn eval(&self, surface: &Surface, light: Vector3<f32>, view: Vector3<f32>) -> Color;
+}
+
+/// Lambertian diffuse and a Phong highlight for specular materials
+pub struct Phong;
+
+impl Bsdf for Phong {
+    fn name(&self) -> &'static str {
+        "phong"
+    }
+
+    fn eval(&self, surface: &Surface, light: Vector3<f32>, view: Vector3<f32>) -> Color {
+        let hit = surface.hit;
+        let mut color = surface.diffuse * light.dot(&hit.normal).max(0.0);
+
+        if let Some(material) = hit.material.filter(|m| m.illumination_model.specular()) {
+            let specular = light
+                .dot(&Raytracer::reflect(-view, hit.normal))
+                .max(0.0)
+                .powf(material.specular_exponent.unwrap_or(1.0));
+            color += surface.specular * specular;
+        }
+
+        color
+    }
+}
+
+/// Only the diffuse part of the materials, without highlights
+pub struct Lambert;
+
+impl Bsdf for Lambert {
+    fn name(&self) -> &'static str {
+        "lambert"
+    }
+
+    fn eval(&self, surface: &Surface, light: Vector3<f32>, _view: Vector3<f32>) -> Color {
+        surface.diffuse * light.dot(&surface.hit.normal).max(0.0)
+    }
+}
@@ -0,0 +1,481 @@
+use super::{
+    post::{Post, ToneMapping},
+    render::{Aovs, DepthImage},
+};
+use crate::scene::Color;
+use anyhow::Context;
+use image::{
+    codecs::jpeg::JpegEncoder, DynamicImage, ImageFormat, Rgb32FImage, RgbImage, Rgba32FImage,
+};
+use rayon::{iter::ParallelIterator, slice::ParallelSlice};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// File format of an exported render
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    #[default]
+    Png8,
+    Png16,
+    Jpeg,
+    /// Raw colors without post processing
+    Exr,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 4] = [Self::Png8, Self::Png16, Self::Jpeg, Self::Exr];
+
+    /// Whether the format can store an alpha channel
+    #[must_use]
+    pub const fn has_alpha(self) -> bool {
+        !matches!(self, Self::Jpeg)
+    }
+
+    /// Whether the format keeps enough precision for the depth pass
+    #[must_use]
+    pub const fn has_depth(self) -> bool {
+        matches!(self, Self::Png16 | Self::Exr)
+    }
+
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Png8 | Self::Png16 => "png",
+            Self::Jpeg => "jpg",
+            Self::Exr => "exr",
+        }
+    }
+}
+
+/// Image of a render that gets exported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pass {
+    /// Colors with post processing
+    #[default]
+    Beauty,
+    /// Distance of the first hit along the view direction
+    Depth,
+    /// Ambient light and each light alone
+    Lights,
+}
+
+impl Pass {
+    pub const ALL: [Self; 3] = [Self::Beauty, Self::Depth, Self::Lights];
+}
+
+/// Depths mapped to black and white in an exported depth pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthRange {
+    pub near: f32,
+    pub far: f32,
+}
+
+impl DepthRange {
+    /// Range from the closest to the farthest hit, `None` if nothing was hit
+    #[must_use]
+    pub fn fit(depth: &DepthImage) -> Option<Self> {
+        depth.pixels().map(|p| p[0]).filter(|d| d.is_finite()).fold(
+            None,
+            |range: Option<Self>, d| {
+                Some(range.map_or(Self { near: d, far: d }, |r| Self {
+                    near: r.near.min(d),
+                    far: r.far.max(d),
+                }))
+            },
+        )
+    }
+
+    /// Depth in the range [0, 1], misses and unrendered pixels are far away
+    fn normalize(self, depth: f32) -> f32 {
+        if !depth.is_finite() {
+            return 1.0;
+        }
+        ((depth - self.near) / (self.far - self.near).max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+
+    /// Write the depth pass normalized to the range, as a grayscale 16 bit PNG or an EXR
+    ///
+    /// # Errors
+    ///
+    /// Fails if the render has no depth or the file can't be written
+    pub fn export(
+        self,
+        depth: &DepthImage,
+        format: ExportFormat,
+        metadata: &Metadata,
+        path: &Path,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            depth.pixels().any(|p| !p[0].is_nan()),
+            "The render has no depth, render it again"
+        );
+        let (width, height) = depth.dimensions();
+        let values = depth.pixels().map(|p| self.normalize(p[0]));
+
+        match format {
+            ExportFormat::Png16 => {
+                let data = values
+                    .flat_map(|d| ((d * f32::from(u16::MAX)) as u16).to_be_bytes())
+                    .collect::<Vec<_>>();
+                ExportOptions::write_png(
+                    path,
+                    (width, height),
+                    png::ColorType::Grayscale,
+                    png::BitDepth::Sixteen,
+                    &data,
+                    metadata,
+                )
+                .context("Failed to save PNG")?;
+            }
+            ExportFormat::Exr => {
+                let data = values.flat_map(|d| [d; 3]).collect();
+                let image = Rgb32FImage::from_raw(width, height, data)
+                    .context("Failed to create the image")?;
+                DynamicImage::ImageRgb32F(image)
+                    .save_with_format(path, ImageFormat::OpenExr)
+                    .context("Failed to save EXR")?;
+            }
+            ExportFormat::Png8 | ExportFormat::Jpeg => {
+                anyhow::bail!("{format:?} is too coarse for depth");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Settings a render was made with, written into PNG text chunks and JPEG comments
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    /// Scene file the render was made of
+    pub scene: Option<PathBuf>,
+    pub resolution: (u32, u32),
+    pub samples: u32,
+    /// Rendering time in milliseconds
+    pub time: u32,
+    /// Seed of the anti-aliasing jitter
+    pub seed: u64,
+}
+
+impl Metadata {
+    /// Keywords and their values, the software holds the version of the app
+    fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = vec![(
+            "Software",
+            format!("Trayracer {}", env!("CARGO_PKG_VERSION")),
+        )];
+        if let Some(scene) = &self.scene {
+            entries.push(("Source", scene.display().to_string()));
+        }
+        entries.extend([
+            (
+                "Resolution",
+                format!("{}x{}", self.resolution.0, self.resolution.1),
+            ),
+            ("Samples", self.samples.to_string()),
+            ("Render time", format!("{} ms", self.time)),
+            ("Seed", self.seed.to_string()),
+        ]);
+
+        entries
+    }
+
+    /// All entries as one line each
+    fn comment(&self) -> String {
+        self.entries()
+            .into_iter()
+            .map(|(key, value)| [key, ": ", &value, "\n"].concat())
+            .collect()
+    }
+}
+
+/// How a render is written to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Quality of JPEG files from 1 to 100
+    pub jpeg_quality: u8,
+    pub tone_mapping: ToneMapping,
+    /// Write the alpha channel of the render, if the format supports it
+    pub alpha: bool,
+    /// Write the object and material ID mattes next to the render
+    pub id_mattes: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            jpeg_quality: 90,
+            tone_mapping: ToneMapping::default(),
+            alpha: false,
+            id_mattes: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Write the raw render with the post processing and tone mapping applied,
+    /// PNG and JPEG files get the metadata of the render
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be encoded or written
+    pub fn export(
+        &self,
+        raw: &Rgba32FImage,
+        post: &Post,
+        metadata: &Metadata,
+        path: &Path,
+    ) -> anyhow::Result<()> {
+        let alpha = self.alpha && self.format.has_alpha();
+        let (channels, color) = if alpha {
+            (4, png::ColorType::Rgba)
+        } else {
+            (3, png::ColorType::Rgb)
+        };
+        let (width, height) = raw.dimensions();
+
+        match self.format {
+            ExportFormat::Exr => {
+                let image = DynamicImage::ImageRgba32F(raw.clone());
+                let image = if alpha {
+                    image
+                } else {
+                    DynamicImage::ImageRgb32F(image.to_rgb32f())
+                };
+                image
+                    .save_with_format(path, ImageFormat::OpenExr)
+                    .context("Failed to save EXR")?;
+            }
+            ExportFormat::Png8 => {
+                let data = self
+                    .graded(raw, post, channels)
+                    .into_iter()
+                    .map(|c| (c * 255.0) as u8)
+                    .collect::<Vec<_>>();
+                Self::write_png(
+                    path,
+                    (width, height),
+                    color,
+                    png::BitDepth::Eight,
+                    &data,
+                    metadata,
+                )
+                .context("Failed to save PNG")?;
+            }
+            ExportFormat::Png16 => {
+                // PNG stores 16 bit samples big endian
+                let data = self
+                    .graded(raw, post, channels)
+                    .into_iter()
+                    .flat_map(|c| ((c * f32::from(u16::MAX)) as u16).to_be_bytes())
+                    .collect::<Vec<_>>();
+                Self::write_png(
+                    path,
+                    (width, height),
+                    color,
+                    png::BitDepth::Sixteen,
+                    &data,
+                    metadata,
+                )
+                .context("Failed to save PNG")?;
+            }
+            ExportFormat::Jpeg => {
+                let pixels = self
+                    .graded(raw, post, channels)
+                    .into_iter()
+                    .map(|c| (c * 255.0) as u8)
+                    .collect();
+                let image = RgbImage::from_raw(width, height, pixels)
+                    .context("Failed to create the image")?;
+
+                let mut jpeg = Vec::new();
+                JpegEncoder::new_with_quality(&mut jpeg, self.jpeg_quality.clamp(1, 100))
+                    .encode_image(&image)
+                    .context("Failed to encode JPEG")?;
+
+                let mut file = BufWriter::new(File::create(path).context("Failed to create file")?);
+                file.write_all(&with_comment(&jpeg, &metadata.comment()))
+                    .and_then(|()| file.flush())
+                    .context("Failed to save JPEG")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the light passes as layers of one EXR file,
+    /// or processed like the render to numbered files next to the path
+    ///
+    /// # Errors
+    ///
+    /// Fails if the render has no light passes, the path has no file name
+    /// or a file can't be written
+    pub fn export_lights(
+        &self,
+        lights: &[(String, Rgba32FImage)],
+        post: &Post,
+        metadata: &Metadata,
+        path: &Path,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !lights.is_empty(),
+            "The render has no light passes, turn them on in the render settings and render again"
+        );
+
+        if self.format == ExportFormat::Exr {
+            return write_layers(lights, path).context("Failed to save EXR");
+        }
+
+        let stem = path
+            .file_stem()
+            .context("The path has no file name")?
+            .to_string_lossy();
+        for (i, (name, image)) in lights.iter().enumerate() {
+            let file = path.with_file_name(format!(
+                "{stem}_{}_{}.{}",
+                i + 1,
+                layer_name(name),
+                self.format.extension()
+            ));
+            self.export(image, post, metadata, &file)
+                .with_context(|| format!("Failed to save the pass {name}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the object and material ID mattes as 8 bit PNGs named after the path
+    ///
+    /// # Errors
+    ///
+    /// Fails if the render has no ID mattes, the path has no file name
+    /// or a file can't be written
+    pub fn export_mattes(aovs: &Aovs, metadata: &Metadata, path: &Path) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            aovs.is_rendered(),
+            "The render has no ID mattes, render it again"
+        );
+
+        let stem = path
+            .file_stem()
+            .context("The path has no file name")?
+            .to_string_lossy();
+        for (suffix, matte) in [("objects", &aovs.objects), ("materials", &aovs.materials)] {
+            Self::write_png(
+                &path.with_file_name(format!("{stem}_{suffix}.png")),
+                matte.dimensions(),
+                png::ColorType::Rgb,
+                png::BitDepth::Eight,
+                matte.as_raw(),
+                metadata,
+            )
+            .with_context(|| format!("Failed to save the {suffix} matte"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Graded colors in the range [0, 1], followed by the alpha if there are 4 channels
+    fn graded(self, raw: &Rgba32FImage, post: &Post, channels: usize) -> Vec<f32> {
+        raw.as_raw()
+            .par_chunks_exact(4)
+            .flat_map_iter(|c| {
+                let color = post.graded(Color::new(c[0], c[1], c[2]), self.tone_mapping);
+                // unrendered pixels are black and transparent
+                [color.x, color.y, color.z, c[3].clamp(0.0, 1.0)]
+                    .map(|c| if c.is_nan() { 0.0 } else { c })
+                    .into_iter()
+                    .take(channels)
+            })
+            .collect()
+    }
+
+    fn write_png(
+        path: &Path,
+        (width, height): (u32, u32),
+        color: png::ColorType,
+        depth: png::BitDepth,
+        data: &[u8],
+        metadata: &Metadata,
+    ) -> anyhow::Result<()> {
+        let file = BufWriter::new(File::create(path).context("Failed to create file")?);
+
+        let mut encoder = png::Encoder::new(file, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        for (key, value) in metadata.entries() {
+            encoder.add_text_chunk(key.to_string(), value)?;
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(data)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+}
+
+/// Light pass names reduced to characters that are safe in file and layer names
+fn layer_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Raw colors and alpha of the light passes, one layer each
+fn write_layers(lights: &[(String, Rgba32FImage)], path: &Path) -> anyhow::Result<()> {
+    use exr::prelude::{
+        Encoding, Image, ImageAttributes, IntegerBounds, Layer, LayerAttributes, SpecificChannels,
+        Vec2, WritableImage,
+    };
+
+    let size = lights
+        .first()
+        .map(|(_, image)| (image.width() as usize, image.height() as usize))
+        .unwrap_or_default();
+    let layers = lights
+        .iter()
+        .map(|(name, image)| {
+            Layer::new(
+                size,
+                LayerAttributes::named(layer_name(name).as_str()),
+                Encoding::FAST_LOSSLESS,
+                SpecificChannels::rgba(move |Vec2(x, y): Vec2<usize>| {
+                    image.get_pixel(x as u32, y as u32).0.into()
+                }),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Image::from_layers(
+        ImageAttributes::new(IntegerBounds::from_dimensions(size)),
+        layers,
+    )
+    .write()
+    .to_file(path)?;
+
+    Ok(())
+}
+
+/// JPEG data with a comment segment after the start of image marker
+fn with_comment(jpeg: &[u8], comment: &str) -> Vec<u8> {
+    // the segment length includes its own two bytes
+    let comment = &comment.as_bytes()[..comment.len().min(usize::from(u16::MAX) - 2)];
+    let length = (comment.len() + 2) as u16;
+    let (start, rest) = jpeg.split_at(jpeg.len().min(2));
+
+    let mut data = Vec::with_capacity(jpeg.len() + comment.len() + 4);
+    data.extend_from_slice(start);
+    data.extend_from_slice(&[0xFF, 0xFE]);
+    data.extend_from_slice(&length.to_be_bytes());
+    data.extend_from_slice(comment);
+    data.extend_from_slice(rest);
+    data
+}
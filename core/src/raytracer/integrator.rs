@@ -0,0 +1,156 @@
+use super::{Hit, LightPasses, Ray, Raytracer};
+use crate::scene::Color;
+
+/// Computes the light leaving a hit towards the viewer, e.g. by following reflections
+pub trait Integrator: Send + Sync {
+    /// Name in scene files and the render settings
+    fn name(&self) -> &'static str;
+
+    /// Color of a hit of the ray, further rays are shaded with [`Raytracer::shade`]
+    /// and one level deeper
+    fn shade(&self, raytracer: &Raytracer, ray: Ray, hit: &Hit, depth: u32) -> Color;
+
+    /// Color of a hit like [`Integrator::shade`], also added to the light passes
+    /// by the light it comes from. Integrators that do not split it add it to the ambient pass.
+    fn shade_passes(
+        &self,
+        raytracer: &Raytracer,
+        ray: Ray,
+        hit: &Hit,
+        depth: u32,
+        passes: &mut LightPasses,
+    ) -> Color {
+        let color = self.shade(raytracer, ray, hit, depth);
+        passes.ambient += color;
+        color
+    }
+}
+
+/// Ambient and direct light with shadows through transparent objects and mirror reflections
+pub struct Whitted;
+
+impl Integrator for Whitted {
+    fn name(&self) -> &'static str {
+        "whitted"
+    }
+
+    fn shade(&self, raytracer: &Raytracer, ray: Ray, hit: &Hit, depth: u32) -> Color {
+        Self::shade_split(raytracer, ray, hit, depth, None)
+    }
+
+    fn shade_passes(
+        &self,
+        raytracer: &Raytracer,
+        ray: Ray,
+        hit: &Hit,
+        depth: u32,
+        passes: &mut LightPasses,
+    ) -> Color {
+        Self::shade_split(raytracer, ray, hit, depth, Some(passes))
+    }
+}
+
+impl Whitted {
+    /// Shade a hit, adding the change of the color by each light to its pass if given,
+    /// so the passes add up to the color. Reflections go to the pass of the light they follow.
+    fn shade_split(
+        raytracer: &Raytracer,
+        ray: Ray,
+        hit: &Hit,
+        depth: u32,
+        mut passes: Option<&mut LightPasses>,
+    ) -> Color {
+        if depth >= raytracer.max_depth() {
+            let skybox = raytracer.skybox(ray.direction);
+            if let Some(passes) = passes {
+                passes.ambient += skybox;
+            }
+            return skybox;
+        }
+
+        let scene = raytracer.scene();
+        let surface = raytracer.surface(hit);
+
+        let mut color = scene.settings.ambient_color.component_mul(&surface.diffuse)
+            * scene.settings.ambient_intensity;
+        if let Some(passes) = passes.as_deref_mut() {
+            passes.ambient += color;
+        }
+
+        // either all lights linked to the object or one picked by its contribution,
+        // divided by its probability
+        let sampled = scene
+            .settings
+            .light_sampling
+            .then(|| raytracer.sample_light(hit))
+            .flatten();
+        let lights = scene
+            .active_lights()
+            .filter(|light| !scene.settings.light_sampling && light.illuminates(hit.id))
+            .map(|light| (light, 1.0))
+            .chain(sampled);
+
+        for (light, probability) in lights {
+            let before = color;
+            let light_direction = (light.position - hit.point).normalize();
+            let light_ray = Ray {
+                origin: hit.point + light_direction * raytracer.delta(),
+                direction: light_direction,
+            };
+
+            if let Some(stats) = raytracer.stats() {
+                stats.add_shadow_ray();
+            }
+
+            let light_transmission_color = raytracer
+                .raycast_transparent(light_ray)
+                .iter()
+                .last()
+                .map_or_else(
+                    || Color::from_element(1.0),
+                    |hit| {
+                        color.component_mul(
+                            &hit.material
+                                .and_then(|m| m.diffuse_color)
+                                .unwrap_or_else(|| Color::from_element(1.0)),
+                        ) * hit.material.and_then(|m| m.dissolve).unwrap_or(1.0)
+                    },
+                )
+                .component_mul(&light.color);
+
+            if light_transmission_color.norm() < 0.01 {
+                continue;
+            }
+
+            // diffuse and specular components
+            let light_intensity =
+                light.intensity / (light.position - hit.point).norm_squared() / probability;
+            color += raytracer
+                .bsdf()
+                .eval(&surface, light_direction, ray.direction)
+                .component_mul(&light_transmission_color)
+                * light_intensity;
+
+            // reflection
+            if hit
+                .material
+                .is_some_and(|m| m.illumination_model.reflection())
+            {
+                let reflection_ray = Ray {
+                    origin: hit.point + hit.normal * raytracer.delta(),
+                    direction: Raytracer::reflect(ray.direction, hit.normal),
+                };
+                color += color.component_mul(&raytracer.shade(reflection_ray, depth + 1));
+            }
+
+            if let Some(passes) = passes.as_deref_mut() {
+                let index = scene.lights.iter().position(|l| std::ptr::eq(l, light));
+                if let Some(pass) = index.and_then(|i| passes.lights.get_mut(i)) {
+                    *pass += color - before;
+                }
+            }
+        }
+
+        color
+    }
+}
@@ -0,0 +1,150 @@
+use crate::scene::Color;
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+
+/// 3D lookup table for color grading, loaded from a `.cube` file
+/// <https://kono.phpage.fr/images/a/a1/Adobe-cube-lut-specification-1.0.pdf>
+#[derive(Debug, Clone)]
+pub struct Lut {
+    pub path: PathBuf,
+    size: usize,
+    domain_min: Color,
+    domain_max: Color,
+    /// entries with red changing fastest, then green, then blue
+    table: Vec<Color>,
+}
+
+fn parse_color<'a>(mut values: impl Iterator<Item = &'a str>) -> anyhow::Result<Color> {
+    let mut next = || -> anyhow::Result<f32> {
+        values
+            .next()
+            .context("Missing color component")?
+            .parse()
+            .context("Invalid color component")
+    };
+
+    Ok(Color::new(next()?, next()?, next()?))
+}
+
+impl Lut {
+    /// Load a LUT from a `.cube` file
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be read or isn't a valid 3D LUT
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let s = std::fs::read_to_string(path.as_ref()).context(format!(
+            "Failed to read LUT from path: {}",
+            path.as_ref().display()
+        ))?;
+
+        Self::parse(&s)
+            .context(format!(
+                "Failed to parse LUT from path: {}",
+                path.as_ref().display()
+            ))
+            .map(|lut| Self {
+                path: path.as_ref().to_path_buf(),
+                ..lut
+            })
+    }
+
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        let mut size = None;
+        let mut domain_min = Color::zeros();
+        let mut domain_max = Color::from_element(1.0);
+        let mut table = Vec::new();
+
+        for (n, line) in s.lines().enumerate() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+
+            match words.next() {
+                None | Some("TITLE") => {}
+                Some("LUT_1D_SIZE") => anyhow::bail!("1D LUTs are not supported"),
+                Some("LUT_3D_SIZE") => {
+                    size = Some(
+                        words
+                            .next()
+                            .context("Missing LUT size")?
+                            .parse::<usize>()
+                            .context("Invalid LUT size")?,
+                    );
+                }
+                Some("DOMAIN_MIN") => domain_min = parse_color(words)?,
+                Some("DOMAIN_MAX") => domain_max = parse_color(words)?,
+                Some(word) => {
+                    table.push(
+                        parse_color(std::iter::once(word).chain(words))
+                            .context(format!("Invalid entry in line {}", n + 1))?,
+                    );
+                }
+            }
+        }
+
+        let size = size.context("Missing LUT_3D_SIZE")?;
+
+        anyhow::ensure!(size >= 2, "LUT size must be at least 2");
+        anyhow::ensure!(
+            table.len() == size * size * size,
+            "Expected {} entries but found {}",
+            size * size * size,
+            table.len()
+        );
+
+        Ok(Self {
+            path: PathBuf::new(),
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+
+    fn entry(&self, r: usize, g: usize, b: usize) -> Color {
+        self.table[r + g * self.size + b * self.size * self.size]
+    }
+
+    /// Map a color through the LUT using trilinear interpolation
+    #[must_use]
+    pub fn apply(&self, color: Color) -> Color {
+        let max = (self.size - 1) as f32;
+
+        // position in the table in the range [0, size - 1]
+        let position = (color - self.domain_min)
+            .component_div(&(self.domain_max - self.domain_min))
+            .map(|c| c.clamp(0.0, 1.0) * max);
+
+        let low = position.map(|c| c.floor() as usize);
+        let high = low.map(|c| (c + 1).min(self.size - 1));
+        let t = position - low.map(|c| c as f32);
+
+        let lerp = |a: Color, b: Color, t: f32| a + (b - a) * t;
+
+        let c00 = lerp(
+            self.entry(low.x, low.y, low.z),
+            self.entry(high.x, low.y, low.z),
+            t.x,
+        );
+        let c10 = lerp(
+            self.entry(low.x, high.y, low.z),
+            self.entry(high.x, high.y, low.z),
+            t.x,
+        );
+        let c01 = lerp(
+            self.entry(low.x, low.y, high.z),
+            self.entry(high.x, low.y, high.z),
+            t.x,
+        );
+        let c11 = lerp(
+            self.entry(low.x, high.y, high.z),
+            self.entry(high.x, high.y, high.z),
+            t.x,
+        );
+
+        lerp(lerp(c00, c10, t.y), lerp(c01, c11, t.y), t.z)
+    }
+}
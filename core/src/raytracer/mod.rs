@@ -0,0 +1,766 @@
+use crate::scene::{Color, Light, Material, Scene, ShadingMode, Skybox};
+use bsdf::{Bsdf, Surface};
+use image::RgbImage;
+use integrator::Integrator;
+use nalgebra::{Point3, Vector2, Vector3};
+use ordered_float::OrderedFloat;
+use post::luminance;
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use registry::Registry;
+use stats::Stats;
+use std::sync::{Arc, OnceLock};
+
+pub mod audit;
+pub mod benchmark;
+pub mod bsdf;
+pub mod export;
+pub mod integrator;
+pub mod lut;
+pub mod post;
+pub mod registry;
+pub mod render;
+pub mod stats;
+
+/// Smallest radius of the pixel filter in pixels, filter weights divide by it
+const MIN_FILTER_RADIUS: f32 = 0.01;
+
+/// Color of a pixel from its weighted samples, black if the weights cancel out
+fn filtered(sum: Color, weights: f32) -> Color {
+    if weights > f32::EPSILON {
+        // negative lobes of the filter can undershoot
+        (sum / weights).map(|c| c.max(0.0))
+    } else {
+        Color::zeros()
+    }
+}
+
+/// Hash of a seed and some values, every bit of them affects every bit of the hash.
+/// Chained `SplitMix64` finalizers, much cheaper than seeding a random number generator
+fn hash(seed: u64, values: &[u64]) -> u64 {
+    values.iter().fold(seed, |hash, value| {
+        let mut z = (hash ^ value).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
+}
+
+/// Number in the range [0, 1) from the upper 24 bits of a hash
+fn unit(hash: u64) -> f32 {
+    (hash >> 40) as f32 / (1_u64 << 24) as f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hit<'a> {
+    #[allow(dead_code)]
+    pub name: &'a str,
+    /// Id of the object that was hit, for the light linking and the ID matte
+    pub id: u64,
+    pub point: Point3<f32>,
+    pub normal: Vector3<f32>,
+    pub material: Option<&'a Material>,
+    pub uv: Vector2<f32>,
+    /// Interpolated vertex color, used where the material has no diffuse color
+    pub color: Option<Color>,
+}
+
+/// Flat color of an ID matte for an object id or a material name,
+/// the same in every render and session
+#[must_use]
+pub fn id_color(key: &[u8]) -> [u8; 3] {
+    // FNV-1a, unlike the std hasher it is stable across Rust versions
+    let hash = key.iter().fold(0x811c_9dc5_u32, |hash, b| {
+        (hash ^ u32::from(*b)).wrapping_mul(0x0100_0193)
+    });
+    let [r, g, b, _] = hash.to_be_bytes();
+    [r, g, b]
+}
+
+/// Data of the first hit of a pixel, written to the render passes next to the colors
+#[derive(Debug, Clone)]
+pub struct Aov {
+    /// Depth along the view direction, infinite where the skybox is seen
+    pub depth: f32,
+    /// Colors of the light passes, empty unless the scene renders them
+    pub lights: Vec<Color>,
+    /// ID matte colors of the object and its material, black where the skybox is seen
+    pub object: [u8; 3],
+    pub material: [u8; 3],
+}
+
+/// Colors of the ambient light with the skybox and of every light of the scene,
+/// split in the shading of the render so they add up to it
+#[derive(Debug, Clone, Default)]
+pub struct LightPasses {
+    pub ambient: Color,
+    /// Indexed like the lights of the scene
+    pub lights: Vec<Color>,
+}
+
+impl LightPasses {
+    /// Black passes for a number of lights
+    #[must_use]
+    pub fn new(lights: usize) -> Self {
+        Self {
+            ambient: Color::zeros(),
+            lights: vec![Color::zeros(); lights],
+        }
+    }
+
+    /// Add the passes of a sample scaled by its filter weight
+    fn add_weighted(&mut self, other: &Self, weight: f32) {
+        self.ambient += other.ambient * weight;
+        for (light, other) in self.lights.iter_mut().zip(&other.lights) {
+            *light += other * weight;
+        }
+    }
+
+    /// Passes of a sample scaled by its filter weight
+    fn weighted(mut self, weight: f32) -> Self {
+        self.ambient *= weight;
+        for light in &mut self.lights {
+            *light *= weight;
+        }
+        self
+    }
+
+    /// Passes of a pixel from its weighted samples, see [`filtered`]
+    fn filtered(&self, weights: f32) -> Self {
+        Self {
+            ambient: filtered(self.ambient, weights),
+            lights: self.lights.iter().map(|&l| filtered(l, weights)).collect(),
+        }
+    }
+}
+
+/// Sum of the weighted samples of a pixel, merged from the threads tracing them
+struct Samples<'a> {
+    color: Color,
+    weights: f32,
+    hits: u32,
+    passes: Option<LightPasses>,
+    /// The passes are taken from the first hit of the sample closest to the pixel center
+    center: (f32, Option<Hit<'a>>),
+}
+
+impl Samples<'_> {
+    const fn new(passes: Option<LightPasses>) -> Self {
+        Self {
+            color: Color::new(0.0, 0.0, 0.0),
+            weights: 0.0,
+            hits: 0,
+            passes,
+            center: (f32::INFINITY, None),
+        }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        if let (Some(passes), Some(other)) = (&mut self.passes, &other.passes) {
+            passes.add_weighted(other, 1.0);
+        }
+        Self {
+            color: self.color + other.color,
+            weights: self.weights + other.weights,
+            hits: self.hits + other.hits,
+            passes: self.passes,
+            center: if other.center.0 < self.center.0 {
+                other.center
+            } else {
+                self.center
+            },
+        }
+    }
+}
+
+impl Default for Aov {
+    fn default() -> Self {
+        Self {
+            depth: f32::INFINITY,
+            lights: Vec::new(),
+            object: [0; 3],
+            material: [0; 3],
+        }
+    }
+}
+
+pub struct Raytracer {
+    /// Scene is cloned into here for rendering
+    scene: Scene,
+    delta: f32,
+    /// max number of nested shade calls
+    max_depth: u32,
+    /// Seed for the anti-aliasing jitter, random if None
+    seed: Option<u64>,
+    /// Counters for rays, BVH traversal and triangle tests, only gathered if set
+    stats: Option<Arc<Stats>>,
+    /// Distance from the camera to the farthest vertex,
+    /// computed on first use by the depth shading mode
+    far: OnceLock<f32>,
+    /// Shades the hits of the full shading mode
+    integrator: Arc<dyn Integrator>,
+    /// Shading model of the materials
+    bsdf: Arc<dyn Bsdf>,
+}
+
+impl Raytracer {
+    const NO_MATERIAL_COLOR: Color = Color::new(0.9, 0.9, 0.9);
+
+    /// Raytracer with the integrator and BSDF selected in the settings of the scene
+    /// looked up in the registry, meshes small enough are baked into world space for it
+    #[must_use]
+    pub fn new(mut scene: Scene, registry: &Registry, delta: f32, max_depth: u32) -> Self {
+        let (integrator, bsdf) = registry.resolve(&scene.settings);
+        scene.bake();
+
+        Self {
+            scene,
+            delta,
+            max_depth,
+            seed: None,
+            stats: None,
+            far: OnceLock::new(),
+            integrator,
+            bsdf,
+        }
+    }
+
+    /// Use a fixed seed for the anti-aliasing jitter to get reproducible renders
+    #[must_use]
+    pub const fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Gather ray, BVH and triangle statistics into the given counters
+    #[must_use]
+    pub fn with_stats(mut self, stats: Arc<Stats>) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    pub const fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    /// Offset of secondary rays from the surface, to not hit it again
+    pub const fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// Maximum depth of nested shading
+    pub const fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    /// Counters to add shadow rays to, if statistics are gathered
+    pub fn stats(&self) -> Option<&Stats> {
+        self.stats.as_deref()
+    }
+
+    pub fn bsdf(&self) -> &dyn Bsdf {
+        self.bsdf.as_ref()
+    }
+
+    /// Closest hit of the ray with an object visible in the render
+    pub fn raycast(&self, ray: Ray) -> Option<Hit<'_>> {
+        self.raycast_within(ray, f32::INFINITY)
+    }
+
+    /// Closest hit before the ray parameter `t_max`
+    fn raycast_within(&self, ray: Ray, t_max: f32) -> Option<Hit<'_>> {
+        if let Some(stats) = &self.stats {
+            stats.add_ray();
+        }
+
+        self.scene
+            .objects
+            .iter()
+            .filter(|o| o.visible_in_render)
+            .filter_map(|o| {
+                o.intersect_clipped(
+                    ray,
+                    self.delta,
+                    t_max,
+                    self.stats.as_deref(),
+                    &self.scene.settings.section_planes,
+                )
+            })
+            .min_by_key(|h| OrderedFloat((h.point - ray.origin).norm()))
+            .map(|hit| {
+                // clay override: every hit is shaded with the neutral default material
+                if self.scene.settings.clay {
+                    Hit {
+                        material: None,
+                        color: None,
+                        ..hit
+                    }
+                } else {
+                    hit
+                }
+            })
+    }
+
+    #[must_use]
+    pub fn reflect(incoming: Vector3<f32>, normal: Vector3<f32>) -> Vector3<f32> {
+        incoming - 2.0 * incoming.dot(&normal) * normal
+    }
+
+    /// Color of the skybox seen in a direction
+    pub fn skybox(&self, direction: Vector3<f32>) -> Color {
+        match &self.scene.settings.skybox {
+            Skybox::Image { image, .. } => {
+                let direction = direction
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::y);
+
+                // spherical mapping
+                let x = ((0.5 + direction.z.atan2(direction.x) / (2.0 * std::f32::consts::PI))
+                    * image.width() as f32) as u32
+                    % image.width();
+                let y = ((0.5 - direction.y.asin() / std::f32::consts::PI) * image.height() as f32)
+                    as u32
+                    % image.height();
+
+                let pixel = image.get_pixel(x, y);
+
+                Color::new(
+                    f32::from(pixel[0]) / 255.0,
+                    f32::from(pixel[1]) / 255.0,
+                    f32::from(pixel[2]) / 255.0,
+                )
+            }
+            Skybox::Color(color) => *color,
+        }
+    }
+
+    fn texture(texture: &RgbImage, uv: Vector2<f32>) -> Color {
+        let x = (uv.x * texture.width() as f32) as u32 % texture.width();
+        let y = ((1.0 - uv.y) * texture.height() as f32) as u32 % texture.height();
+        let pixel = texture.get_pixel(x, y);
+        Color::new(
+            f32::from(pixel[0]) / 255.0,
+            f32::from(pixel[1]) / 255.0,
+            f32::from(pixel[2]) / 255.0,
+        )
+    }
+
+    /// Raycast and continue on hits if the material is transparent
+    pub fn raycast_transparent(&self, ray: Ray) -> Box<[Hit<'_>]> {
+        self.raycast_transparent_within(ray, f32::INFINITY)
+    }
+
+    /// Hits through transparent materials before the ray parameter `t_max`
+    fn raycast_transparent_within(&self, ray: Ray, t_max: f32) -> Box<[Hit<'_>]> {
+        let mut hits = Vec::<Hit>::new();
+        let mut ray = ray;
+        let mut t_max = t_max;
+
+        while let Some(hit) = self.raycast_within(ray, t_max) {
+            hits.push(hit.clone());
+
+            if let Some(material) = hit.material {
+                if material.illumination_model.transparency() {
+                    // hochwissenschaftliche Formel
+                    ray.origin += ray.direction * 0.05;
+                    t_max -= 0.05;
+                    continue;
+                }
+            }
+            break;
+        }
+
+        hits.into_boxed_slice()
+    }
+
+    /// Color seen along a ray, the hits are shaded by the integrator
+    pub fn shade(&self, ray: Ray, depth: u32) -> Color {
+        // hochwissnschaftliche Formel +- x
+        self.raycast_transparent(ray).last().map_or_else(
+            || self.skybox(ray.direction),
+            |hit| self.integrator.shade(self, ray, hit, depth),
+        )
+    }
+
+    /// Diffuse and specular color of the material at a hit
+    pub fn surface<'a>(&self, hit: &'a Hit<'a>) -> Surface<'a> {
+        let diffuse = hit
+            .material
+            .and_then(|m| m.diffuse_texture.as_ref())
+            .map(|map| Self::texture(map, hit.uv))
+            .or_else(|| hit.material.and_then(|m| m.diffuse_color))
+            .or(hit.color)
+            .unwrap_or(Self::NO_MATERIAL_COLOR);
+
+        let specular = hit
+            .material
+            .and_then(|m| m.specular_color)
+            .map_or(Self::NO_MATERIAL_COLOR, Color::from);
+
+        Surface {
+            hit,
+            diffuse,
+            specular,
+        }
+    }
+
+    /// Pick one of the active lights illuminating a hit,
+    /// weighted by its power over the squared distance.
+    /// Returns the light and the probability it was picked with, None if no light reaches it
+    pub fn sample_light(&self, hit: &Hit) -> Option<(&Light, f32)> {
+        let point = hit.point;
+        let weight = |light: &Light| {
+            if !light.illuminates(hit.id) {
+                return 0.0;
+            }
+            luminance(light.color) * light.intensity
+                / (light.position - point).norm_squared().max(self.delta)
+        };
+        let total = self.scene.active_lights().map(weight).sum::<f32>();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut target = self.random(point) * total;
+        let mut picked = None;
+        for light in self.scene.active_lights() {
+            let weight = weight(light);
+            if weight <= 0.0 {
+                continue;
+            }
+            picked = Some((light, weight / total));
+            if target < weight {
+                break;
+            }
+            target -= weight;
+        }
+        // rounding may leave the target just above the last weight
+        picked
+    }
+
+    /// Random number in the range [0, 1) for a point,
+    /// deterministic per point if a seed is set
+    fn random(&self, point: Point3<f32>) -> f32 {
+        self.seed.map_or_else(rand::random, |seed| {
+            let [x, y, z] = [point.x, point.y, point.z].map(|c| u64::from(c.to_bits()));
+            unit(hash(seed, &[x, y, z]))
+        })
+    }
+
+    fn far(&self) -> f32 {
+        *self.far.get_or_init(|| {
+            let camera = self.scene.camera().position;
+
+            self.scene
+                .objects
+                .par_iter()
+                .filter(|o| o.visible_in_render)
+                .flat_map_iter(|o| {
+                    let transform = o.transform();
+                    o.mesh.triangles.iter().flat_map(move |t| {
+                        [t.a, t.b, t.c].map(|p| (transform.transform_point(&p) - camera).norm())
+                    })
+                })
+                .reduce(|| 0.0, f32::max)
+                .max(f32::EPSILON)
+        })
+    }
+
+    /// Shade a camera ray according to the shading mode of the scene,
+    /// the debug modes only look at the first hit.
+    /// The color is split into the light passes if given, the debug modes go to the ambient one.
+    /// Returns the color and the first hit, none where the skybox is seen.
+    fn trace(&self, ray: Ray, passes: Option<&mut LightPasses>) -> (Color, Option<Hit<'_>>) {
+        // camera rays start at the near plane and skip everything beyond the far plane,
+        // the depth check is only needed for the looser bound of panoramas
+        let camera = self.scene.camera();
+        let ray = camera.clip_near(ray);
+        let far = camera.far_distance(ray);
+        let visible = |hit: &Hit| camera.depth(hit.point) <= camera.far;
+
+        let (color, hit) = match self.scene.settings.shading_mode {
+            ShadingMode::Full => {
+                let hits = self.raycast_transparent_within(ray, far);
+                let hits = &hits[..hits.iter().take_while(|hit| visible(hit)).count()];
+                if let Some(hit) = hits.last() {
+                    let color = passes.map_or_else(
+                        || self.integrator.shade(self, ray, hit, 0),
+                        |passes| self.integrator.shade_passes(self, ray, hit, 0, passes),
+                    );
+                    return (color, hits.first().cloned());
+                }
+                (self.skybox(ray.direction), None)
+            }
+            ShadingMode::Normal => self.raycast_within(ray, far).filter(visible).map_or_else(
+                || (Color::zeros(), None),
+                |hit| {
+                    let normal = hit
+                        .normal
+                        .try_normalize(f32::EPSILON)
+                        .map_or_else(Color::zeros, |n| n.map(|c| c.mul_add(0.5, 0.5)));
+                    (normal, Some(hit))
+                },
+            ),
+            ShadingMode::Uv => self.raycast_within(ray, far).filter(visible).map_or_else(
+                || (Color::zeros(), None),
+                |hit| {
+                    let uv = Color::new(hit.uv.x.rem_euclid(1.0), hit.uv.y.rem_euclid(1.0), 0.0);
+                    (uv, Some(hit))
+                },
+            ),
+            ShadingMode::Depth => {
+                self.raycast_within(ray, far).filter(visible).map_or_else(
+                    || (Color::zeros(), None),
+                    |hit| {
+                        // near is white, far is black
+                        let depth =
+                            1.0 - ((hit.point - camera.position).norm() / self.far()).min(1.0);
+                        (Color::from_element(depth), Some(hit))
+                    },
+                )
+            }
+        };
+
+        // the skybox and the debug modes are not lit
+        if let Some(passes) = passes {
+            passes.ambient += color;
+        }
+        (color, hit)
+    }
+
+    /// Black light passes to split the samples of a pixel into, None unless the scene renders them
+    fn light_passes(&self) -> Option<LightPasses> {
+        self.scene
+            .settings
+            .light_passes
+            .then(|| LightPasses::new(self.scene.lights.len()))
+    }
+
+    /// Passes of the first hit of a pixel, the defaults where the skybox is seen,
+    /// with the light passes of the pixel in the order of [`Scene::light_passes`]
+    fn aov(&self, hit: Option<&Hit>, passes: Option<LightPasses>) -> Aov {
+        let camera = self.scene.camera();
+        let lights = passes.map_or_else(Vec::new, |passes| {
+            std::iter::once(passes.ambient)
+                .chain(self.scene.active_light_indices().map(|i| passes.lights[i]))
+                .collect()
+        });
+
+        let aov = hit.map_or_else(Aov::default, |hit| Aov {
+            depth: camera.depth(hit.point),
+            // instances and objects of the same name get their own matte
+            object: id_color(&hit.id.to_le_bytes()),
+            material: hit.material.map_or([0; 3], |m| id_color(m.name.as_bytes())),
+            ..Aov::default()
+        });
+        Aov { lights, ..aov }
+    }
+
+    /// Alpha of a pixel from the share of its samples that hit an object,
+    /// opaque unless the scene records a transparent background
+    fn alpha(&self, hits: u32, samples: u32) -> f32 {
+        if self.scene.settings.transparent_background {
+            hits as f32 / samples.max(1) as f32
+        } else {
+            1.0
+        }
+    }
+
+    /// Random offsets in the range [0, 1) for an anti-aliasing sample,
+    /// deterministic per pixel and sample if a seed is set
+    fn jitter(&self, (x, y): (u32, u32), sample: u32) -> (f32, f32) {
+        self.seed.map_or_else(
+            || (rand::random(), rand::random()),
+            |seed| {
+                let hash = hash(seed, &[x, y, sample].map(u64::from));
+                // the lower bits are independent of the upper ones
+                (unit(hash), unit(hash << 24))
+            },
+        )
+    }
+
+    /// Sample a pixel with uniformly jittered rays until the standard error
+    /// of the luminance drops below the noise threshold of the scene.
+    /// Returns the color, the alpha and the number of samples taken.
+    fn render_adaptive(
+        &self,
+        (x, y): (u32, u32),
+        (width, height): (u32, u32),
+    ) -> ((Color, f32, u32), Aov) {
+        const MIN_SAMPLES: u32 = 4;
+
+        let max_samples = self.scene.settings.samples.max(1);
+        let threshold = self.scene.settings.noise_threshold;
+
+        let filter = self.scene.settings.pixel_filter;
+        let radius = self.scene.settings.filter_radius.max(MIN_FILTER_RADIUS);
+
+        let mut sum = Color::zeros();
+        let mut weights = 0.0;
+        let mut hits = 0;
+        let mut sum_luminance = 0.0;
+        let mut sum_luminance_sq = 0.0;
+        let mut passes = self.light_passes();
+        // the passes are taken from the first hit of the sample closest to the pixel center
+        let mut center = (f32::INFINITY, None);
+
+        for i in 0..max_samples {
+            let (rx, ry) = self.jitter((x, y), i);
+            let offset = (
+                rx.mul_add(2.0, -1.0) * radius,
+                ry.mul_add(2.0, -1.0) * radius,
+            );
+            let weight = filter.weight(offset, radius);
+
+            let mut sample_passes = self.light_passes();
+            let (color, hit) = self.trace(
+                self.pixel_ray((x, y), offset, (width, height)),
+                sample_passes.as_mut(),
+            );
+            if let (Some(passes), Some(sample_passes)) = (&mut passes, &sample_passes) {
+                passes.add_weighted(sample_passes, weight);
+            }
+            let l = luminance(color);
+            sum += color * weight;
+            weights += weight;
+            hits += u32::from(hit.is_some());
+            sum_luminance += l;
+            sum_luminance_sq += l * l;
+
+            let distance = offset.0.hypot(offset.1);
+            if distance < center.0 {
+                center = (distance, hit);
+            }
+
+            let n = (i + 1) as f32;
+            if i + 1 >= MIN_SAMPLES {
+                let mean = sum_luminance / n;
+                let variance = mean.mul_add(-mean, sum_luminance_sq / n).max(0.0);
+
+                if (variance / n).sqrt() <= threshold * mean.max(0.01) {
+                    return (
+                        (filtered(sum, weights), self.alpha(hits, i + 1), i + 1),
+                        self.aov(center.1.as_ref(), passes.map(|p| p.filtered(weights))),
+                    );
+                }
+            }
+        }
+
+        (
+            (
+                filtered(sum, weights),
+                self.alpha(hits, max_samples),
+                max_samples,
+            ),
+            self.aov(center.1.as_ref(), passes.map(|p| p.filtered(weights))),
+        )
+    }
+
+    /// Ray through a pixel, offset in pixels from its center
+    fn pixel_ray(
+        &self,
+        (x, y): (u32, u32),
+        (dx, dy): (f32, f32),
+        (width, height): (u32, u32),
+    ) -> Ray {
+        self.scene.camera().image_ray(
+            (x as f32 + 0.5 + dx) / width as f32,
+            (y as f32 + 0.5 + dy) / height as f32,
+        )
+    }
+
+    /// Render a pixel at the given coordinates.
+    /// x and y are in the range 0..width and 0..height
+    /// where (0, 0) is the top left corner.
+    /// Anti-aliasing samples multiple rays per pixel, enhanced with stratified sampling.
+    /// The samples are spread over the radius of the pixel filter and weighted by it.
+    /// Returns the color, the alpha and the number of samples taken.
+    pub fn render(
+        &self,
+        (x, y): (u32, u32),
+        (width, height): (u32, u32),
+        anti_aliasing: bool,
+    ) -> (Color, f32, u32) {
+        self.render_with_aov((x, y), (width, height), anti_aliasing)
+            .0
+    }
+
+    /// Render a pixel along with its passes, taken from the first hit of the sample
+    /// closest to the pixel center instead of casting another ray.
+    /// The light passes are split from the shading of the same samples.
+    pub fn render_with_aov(
+        &self,
+        (x, y): (u32, u32),
+        (width, height): (u32, u32),
+        anti_aliasing: bool,
+    ) -> ((Color, f32, u32), Aov) {
+        if anti_aliasing && self.scene.settings.adaptive_sampling {
+            self.render_adaptive((x, y), (width, height))
+        } else if anti_aliasing {
+            let samples_per_pixel = self.scene.settings.samples;
+            let sqrt_samples = ((samples_per_pixel as f32).sqrt() as u32).max(1);
+            let filter = self.scene.settings.pixel_filter;
+            let radius = self.scene.settings.filter_radius.max(MIN_FILTER_RADIUS);
+
+            let samples = (0..samples_per_pixel)
+                .into_par_iter()
+                .map(|i| {
+                    // samples beyond the last full square of strata start over with new jitter
+                    let xi = i % sqrt_samples;
+                    let yi = i / sqrt_samples % sqrt_samples;
+                    let (rx, ry) = self.jitter((x, y), i);
+                    let offset = (
+                        ((xi as f32 + rx) / sqrt_samples as f32).mul_add(2.0, -1.0) * radius,
+                        ((yi as f32 + ry) / sqrt_samples as f32).mul_add(2.0, -1.0) * radius,
+                    );
+                    let weight = filter.weight(offset, radius);
+
+                    let mut passes = self.light_passes();
+                    let (color, hit) = self.trace(
+                        self.pixel_ray((x, y), offset, (width, height)),
+                        passes.as_mut(),
+                    );
+                    Samples {
+                        color: color * weight,
+                        weights: weight,
+                        hits: u32::from(hit.is_some()),
+                        passes: passes.map(|p| p.weighted(weight)),
+                        center: (offset.0.hypot(offset.1), hit),
+                    }
+                })
+                .reduce(|| Samples::new(self.light_passes()), Samples::merge);
+            let Samples {
+                color,
+                weights,
+                hits,
+                passes,
+                center,
+            } = samples;
+
+            (
+                (
+                    filtered(color, weights),
+                    self.alpha(hits, samples_per_pixel),
+                    samples_per_pixel,
+                ),
+                self.aov(center.1.as_ref(), passes.map(|p| p.filtered(weights))),
+            )
+        } else {
+            let ray = self
+                .scene
+                .camera()
+                .image_ray(x as f32 / width as f32, y as f32 / height as f32);
+            let mut passes = self.light_passes();
+            let (color, hit) = self.trace(ray, passes.as_mut());
+            (
+                (color, self.alpha(u32::from(hit.is_some()), 1), 1),
+                self.aov(hit.as_ref(), passes),
+            )
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use super::lut::Lut;
+use crate::scene::Color;
+use image::{RgbImage, Rgba32FImage};
+use rayon::{iter::ParallelIterator, slice::ParallelSlice};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Number of bins of the histogram over the range [0, 1]
+pub const HISTOGRAM_BINS: usize = 64;
+
+/// Luminance the log-average of a render is mapped to by the auto exposure
+const MIDDLE_GRAY: f32 = 0.18;
+/// Keeps the logarithm of black pixels finite
+const LOG_DELTA: f32 = 1e-4;
+/// Range of the exposure chosen by the auto exposure, in stops
+const AUTO_EXPOSURE_RANGE: std::ops::RangeInclusive<f32> = -5.0..=5.0;
+
+/// Relative luminance of a linear color (Rec. 709)
+#[must_use]
+pub fn luminance(color: Color) -> f32 {
+    color.dot(&Color::new(0.2126, 0.7152, 0.0722))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clipping {
+    /// at least one channel is above 1
+    Blown,
+    /// the luminance is below a single 8-bit step
+    Crushed,
+}
+
+/// Histogram of a render after exposure and white balance,
+/// values outside of [0, 1] are counted in the first or last bin
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    pub luminance: [u32; HISTOGRAM_BINS],
+    pub red: [u32; HISTOGRAM_BINS],
+    pub green: [u32; HISTOGRAM_BINS],
+    pub blue: [u32; HISTOGRAM_BINS],
+    pub blown: u32,
+    pub crushed: u32,
+}
+
+impl Histogram {
+    const fn new() -> Self {
+        Self {
+            luminance: [0; HISTOGRAM_BINS],
+            red: [0; HISTOGRAM_BINS],
+            green: [0; HISTOGRAM_BINS],
+            blue: [0; HISTOGRAM_BINS],
+            blown: 0,
+            crushed: 0,
+        }
+    }
+
+    fn bin(value: f32) -> usize {
+        (value.clamp(0.0, 1.0) * (HISTOGRAM_BINS - 1) as f32).round() as usize
+    }
+
+    fn add(&mut self, color: Color, clipping: Option<Clipping>) {
+        self.luminance[Self::bin(luminance(color))] += 1;
+        self.red[Self::bin(color.x)] += 1;
+        self.green[Self::bin(color.y)] += 1;
+        self.blue[Self::bin(color.z)] += 1;
+
+        match clipping {
+            Some(Clipping::Blown) => self.blown += 1,
+            Some(Clipping::Crushed) => self.crushed += 1,
+            None => {}
+        }
+    }
+
+    fn merge(mut self, other: &Self) -> Self {
+        for i in 0..HISTOGRAM_BINS {
+            self.luminance[i] += other.luminance[i];
+            self.red[i] += other.red[i];
+            self.green[i] += other.green[i];
+            self.blue[i] += other.blue[i];
+        }
+        self.blown += other.blown;
+        self.crushed += other.crushed;
+        self
+    }
+
+    /// Highest count of all bins
+    #[must_use]
+    pub fn max(&self) -> u32 {
+        [&self.luminance, &self.red, &self.green, &self.blue]
+            .into_iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Curve compressing colors above white at export, the display clips them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToneMapping {
+    #[default]
+    Clip,
+    Reinhard,
+    /// Filmic curve, fitted to the ACES reference transform
+    Aces,
+}
+
+impl ToneMapping {
+    pub const ALL: [Self; 3] = [Self::Clip, Self::Reinhard, Self::Aces];
+
+    /// Map a linear color into the range [0, 1]
+    #[must_use]
+    pub fn map(self, color: Color) -> Color {
+        match self {
+            Self::Clip => color,
+            Self::Reinhard => color.map(|c| c / (1.0 + c)),
+            Self::Aces => color.map(|c| {
+                (c * 2.51f32.mul_add(c, 0.03)) / c.mul_add(2.43f32.mul_add(c, 0.59), 0.14)
+            }),
+        }
+        .map(|c| c.clamp(0.0, 1.0))
+    }
+}
+
+/// Post processing applied to the raw render
+/// before it is displayed or exported
+#[derive(Debug, Clone, Default)]
+pub struct Post {
+    /// Exposure correction in stops (EV)
+    pub exposure: f32,
+    /// Choose the exposure from the brightness of the render once it has finished
+    pub auto_exposure: bool,
+    /// White balance from cool (-1) to warm (1)
+    pub temperature: f32,
+    /// White balance from green (-1) to magenta (1)
+    pub tint: f32,
+    /// 3D LUT for color grading
+    pub lut: Option<Arc<Lut>>,
+}
+
+impl Post {
+    /// Per channel multipliers of exposure and white balance
+    #[must_use]
+    pub fn gain(&self) -> Color {
+        Color::new(
+            self.temperature.mul_add(0.3, 1.0),
+            self.tint.mul_add(-0.3, 1.0),
+            self.temperature.mul_add(-0.3, 1.0),
+        ) * self.exposure.exp2()
+    }
+
+    /// Whether a raw color is blown out or crushed after exposure and white balance
+    #[must_use]
+    pub fn clipping(&self, color: Color) -> Option<Clipping> {
+        let color = color.component_mul(&self.gain());
+
+        if color.max() > 1.0 {
+            Some(Clipping::Blown)
+        } else if luminance(color) < 0.5 / 255.0 {
+            Some(Clipping::Crushed)
+        } else {
+            None
+        }
+    }
+
+    /// Set the exposure that maps the log-average luminance of the raw render to middle gray,
+    /// unrendered (NaN) pixels are skipped
+    pub fn expose(&mut self, raw: &Rgba32FImage) {
+        let (sum, count) = raw
+            .as_raw()
+            .par_chunks_exact(4)
+            .filter(|c| !c[0].is_nan())
+            .map(|c| {
+                let luminance = luminance(Color::new(c[0], c[1], c[2])).max(0.0);
+                (f64::from((luminance + LOG_DELTA).ln()), 1_u64)
+            })
+            .reduce(|| (0.0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+        if count > 0 {
+            let average = (sum / count as f64).exp() as f32;
+            self.exposure = (MIDDLE_GRAY / average)
+                .log2()
+                .clamp(*AUTO_EXPOSURE_RANGE.start(), *AUTO_EXPOSURE_RANGE.end());
+        }
+    }
+
+    /// Compute the histogram of the raw render, skipping unrendered (NaN) pixels
+    pub fn histogram(&self, raw: &Rgba32FImage) -> Histogram {
+        let gain = self.gain();
+
+        raw.as_raw()
+            .par_chunks((raw.width() as usize * 4).max(4))
+            .map(|row| {
+                let mut histogram = Histogram::new();
+                row.chunks_exact(4)
+                    .map(|c| Color::new(c[0], c[1], c[2]))
+                    .filter(|c| !c.x.is_nan())
+                    .for_each(|c| histogram.add(c.component_mul(&gain), self.clipping(c)));
+                histogram
+            })
+            .reduce(Histogram::new, |a, b| a.merge(&b))
+    }
+
+    /// Map a raw color to a color in the range [0, 1]
+    #[must_use]
+    pub fn graded(&self, color: Color, tone_mapping: ToneMapping) -> Color {
+        let color = tone_mapping.map(color.component_mul(&self.gain()));
+        self.lut.as_ref().map_or(color, |lut| lut.apply(color))
+    }
+
+    /// Map a raw color to an 8-bit display color
+    #[must_use]
+    pub fn apply(&self, color: Color) -> [u8; 3] {
+        let color = self.graded(color, ToneMapping::Clip);
+
+        [
+            (color.x * 255.0) as u8,
+            (color.y * 255.0) as u8,
+            (color.z * 255.0) as u8,
+        ]
+    }
+
+    /// Apply the post processing to a whole raw render
+    #[must_use]
+    pub fn apply_image(&self, raw: &Rgba32FImage) -> RgbImage {
+        let (width, height) = raw.dimensions();
+
+        let pixels = raw
+            .as_raw()
+            .par_chunks_exact(4)
+            .flat_map_iter(|c| self.apply(Color::new(c[0], c[1], c[2])))
+            .collect::<Vec<u8>>();
+
+        RgbImage::from_raw(width, height, pixels).unwrap_or_else(|| RgbImage::new(width, height))
+    }
+}
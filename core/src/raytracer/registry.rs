@@ -0,0 +1,72 @@
+use super::{
+    bsdf::{Bsdf, Lambert, Phong},
+    integrator::{Integrator, Whitted},
+};
+use crate::scene::Settings;
+use log::warn;
+use std::sync::Arc;
+
+/// Integrators and BSDFs the render settings can choose from by name,
+/// the built-in ones first and then those registered by the application
+#[derive(Clone)]
+pub struct Registry {
+    integrators: Vec<Arc<dyn Integrator>>,
+    bsdfs: Vec<Arc<dyn Bsdf>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            integrators: vec![Arc::new(Whitted)],
+            bsdfs: vec![Arc::new(Phong), Arc::new(Lambert)],
+        }
+    }
+}
+
+impl Registry {
+    /// Add an integrator, replacing a registered one with the same name
+    pub fn register_integrator(&mut self, integrator: Arc<dyn Integrator>) {
+        self.integrators.retain(|i| i.name() != integrator.name());
+        self.integrators.push(integrator);
+    }
+
+    /// Add a BSDF, replacing a registered one with the same name
+    pub fn register_bsdf(&mut self, bsdf: Arc<dyn Bsdf>) {
+        self.bsdfs.retain(|b| b.name() != bsdf.name());
+        self.bsdfs.push(bsdf);
+    }
+
+    pub fn integrators(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.integrators.iter().map(|i| i.name())
+    }
+
+    pub fn bsdfs(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.bsdfs.iter().map(|b| b.name())
+    }
+
+    /// Integrator and BSDF selected in the settings,
+    /// the built-in ones if they aren't registered
+    #[must_use]
+    pub fn resolve(&self, settings: &Settings) -> (Arc<dyn Integrator>, Arc<dyn Bsdf>) {
+        let integrator = self
+            .integrators
+            .iter()
+            .find(|i| i.name() == settings.integrator)
+            .cloned()
+            .unwrap_or_else(|| {
+                warn!("Integrator {} is not registered", settings.integrator);
+                Arc::new(Whitted)
+            });
+        let bsdf = self
+            .bsdfs
+            .iter()
+            .find(|b| b.name() == settings.bsdf)
+            .cloned()
+            .unwrap_or_else(|| {
+                warn!("BSDF {} is not registered", settings.bsdf);
+                Arc::new(Phong)
+            });
+
+        (integrator, bsdf)
+    }
+}
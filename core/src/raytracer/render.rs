@@ -0,0 +1,695 @@
+use super::{export::Metadata, post::Post, registry::Registry, stats::Stats};
+use crate::{
+    profiler,
+    raytracer::{Aov, Raytracer},
+    scene::{Color, Scene},
+};
+use image::{ImageBuffer, Luma, RgbImage, Rgba32FImage};
+use log::{debug, info, warn};
+use parking_lot::{Mutex, RwLock};
+#[cfg(not(target_arch = "wasm32"))]
+use rayon::iter::IntoParallelRefIterator;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread::JoinHandle;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use web_time::Instant;
+
+/// Number of samples taken per pixel
+pub type SampleImage = ImageBuffer<Luma<u32>, Vec<u32>>;
+
+/// Depth of the first hit of each pixel
+pub type DepthImage = ImageBuffer<Luma<f32>, Vec<f32>>;
+
+/// Passes of a render next to the colors, for compositing
+#[derive(Debug, Clone, Default)]
+pub struct Aovs {
+    /// NaN for unrendered pixels
+    pub depth: DepthImage,
+    /// Names and raw colors of the ambient light and each light alone,
+    /// with the alpha of the render
+    pub lights: Vec<(String, Rgba32FImage)>,
+    /// ID mattes with a flat color per object and per material
+    pub objects: RgbImage,
+    pub materials: RgbImage,
+}
+
+impl Aovs {
+    fn new(width: u32, height: u32, lights: Vec<String>) -> Self {
+        Self {
+            depth: DepthImage::from_pixel(width, height, Luma([f32::NAN])),
+            lights: lights
+                .into_iter()
+                .map(|name| {
+                    let image = Rgba32FImage::from_pixel(width, height, image::Rgba([f32::NAN; 4]));
+                    (name, image)
+                })
+                .collect(),
+            objects: RgbImage::new(width, height),
+            materials: RgbImage::new(width, height),
+        }
+    }
+
+    /// Whether any pixel of the passes was rendered, renders opened from the history have none
+    #[must_use]
+    pub fn is_rendered(&self) -> bool {
+        self.depth.pixels().any(|p| !p[0].is_nan())
+    }
+}
+
+/// Rectangle of pixels of a render
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Shows the post processed pixels of a render while it is running, e.g. in a texture of a GUI
+pub trait RenderOutput: Send + Sync {
+    /// Replace the whole image, after the resolution or the post processing changed
+    fn show(&self, image: &RgbImage);
+
+    /// Update the pixels of a region that was just rendered, row by row
+    fn show_region(&self, region: Region, pixels: &[[u8; 3]]);
+}
+
+/// Renders without showing the pixels anywhere, e.g. to only export the finished render
+impl RenderOutput for () {
+    fn show(&self, _image: &RgbImage) {}
+
+    fn show_region(&self, _region: Region, _pixels: &[[u8; 3]]) {}
+}
+
+/// Progress of a block of the render
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TileState {
+    #[default]
+    Queued,
+    Rendering,
+    Done,
+}
+
+/// Blocks of the current render row by row, empty if it isn't rendered in blocks
+#[derive(Debug, Clone, Default)]
+pub struct Tiles {
+    pub columns: u32,
+    pub rows: u32,
+    pub states: Vec<TileState>,
+}
+
+impl Tiles {
+    fn new(columns: u32, rows: u32) -> Self {
+        Self {
+            columns,
+            rows,
+            states: vec![TileState::Queued; (columns * rows) as usize],
+        }
+    }
+
+    fn set(&mut self, (x, y): (u32, u32), state: TileState) {
+        if let Some(tile) = self.states.get_mut((x + y * self.columns) as usize) {
+            *tile = state;
+        }
+    }
+}
+
+/// Settings a render was started with
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderInfo {
+    pub resolution: (u32, u32),
+    /// Maximum samples per pixel
+    pub samples: u32,
+    /// Hash of the serialized scene, to tell renders of different scene states apart
+    pub scene_hash: u64,
+    /// Seed of the anti-aliasing jitter, rendering again with it gives the same image
+    pub seed: u64,
+}
+
+impl RenderInfo {
+    fn new(scene: &Scene) -> Self {
+        let mut hasher = DefaultHasher::new();
+        scene
+            .to_yaml()
+            .map_err(|e| warn!("Failed to hash scene: {e}"))
+            .unwrap_or_default()
+            .hash(&mut hasher);
+
+        Self {
+            resolution: scene.camera().resolution,
+            samples: scene.settings.samples,
+            scene_hash: hasher.finish(),
+            seed: rand::random(),
+        }
+    }
+}
+
+/// Renders a scene on a background thread, or step by step on the web,
+/// the pixels are stored and passed to the output as they are done
+pub struct Render {
+    pub output: Arc<dyn RenderOutput>,
+    /// Progress of the rendering in the range [0, `u16::MAX`]
+    pub progress: Arc<AtomicU16>,
+    pub thread: Option<RenderThread>,
+    /// Cancel the rendering if true
+    pub cancel: Arc<AtomicBool>,
+    /// Post processed image, as displayed and exported
+    pub image: Arc<Mutex<RgbImage>>,
+    /// Raw colors and alpha of the render before post processing
+    pub raw: Arc<Mutex<Rgba32FImage>>,
+    /// Post processing applied to the raw colors
+    pub post: Arc<RwLock<Post>>,
+    /// Samples taken per pixel, 0 for unrendered pixels
+    pub samples: Arc<Mutex<SampleImage>>,
+    /// Depth and the other passes of the current render
+    pub aovs: Arc<Mutex<Aovs>>,
+    /// Which blocks are queued, rendering and done
+    pub tiles: Arc<Mutex<Tiles>>,
+    /// Write the rendering time in milliseconds
+    pub time: Arc<AtomicU32>,
+    /// Gather ray and BVH statistics in the next render
+    pub gather_stats: bool,
+    /// Statistics of the current render, if gathered
+    pub stats: Option<Arc<Stats>>,
+    /// Post processed image of the previous render, to compare against
+    pub previous: Option<Arc<RgbImage>>,
+    /// Settings of the current render
+    pub info: RenderInfo,
+    /// Integrators and BSDFs the scene settings can select
+    pub registry: Arc<Registry>,
+}
+
+impl Render {
+    pub fn new(output: Arc<dyn RenderOutput>) -> Self {
+        Self {
+            output,
+            progress: Arc::new(AtomicU16::new(0)),
+            thread: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            image: Arc::new(Mutex::new(RgbImage::new(0, 0))),
+            raw: Arc::new(Mutex::new(Rgba32FImage::new(0, 0))),
+            post: Arc::new(RwLock::new(Post::default())),
+            samples: Arc::new(Mutex::new(SampleImage::new(0, 0))),
+            aovs: Arc::new(Mutex::new(Aovs::default())),
+            tiles: Arc::new(Mutex::new(Tiles::default())),
+            time: Arc::new(AtomicU32::new(0)),
+            gather_stats: false,
+            stats: None,
+            previous: None,
+            info: RenderInfo::default(),
+            registry: Arc::new(Registry::default()),
+        }
+    }
+
+    /// Reapply the post processing to the raw render,
+    /// e.g. after the post processing settings have changed
+    pub fn apply_post(&self) {
+        let image = self.post.read().apply_image(&self.raw.lock());
+        self.output.show(&image);
+        *self.image.lock() = image;
+    }
+
+    /// Settings of the current render, for the metadata of exported files
+    #[must_use]
+    pub fn metadata(&self, scene: Option<&Scene>) -> Metadata {
+        Metadata {
+            scene: scene.map(|scene| scene.path.clone()),
+            resolution: self.info.resolution,
+            samples: self.info.samples,
+            time: self.time.load(Ordering::Relaxed),
+            seed: self.info.seed,
+        }
+    }
+
+    /// Show an earlier render again, e.g. from the render history
+    pub fn open(&mut self, raw: Rgba32FImage, info: RenderInfo, time: u32) {
+        let (width, height) = raw.dimensions();
+
+        let previous = std::mem::replace(&mut *self.image.lock(), RgbImage::new(width, height));
+        if self.progress.load(Ordering::Relaxed) > 0 {
+            self.previous = Some(Arc::new(previous));
+        }
+
+        *self.raw.lock() = raw;
+        *self.samples.lock() = SampleImage::new(width, height);
+        *self.aovs.lock() = Aovs::new(width, height, Vec::new());
+        *self.tiles.lock() = Tiles::default();
+        self.progress.store(u16::MAX, Ordering::Relaxed);
+        self.time.store(time, Ordering::Relaxed);
+        self.stats = None;
+        self.info = info;
+
+        self.apply_post();
+    }
+
+    /// Start rendering the whole scene, cancelling isn't waited for
+    pub fn render(&mut self, scene: &Scene) {
+        let rsize = scene.camera().resolution;
+        info!("Rendering scene with resolution {rsize:?}");
+        self.info = RenderInfo::new(scene);
+
+        // resize output and image buffer to match the new resolution
+        self.output.show(&RgbImage::new(rsize.0, rsize.1));
+        let previous = std::mem::replace(&mut *self.image.lock(), RgbImage::new(rsize.0, rsize.1));
+        // keep the last render, unless nothing of it was rendered
+        if self.progress.load(Ordering::Relaxed) > 0 {
+            self.previous = Some(Arc::new(previous));
+        }
+        // unrendered pixels are NaN to tell them apart from black pixels
+        *self.raw.lock() = Rgba32FImage::from_pixel(rsize.0, rsize.1, image::Rgba([f32::NAN; 4]));
+        *self.samples.lock() = SampleImage::new(rsize.0, rsize.1);
+        let lights = if scene.settings.light_passes {
+            scene.light_passes()
+        } else {
+            Vec::new()
+        };
+        *self.aovs.lock() = Aovs::new(rsize.0, rsize.1, lights);
+        // the rendering thread sets up the tiles once it knows the block size
+        *self.tiles.lock() = Tiles::default();
+
+        // reset progress and time
+        self.progress.store(0, Ordering::Relaxed);
+        self.time.store(0, Ordering::Relaxed);
+        self.stats = self.gather_stats.then(|| Self::stats(scene));
+
+        let (args, finished) = self.thread_args(scene.clone());
+
+        // spawn rendering thread
+        self.thread = Some(RenderThread::spawn(move || args.job(), finished));
+    }
+
+    /// Render a region of the finished render again with the given samples per pixel,
+    /// the result is composited into the existing render
+    pub fn render_region(&mut self, scene: &Scene, region: Region, samples: u32) {
+        let (width, height) = self.image.lock().dimensions();
+        if scene.camera().resolution != (width, height) {
+            warn!("The resolution of the scene has changed, render the whole image first");
+            return;
+        }
+
+        info!("Rendering region {region:?} with {samples} samples");
+
+        // compare against the render before the region was rendered again
+        self.previous = Some(Arc::new(self.image.lock().clone()));
+        // regions are rendered row by row
+        *self.tiles.lock() = Tiles::default();
+
+        self.progress.store(0, Ordering::Relaxed);
+        self.time.store(0, Ordering::Relaxed);
+        self.stats = self.gather_stats.then(|| Self::stats(scene));
+
+        let mut scene = scene.clone();
+        scene.settings.samples = samples;
+        let (args, finished) = self.thread_args(scene);
+
+        self.thread = Some(RenderThread::spawn(
+            move || args.region_job(region),
+            finished,
+        ));
+    }
+
+    /// Empty counters of a render, with the time the BVHs of the scene took to build
+    fn stats(scene: &Scene) -> Arc<Stats> {
+        let stats = Stats::default();
+        stats.set_bvh_build_time(scene.bvh_build_time());
+        Arc::new(stats)
+    }
+
+    /// Arguments of the rendering thread and the flag it sets once it is finished
+    fn thread_args(&self, scene: Scene) -> (RenderingThread, Arc<AtomicBool>) {
+        let finished = Arc::new(AtomicBool::new(false));
+        let args = RenderingThread {
+            cancel: self.cancel.clone(),
+            scene,
+            progress: self.progress.clone(),
+            output: self.output.clone(),
+            image: self.image.clone(),
+            raw: self.raw.clone(),
+            post: self.post.clone(),
+            samples: self.samples.clone(),
+            aovs: self.aovs.clone(),
+            tiles: self.tiles.clone(),
+            time: self.time.clone(),
+            stats: self.stats.clone(),
+            seed: self.info.seed,
+            registry: self.registry.clone(),
+            finished: finished.clone(),
+        };
+
+        (args, finished)
+    }
+}
+
+/// A running render, finished once all of its tasks are done or it was cancelled.
+/// On the web there are no threads, the tasks are rendered a few per frame by [`Self::step`]
+pub struct RenderThread {
+    /// Set at the end of the render, on every target
+    finished: Arc<AtomicBool>,
+    #[cfg(not(target_arch = "wasm32"))]
+    handle: JoinHandle<()>,
+    #[cfg(target_arch = "wasm32")]
+    job: Mutex<Job>,
+}
+
+impl RenderThread {
+    /// Run the rendering on its own thread
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn(job: impl FnOnce() -> Job + Send + 'static, finished: Arc<AtomicBool>) -> Self {
+        Self {
+            finished,
+            handle: std::thread::spawn(move || job().run()),
+        }
+    }
+
+    /// The web has no threads, the rendering waits for the calls to [`Self::step`]
+    #[cfg(target_arch = "wasm32")]
+    fn spawn(job: impl FnOnce() -> Job + Send + 'static, finished: Arc<AtomicBool>) -> Self {
+        Self {
+            finished,
+            job: Mutex::new(job()),
+        }
+    }
+
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Continue the rendering on the web for about the given time, e.g. once per frame.
+    /// Renders running on their own thread don't need to be stepped
+    #[cfg_attr(
+        not(target_arch = "wasm32"),
+        allow(unused_variables, clippy::unused_self, clippy::missing_const_for_fn)
+    )]
+    pub fn step(&self, budget: Duration) {
+        #[cfg(target_arch = "wasm32")]
+        self.job.lock().step(budget);
+    }
+
+    /// Wait for the render to finish
+    pub fn join(self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.handle.join().is_err() {
+            warn!("The rendering thread panicked");
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        self.job.into_inner().step(Duration::MAX);
+    }
+}
+
+/// Blocks a render is split into, a twentieth of the resolution and at least a pixel in size,
+/// the blocks at the right and bottom edge are cut off to the remaining pixels.
+/// Returns the number of columns and rows and the blocks row by row,
+/// the blocks are the tasks spread over the threads
+pub(super) fn blocks((width, height): (u32, u32)) -> ((u32, u32), Vec<Region>) {
+    let block_size = [(width / 20).max(1), (height / 20).max(1)];
+    let columns = width.div_ceil(block_size[0]);
+    let rows = height.div_ceil(block_size[1]);
+
+    let blocks = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .map(|(column, row)| {
+            let (x, y) = (column * block_size[0], row * block_size[1]);
+            Region {
+                x,
+                y,
+                width: block_size[0].min(width - x),
+                height: block_size[1].min(height - y),
+            }
+        })
+        .collect();
+
+    ((columns, rows), blocks)
+}
+
+struct RenderingThread {
+    scene: Scene,
+    /// shows the pixels while rendering
+    output: Arc<dyn RenderOutput>,
+    /// image data (CPU exclusive)
+    image: Arc<Mutex<RgbImage>>,
+    /// raw colors and alpha before post processing
+    raw: Arc<Mutex<Rgba32FImage>>,
+    post: Arc<RwLock<Post>>,
+    /// samples taken per pixel
+    samples: Arc<Mutex<SampleImage>>,
+    aovs: Arc<Mutex<Aovs>>,
+    tiles: Arc<Mutex<Tiles>>,
+    /// Cancel the rendering if true
+    cancel: Arc<AtomicBool>,
+    /// Progress of the rendering in the range [0, `u16::MAX`]
+    progress: Arc<AtomicU16>,
+    /// Write the rendering time in milliseconds
+    time: Arc<AtomicU32>,
+    stats: Option<Arc<Stats>>,
+    seed: u64,
+    registry: Arc<Registry>,
+    /// Set once the render is finished or cancelled
+    finished: Arc<AtomicBool>,
+}
+
+/// Part of a render, a block of the whole render or a row of a region rendered again
+#[derive(Debug, Clone, Copy)]
+struct Task {
+    region: Region,
+    /// Position of the block in the tiles, rows of a region have none
+    tile: Option<(u32, u32)>,
+}
+
+impl RenderingThread {
+    /// Split the whole render into blocks
+    fn job(self) -> Job {
+        let (width, height) = self.image.lock().dimensions();
+        let ((columns, rows), blocks) = blocks((width, height));
+        *self.tiles.lock() = Tiles::new(columns, rows);
+
+        let tasks = (0..)
+            .zip(blocks)
+            .map(|(i, region)| Task {
+                region,
+                tile: Some((i % columns, i / columns)),
+            })
+            .collect();
+
+        Job::new(self, tasks, true)
+    }
+
+    /// Split a region that is rendered again into rows, keeping the rest of the render
+    fn region_job(self, region: Region) -> Job {
+        let tasks = (region.y..region.y + region.height)
+            .map(|y| Task {
+                region: Region {
+                    y,
+                    height: 1,
+                    ..region
+                },
+                tile: None,
+            })
+            .collect();
+
+        Job::new(self, tasks, false)
+    }
+
+    /// Choose the exposure of the finished render if the auto exposure is enabled
+    fn auto_expose(&self) {
+        if !self.post.read().auto_exposure {
+            return;
+        }
+
+        let raw = self.raw.lock();
+        let image = {
+            let mut post = self.post.write();
+            post.expose(&raw);
+            debug!("auto exposure: {:.2} EV", post.exposure);
+            post.apply_image(&raw)
+        };
+        drop(raw);
+
+        self.output.show(&image);
+        *self.image.lock() = image;
+    }
+
+    /// Copy rendered colors of a region to the output, the images, the sample counts
+    /// and the passes
+    #[allow(clippy::significant_drop_tightening)]
+    fn store(&self, region: Region, colors: &[(Color, f32, u32)], aovs: &[Aov]) {
+        let post = self.post.read().clone();
+        let pixels = colors
+            .iter()
+            .map(|(c, _, _)| post.apply(*c))
+            .collect::<Vec<_>>();
+
+        // copy pixels to image, raw colors to raw image and sample counts
+        let mut image = self.image.lock();
+        let mut raw = self.raw.lock();
+        let mut samples = self.samples.lock();
+        let mut passes = self.aovs.lock();
+        for x in 0..region.width {
+            for y in 0..region.height {
+                let i = (x + y * region.width) as usize;
+                let (x, y) = (region.x + x, region.y + y);
+                let (color, alpha, count) = colors[i];
+
+                image.put_pixel(x, y, image::Rgb(pixels[i]));
+                raw.put_pixel(x, y, image::Rgba([color.x, color.y, color.z, alpha]));
+                samples.put_pixel(x, y, Luma([count]));
+                passes.depth.put_pixel(x, y, Luma([aovs[i].depth]));
+                passes.objects.put_pixel(x, y, image::Rgb(aovs[i].object));
+                passes
+                    .materials
+                    .put_pixel(x, y, image::Rgb(aovs[i].material));
+                // a region rendered after the light passes were turned off leaves them as they are
+                for ((_, light), c) in passes.lights.iter_mut().zip(&aovs[i].lights) {
+                    light.put_pixel(x, y, image::Rgba([c.x, c.y, c.z, alpha]));
+                }
+            }
+        }
+        drop((image, raw, samples, passes));
+
+        // the images are up to date once the output is told about the pixels
+        self.output.show_region(region, &pixels);
+    }
+}
+
+/// Raytracers and tasks of a render, rendered by a thread or step by step
+struct Job {
+    thread: RenderingThread,
+    raytracer: Raytracer,
+    tasks: Vec<Task>,
+    /// Tasks that are done, rendered or skipped after cancelling
+    done: AtomicUsize,
+    /// Expose the render once it is finished, regions keep the exposure
+    expose: bool,
+    start: Instant,
+}
+
+impl Job {
+    fn new(thread: RenderingThread, tasks: Vec<Task>, expose: bool) -> Self {
+        let start = Instant::now();
+
+        let raytracer = Raytracer::new(
+            thread.scene.clone(),
+            &thread.registry,
+            1e-5,
+            thread.scene.settings.max_bounces,
+        )
+        .with_seed(thread.seed);
+        let raytracer = match thread.stats.clone() {
+            Some(stats) => raytracer.with_stats(stats),
+            None => raytracer,
+        };
+        Self {
+            thread,
+            raytracer,
+            tasks,
+            done: AtomicUsize::new(0),
+            expose,
+            start,
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.thread.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Render all tasks on the threads of rayon
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run(self) {
+        self.tasks
+            .par_iter()
+            .take_any_while(|_| !self.cancelled())
+            .for_each(|task| self.render(*task));
+
+        self.finish();
+    }
+
+    /// Render the next tasks for about the given time, the first task is always rendered
+    #[cfg(target_arch = "wasm32")]
+    fn step(&self, budget: Duration) {
+        if self.thread.finished.load(Ordering::Acquire) {
+            return;
+        }
+
+        let start = Instant::now();
+
+        loop {
+            let next = self.done.load(Ordering::Relaxed);
+            if self.cancelled() || next >= self.tasks.len() {
+                self.finish();
+                break;
+            }
+
+            self.render(self.tasks[next]);
+
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+    }
+
+    fn render(&self, Task { region, tile }: Task) {
+        let _scope = profiler::scope("render tile");
+        let (width, height) = self.thread.image.lock().dimensions();
+        let anti_aliasing = self.thread.scene.settings.anti_aliasing;
+
+        if let Some(tile) = tile {
+            self.thread.tiles.lock().set(tile, TileState::Rendering);
+        }
+        debug!(
+            "rendering {:?} ({:.2}%)",
+            region,
+            self.done.load(Ordering::Relaxed) as f32 / self.tasks.len() as f32 * 100.0
+        );
+
+        let (colors, aovs) = (0..region.width * region.height)
+            // parallelize over pixels
+            .into_par_iter()
+            .map(|i| {
+                let x = i % region.width + region.x;
+                let y = i / region.width + region.y;
+                self.raytracer
+                    .render_with_aov((x, y), (width, height), anti_aliasing)
+            })
+            .unzip::<_, _, Vec<_>, Vec<_>>();
+
+        self.thread.progress.store(
+            ((self.done.fetch_add(1, Ordering::Relaxed) + 1) as f32 / self.tasks.len() as f32
+                * f32::from(u16::MAX))
+            .round() as u16,
+            Ordering::Relaxed,
+        );
+
+        // the pixels of a task finished after cancelling are dropped
+        if self.cancelled() {
+            return;
+        }
+
+        if let Some(tile) = tile {
+            self.thread.tiles.lock().set(tile, TileState::Done);
+        }
+        self.thread.store(region, &colors, &aovs);
+    }
+
+    fn finish(&self) {
+        if self.expose && !self.cancelled() {
+            self.thread.auto_expose();
+        }
+
+        self.thread.progress.store(u16::MAX, Ordering::Relaxed);
+        self.thread
+            .time
+            .store(self.start.elapsed().as_millis() as u32, Ordering::Relaxed);
+
+        info!("rendering finished: {:?}", self.start.elapsed());
+        self.thread.finished.store(true, Ordering::Release);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fmt::{self, Display},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Counters gathered while rendering,
+/// shared between all threads of a render
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// number of rays cast into the scene
+    rays: AtomicU64,
+    /// number of rays cast towards lights, included in `rays`
+    shadow_rays: AtomicU64,
+    /// number of BVH nodes visited while traversing
+    bvh_nodes: AtomicU64,
+    /// number of ray triangle intersection tests
+    triangle_tests: AtomicU64,
+    /// time it took to build the BVHs of the scene when it was loaded, in microseconds
+    bvh_build: AtomicU64,
+}
+
+/// Counts per ray, 0 if no rays have been cast
+fn per_ray(count: u64, rays: u64) -> f64 {
+    if rays > 0 {
+        count as f64 / rays as f64
+    } else {
+        0.0
+    }
+}
+
+impl Stats {
+    pub fn add_ray(&self) {
+        self.rays.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_shadow_ray(&self) {
+        self.shadow_rays.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_bvh_nodes(&self, count: u64) {
+        self.bvh_nodes.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn add_triangle_tests(&self, count: u64) {
+        self.triangle_tests.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn set_bvh_build_time(&self, time: Duration) {
+        self.bvh_build.store(
+            u64::try_from(time.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    pub fn rays(&self) -> u64 {
+        self.rays.load(Ordering::Relaxed)
+    }
+
+    pub fn shadow_rays(&self) -> u64 {
+        self.shadow_rays.load(Ordering::Relaxed)
+    }
+
+    pub fn bvh_nodes(&self) -> u64 {
+        self.bvh_nodes.load(Ordering::Relaxed)
+    }
+
+    pub fn triangle_tests(&self) -> u64 {
+        self.triangle_tests.load(Ordering::Relaxed)
+    }
+
+    pub fn bvh_build_time(&self) -> Duration {
+        Duration::from_micros(self.bvh_build.load(Ordering::Relaxed))
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rays = self.rays();
+
+        writeln!(f, "rays:           {rays}")?;
+        writeln!(f, "shadow rays:    {}", self.shadow_rays())?;
+        writeln!(
+            f,
+            "BVH nodes:      {} ({:.2}/ray)",
+            self.bvh_nodes(),
+            per_ray(self.bvh_nodes(), rays)
+        )?;
+        writeln!(
+            f,
+            "triangle tests: {} ({:.2}/ray)",
+            self.triangle_tests(),
+            per_ray(self.triangle_tests(), rays)
+        )?;
+        write!(
+            f,
+            "BVH build:      {:.1} ms",
+            self.bvh_build_time().as_secs_f64() * 1000.0
+        )
+    }
+}
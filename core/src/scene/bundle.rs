@@ -0,0 +1,277 @@
+use super::{
+    object::{references, rename_references},
+    Scene, Skybox,
+};
+use anyhow::Context;
+use log::{info, warn};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{Read, Write},
+    path::{Component, Path, PathBuf},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+/// Name of the scene file inside a bundle
+const BUNDLE_SCENE: &str = "scene.yaml";
+
+/// Path of a file referenced by a model inside the model directory of the bundle,
+/// files outside of the model directory can't keep their relative path
+/// and are moved to `external/<n>/` next to the model
+fn bundled_name(file: &Path, external: &mut HashMap<PathBuf, PathBuf>) -> PathBuf {
+    let inside = file
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if inside {
+        return file.to_path_buf();
+    }
+
+    let count = external.len();
+    external
+        .entry(file.to_path_buf())
+        .or_insert_with(|| {
+            PathBuf::from("external")
+                .join(count.to_string())
+                .join(file.file_name().unwrap_or_default())
+        })
+        .clone()
+}
+
+fn zip_name(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn add_bytes<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    content: &[u8],
+    name: &str,
+) -> anyhow::Result<()> {
+    zip.start_file(
+        name,
+        SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+    )?;
+    zip.write_all(content)?;
+
+    Ok(())
+}
+
+fn add_file<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    source: &Path,
+    name: &str,
+) -> anyhow::Result<()> {
+    let mut content = Vec::new();
+    File::open(source)
+        .and_then(|mut f| f.read_to_end(&mut content))
+        .context(format!("Failed to read {} for bundling", source.display()))?;
+
+    add_bytes(zip, &content, name)
+}
+
+/// Add an OBJ or MTL file, with its references to moved files renamed
+fn add_model_file<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    source: &Path,
+    name: &str,
+    external: &HashMap<PathBuf, PathBuf>,
+) -> anyhow::Result<()> {
+    if external.is_empty() {
+        return add_file(zip, source, name);
+    }
+
+    let content = std::fs::read_to_string(source)
+        .context(format!("Failed to read {} for bundling", source.display()))?;
+    add_bytes(zip, rename_references(&content, external).as_bytes(), name)
+}
+
+/// Add a model with its materials and textures to the model directory of the bundle
+fn add_model<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    source: &Path,
+    directory: &Path,
+) -> anyhow::Result<()> {
+    let source_directory = source.parent().unwrap_or_else(|| Path::new(""));
+
+    // textures are relative to the model directory, like the materials
+    let materials = references(source)
+        .into_iter()
+        .map(|mtl| {
+            let textures = references(&source_directory.join(&mtl));
+            (mtl, textures)
+        })
+        .collect::<Vec<_>>();
+
+    let mut external = HashMap::new();
+    for (mtl, textures) in &materials {
+        for file in std::iter::once(mtl).chain(textures) {
+            bundled_name(file, &mut external);
+        }
+    }
+    for (file, name) in &external {
+        info!(
+            "Moving {} referenced by {} to {} in the bundle",
+            file.display(),
+            source.display(),
+            name.display()
+        );
+    }
+
+    let model = directory.join(source.file_name().unwrap_or_default());
+    add_model_file(zip, source, &zip_name(&model), &external)?;
+
+    // materials may share textures, every file is added once
+    let mut added = HashSet::new();
+    for (mtl, textures) in &materials {
+        let name = directory.join(bundled_name(mtl, &mut external));
+        if added.insert(name.clone()) {
+            add_model_file(
+                zip,
+                &source_directory.join(mtl),
+                &zip_name(&name),
+                &external,
+            )?;
+        }
+
+        for texture in textures {
+            let name = directory.join(bundled_name(texture, &mut external));
+            if added.insert(name.clone()) {
+                add_file(zip, &source_directory.join(texture), &zip_name(&name))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Scene {
+    /// Pack the scene and all referenced models, materials
+    /// and textures into a zip file with relative paths
+    ///
+    /// # Errors
+    ///
+    /// Fails if the bundle can't be written or a referenced file can't be read
+    pub fn export_bundle<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let scene_directory = self.path.parent().unwrap_or_else(|| Path::new(""));
+        let mut zip = ZipWriter::new(File::create(path.as_ref()).context(format!(
+            "Failed to create bundle: {}",
+            path.as_ref().display()
+        ))?);
+
+        let mut bundled = self.clone();
+        // model directories inside the bundle, keyed by the model file
+        let mut models = HashMap::<PathBuf, PathBuf>::new();
+
+        for object in &mut bundled.objects {
+            if object.path.as_os_str().is_empty() {
+                warn!("Skipping {} without a file for bundling", object.name);
+                continue;
+            }
+
+            let source = scene_directory.join(&object.path);
+
+            if let Some(bundled_path) = models.get(&source) {
+                object.path.clone_from(bundled_path);
+                continue;
+            }
+
+            let directory = PathBuf::from("models").join(models.len().to_string());
+            add_model(&mut zip, &source, &directory)?;
+
+            let bundled_path = directory.join(source.file_name().unwrap_or_default());
+            models.insert(source, bundled_path.clone());
+            object.path = bundled_path;
+        }
+
+        // the bundle carries the library materials in the scene itself
+        bundled.materials = bundled.library();
+        bundled.material_library = None;
+
+        // textures of library materials and material overrides, keyed by the texture file
+        let mut textures = HashMap::<PathBuf, PathBuf>::new();
+        let overridden = bundled
+            .objects
+            .iter_mut()
+            .flat_map(|o| o.material_overrides.values_mut())
+            .chain(bundled.materials.values_mut())
+            .filter_map(|o| o.diffuse_texture.as_mut());
+
+        for texture in overridden {
+            let source = scene_directory.join(&*texture);
+
+            if let Some(bundled_path) = textures.get(&source) {
+                texture.clone_from(bundled_path);
+                continue;
+            }
+
+            let name = PathBuf::from("textures")
+                .join(textures.len().to_string())
+                .join(source.file_name().unwrap_or_default());
+            add_file(&mut zip, &source, &zip_name(&name))?;
+
+            textures.insert(source, name.clone());
+            *texture = name;
+        }
+
+        if let Skybox::Image { path, .. } = &mut bundled.settings.skybox {
+            let name = PathBuf::from("skybox").join(path.file_name().unwrap_or_default());
+            add_file(&mut zip, path, &zip_name(&name))?;
+            *path = name;
+        }
+
+        zip.start_file(BUNDLE_SCENE, SimpleFileOptions::default())?;
+        zip.write_all(bundled.to_yaml()?.as_bytes())?;
+        zip.finish()?;
+
+        info!(
+            "Bundled scene with {} model files to {}",
+            models.len(),
+            path.as_ref().display()
+        );
+
+        Ok(())
+    }
+
+    /// Extract a bundle into a new directory next to it, named like the bundle,
+    /// and load the contained scene
+    ///
+    /// # Errors
+    ///
+    /// Fails if the bundle can't be read or extracted, or the contained scene can't be loaded
+    pub fn open_bundle<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let directory = Self::extract_directory(path.as_ref());
+
+        let mut archive = ZipArchive::new(File::open(path.as_ref()).context(format!(
+            "Failed to open bundle: {}",
+            path.as_ref().display()
+        ))?)
+        .context("Failed to read bundle")?;
+
+        archive.extract(&directory).context(format!(
+            "Failed to extract bundle to {}",
+            directory.display()
+        ))?;
+
+        info!("Extracted bundle to {}", directory.display());
+
+        Ok(Self::load(directory.join(BUNDLE_SCENE))?)
+    }
+
+    /// First directory next to the bundle that doesn't exist yet, so opening a bundle
+    /// never overwrites earlier extractions or other files
+    fn extract_directory(path: &Path) -> PathBuf {
+        let directory = path.with_extension("");
+        let name = directory
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut candidate = directory.clone();
+        let mut n = 1;
+        while candidate.exists() {
+            candidate = directory.with_file_name(format!("{name}-{n}"));
+            n += 1;
+        }
+
+        candidate
+    }
+}
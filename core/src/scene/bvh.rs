@@ -0,0 +1,68 @@
+use nalgebra::{Point3, Vector3};
+
+/// Axis aligned bounding box, empty boxes have `min` above `max`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    pub const fn empty() -> Self {
+        Self {
+            min: Point3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Point3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    /// Smallest box containing this one and the point
+    pub fn grow(&self, point: &Point3<f32>) -> Self {
+        Self {
+            min: self.min.inf(point),
+            max: self.max.sup(point),
+        }
+    }
+
+    /// Smallest box containing both boxes
+    pub fn join(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    pub fn size(&self) -> Vector3<f32> {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        nalgebra::center(&self.min, &self.max)
+    }
+
+    /// Area of the six sides, zero for empty boxes
+    pub fn surface_area(&self) -> f32 {
+        let size = self.size().map(|s| s.max(0.0));
+        2.0 * size.z.mul_add(size.x + size.y, size.x * size.y)
+    }
+}
+
+/// Node of a flattened BVH, the children of a node come after it
+#[derive(Debug, Clone)]
+pub enum BvhNode {
+    Leaf {
+        shape_index: usize,
+    },
+    /// Indices of the children and their bounding boxes
+    Node {
+        left: usize,
+        left_aabb: Aabb,
+        right: usize,
+        right_aabb: Aabb,
+    },
+}
+
+/// Bounding volume hierarchy over the triangles of a mesh, the root is the first node
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    pub nodes: Vec<BvhNode>,
+}
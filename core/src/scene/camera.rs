@@ -0,0 +1,343 @@
+use crate::raytracer::Ray;
+use nalgebra::{Point3, Rotation3, Vector3};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, TAU};
+
+/// Sensor width of a 35mm camera in mm
+pub const FULL_FRAME_WIDTH: f32 = 36.0;
+
+/// How the image is mapped to rays, the stereo projections render
+/// the left eye into the top half of the image and the right eye below
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Projection {
+    #[default]
+    Perspective,
+    /// Parallel perspective views of both eyes
+    Stereo,
+    /// Omni-directional stereo, 360° equirectangular panoramas of both eyes for VR
+    Ods,
+}
+
+impl Projection {
+    pub const ALL: [Self; 3] = [Self::Perspective, Self::Stereo, Self::Ods];
+
+    #[must_use]
+    pub const fn is_stereo(self) -> bool {
+        matches!(self, Self::Stereo | Self::Ods)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    /// Shown in the camera selector instead of a numbered label
+    pub name: Option<String>,
+    pub position: Point3<f32>,
+    pub look_at: Point3<f32>,
+    pub up: Vector3<f32>,
+    /// Vertical field of view in radians
+    pub fov: f32,
+    pub resolution: (u32, u32),
+    /// Width of the sensor in mm the focal length refers to, 36 for full frame
+    pub sensor_width: f32,
+    /// Whether the camera was set by focal length, it is saved that way instead of the field of view
+    pub by_focal_length: bool,
+    /// Depth of the near clipping plane, closer geometry is cut away
+    pub near: f32,
+    /// Depth of the far clipping plane, farther geometry is ignored
+    pub far: f32,
+    pub projection: Projection,
+    /// Distance between the eyes of the stereo projections, 64 mm for an average adult
+    pub eye_separation: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            name: None,
+            position: Point3::new(1.0, 1.0, 1.0),
+            look_at: Point3::origin(),
+            up: Vector3::y(),
+            fov: 60.0_f32.to_radians(),
+            resolution: (1920, 1080),
+            sensor_width: FULL_FRAME_WIDTH,
+            by_focal_length: false,
+            near: 0.1,
+            far: 1000.0,
+            projection: Projection::Perspective,
+            eye_separation: 0.064,
+        }
+    }
+}
+
+impl Camera {
+    /// Returns a ray from the given pixel coordinates.
+    /// x and y are in the range -1..1 and represent
+    /// the relative position of the pixel in the image.
+    /// (0, 0) is the center of the image.
+    #[must_use]
+    pub fn ray(&self, x: f32, y: f32) -> Ray {
+        // direction in coordinate system of camera
+        let direction = Vector3::new(x, -y, -1.0 / (self.fov / 2.0).tan());
+
+        // rotate direction to world coordinate system
+        let direction = self.rotation().inverse_transform_vector(&direction);
+
+        Ray {
+            origin: self.position,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Ray through a point of the image, u and v are in the range 0..1
+    /// from the top left corner, according to the projection
+    #[must_use]
+    pub fn image_ray(&self, u: f32, v: f32) -> Ray {
+        match self.projection {
+            Projection::Perspective => self.ray(
+                u.mul_add(2.0, -1.0) * self.aspect_ratio(),
+                v.mul_add(2.0, -1.0),
+            ),
+            Projection::Stereo => {
+                let (eye, v) = Self::eye(v);
+                let ray = self.ray(
+                    u.mul_add(2.0, -1.0) * self.aspect_ratio(),
+                    v.mul_add(2.0, -1.0),
+                );
+                let right = self.rotation().inverse_transform_vector(&Vector3::x());
+
+                Ray {
+                    origin: ray.origin + right * (eye * self.eye_separation / 2.0),
+                    ..ray
+                }
+            }
+            Projection::Ods => {
+                let (eye, v) = Self::eye(v);
+                // the center of the image looks ahead
+                let longitude = (u - 0.5) * TAU;
+                let latitude = (0.5 - v) * PI;
+                let direction = Vector3::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    -latitude.cos() * longitude.cos(),
+                );
+                // the eyes turn on a circle with the view, so every direction is seen in stereo
+                let offset = Vector3::new(longitude.cos(), 0.0, longitude.sin())
+                    * (eye * self.eye_separation / 2.0);
+
+                let rotation = self.rotation();
+                Ray {
+                    origin: self.position + rotation.inverse_transform_vector(&offset),
+                    direction: rotation.inverse_transform_vector(&direction),
+                }
+            }
+        }
+    }
+
+    /// The eye a point of a stereo image belongs to, -1 for the left eye in the top half
+    /// and 1 for the right eye, with v within the half of the eye
+    fn eye(v: f32) -> (f32, f32) {
+        if v < 0.5 {
+            (-1.0, v * 2.0)
+        } else {
+            (1.0, v.mul_add(2.0, -1.0))
+        }
+    }
+
+    fn rotation(&self) -> Rotation3<f32> {
+        Rotation3::look_at_rh(&(self.look_at - self.position), &self.up)
+    }
+
+    /// Aspect ratio of the image of one eye, the stereo projections stack two images
+    #[must_use]
+    pub fn aspect_ratio(&self) -> f32 {
+        let height = if self.projection.is_stereo() {
+            self.resolution.1 / 2
+        } else {
+            self.resolution.1
+        };
+        self.resolution.0 as f32 / height.max(1) as f32
+    }
+
+    /// Focal length in mm on the sensor that gives the field of view at the resolution
+    #[must_use]
+    pub fn focal_length(&self) -> f32 {
+        self.sensor_width / (2.0 * self.aspect_ratio() * (self.fov / 2.0).tan())
+    }
+
+    /// Set the field of view from a focal length in mm on the sensor
+    pub fn set_focal_length(&mut self, focal_length: f32) {
+        self.fov = 2.0 * (self.sensor_width / (2.0 * self.aspect_ratio() * focal_length)).atan();
+        self.by_focal_length = true;
+    }
+
+    /// Distance of a point from the camera along the viewing direction,
+    /// panoramas look in all directions
+    #[must_use]
+    pub fn depth(&self, point: Point3<f32>) -> f32 {
+        if self.projection == Projection::Ods {
+            return (point - self.position).norm();
+        }
+        (point - self.position).dot(&(self.look_at - self.position).normalize())
+    }
+
+    /// Ray parameter at which a ray crosses the far clipping plane, infinite if it never does.
+    /// Panoramas get a bound no hit within the far distance lies beyond
+    #[must_use]
+    pub fn far_distance(&self, ray: Ray) -> f32 {
+        let offset = ray.origin - self.position;
+        if self.projection == Projection::Ods {
+            return (self.far + offset.norm()) / ray.direction.norm();
+        }
+
+        let forward = (self.look_at - self.position).normalize();
+        let cos = ray.direction.dot(&forward);
+        if cos <= 0.0 {
+            return f32::INFINITY;
+        }
+        (self.far - offset.dot(&forward)) / cos
+    }
+
+    /// Move the origin of a ray from the camera onto the near clipping plane
+    #[must_use]
+    pub fn clip_near(&self, ray: Ray) -> Ray {
+        let cos = if self.projection == Projection::Ods {
+            1.0
+        } else {
+            ray.direction
+                .dot(&(self.look_at - self.position).normalize())
+        };
+        if cos <= 0.0 {
+            return ray;
+        }
+
+        Ray {
+            origin: ray.origin + ray.direction * (self.near / cos),
+            ..ray
+        }
+    }
+}
+
+mod yaml {
+    use super::{Camera, Projection};
+    use nalgebra::{Point3, Vector3};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct CameraDef {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub name: Option<String>,
+        #[serde(with = "super::super::yaml::point")]
+        pub position: Point3<f32>,
+        #[serde(rename = "lookAt")]
+        #[serde(with = "super::super::yaml::point")]
+        pub look_at: Point3<f32>,
+        #[serde(rename = "upVec")]
+        #[serde(with = "super::super::yaml::vector")]
+        pub up_vec: Vector3<f32>,
+        #[serde(
+            rename = "fieldOfView",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub field_of_view: Option<f32>,
+        /// Focal length in mm instead of the field of view
+        #[serde(
+            rename = "focalLength",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub focal_length: Option<f32>,
+        #[serde(rename = "sensorWidth", default = "default_sensor_width")]
+        pub sensor_width: f32,
+        pub width: u32,
+        pub height: u32,
+        #[serde(default = "default_near")]
+        pub near: f32,
+        #[serde(default = "default_far")]
+        pub far: f32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub projection: Option<Projection>,
+        #[serde(
+            rename = "eyeSeparation",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub eye_separation: Option<f32>,
+    }
+
+    const fn default_sensor_width() -> f32 {
+        super::FULL_FRAME_WIDTH
+    }
+
+    fn default_near() -> f32 {
+        Camera::default().near
+    }
+
+    fn default_far() -> f32 {
+        Camera::default().far
+    }
+
+    impl<'de> Deserialize<'de> for Camera {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let yaml_camera = CameraDef::deserialize(deserializer)?;
+            let mut camera = Self {
+                name: yaml_camera.name,
+                position: yaml_camera.position,
+                look_at: yaml_camera.look_at,
+                up: yaml_camera.up_vec,
+                fov: yaml_camera.field_of_view.unwrap_or_default().to_radians(),
+                resolution: (yaml_camera.width, yaml_camera.height),
+                sensor_width: yaml_camera.sensor_width,
+                by_focal_length: false,
+                near: yaml_camera.near,
+                far: yaml_camera.far,
+                projection: yaml_camera.projection.unwrap_or_default(),
+                eye_separation: yaml_camera
+                    .eye_separation
+                    .unwrap_or_else(|| Self::default().eye_separation),
+            };
+
+            // the focal length takes precedence, photographers think in lenses
+            match (yaml_camera.focal_length, yaml_camera.field_of_view) {
+                (Some(focal_length), _) => camera.set_focal_length(focal_length),
+                (None, Some(_)) => {}
+                (None, None) => {
+                    return Err(serde::de::Error::custom(
+                        "camera needs a fieldOfView or a focalLength",
+                    ))
+                }
+            }
+
+            Ok(camera)
+        }
+    }
+
+    impl Serialize for Camera {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            CameraDef {
+                name: self.name.clone(),
+                position: self.position,
+                look_at: self.look_at,
+                up_vec: self.up,
+                field_of_view: (!self.by_focal_length).then(|| self.fov.to_degrees()),
+                focal_length: self.by_focal_length.then(|| self.focal_length()),
+                sensor_width: self.sensor_width,
+                width: self.resolution.0,
+                height: self.resolution.1,
+                near: self.near,
+                far: self.far,
+                // only stereo cameras need the projection and the eye separation
+                projection: self.projection.is_stereo().then_some(self.projection),
+                eye_separation: self.projection.is_stereo().then_some(self.eye_separation),
+            }
+            .serialize(serializer)
+        }
+    }
+}
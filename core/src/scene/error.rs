@@ -0,0 +1,98 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+/// Why a scene or one of its files couldn't be loaded, to tell the user how to fix it
+#[derive(Debug)]
+pub enum SceneError {
+    /// A scene or model file doesn't exist or can't be read
+    MissingFile { path: PathBuf, message: String },
+    /// The scene isn't valid YAML or JSON or doesn't match the scene format,
+    /// the line starts at 1 if it is known
+    Malformed {
+        path: PathBuf,
+        line: Option<usize>,
+        message: String,
+    },
+    /// A model can't be parsed or its material libraries can't be loaded
+    Model { path: PathBuf, message: String },
+    /// An image can't be decoded as texture
+    UnsupportedTexture { path: PathBuf, message: String },
+    /// An object uses a material that isn't defined in the file of the path,
+    /// the MTL of its model or the material library, it gets the default material instead
+    UnknownMaterial {
+        path: PathBuf,
+        object: String,
+        material: String,
+    },
+}
+
+impl SceneError {
+    /// File the error is about
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::MissingFile { path, .. }
+            | Self::Malformed { path, .. }
+            | Self::Model { path, .. }
+            | Self::UnsupportedTexture { path, .. }
+            | Self::UnknownMaterial { path, .. } => path,
+        }
+    }
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingFile { path, message } => {
+                write!(f, "Failed to read {}: {message}", path.display())
+            }
+            Self::Malformed {
+                path,
+                line: Some(line),
+                message,
+            } => write!(
+                f,
+                "Invalid scene {} in line {line}: {message}",
+                path.display()
+            ),
+            Self::Malformed { path, message, .. } => {
+                write!(f, "Invalid scene {}: {message}", path.display())
+            }
+            Self::Model { path, message } => {
+                write!(f, "Failed to load model {}: {message}", path.display())
+            }
+            Self::UnsupportedTexture { path, message } => {
+                write!(f, "Failed to load texture {}: {message}", path.display())
+            }
+            Self::UnknownMaterial {
+                path,
+                object,
+                material,
+            } => write!(
+                f,
+                "Material {material} of {object} is not defined in {}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+thread_local! {
+    /// Error of a model of the scene being deserialized, serde only passes it on as text
+    static MODEL_ERROR: RefCell<Option<SceneError>> = const { RefCell::new(None) };
+}
+
+/// Keep the error of a model until the deserialization of the scene has failed
+pub(super) fn keep(error: SceneError) {
+    MODEL_ERROR.with_borrow_mut(|e| *e = Some(error));
+}
+
+/// Error of a model kept while deserializing the scene on this thread
+pub(super) fn take() -> Option<SceneError> {
+    MODEL_ERROR.with_borrow_mut(Option::take)
+}
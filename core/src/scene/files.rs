@@ -0,0 +1,54 @@
+use image::{DynamicImage, ImageFormat, ImageResult};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, PoisonError, RwLock},
+};
+
+/// Contents of files provided by the app, read in place of the file system.
+/// The web has no file system, models and textures are fetched and provided here.
+static FILES: LazyLock<RwLock<HashMap<PathBuf, Arc<[u8]>>>> = LazyLock::new(RwLock::default);
+
+/// Provide the content of a file to the loaders of models and textures,
+/// it is read instead of the file at the path from now on
+pub fn provide_file<P: Into<PathBuf>>(path: P, content: impl Into<Arc<[u8]>>) {
+    FILES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(path.into(), content.into());
+}
+
+/// Whether the file was provided or exists in the file system
+pub(super) fn exists(path: &Path) -> bool {
+    FILES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains_key(path)
+        || path.exists()
+}
+
+/// Content of a provided file, else of the file in the file system
+pub(super) fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let provided = FILES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(path)
+        .cloned();
+
+    provided.map_or_else(|| std::fs::read(path), |content| Ok(content.to_vec()))
+}
+
+/// Content of a text file like [`read`]
+pub(super) fn read_to_string(path: &Path) -> io::Result<String> {
+    String::from_utf8(read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Decode an image like [`image::open`], the format is taken from the extension
+/// and guessed from the content for unknown extensions
+pub(super) fn open_image(path: &Path) -> ImageResult<DynamicImage> {
+    let content = read(path)?;
+    let format = ImageFormat::from_path(path).or_else(|_| image::guess_format(&content))?;
+
+    image::load_from_memory_with_format(&content, format)
+}
@@ -0,0 +1,387 @@
+use super::{material::Material, object::Object, triangle::Triangle, Scene};
+use anyhow::Context;
+use image::{ImageFormat, RgbImage};
+use nalgebra::{UnitQuaternion, Vector3};
+use serde_json::{json, Value};
+use std::{collections::HashMap, io::Cursor, path::Path, sync::Arc};
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const FLOAT: u32 = 5126;
+const ARRAY_BUFFER: u32 = 34962;
+
+/// Accessors of a group of triangles sharing a material
+struct Primitive {
+    material_index: Option<usize>,
+    position: usize,
+    normal: usize,
+    uv: usize,
+}
+
+/// Collects the JSON arrays and the binary buffer of a glTF file
+#[derive(Default)]
+struct Builder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    images: Vec<Value>,
+    textures: Vec<Value>,
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    /// Primitives of meshes already written, keyed by the shared mesh
+    geometry: HashMap<*const super::object::Mesh, Vec<Primitive>>,
+    /// Textures already written, keyed by the shared image
+    texture_indices: HashMap<*const RgbImage, usize>,
+}
+
+impl Builder {
+    /// Append data to the binary buffer as a new buffer view
+    fn buffer_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        // buffer views of floats have to be 4 byte aligned
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": data.len(),
+        });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+
+        self.buffer.extend_from_slice(data);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn accessor<const N: usize>(&mut self, values: &[[f32; N]], min_max: bool) -> usize {
+        let data = values
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect::<Vec<_>>();
+        let view = self.buffer_view(&data, Some(ARRAY_BUFFER));
+        let kind = if N == 2 { "VEC2" } else { "VEC3" };
+
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": kind,
+        });
+
+        // POSITION accessors require bounds
+        if min_max {
+            let bound = |f: fn(f32, f32) -> f32, init: f32| {
+                values
+                    .iter()
+                    .fold([init; N], |acc, v| std::array::from_fn(|i| f(acc[i], v[i])))
+            };
+            accessor["min"] = json!(bound(f32::min, f32::INFINITY).to_vec());
+            accessor["max"] = json!(bound(f32::max, f32::NEG_INFINITY).to_vec());
+        }
+
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    /// Write the triangles grouped by material, flat without indices
+    fn primitives(&mut self, triangles: &[Triangle]) -> Vec<Primitive> {
+        let mut groups = HashMap::<Option<usize>, Vec<&Triangle>>::new();
+        for t in triangles {
+            groups.entry(t.material_index).or_default().push(t);
+        }
+
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        groups.sort_by_key(|(material_index, _)| *material_index);
+
+        groups
+            .into_iter()
+            .map(|(material_index, triangles)| {
+                let vertices = triangles.iter().flat_map(|t| {
+                    [
+                        (t.a, t.a_normal, t.a_uv),
+                        (t.b, t.b_normal, t.b_uv),
+                        (t.c, t.c_normal, t.c_uv),
+                    ]
+                });
+
+                let positions = vertices
+                    .clone()
+                    .map(|(p, _, _)| p.coords.into())
+                    .collect::<Vec<[f32; 3]>>();
+                let normals = vertices
+                    .clone()
+                    .map(|(_, n, _)| {
+                        n.try_normalize(f32::EPSILON)
+                            .unwrap_or_else(Vector3::y)
+                            .into()
+                    })
+                    .collect::<Vec<[f32; 3]>>();
+                // glTF has the UV origin in the top left corner
+                let uvs = vertices
+                    .map(|(_, _, uv)| [uv.x, 1.0 - uv.y])
+                    .collect::<Vec<_>>();
+
+                Primitive {
+                    material_index,
+                    position: self.accessor::<3>(&positions, true),
+                    normal: self.accessor::<3>(&normals, false),
+                    uv: self.accessor::<2>(&uvs, false),
+                }
+            })
+            .collect()
+    }
+
+    fn texture(&mut self, image: &Arc<RgbImage>) -> anyhow::Result<usize> {
+        if let Some(&index) = self.texture_indices.get(&Arc::as_ptr(image)) {
+            return Ok(index);
+        }
+
+        let mut png = Cursor::new(Vec::new());
+        image
+            .write_to(&mut png, ImageFormat::Png)
+            .context("Failed to encode texture as png")?;
+
+        let view = self.buffer_view(png.get_ref(), None);
+        self.images
+            .push(json!({ "bufferView": view, "mimeType": "image/png" }));
+        self.textures
+            .push(json!({ "source": self.images.len() - 1 }));
+
+        let index = self.textures.len() - 1;
+        self.texture_indices.insert(Arc::as_ptr(image), index);
+        Ok(index)
+    }
+
+    fn material(&mut self, material: &Material) -> anyhow::Result<usize> {
+        let [r, g, b] = material
+            .diffuse_color
+            .map_or([1.0; 3], std::convert::Into::into);
+        let alpha = 1.0 - material.dissolve.unwrap_or(0.0).clamp(0.0, 1.0);
+        // approximate the roughness from the phong exponent
+        let roughness = material
+            .specular_exponent
+            .map_or(1.0, |ns| (2.0 / (ns.max(0.0) + 2.0)).sqrt());
+
+        let metallic = if material.illumination_model.reflection() {
+            1.0
+        } else {
+            0.0
+        };
+
+        let mut pbr = json!({
+            "baseColorFactor": [r, g, b, alpha],
+            "metallicFactor": metallic,
+            "roughnessFactor": roughness,
+        });
+        if let Some(texture) = &material.diffuse_texture {
+            let index = self.texture(texture)?;
+            pbr["baseColorTexture"] = json!({ "index": index });
+        }
+
+        let mut value = json!({
+            "name": material.name,
+            "pbrMetallicRoughness": pbr,
+        });
+        if alpha < 1.0 {
+            value["alphaMode"] = json!("BLEND");
+        }
+
+        self.materials.push(value);
+        Ok(self.materials.len() - 1)
+    }
+
+    fn node(&mut self, node: Value) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Add the mesh and node of an object, objects sharing a mesh share its accessors
+    fn object(&mut self, object: &Object) -> anyhow::Result<usize> {
+        let key = Arc::as_ptr(&object.mesh);
+        if !self.geometry.contains_key(&key) {
+            let primitives = self.primitives(&object.mesh.triangles);
+            self.geometry.insert(key, primitives);
+        }
+
+        let materials = object
+            .materials
+            .iter()
+            .map(|m| self.material(m))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let primitives = self.geometry[&key]
+            .iter()
+            .map(|p| {
+                let mut primitive = json!({
+                    "attributes": {
+                        "POSITION": p.position,
+                        "NORMAL": p.normal,
+                        "TEXCOORD_0": p.uv,
+                    },
+                });
+                if let Some(&material) = p.material_index.and_then(|i| materials.get(i)) {
+                    primitive["material"] = json!(material);
+                }
+                primitive
+            })
+            .collect::<Vec<_>>();
+
+        self.meshes
+            .push(json!({ "name": object.name, "primitives": primitives }));
+
+        let mesh = self.meshes.len() - 1;
+        Ok(self.node(json!({
+            "name": object.name,
+            "mesh": mesh,
+            "translation": <[f32; 3]>::from(object.translation.vector),
+            "rotation": <[f32; 4]>::from(object.rotation.coords),
+            "scale": <[f32; 3]>::from(object.scale.vector),
+        })))
+    }
+}
+
+impl Scene {
+    /// Export the scene with its objects, materials, lights and camera as binary glTF
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be written
+    pub fn export_gltf<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let mut builder = Builder::default();
+        let mut root_nodes = Vec::new();
+
+        let group_nodes = self
+            .groups
+            .iter()
+            .map(|group| {
+                builder.node(json!({
+                    "name": group.name,
+                    "translation": <[f32; 3]>::from(group.translation.vector),
+                    "rotation": <[f32; 4]>::from(group.rotation.coords),
+                    "scale": <[f32; 3]>::from(group.scale.vector),
+                }))
+            })
+            .collect::<Vec<_>>();
+        let mut children = vec![Vec::new(); self.groups.len()];
+        for (group, &node) in self.groups.iter().zip(&group_nodes) {
+            match group.parent {
+                Some(parent) => children[parent].push(node),
+                None => root_nodes.push(node),
+            }
+        }
+
+        for object in &self.objects {
+            let node = builder.object(object)?;
+            match object.parent {
+                Some(parent) => children[parent].push(node),
+                None => root_nodes.push(node),
+            }
+        }
+
+        for (node, children) in group_nodes.into_iter().zip(children) {
+            if !children.is_empty() {
+                builder.nodes[node]["children"] = json!(children);
+            }
+        }
+
+        let lights = self
+            .lights
+            .iter()
+            .map(|light| {
+                json!({
+                    "type": "point",
+                    "color": <[f32; 3]>::from(light.color),
+                    "intensity": light.intensity,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (i, light) in self.lights.iter().enumerate() {
+            let node = builder.node(json!({
+                "name": light.name.clone().unwrap_or_else(|| format!("Light {}", i + 1)),
+                "translation": <[f32; 3]>::from(light.position.coords),
+                "extensions": { "KHR_lights_punctual": { "light": i } },
+            }));
+            root_nodes.push(node);
+        }
+
+        // glTF cameras look along -Z with +Y up
+        let camera = self.camera();
+        let rotation =
+            UnitQuaternion::look_at_rh(&(camera.look_at - camera.position), &camera.up).inverse();
+        let node = builder.node(json!({
+            "name": "Camera",
+            "camera": 0,
+            "translation": <[f32; 3]>::from(camera.position.coords),
+            "rotation": <[f32; 4]>::from(rotation.coords),
+        }));
+        root_nodes.push(node);
+
+        let mut document = json!({
+            "asset": {
+                "version": "2.0",
+                "generator": format!("Trayracer {}", env!("CARGO_PKG_VERSION")),
+            },
+            "scene": 0,
+            "scenes": [{ "nodes": root_nodes }],
+            "nodes": builder.nodes,
+            "meshes": builder.meshes,
+            "materials": builder.materials,
+            "accessors": builder.accessors,
+            "bufferViews": builder.buffer_views,
+            "buffers": [{ "byteLength": builder.buffer.len() }],
+            "cameras": [{
+                "type": "perspective",
+                "perspective": {
+                    "yfov": camera.fov,
+                    "aspectRatio": camera.resolution.0 as f32 / camera.resolution.1 as f32,
+                    "znear": 0.01,
+                },
+            }],
+        });
+
+        if !builder.textures.is_empty() {
+            document["images"] = json!(builder.images);
+            document["textures"] = json!(builder.textures);
+        }
+
+        if !lights.is_empty() {
+            document["extensionsUsed"] = json!(["KHR_lights_punctual"]);
+            document["extensions"] = json!({ "KHR_lights_punctual": { "lights": lights } });
+        }
+
+        let json = serde_json::to_vec(&document).context("Failed to serialize glTF")?;
+
+        std::fs::write(path.as_ref(), glb(json, builder.buffer)).context(format!(
+            "Failed to write glTF to path: {}",
+            path.as_ref().display()
+        ))
+    }
+}
+
+/// Pack the JSON document and the binary buffer into a GLB container
+fn glb(mut json: Vec<u8>, mut bin: Vec<u8>) -> Vec<u8> {
+    // chunks are padded to 4 bytes, JSON with spaces
+    json.resize(json.len().next_multiple_of(4), b' ');
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let length = 12 + 8 + json.len() + 8 + bin.len();
+
+    let mut glb = Vec::with_capacity(length);
+    glb.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(length as u32).to_le_bytes());
+
+    for (kind, chunk) in [(CHUNK_JSON, json), (CHUNK_BIN, bin)] {
+        glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&kind.to_le_bytes());
+        glb.extend_from_slice(&chunk);
+    }
+
+    glb
+}
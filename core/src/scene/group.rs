@@ -0,0 +1,192 @@
+use super::Scene;
+use nalgebra::{Affine3, Isometry3, Scale3, Translation3, UnitQuaternion};
+
+/// Transform node without geometry that objects and other groups can be parented to,
+/// the transforms of all parents are composed with the transform of a child
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub translation: Translation3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub scale: Scale3<f32>,
+    /// Index of the parent group
+    pub parent: Option<usize>,
+}
+
+impl Group {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            translation: Translation3::identity(),
+            rotation: UnitQuaternion::identity(),
+            scale: Scale3::identity(),
+            parent: None,
+        }
+    }
+
+    /// Transform relative to the parent group
+    #[must_use]
+    pub fn local_transform(&self) -> Affine3<f32> {
+        Affine3::from_matrix_unchecked(
+            Isometry3::from_parts(self.translation, self.rotation).to_homogeneous()
+                * self.scale.to_homogeneous(),
+        )
+    }
+}
+
+impl Scene {
+    /// Whether the group is the ancestor or one of its descendants
+    #[must_use]
+    pub fn is_descendant(&self, group: usize, ancestor: usize) -> bool {
+        let mut current = Some(group);
+        // a group can't have more ancestors than there are groups, guards against cycles
+        for _ in 0..=self.groups.len() {
+            match current {
+                Some(g) if g == ancestor => return true,
+                Some(g) => current = self.groups.get(g).and_then(|g| g.parent),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Check that all parents exist and that no group is its own ancestor
+    ///
+    /// # Errors
+    ///
+    /// Fails with the first missing parent or group that is its own ancestor
+    pub fn validate_hierarchy(&self) -> anyhow::Result<()> {
+        let parents = self
+            .groups
+            .iter()
+            .filter_map(|g| g.parent)
+            .chain(self.objects.iter().filter_map(|o| o.parent));
+        for parent in parents {
+            if parent >= self.groups.len() {
+                anyhow::bail!("Parent group {parent} does not exist");
+            }
+        }
+
+        for (i, group) in self.groups.iter().enumerate() {
+            if group.parent.is_some_and(|p| self.is_descendant(p, i)) {
+                anyhow::bail!("Group {} is its own ancestor", group.name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// World transform of a group, composed from the transforms of its ancestors
+    #[must_use]
+    pub fn group_transform(&self, group: usize) -> Affine3<f32> {
+        let mut transform = Affine3::identity();
+        let mut current = Some(group);
+        for _ in 0..self.groups.len() {
+            let Some(g) = current.and_then(|g| self.groups.get(g)) else {
+                break;
+            };
+            transform = g.local_transform() * transform;
+            current = g.parent;
+        }
+        transform
+    }
+
+    /// Pass the world transforms of the groups on to their objects,
+    /// needs to be called after the hierarchy or a group transform changed
+    pub fn update_transforms(&mut self) {
+        let transforms = (0..self.groups.len())
+            .map(|g| self.group_transform(g))
+            .collect::<Vec<_>>();
+
+        for object in &mut self.objects {
+            object.set_parent_transform(
+                object
+                    .parent
+                    .and_then(|p| transforms.get(p).copied())
+                    .unwrap_or_else(Affine3::identity),
+            );
+        }
+    }
+
+    /// Remove a group, its children move up to the parent of the removed group
+    pub fn remove_group(&mut self, index: usize) {
+        let removed = self.groups.remove(index);
+
+        let reparent = |parent: &mut Option<usize>| {
+            *parent = match *parent {
+                Some(p) if p == index => removed.parent,
+                other => other,
+            }
+            // indices after the removed group shift down
+            .map(|p| if p > index { p - 1 } else { p });
+        };
+
+        self.groups.iter_mut().for_each(|g| reparent(&mut g.parent));
+        self.objects
+            .iter_mut()
+            .for_each(|o| reparent(&mut o.parent));
+
+        self.update_transforms();
+    }
+}
+
+mod yaml {
+    use super::Group;
+    use nalgebra::{Point3, Scale3, Translation3, UnitQuaternion, Vector3};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct GroupDef {
+        pub name: String,
+        #[serde(with = "super::super::yaml::point")]
+        pub position: Point3<f32>,
+        #[serde(with = "super::super::yaml::vector")]
+        pub rotation: Vector3<f32>,
+        #[serde(with = "super::super::yaml::vector")]
+        pub scale: Vector3<f32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub parent: Option<usize>,
+    }
+
+    impl<'de> Deserialize<'de> for Group {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            GroupDef::deserialize(deserializer).map(|yaml_group| Self {
+                name: yaml_group.name,
+                translation: Translation3::from(yaml_group.position.coords),
+                rotation: UnitQuaternion::from_euler_angles(
+                    yaml_group.rotation.x.to_radians(),
+                    yaml_group.rotation.y.to_radians(),
+                    yaml_group.rotation.z.to_radians(),
+                ),
+                scale: Scale3::from(yaml_group.scale),
+                parent: yaml_group.parent,
+            })
+        }
+    }
+
+    impl Serialize for Group {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            let rotation = self.rotation.euler_angles();
+
+            GroupDef {
+                name: self.name.clone(),
+                position: self.translation.vector.into(),
+                rotation: Vector3::new(
+                    rotation.0.to_degrees(),
+                    rotation.1.to_degrees(),
+                    rotation.2.to_degrees(),
+                ),
+                scale: self.scale.vector,
+                parent: self.parent,
+            }
+            .serialize(serializer)
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::{MaterialOverride, Scene, SceneError};
+use anyhow::Context;
+use log::{info, warn};
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::Path};
+
+/// Material library file, with the same `materials` section as a scene
+#[derive(Deserialize)]
+struct Library {
+    materials: BTreeMap<String, MaterialOverride>,
+}
+
+impl Scene {
+    /// Named materials of the library file, overridden by the ones defined in the scene
+    #[must_use]
+    pub fn library(&self) -> BTreeMap<String, MaterialOverride> {
+        let mut library = self.library_materials.clone();
+        library.extend(self.materials.clone());
+        library
+    }
+
+    /// Material references that nothing defines, of the models and the named materials,
+    /// the objects are rendered with the default material instead
+    #[must_use]
+    pub fn material_errors(&self) -> Vec<SceneError> {
+        let library = self.library();
+
+        self.objects
+            .iter()
+            .flat_map(|object| {
+                let model =
+                    object
+                        .unknown_materials()
+                        .iter()
+                        .map(|material| SceneError::UnknownMaterial {
+                            path: object.path.clone(),
+                            object: object.name.clone(),
+                            material: material.clone(),
+                        });
+                // named materials are defined in the library file, or in the scene without one
+                let named = object
+                    .material
+                    .as_ref()
+                    .filter(|material| !library.contains_key(*material))
+                    .map(|material| SceneError::UnknownMaterial {
+                        path: self
+                            .material_library
+                            .clone()
+                            .unwrap_or_else(|| self.path.clone()),
+                        object: object.name.clone(),
+                        material: material.clone(),
+                    });
+
+                model.chain(named)
+            })
+            .collect()
+    }
+
+    /// Load the materials of the referenced material library file
+    ///
+    /// # Errors
+    ///
+    /// Fails if the library can't be read or parsed, the library materials are empty then
+    pub fn load_material_library(&mut self) -> anyhow::Result<()> {
+        self.library_materials.clear();
+
+        let Some(path) = &self.material_library else {
+            return Ok(());
+        };
+
+        let file = self
+            .path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(path);
+        let content = std::fs::read_to_string(&file).context(format!(
+            "Failed to read material library: {}",
+            file.display()
+        ))?;
+        let library = serde_yml::from_str::<Library>(&content).context(format!(
+            "Failed to parse material library: {}",
+            file.display()
+        ))?;
+
+        // textures are relative to the library, make them relative to the scene
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        self.library_materials = library
+            .materials
+            .into_iter()
+            .map(|(name, mut material)| {
+                if let Some(texture) = &mut material.diffuse_texture {
+                    *texture = directory.join(&*texture);
+                }
+                (name, material)
+            })
+            .collect();
+
+        info!(
+            "Loaded {} materials from {}",
+            self.library_materials.len(),
+            file.display()
+        );
+
+        Ok(())
+    }
+
+    /// Resolve the materials of all objects, needs to be called after the library changed
+    pub fn apply_materials(&mut self) {
+        let library = self.library();
+        let scene_directory = self
+            .path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
+
+        for object in &mut self.objects {
+            let material = object.material.as_ref().and_then(|name| {
+                let material = library.get(name);
+                if material.is_none() {
+                    warn!("Material {} of {} not found in library", name, object.name);
+                }
+                material
+            });
+
+            object.apply_materials(material, &scene_directory);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use super::Color;
+use nalgebra::Point3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Light {
+    /// Optional display name, lights are numbered otherwise
+    pub name: Option<String>,
+    pub position: Point3<f32>,
+    pub color: Color,
+    pub intensity: f32,
+    /// Disabled lights are kept in the scene but don't light it
+    pub enabled: bool,
+    /// Ids of the only objects the light illuminates, all objects if empty
+    pub include: Vec<u64>,
+    /// Ids of objects the light doesn't illuminate
+    pub exclude: Vec<u64>,
+}
+
+impl Light {
+    /// Whether the light illuminates the object of the id, it casts shadows either way
+    #[must_use]
+    pub fn illuminates(&self, object: u64) -> bool {
+        (self.include.is_empty() || self.include.contains(&object))
+            && !self.exclude.contains(&object)
+    }
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            name: None,
+            position: Point3::origin(),
+            color: Color::zeros(),
+            intensity: 0.0,
+            enabled: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+mod yaml {
+    use super::super::Color;
+    use super::Light;
+    use nalgebra::Point3;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct LightDef {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub name: Option<String>,
+        #[serde(with = "super::super::yaml::point")]
+        pub position: Point3<f32>,
+        #[serde(with = "super::super::yaml::color", rename = "Ke")]
+        pub ke: Color,
+        pub intensity: f32,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        pub disabled: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub include: Vec<u64>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub exclude: Vec<u64>,
+    }
+
+    impl<'de> Deserialize<'de> for Light {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            LightDef::deserialize(deserializer).map(|yaml_light| Self {
+                name: yaml_light.name,
+                position: yaml_light.position,
+                color: yaml_light.ke.try_normalize(0.0).unwrap_or_default(),
+                intensity: yaml_light.intensity,
+                enabled: !yaml_light.disabled,
+                include: yaml_light.include,
+                exclude: yaml_light.exclude,
+            })
+        }
+    }
+
+    impl Serialize for Light {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            LightDef {
+                name: self.name.clone(),
+                position: self.position,
+                ke: self.color,
+                intensity: self.intensity,
+                disabled: !self.enabled,
+                include: self.include.clone(),
+                exclude: self.exclude.clone(),
+            }
+            .serialize(serializer)
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use super::{bvh::Aabb, triangle::Triangle};
+use nalgebra::{Point3, Vector3};
+use std::collections::HashMap;
+
+/// Meshes with more triangles get a decimated level of detail for the preview
+const THRESHOLD: usize = 50_000;
+/// Cells of the clustering grid along the longest side of the bounding box
+const CELLS: f32 = 64.0;
+
+/// Decimated copy of a large mesh, none for small meshes or if decimating doesn't help
+pub fn level_of_detail(triangles: &[Triangle], bounds: &Aabb) -> Option<Vec<Triangle>> {
+    if triangles.len() <= THRESHOLD {
+        return None;
+    }
+
+    let lod = decimate(triangles, bounds);
+    (lod.len() < triangles.len() / 2).then_some(lod)
+}
+
+/// Vertex clustering, the vertices in a cell of a grid are merged into their average position
+/// and triangles with two vertices in the same cell are dropped
+fn decimate(triangles: &[Triangle], bounds: &Aabb) -> Vec<Triangle> {
+    let cell = bounds.size().max() / CELLS;
+    if cell <= 0.0 {
+        return triangles.to_vec();
+    }
+
+    let key = |p: &Point3<f32>| ((p - bounds.min) / cell).map(|c| c as u32);
+
+    let mut clusters = HashMap::<Vector3<u32>, (Vector3<f32>, u32)>::new();
+    for t in triangles {
+        for p in [&t.a, &t.b, &t.c] {
+            let cluster = clusters
+                .entry(key(p))
+                .or_insert_with(|| (Vector3::zeros(), 0));
+            cluster.0 += p.coords;
+            cluster.1 += 1;
+        }
+    }
+
+    let position = |key: &Vector3<u32>| {
+        let (sum, count) = clusters[key];
+        Point3::from(sum / count as f32)
+    };
+
+    triangles
+        .iter()
+        .filter_map(|t| {
+            let (a, b, c) = (key(&t.a), key(&t.b), key(&t.c));
+            if a == b || b == c || a == c {
+                return None;
+            }
+
+            // the normals are kept, the shading only has to be close at a distance
+            Some(Triangle::new(
+                position(&a),
+                position(&b),
+                position(&c),
+                t.a_normal,
+                t.b_normal,
+                t.c_normal,
+                t.a_uv,
+                t.b_uv,
+                t.c_uv,
+                t.material_index,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,121 @@
+use super::Color;
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+
+#[derive(Debug, Clone, Default)]
+pub struct Material {
+    pub name: String,
+    pub diffuse_color: Option<Color>,
+    pub specular_color: Option<Color>,
+    pub specular_exponent: Option<f32>,
+    pub diffuse_texture: Option<Arc<RgbImage>>,
+    pub illumination_model: IlluminationModel,
+    pub dissolve: Option<f32>,
+    pub refraction_index: Option<f32>,
+}
+
+/**
+0. Color on and Ambient off
+1. Color on and Ambient on
+2. Highlight on
+3. Reflection on and Ray trace on
+4. Transparency: Glass on, Reflection: Ray trace on
+5. Reflection: Fresnel on and Ray trace on
+6. Transparency: Refraction on, Reflection: Fresnel off and Ray trace on
+7. Transparency: Refraction on, Reflection: Fresnel on and Ray trace on
+8. Reflection on and Ray trace off
+9. Transparency: Glass on, Reflection: Ray trace off
+10. Casts shadows onto invisible surfaces
+    <http://www.paulbourke.net/dataformats/mtl/> <https://en.wikipedia.org/wiki/Wavefront_.obj_file>
+*/
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IlluminationModel(i32);
+
+impl IlluminationModel {
+    pub fn from_i32(i: i32) -> Option<Self> {
+        if (0..=10).contains(&i) {
+            Some(Self(i))
+        } else {
+            None
+        }
+    }
+
+    pub const fn as_i32(self) -> i32 {
+        self.0
+    }
+
+    pub const fn specular(self) -> bool {
+        self.0 == 2
+    }
+
+    pub const fn reflection(self) -> bool {
+        self.0 == 3 || self.0 == 4
+    }
+
+    pub const fn transparency(self) -> bool {
+        self.0 == 6 || self.0 == 7
+    }
+}
+
+/// Material properties set in the scene file instead of the MTL,
+/// unset properties keep the value from the MTL.
+///
+/// Also used for the named materials of the material library,
+/// which are applied on top of a default material
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MaterialOverride {
+    #[serde(
+        rename = "Kd",
+        default,
+        with = "super::yaml::optional_color",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub diffuse_color: Option<Color>,
+    #[serde(
+        rename = "Ks",
+        default,
+        with = "super::yaml::optional_color",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub specular_color: Option<Color>,
+    #[serde(rename = "Ns", default, skip_serializing_if = "Option::is_none")]
+    pub specular_exponent: Option<f32>,
+    /// Texture file relative to the scene
+    #[serde(rename = "map_Kd", default, skip_serializing_if = "Option::is_none")]
+    pub diffuse_texture: Option<PathBuf>,
+    #[serde(rename = "illum", default, skip_serializing_if = "Option::is_none")]
+    pub illumination_model: Option<i32>,
+    /// Opacity like in the MTL, 1 is opaque
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    pub dissolve: Option<f32>,
+    #[serde(rename = "Ni", default, skip_serializing_if = "Option::is_none")]
+    pub refraction_index: Option<f32>,
+}
+
+impl MaterialOverride {
+    /// Apply everything but the texture, which has to be loaded by the object
+    pub fn apply(&self, material: &mut Material) {
+        if let Some(color) = self.diffuse_color {
+            material.diffuse_color = Some(color);
+        }
+        if let Some(color) = self.specular_color {
+            material.specular_color = Some(color);
+        }
+        if let Some(exponent) = self.specular_exponent {
+            material.specular_exponent = Some(exponent);
+        }
+        if let Some(model) = self
+            .illumination_model
+            .and_then(IlluminationModel::from_i32)
+        {
+            material.illumination_model = model;
+        }
+        if let Some(d) = self.dissolve {
+            material.dissolve = Some(1.0 - d);
+        }
+        if let Some(ni) = self.refraction_index {
+            material.refraction_index = Some(ni);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use log::info;
+use serde_yml::{Mapping, Value};
+
+/// Version of the scene format written by this build
+pub const SCENE_VERSION: u64 = 2;
+
+/// Upgrades a scene from the version at its index to the next one
+type Migration = fn(&mut Mapping) -> anyhow::Result<()>;
+
+const MIGRATIONS: [Migration; SCENE_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Scenes without a version field are version 0,
+/// an empty `extraArgs` now means default settings
+#[allow(clippy::unnecessary_wraps)] // every migration is a `Migration`
+fn v0_to_v1(scene: &mut Mapping) -> anyhow::Result<()> {
+    if scene.get("extraArgs").is_some_and(Value::is_null) {
+        scene.remove("extraArgs");
+    }
+
+    Ok(())
+}
+
+/// A scene can have multiple cameras, the single `camera` becomes the first of `cameras`
+fn v1_to_v2(scene: &mut Mapping) -> anyhow::Result<()> {
+    let camera = scene
+        .remove("camera")
+        .ok_or_else(|| anyhow::anyhow!("Scene has no camera"))?;
+
+    scene.insert("cameras".into(), Value::Sequence(vec![camera]));
+    scene.insert("activeCamera".into(), 0.into());
+
+    Ok(())
+}
+
+/// Upgrade a parsed scene to the current version in place
+pub fn migrate(scene: &mut Value) -> anyhow::Result<()> {
+    let scene = scene
+        .as_mapping_mut()
+        .ok_or_else(|| anyhow::anyhow!("Scene is not a mapping"))?;
+
+    let version = match scene.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("Invalid scene version: {version:?}"))?,
+    };
+
+    if version > SCENE_VERSION {
+        anyhow::bail!(
+            "Scene version {version} is newer than the supported version {SCENE_VERSION}"
+        );
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        info!("Migrating scene from version {} to {}", from, from + 1);
+        migration(scene)?;
+    }
+
+    scene.insert("version".into(), SCENE_VERSION.into());
+
+    Ok(())
+}
+
+/// Add the current version in front of a serialized scene
+pub fn with_version(scene: Value) -> Value {
+    let mut versioned = Mapping::new();
+    versioned.insert("version".into(), SCENE_VERSION.into());
+
+    if let Value::Mapping(scene) = scene {
+        versioned.extend(scene);
+    }
+
+    Value::Mapping(versioned)
+}
@@ -0,0 +1,439 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use log::warn;
+use nalgebra::Vector3;
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+use self::object::Mesh;
+use crate::profiler;
+
+pub use self::{
+    camera::{Camera, Projection},
+    error::SceneError,
+    files::provide_file,
+    group::Group,
+    light::Light,
+    material::{Material, MaterialOverride},
+    object::{model_files, Import, Object},
+    settings::{PixelFilter, SectionPlane, Settings, ShadingMode},
+    skybox::Skybox,
+    snippet::{CameraPose, Transform},
+    template::Template,
+    validation::{Severity, ValidationReport},
+};
+
+mod bundle;
+mod bvh;
+mod camera;
+mod error;
+mod files;
+mod gltf;
+mod group;
+mod library;
+mod light;
+mod lod;
+mod material;
+mod migration;
+mod obj_export;
+mod object;
+mod primitive;
+mod settings;
+mod skybox;
+mod snippet;
+mod stl;
+mod template;
+mod triangle;
+mod validation;
+mod yaml;
+
+pub type Color = Vector3<f32>;
+
+/// Meshes and textures are shared behind `Arc`s,
+/// so cloning a scene for rendering is cheap
+#[derive(Debug, Clone, Serialize)]
+pub struct Scene {
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(rename = "models")]
+    pub objects: Vec<Object>,
+    /// Groups the objects can be parented to, may be nested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<Group>,
+    #[serde(rename = "pointLights")]
+    pub lights: Vec<Light>,
+    /// Shots of the scene, the active one is used by the preview and the render
+    pub cameras: Vec<Camera>,
+    #[serde(rename = "activeCamera")]
+    pub active_camera: usize,
+    #[serde(rename = "extraArgs", default)]
+    pub settings: Settings,
+    /// Named materials objects can use instead of the materials of their model
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub materials: BTreeMap<String, MaterialOverride>,
+    /// File with more named materials, relative to the scene
+    #[serde(
+        rename = "materialLibrary",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub material_library: Option<PathBuf>,
+    /// Materials loaded from the material library file
+    #[serde(skip)]
+    pub library_materials: BTreeMap<String, MaterialOverride>,
+    /// Light that temporarily lights the scene alone, to judge its contribution
+    #[serde(skip)]
+    pub solo_light: Option<usize>,
+}
+
+/// Skybox images next to the scene take precedence over the working directory
+fn resolve_skybox(map: &mut serde_yml::Value, scene: &Path) {
+    if let Some(serde_yml::Value::String(path)) = map
+        .get_mut("extraArgs")
+        .and_then(|s| s.get_mut("skybox"))
+        .and_then(|s| s.get_mut("Path"))
+    {
+        let relative = scene
+            .parent()
+            .map(|parent| parent.join(path.as_str()))
+            .filter(|p| Path::new(path.as_str()).is_relative() && p.exists());
+
+        if let Some(relative) = relative {
+            *path = relative.to_string_lossy().to_string();
+        }
+    }
+}
+
+struct WithRelativePath<P: AsRef<std::path::Path>>(P);
+
+impl<'de, P: AsRef<std::path::Path> + std::marker::Sync> serde::de::DeserializeSeed<'de>
+    for WithRelativePath<P>
+{
+    type Value = Scene;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut map = <serde_yml::Value as serde::Deserialize>::deserialize(deserializer)?;
+
+        let objects = object::WithRelativePath(self.0.as_ref())
+            .deserialize(
+                map.get("models")
+                    .ok_or_else(|| serde::de::Error::missing_field("models"))?,
+            )
+            .map_err(serde::de::Error::custom)?;
+
+        let groups = map
+            .get("groups")
+            .map(Vec::<Group>::deserialize)
+            .transpose()
+            .map_err(serde::de::Error::custom)?
+            .unwrap_or_default();
+
+        let materials = map
+            .get("materials")
+            .map(BTreeMap::<String, MaterialOverride>::deserialize)
+            .transpose()
+            .map_err(serde::de::Error::custom)?
+            .unwrap_or_default();
+
+        let material_library = map
+            .get("materialLibrary")
+            .map(PathBuf::deserialize)
+            .transpose()
+            .map_err(serde::de::Error::custom)?;
+
+        let lights = map
+            .get("pointLights")
+            .ok_or_else(|| serde::de::Error::missing_field("pointLights"))?
+            .as_sequence()
+            .ok_or_else(|| {
+                serde::de::Error::invalid_type(serde::de::Unexpected::Map, &"a sequence")
+            })?
+            .iter()
+            .map(Light::deserialize)
+            .collect::<Result<Vec<Light>, serde_yml::Error>>()
+            .map_err(serde::de::Error::custom)?;
+
+        let cameras = map
+            .get("cameras")
+            .ok_or_else(|| serde::de::Error::missing_field("cameras"))?;
+        let cameras = Vec::<Camera>::deserialize(cameras).map_err(serde::de::Error::custom)?;
+        if cameras.is_empty() {
+            return Err(serde::de::Error::invalid_length(0, &"at least one camera"));
+        }
+
+        let active_camera = map
+            .get("activeCamera")
+            .map(usize::deserialize)
+            .transpose()
+            .map_err(serde::de::Error::custom)?
+            .unwrap_or_default();
+        if active_camera >= cameras.len() {
+            return Err(serde::de::Error::custom(format!(
+                "Active camera {active_camera} does not exist"
+            )));
+        }
+
+        resolve_skybox(&mut map, self.0.as_ref());
+
+        // dont fail if extraArgs is missing but warn
+        let settings = map
+            .get("extraArgs")
+            .map(Settings::deserialize)
+            .transpose()
+            .map_err(|e| {
+                warn!("Failed to deserialize extraArgs: {e}");
+                e
+            })
+            .unwrap_or_default()
+            .unwrap_or_default();
+
+        let mut scene = Scene {
+            path: self.0.as_ref().to_path_buf(),
+            objects,
+            groups,
+            lights,
+            cameras,
+            active_camera,
+            settings,
+            materials,
+            material_library,
+            library_materials: BTreeMap::new(),
+            solo_light: None,
+        };
+
+        scene
+            .validate_hierarchy()
+            .map_err(serde::de::Error::custom)?;
+        scene.update_transforms();
+        scene.assign_object_ids();
+
+        // a missing library leaves the objects with the materials of their models
+        scene
+            .load_material_library()
+            .unwrap_or_else(|e| warn!("{e:?}"));
+        scene.apply_materials();
+
+        Ok(scene)
+    }
+}
+
+impl Scene {
+    /// Names of the render passes, the ambient light with the skybox
+    /// and then one pass per active light
+    #[must_use]
+    pub fn light_passes(&self) -> Vec<String> {
+        let lights = self.active_light_indices().map(|i| {
+            self.lights[i]
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Light {}", i + 1))
+        });
+
+        std::iter::once(String::from("Ambient"))
+            .chain(lights)
+            .collect()
+    }
+
+    /// Bake the meshes of visible objects into world space for rendering,
+    /// meshes shared by several objects stay instanced and transform the rays instead,
+    /// as a baked copy per object would cost the memory instancing saves.
+    /// Bake the scene of a render only, the copies live as long as the scene
+    pub fn bake(&mut self) {
+        if !self.settings.bake_meshes {
+            return;
+        }
+
+        let mut users = HashMap::<*const Mesh, usize>::new();
+        for object in self.objects.iter().filter(|o| o.visible_in_render) {
+            *users.entry(Arc::as_ptr(&object.mesh)).or_default() += 1;
+        }
+
+        self.objects
+            .iter_mut()
+            .filter(|o| o.visible_in_render && users[&Arc::as_ptr(&o.mesh)] == 1)
+            .for_each(Object::bake);
+    }
+
+    /// Time it took to build the BVHs of the objects, meshes shared by objects count once
+    #[must_use]
+    pub fn bvh_build_time(&self) -> Duration {
+        let mut meshes = self
+            .objects
+            .iter()
+            .map(|o| (Arc::as_ptr(&o.mesh), o.mesh.build_time))
+            .collect::<Vec<_>>();
+        meshes.sort_unstable_by_key(|&(mesh, _)| mesh);
+        meshes.dedup_by_key(|&mut (mesh, _)| mesh);
+        meshes.into_iter().map(|(_, time)| time).sum()
+    }
+
+    /// Camera used by the preview and the render
+    #[must_use]
+    pub fn camera(&self) -> &Camera {
+        &self.cameras[self.active_camera]
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.cameras[self.active_camera]
+    }
+
+    /// Give objects without an id, or with the id of an object before them, a new one.
+    /// Needs to be called after objects were added or duplicated
+    pub fn assign_object_ids(&mut self) {
+        let mut next = self.objects.iter().map(|o| o.id).max().unwrap_or(0) + 1;
+        let mut ids = HashSet::new();
+
+        for object in &mut self.objects {
+            if object.id == 0 || !ids.insert(object.id) {
+                object.id = next;
+                ids.insert(next);
+                next += 1;
+            }
+        }
+    }
+
+    /// Lights that contribute to the scene, the soloed light or the enabled ones
+    pub fn active_lights(&self) -> impl Iterator<Item = &Light> {
+        self.active_light_indices().map(|i| &self.lights[i])
+    }
+
+    /// Indices of the active lights in the lights of the scene
+    pub fn active_light_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let solo = self.solo_light.filter(|&s| s < self.lights.len());
+        self.lights
+            .iter()
+            .enumerate()
+            .filter(move |&(i, light)| solo.map_or(light.enabled, |s| s == i))
+            .map(|(i, _)| i)
+    }
+
+    /// Remove a camera, the last camera of a scene can't be removed
+    pub fn remove_camera(&mut self, index: usize) {
+        if self.cameras.len() <= 1 {
+            return;
+        }
+
+        self.cameras.remove(index);
+        // keep the active camera if it wasn't removed
+        if self.active_camera > index || self.active_camera == self.cameras.len() {
+            self.active_camera -= 1;
+        }
+    }
+
+    /// Load a scene file or extract and load a scene bundle
+    ///
+    /// # Errors
+    ///
+    /// Fails if the scene can't be loaded or the bundle can't be extracted
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
+        if path
+            .as_ref()
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+        {
+            Self::open_bundle(path)
+        } else {
+            Ok(Self::load(path)?)
+        }
+    }
+
+    /// Load a YAML or JSON scene file with its objects, materials and skybox
+    ///
+    /// # Errors
+    ///
+    /// Fails with the [`SceneError`] of the scene file or the first asset that can't be loaded
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, SceneError> {
+        let _scope = profiler::scope("scene load");
+        let path = path.as_ref();
+        let malformed = |line, message| SceneError::Malformed {
+            path: path.to_path_buf(),
+            line,
+            message,
+        };
+
+        let s = std::fs::read_to_string(path).map_err(|e| SceneError::MissingFile {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+
+        // JSON scenes share the model of the YAML scenes
+        let mut value = if is_json(path) {
+            serde_json::from_str::<serde_yml::Value>(&s)
+                .map_err(|e| malformed(Some(e.line()), e.to_string()))?
+        } else {
+            serde_yml::from_str::<serde_yml::Value>(&s)
+                .map_err(|e| malformed(e.location().map(|l| l.line()), e.to_string()))?
+        };
+
+        // upgrade scenes saved by older versions
+        migration::migrate(&mut value).map_err(|e| malformed(None, format!("{e:#}")))?;
+
+        // errors of models are kept aside, serde only passes them on as text
+        error::take();
+        WithRelativePath(path)
+            .deserialize(value)
+            .map_err(|e| error::take().unwrap_or_else(|| malformed(None, e.to_string())))
+    }
+
+    /// Serialize the scene as YAML tagged with the current format version
+    ///
+    /// # Errors
+    ///
+    /// Fails if the scene can't be serialized
+    pub fn to_yaml(&self) -> anyhow::Result<String> {
+        let value = serde_yml::to_value(self).context("Failed to serialize scene")?;
+        serde_yml::to_string(&migration::with_version(value)).context("Failed to serialize scene")
+    }
+
+    /// Serialize the scene as JSON tagged with the current format version
+    ///
+    /// # Errors
+    ///
+    /// Fails if the scene can't be serialized
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        let value = serde_yml::to_value(self).context("Failed to serialize scene")?;
+        serde_json::to_string_pretty(&migration::with_version(value))
+            .context("Failed to serialize scene")
+    }
+
+    /// Save the scene to its path, as JSON if the extension is `.json`, else as YAML
+    ///
+    /// # Errors
+    ///
+    /// Fails if the scene can't be serialized or written
+    pub fn save(&self) -> anyhow::Result<()> {
+        let content = if is_json(&self.path) {
+            self.to_json()?
+        } else {
+            self.to_yaml()?
+        };
+
+        std::fs::write(&self.path, content).context(format!(
+            "Failed to save scene to path: {}",
+            self.path.display()
+        ))
+    }
+}
+
+fn is_json(path: &std::path::Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Whether the path has the extension of a scene file
+#[must_use]
+pub fn is_scene_file(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        ["yaml", "yml", "json"]
+            .iter()
+            .any(|e| ext.eq_ignore_ascii_case(e))
+    })
+}
@@ -0,0 +1,183 @@
+use super::{
+    material::Material,
+    object::{is_mirrored, normal_matrix},
+    Object, Scene,
+};
+use anyhow::Context;
+use image::RgbImage;
+use nalgebra::Affine3;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+/// Replace characters that would break OBJ and MTL statements
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect()
+}
+
+impl Scene {
+    /// Merge all objects with their transforms applied into a single OBJ file,
+    /// materials are written to an MTL file and textures to PNGs next to it
+    ///
+    /// # Errors
+    ///
+    /// Fails if the OBJ, the MTL or a texture can't be written
+    pub fn export_obj<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        write_obj(
+            path.as_ref(),
+            self.objects.iter().map(|o| (o, o.transform())),
+        )
+    }
+}
+
+impl Object {
+    /// Save the geometry and materials of the object without its transform
+    ///
+    /// # Errors
+    ///
+    /// Fails if the OBJ, the MTL or a texture can't be written
+    pub fn save_obj<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        write_obj(path.as_ref(), std::iter::once((self, Affine3::identity())))
+    }
+}
+
+fn write_obj<'a>(
+    path: &Path,
+    objects: impl Iterator<Item = (&'a Object, Affine3<f32>)>,
+) -> anyhow::Result<()> {
+    let directory = path.parent().unwrap_or_else(|| Path::new("."));
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "scene".to_string(), |s| s.to_string_lossy().to_string());
+    let mtl_name = format!("{stem}.mtl");
+
+    let mut obj = BufWriter::new(
+        File::create(path).context(format!("Failed to create obj: {}", path.display()))?,
+    );
+    let mut mtl = BufWriter::new(
+        File::create(directory.join(&mtl_name))
+            .context(format!("Failed to create mtl: {mtl_name}"))?,
+    );
+    let mut textures = HashMap::<*const RgbImage, String>::new();
+
+    writeln!(obj, "# exported by Trayracer {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(obj, "mtllib {mtl_name}")?;
+
+    // OBJ indices are 1-based and global over the file
+    let mut offset = 1;
+
+    for (i, (object, transform)) in objects.enumerate() {
+        let normal_matrix = normal_matrix(&transform);
+        // mirrored objects would be inside out for viewers culling back faces
+        let mirrored = is_mirrored(&transform);
+
+        let name = format!("{}_{}", sanitize(&object.name), i);
+        writeln!(obj, "o {name}")?;
+
+        let material_names = object
+            .materials
+            .iter()
+            .map(|m| {
+                let material_name = format!("{name}_{}", sanitize(&m.name));
+                write_material(&mut mtl, &material_name, m, directory, &stem, &mut textures)?;
+                Ok(material_name)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        for t in &object.mesh.triangles {
+            for p in [t.a, t.b, t.c] {
+                let p = transform.transform_point(&p);
+                writeln!(obj, "v {} {} {}", p.x, p.y, p.z)?;
+            }
+            for n in [t.a_normal, t.b_normal, t.c_normal] {
+                let n = (normal_matrix * n).try_normalize(f32::EPSILON).unwrap_or(n);
+                writeln!(obj, "vn {} {} {}", n.x, n.y, n.z)?;
+            }
+            for uv in [t.a_uv, t.b_uv, t.c_uv] {
+                writeln!(obj, "vt {} {}", uv.x, uv.y)?;
+            }
+        }
+
+        // faces grouped by material to keep the number of usemtl statements low
+        let mut faces = (0..object.mesh.triangles.len()).collect::<Vec<_>>();
+        faces.sort_by_key(|&f| object.mesh.triangles[f].material_index);
+
+        let mut current = None;
+        for f in faces {
+            let material_index = object.mesh.triangles[f].material_index;
+            if current != Some(material_index) {
+                if let Some(material_name) = material_index.and_then(|m| material_names.get(m)) {
+                    writeln!(obj, "usemtl {material_name}")?;
+                }
+                current = Some(material_index);
+            }
+
+            let v = offset + f * 3;
+            let (b, c) = if mirrored {
+                (v + 2, v + 1)
+            } else {
+                (v + 1, v + 2)
+            };
+            writeln!(obj, "f {v}/{v}/{v} {b}/{b}/{b} {c}/{c}/{c}")?;
+        }
+
+        offset += object.mesh.triangles.len() * 3;
+    }
+
+    obj.flush()?;
+    mtl.flush()?;
+
+    Ok(())
+}
+
+fn write_material(
+    mtl: &mut impl Write,
+    name: &str,
+    material: &Material,
+    directory: &Path,
+    stem: &str,
+    textures: &mut HashMap<*const RgbImage, String>,
+) -> anyhow::Result<()> {
+    writeln!(mtl, "newmtl {name}")?;
+
+    if let Some(kd) = material.diffuse_color {
+        writeln!(mtl, "Kd {} {} {}", kd.x, kd.y, kd.z)?;
+    }
+    if let Some(ks) = material.specular_color {
+        writeln!(mtl, "Ks {} {} {}", ks.x, ks.y, ks.z)?;
+    }
+    if let Some(ns) = material.specular_exponent {
+        writeln!(mtl, "Ns {ns}")?;
+    }
+    // dissolve is stored inverted on load
+    if let Some(dissolve) = material.dissolve {
+        writeln!(mtl, "d {}", 1.0 - dissolve)?;
+    }
+    if let Some(ni) = material.refraction_index {
+        writeln!(mtl, "Ni {ni}")?;
+    }
+    writeln!(mtl, "illum {}", material.illumination_model.as_i32())?;
+
+    if let Some(texture) = &material.diffuse_texture {
+        let file = if let Some(file) = textures.get(&Arc::as_ptr(texture)) {
+            file.clone()
+        } else {
+            let file = format!("{stem}_texture_{}.png", textures.len());
+            texture
+                .save(directory.join(&file))
+                .context(format!("Failed to save texture: {file}"))?;
+            textures.insert(Arc::as_ptr(texture), file.clone());
+            file
+        };
+        writeln!(mtl, "map_Kd {file}")?;
+    }
+
+    writeln!(mtl)?;
+    Ok(())
+}
@@ -0,0 +1,1615 @@
+use super::{
+    bvh::{Aabb, Bvh, BvhNode},
+    error::{self, SceneError},
+    files, lod,
+    material::{IlluminationModel, Material, MaterialOverride},
+    stl,
+    triangle::{Intersector, Triangle},
+    Camera, Color, SectionPlane,
+};
+use crate::{
+    profiler,
+    raytracer::{stats::Stats, Hit, Ray},
+};
+use image::RgbImage;
+use log::{debug, info, warn};
+use nalgebra::{
+    Affine3, Isometry3, Matrix3, Point3, Scale3, Similarity3, Translation3, UnitQuaternion,
+    Vector2, Vector3,
+};
+use obj::{ObjMaterial, SimplePolygon};
+use ordered_float::OrderedFloat;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex, PoisonError, Weak},
+    time::{Duration, SystemTime},
+};
+use web_time::Instant;
+
+/// Immutable geometry of an object,
+/// shared between all clones of the scene
+#[derive(Debug)]
+pub struct Mesh {
+    /// Triangles with their shading data, in the order of the leaves of the BVH
+    pub triangles: Vec<Triangle>,
+    /// Positions and edges of the triangles in the same order, the only data read while
+    /// traversing, the shading data is only looked up for the closest hit
+    hot: Vec<Intersector>,
+    bvh: Bvh,
+    /// Axis aligned bounding box in object space
+    pub bounds: Aabb,
+    /// Decimated triangles of large meshes, drawn by the preview when the object is small
+    pub lod: Option<Vec<Triangle>>,
+    /// Time it took to build the BVH
+    pub build_time: Duration,
+}
+
+impl Mesh {
+    fn new(triangles: Vec<Triangle>) -> Self {
+        let start = Instant::now();
+        let (triangles, bvh) = {
+            let _scope = profiler::scope("bvh build");
+            build_ordered(triangles)
+        };
+        let build_time = start.elapsed();
+        info!(
+            "Built the BVH of {} triangles in {:.1} ms",
+            triangles.len(),
+            build_time.as_secs_f64() * 1000.0
+        );
+
+        let bounds = triangles.iter().fold(Aabb::empty(), |bounds, t| {
+            bounds.grow(&t.a).grow(&t.b).grow(&t.c)
+        });
+        let lod = lod::level_of_detail(&triangles, &bounds);
+        if let Some(lod) = &lod {
+            debug!(
+                "Decimated {} triangles to {} for the preview",
+                triangles.len(),
+                lod.len()
+            );
+        }
+
+        Self {
+            hot: triangles.iter().map(Triangle::intersector).collect(),
+            triangles,
+            bvh,
+            bounds,
+            lod,
+            build_time,
+        }
+    }
+
+    /// Copy of the triangles moved into world space with a BVH for them,
+    /// built again for small meshes and refitted for larger ones
+    fn bake(&self, transform: &Affine3<f32>) -> Arc<Self> {
+        let _scope = profiler::scope("bake mesh");
+        let start = Instant::now();
+        let normals = normal_matrix(transform);
+        let triangles = self
+            .triangles
+            .par_iter()
+            .map(|t| t.transformed_affine(transform, &normals))
+            .collect::<Vec<_>>();
+        let (triangles, bvh) = if triangles.len() <= REBUILD_TRIANGLES {
+            build_ordered(triangles)
+        } else {
+            let bvh = refit_bvh(&self.bvh, &triangles);
+            (triangles, bvh)
+        };
+        let bounds = triangles.iter().fold(Aabb::empty(), |bounds, t| {
+            bounds.grow(&t.a).grow(&t.b).grow(&t.c)
+        });
+        let build_time = start.elapsed();
+        debug!(
+            "Baked {} triangles into world space in {:.1} ms",
+            triangles.len(),
+            build_time.as_secs_f64() * 1000.0
+        );
+
+        Arc::new(Self {
+            hot: triangles.par_iter().map(Triangle::intersector).collect(),
+            triangles,
+            bvh,
+            bounds,
+            lod: None,
+            build_time,
+        })
+    }
+}
+
+/// Conversion of a model file into the scene,
+/// so exports of CAD tools with other conventions fit in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Import {
+    /// The model is Z-up and is turned to the Y-up of the scene
+    pub z_up: bool,
+    /// Scene units per unit of the model, e.g. 0.001 for a model in millimeters
+    pub unit_scale: f32,
+}
+
+impl Default for Import {
+    fn default() -> Self {
+        Self {
+            z_up: false,
+            unit_scale: 1.0,
+        }
+    }
+}
+
+impl Import {
+    /// Transform from the model file into object space
+    #[must_use]
+    pub fn transform(&self) -> Similarity3<f32> {
+        let rotation = if self.z_up {
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), -std::f32::consts::FRAC_PI_2)
+        } else {
+            UnitQuaternion::identity()
+        };
+        Similarity3::from_parts(Translation3::identity(), rotation, self.unit_scale)
+    }
+
+    /// Move the triangles of the model file into object space
+    fn apply(self, triangles: Vec<Triangle>) -> Vec<Triangle> {
+        if self == Self::default() {
+            return triangles;
+        }
+
+        let transform = self.transform();
+        triangles
+            .iter()
+            .map(|t| t.transformed(&transform))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Object {
+    /// Identifies the object in its scene, unlike the name it is unique and stays the same
+    /// when the object is renamed, lights link objects by it. 0 until the scene assigns one
+    pub id: u64,
+    pub name: String,
+    material_name: String,
+    /// File the object was loaded from, relative to the scene
+    pub path: PathBuf,
+    pub mesh: Arc<Mesh>,
+    /// Conversion applied to the mesh when it was loaded
+    pub import: Import,
+    /// Materials with the overrides applied
+    pub materials: Vec<Material>,
+    /// Materials as loaded from the MTL
+    base_materials: Arc<Vec<Material>>,
+    /// Materials the faces of the model use that its MTL doesn't define
+    unknown_materials: Vec<String>,
+    /// Material properties set in the scene, by material name
+    pub material_overrides: BTreeMap<String, MaterialOverride>,
+    /// Named material of the material library used instead of the materials of the model
+    pub material: Option<String>,
+    /// The resolved library material, also used for triangles without a material
+    library_material: Option<Material>,
+    pub translation: Translation3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub scale: Scale3<f32>,
+    /// Index of the group the object is parented to
+    pub parent: Option<usize>,
+    /// World transform of the parent group, kept up to date by `Scene::update_transforms`
+    parent_transform: Affine3<f32>,
+    /// Hidden objects stay in the scene, e.g. to work without heavy background geometry
+    pub visible_in_preview: bool,
+    pub visible_in_render: bool,
+    /// Mesh in world space for the transform it was baked with, rays are intersected with it
+    /// directly instead of being moved into object space. Only the scene of a render bakes,
+    /// the copy is dropped with it
+    world: Option<(Affine3<f32>, Arc<Mesh>)>,
+}
+
+/// Key of a decoded texture, a changed file is decoded again
+type TextureKey = (PathBuf, Option<SystemTime>);
+
+/// Decoded textures shared between all materials and objects using the same file,
+/// a texture is freed once no material holds it anymore
+static TEXTURES: LazyLock<Mutex<HashMap<TextureKey, Weak<RgbImage>>>> =
+    LazyLock::new(Mutex::default);
+
+fn load_texture<P: AsRef<Path>>(path: P) -> Result<Arc<RgbImage>, SceneError> {
+    let path = path.as_ref();
+    let key = (
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+        std::fs::metadata(path).and_then(|m| m.modified()).ok(),
+    );
+
+    let cached = TEXTURES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&key)
+        .and_then(Weak::upgrade);
+    if let Some(texture) = cached {
+        debug!("Reusing texture {}", path.display());
+        return Ok(texture);
+    }
+
+    // decode without holding the lock, models are loaded in parallel
+    let texture = Arc::new(
+        files::open_image(path)
+            .map_err(|e| texture_error(path, e))?
+            .into_rgb8(),
+    );
+
+    let mut textures = TEXTURES.lock().unwrap_or_else(PoisonError::into_inner);
+    textures.retain(|_, t| t.strong_count() > 0);
+    textures.insert(key, Arc::downgrade(&texture));
+    drop(textures);
+
+    Ok(texture)
+}
+
+/// A texture that can't be read is missing, else it can't be decoded
+pub(super) fn texture_error(path: &Path, error: image::ImageError) -> SceneError {
+    match error {
+        image::ImageError::IoError(e) => SceneError::MissingFile {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        },
+        e => SceneError::UnsupportedTexture {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        },
+    }
+}
+
+// extract filename from path and return as String
+fn filename<P: AsRef<Path>>(path: P) -> String {
+    path.as_ref()
+        .file_name()
+        .map_or_else(String::new, |s| s.to_string_lossy().to_string())
+        .split('.')
+        .next()
+        .unwrap_or("")
+        .to_string()
+        // first char to uppercase
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i == 0 { c.to_ascii_uppercase() } else { c })
+        .collect()
+}
+
+impl Object {
+    /// Load an object from a file, the format is chosen by the extension
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`SceneError::MissingFile`] if the file doesn't exist
+    /// and with [`SceneError::Model`] if it can't be parsed
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        import: Import,
+        translation: Translation3<f32>,
+        rotation: UnitQuaternion<f32>,
+        scale: Scale3<f32>,
+    ) -> Result<Self, SceneError> {
+        if !files::exists(path.as_ref()) {
+            return Err(SceneError::MissingFile {
+                path: path.as_ref().to_path_buf(),
+                message: String::from("The file does not exist"),
+            });
+        }
+
+        let import = if import.unit_scale > 0.0 {
+            import
+        } else {
+            warn!(
+                "Ignoring unit scale {}, it must be positive",
+                import.unit_scale
+            );
+            Import::default()
+        };
+
+        if path
+            .as_ref()
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("stl"))
+        {
+            Self::from_stl(path, import, translation, rotation, scale)
+        } else {
+            Self::from_obj(path, import, translation, rotation, scale)
+        }
+    }
+
+    /// Load an STL file as object without materials,
+    /// the import is applied before the BVH is built
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`SceneError::Model`] if the file can't be read or parsed
+    pub fn from_stl<P: AsRef<Path>>(
+        path: P,
+        import: Import,
+        translation: Translation3<f32>,
+        rotation: UnitQuaternion<f32>,
+        scale: Scale3<f32>,
+    ) -> Result<Self, SceneError> {
+        let triangles = stl::load(path.as_ref()).map_err(|e| SceneError::Model {
+            path: path.as_ref().to_path_buf(),
+            message: format!("{e:#}"),
+        })?;
+        let name = filename(&path);
+
+        Ok(Self {
+            id: 0,
+            material_name: name.clone(),
+            name,
+            path: path.as_ref().to_path_buf(),
+            mesh: Arc::new(Mesh::new(import.apply(triangles))),
+            import,
+            materials: Vec::new(),
+            base_materials: Arc::default(),
+            unknown_materials: Vec::new(),
+            material_overrides: BTreeMap::new(),
+            material: None,
+            library_material: None,
+            translation,
+            rotation,
+            scale,
+            parent: None,
+            parent_transform: Affine3::identity(),
+            visible_in_preview: true,
+            visible_in_render: true,
+            world: None,
+        })
+    }
+
+    /// Load an OBJ file with its materials and textures,
+    /// the import is applied before the BVH is built
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`SceneError::Model`] if the file or its materials can't be read or parsed
+    pub fn from_obj<P: AsRef<Path>>(
+        path: P,
+        import: Import,
+        translation: Translation3<f32>,
+        rotation: UnitQuaternion<f32>,
+        scale: Scale3<f32>,
+    ) -> Result<Self, SceneError> {
+        let (obj, source) = load_obj(path.as_ref()).map_err(|message| SceneError::Model {
+            path: path.as_ref().to_path_buf(),
+            message,
+        })?;
+
+        let materials = obj
+            .data
+            .material_libs
+            .iter()
+            .flat_map(|m| &m.materials)
+            .map(|m| Material {
+                name: m.name.clone(),
+                diffuse_color: m.kd.map(Color::from),
+                specular_color: m.ks.map(Color::from),
+                specular_exponent: m.ns,
+                diffuse_texture: {
+                    m.map_kd
+                        .as_deref()
+                        .and_then(|p| path.as_ref().parent().map(|pa| pa.join(p)))
+                        .and_then(|p| {
+                            load_texture(p.as_path())
+                                .map_err(|e| {
+                                    warn!("Failed to load texture from path: {}: {e}", p.display());
+                                })
+                                .ok()
+                        })
+                },
+                illumination_model: m
+                    .illum
+                    .and_then(IlluminationModel::from_i32)
+                    .unwrap_or_else(|| {
+                        warn!("Invalid illumination model: {}", m.illum.unwrap_or(-1));
+                        IlluminationModel::default()
+                    }),
+                dissolve: m.d.map(|d| 1.0 - d),
+                refraction_index: m.ni,
+            })
+            .collect::<Vec<_>>();
+        let ObjExtras { colors, smoothing } = ObjExtras::parse(&source);
+        let smoothing = Smoothing::new(&obj, smoothing);
+        let mut warnings = (0, 0, 0);
+        let mut triangles = Vec::new();
+        let mut unknown_materials = Vec::<String>::new();
+        let mut face = 0;
+        for group in obj.data.objects.iter().flat_map(|object| &object.groups) {
+            let material_index =
+                material_index(group.material.as_ref(), &materials, &mut unknown_materials);
+
+            for poly in &group.polys {
+                let smoothing_group = smoothing.group(face);
+                face += 1;
+                triangles.extend(triangulate(
+                    &obj,
+                    &colors,
+                    &smoothing,
+                    smoothing_group,
+                    poly,
+                    material_index,
+                    &mut warnings,
+                ));
+            }
+        }
+
+        if warnings.0 > 0 {
+            warn!("Computed normals for {} triangles is zero", warnings.0);
+        }
+
+        if warnings.1 > 0 {
+            warn!("No normals for {} triangles", warnings.1);
+        }
+
+        if warnings.2 > 0 {
+            warn!("No UV for {} triangles", warnings.2);
+        }
+
+        Ok(Self {
+            id: 0,
+            name: filename(&path),
+            material_name: obj
+                .data
+                .objects
+                .iter()
+                .map(|o| o.name.clone())
+                .collect::<Vec<_>>()
+                .join(", "),
+            path: path.as_ref().to_path_buf(),
+            mesh: Arc::new(Mesh::new(import.apply(triangles))),
+            import,
+            base_materials: Arc::new(materials.clone()),
+            unknown_materials,
+            materials,
+            material_overrides: BTreeMap::new(),
+            material: None,
+            library_material: None,
+            translation,
+            rotation,
+            scale,
+            parent: None,
+            parent_transform: Affine3::identity(),
+            visible_in_preview: true,
+            visible_in_render: true,
+            world: None,
+        })
+    }
+
+    /// Create another instance of this object with its own transform,
+    /// sharing the mesh and textures
+    #[must_use]
+    pub fn instance(
+        &self,
+        translation: Translation3<f32>,
+        rotation: UnitQuaternion<f32>,
+        scale: Scale3<f32>,
+    ) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+            ..self.clone()
+        }
+    }
+
+    /// Rebuild the materials from the MTL, replaced by the library material if one is used,
+    /// and apply the overrides of the object on top.
+    /// Textures of library materials and overrides are relative to the scene directory
+    pub fn apply_materials(&mut self, library: Option<&MaterialOverride>, scene_directory: &Path) {
+        let resolve = |material: &mut Material, o: &MaterialOverride| {
+            o.apply(material);
+
+            if let Some(path) = &o.diffuse_texture {
+                match load_texture(scene_directory.join(path)) {
+                    Ok(texture) => material.diffuse_texture = Some(texture),
+                    Err(e) => warn!("{e:?}"),
+                }
+            }
+        };
+
+        let library_material = library.map(|l| {
+            let mut material = Material {
+                name: self.material.clone().unwrap_or_default(),
+                ..Default::default()
+            };
+            resolve(&mut material, l);
+            material
+        });
+
+        let materials = self
+            .base_materials
+            .iter()
+            .map(|base| {
+                let mut material = library_material.as_ref().map_or_else(
+                    || base.clone(),
+                    |l| Material {
+                        name: base.name.clone(),
+                        ..l.clone()
+                    },
+                );
+                if let Some(o) = self.material_overrides.get(&base.name) {
+                    resolve(&mut material, o);
+                }
+                material
+            })
+            .collect();
+
+        self.materials = materials;
+        self.library_material = library_material;
+    }
+
+    /// Convert the mesh from the model file with other import settings,
+    /// the mesh is no longer shared with other instances
+    pub fn set_import(&mut self, import: Import) {
+        if import == self.import {
+            return;
+        }
+        if import.unit_scale <= 0.0 {
+            warn!(
+                "Ignoring unit scale {}, it must be positive",
+                import.unit_scale
+            );
+            return;
+        }
+
+        let change = import.transform() * self.import.transform().inverse();
+        let triangles = self
+            .mesh
+            .triangles
+            .iter()
+            .map(|t| t.transformed(&change))
+            .collect();
+        self.mesh = Arc::new(Mesh::new(triangles));
+        self.import = import;
+    }
+
+    /// Materials the faces of the model use that its MTL doesn't define
+    #[must_use]
+    pub fn unknown_materials(&self) -> &[String] {
+        &self.unknown_materials
+    }
+
+    /// Material of a triangle, falls back to the library material
+    #[must_use]
+    pub fn material(&self, index: Option<usize>) -> Option<&Material> {
+        index
+            .and_then(|i| self.materials.get(i))
+            .or(self.library_material.as_ref())
+    }
+
+    /// Create an object from already triangulated geometry,
+    /// used for procedurally generated meshes
+    #[must_use]
+    pub fn from_triangles(name: &str, triangles: Vec<Triangle>, materials: Vec<Material>) -> Self {
+        Self {
+            id: 0,
+            name: name.to_string(),
+            material_name: name.to_string(),
+            path: PathBuf::new(),
+            mesh: Arc::new(Mesh::new(triangles)),
+            import: Import::default(),
+            base_materials: Arc::new(materials.clone()),
+            unknown_materials: Vec::new(),
+            materials,
+            material_overrides: BTreeMap::new(),
+            material: None,
+            library_material: None,
+            translation: Translation3::identity(),
+            rotation: UnitQuaternion::identity(),
+            scale: Scale3::identity(),
+            parent: None,
+            parent_transform: Affine3::identity(),
+            visible_in_preview: true,
+            visible_in_render: true,
+            world: None,
+        }
+    }
+
+    /// Transform relative to the parent group
+    #[must_use]
+    pub fn local_transform(&self) -> Affine3<f32> {
+        Affine3::from_matrix_unchecked(
+            Isometry3::from_parts(self.translation, self.rotation).to_homogeneous()
+                * self.scale.to_homogeneous(),
+        )
+    }
+
+    /// Back to the position, rotation and scale of the model file
+    pub fn reset_transform(&mut self) {
+        self.translation = Translation3::identity();
+        self.rotation = UnitQuaternion::identity();
+        self.scale = Scale3::identity();
+    }
+
+    /// Move the center of the bounding box to the origin of the parent
+    pub fn center_at_origin(&mut self) {
+        if self.mesh.triangles.is_empty() {
+            return;
+        }
+
+        let center = self.rotation * self.scale.transform_point(&self.mesh.bounds.center());
+        self.translation = Translation3::from(-center.coords);
+    }
+
+    /// Scale uniformly so the largest side of the bounding box is one unit long,
+    /// the center of the bounding box stays in place
+    pub fn normalize_scale(&mut self) {
+        let size = self.mesh.bounds.size().max();
+        if self.mesh.triangles.is_empty() || size <= 0.0 {
+            return;
+        }
+
+        let center = self.mesh.bounds.center();
+        let before = self.local_transform().transform_point(&center);
+        self.scale = Scale3::from(Vector3::repeat(1.0 / size));
+        let after = self.local_transform().transform_point(&center);
+        self.translation.vector += before - after;
+    }
+
+    /// Place the object at the point the camera looks at, scaled to cover half of the view
+    pub fn frame(&mut self, camera: &Camera) {
+        let size = self.mesh.bounds.size().max();
+        if self.mesh.triangles.is_empty() || size <= 0.0 {
+            return;
+        }
+
+        let direction = camera.look_at - camera.position;
+        let distance = direction.norm().max(0.1);
+        let view = 2.0 * distance * (camera.fov / 2.0).tan();
+        let target = camera.position
+            + direction
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(|| -Vector3::z())
+                * distance;
+
+        self.rotation = UnitQuaternion::identity();
+        self.scale = Scale3::from(Vector3::repeat(0.5 * view / size));
+        let center = self.scale.transform_point(&self.mesh.bounds.center());
+        self.translation = Translation3::from(target - center);
+    }
+
+    /// Transform into world space, including the transforms of all parent groups
+    #[must_use]
+    pub fn transform(&self) -> Affine3<f32> {
+        self.parent_transform * self.local_transform()
+    }
+
+    #[must_use]
+    pub const fn parent_transform(&self) -> Affine3<f32> {
+        self.parent_transform
+    }
+
+    pub const fn set_parent_transform(&mut self, transform: Affine3<f32>) {
+        self.parent_transform = transform;
+    }
+
+    /// Move the mesh into world space for rendering, if it is small enough for a copy,
+    /// so rays don't have to be moved into object space.
+    /// Only for meshes of a single object, see `Scene::bake`
+    pub fn bake(&mut self) {
+        let transform = self.transform();
+        self.world = (self.mesh.triangles.len() <= BAKE_TRIANGLES)
+            .then(|| (transform, self.mesh.bake(&transform)));
+    }
+
+    /// Collect the indices of all triangles whose leaf bounding box is hit by the ray
+    /// before the ray parameter `t_max` (in the space of the mesh)
+    fn traverse(mesh: &Mesh, ray: Ray, t_max: f32, stats: Option<&Stats>) -> Vec<usize> {
+        let inv_direction = ray.direction.map(|d| 1.0 / d);
+        let mut triangles = Vec::new();
+        let mut stack = vec![0];
+        let mut visited = 0;
+
+        while let Some(index) = stack.pop() {
+            let Some(node) = mesh.bvh.nodes.get(index) else {
+                continue;
+            };
+            visited += 1;
+
+            match node {
+                BvhNode::Leaf { shape_index, .. } => {
+                    triangles.push(*shape_index);
+                }
+                BvhNode::Node {
+                    left,
+                    left_aabb,
+                    right,
+                    right_aabb,
+                    ..
+                } => {
+                    if intersects_aabb(ray.origin, inv_direction, t_max, left_aabb) {
+                        stack.push(*left);
+                    }
+                    if intersects_aabb(ray.origin, inv_direction, t_max, right_aabb) {
+                        stack.push(*right);
+                    }
+                }
+            }
+        }
+
+        if let Some(stats) = stats {
+            stats.add_bvh_nodes(visited);
+            stats.add_triangle_tests(triangles.len() as u64);
+        }
+
+        triangles
+    }
+
+    #[must_use]
+    pub fn intersect(&self, ray: Ray, delta: f32, stats: Option<&Stats>) -> Option<Hit<'_>> {
+        self.intersect_clipped(ray, delta, f32::INFINITY, stats, &[])
+    }
+
+    /// Closest hit that is not cut away by one of the section planes,
+    /// hits beyond the ray parameter `t_max` are skipped along with the BVH nodes behind it
+    #[must_use]
+    pub fn intersect_clipped(
+        &self,
+        ray: Ray,
+        delta: f32,
+        t_max: f32,
+        stats: Option<&Stats>,
+        planes: &[SectionPlane],
+    ) -> Option<Hit<'_>> {
+        let transform = self.transform();
+        // a baked mesh is already in world space, else the ray is moved into object space
+        let (mesh, ray, to_world) = match &self.world {
+            Some((baked, mesh)) if *baked == transform => (mesh.as_ref(), ray, None),
+            _ => (
+                self.mesh.as_ref(),
+                Ray {
+                    origin: transform.inverse_transform_point(&ray.origin),
+                    direction: transform.inverse_transform_vector(&ray.direction),
+                },
+                Some(transform),
+            ),
+        };
+
+        Self::traverse(mesh, ray, t_max, stats)
+            .into_iter()
+            .filter_map(|i| mesh.hot[i].intersect(ray, delta).map(|(t, h)| (i, t, h)))
+            .filter(|&(_, t, _)| t <= t_max)
+            .filter(|&(_, t, _)| {
+                planes.is_empty() || {
+                    let point = ray.origin + ray.direction * t;
+                    let point =
+                        to_world.map_or(point, |transform| transform.transform_point(&point));
+                    !planes.iter().any(|p| p.clips(&point))
+                }
+            })
+            .min_by_key(|&(_, t, _)| OrderedFloat(t))
+            .map(|(i, _, (u, v, w))| {
+                // u, v, w are barycentric coordinates of the hit point on the triangle
+                // interpolate hit point, normal and vertex color
+                let t = &mesh.triangles[i];
+                let point = Point3::from((t.a * u).coords + (t.b * v).coords + (t.c * w).coords);
+                let normal = (t.a_normal * u) + (t.b_normal * v) + (t.c_normal * w);
+                let uv = (t.a_uv * u) + (t.b_uv * v) + (t.c_uv * w);
+                let color = t.colors.map(|[a, b, c]| (a * u) + (b * v) + (c * w));
+
+                // Transform hit point and normal back into world space
+                let (point, normal) = to_world.map_or((point, normal), |transform| {
+                    let normal = (normal_matrix(&transform) * normal)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or(normal);
+                    (transform.transform_point(&point), normal)
+                });
+
+                Hit {
+                    name: self.material_name.as_str(),
+                    id: self.id,
+                    point,
+                    normal,
+                    material: self.material(t.material_index),
+                    uv,
+                    color,
+                }
+            })
+    }
+}
+
+/// Transforms normals, the inverse transpose keeps them perpendicular to the surface
+/// under non-uniform and mirroring scales
+pub(super) fn normal_matrix(transform: &Affine3<f32>) -> Matrix3<f32> {
+    transform
+        .matrix()
+        .fixed_view::<3, 3>(0, 0)
+        .into_owned()
+        .try_inverse()
+        .map_or_else(Matrix3::identity, |m| m.transpose())
+}
+
+/// Whether the transform mirrors, which turns counter-clockwise triangles clockwise
+pub(super) fn is_mirrored(transform: &Affine3<f32>) -> bool {
+    transform.matrix().fixed_view::<3, 3>(0, 0).determinant() < 0.0
+}
+
+/// Slab test of a ray against an axis aligned bounding box, between the origin and `t_max`
+fn intersects_aabb(
+    origin: Point3<f32>,
+    inv_direction: Vector3<f32>,
+    t_max: f32,
+    aabb: &Aabb,
+) -> bool {
+    let t1 = (aabb.min - origin).component_mul(&inv_direction);
+    let t2 = (aabb.max - origin).component_mul(&inv_direction);
+
+    t1.sup(&t2).min().min(t_max) >= t1.inf(&t2).max().max(0.0)
+}
+
+/// Meshes up to this size are baked into world space for rendering,
+/// larger ones move every ray into object space instead of being copied
+const BAKE_TRIANGLES: usize = 1_000_000;
+/// Baked meshes up to this size get a new BVH, larger ones refit the BVH of the model
+const REBUILD_TRIANGLES: usize = 50_000;
+
+/// Bins along the split axis of the BVH builder
+const SAH_BINS: usize = 16;
+/// Subtrees with more triangles are built in parallel
+const PARALLEL_BUILD: usize = 4096;
+
+/// Bounding box and centroid of a triangle
+type Primitive = (Aabb, Point3<f32>);
+
+/// Subtree of the BVH before it is flattened into the node list of the traversal
+enum BuildNode {
+    Leaf(usize),
+    Node(Box<(Self, Aabb)>, Box<(Self, Aabb)>),
+}
+
+/// BVH with one triangle per leaf, split by the binned surface area heuristic
+/// and built in parallel, so millions of triangles take seconds instead of minutes
+fn build_bvh(triangles: &[Triangle]) -> Bvh {
+    let primitives = triangles
+        .par_iter()
+        .map(|t| {
+            let aabb = Aabb::empty().grow(&t.a).grow(&t.b).grow(&t.c);
+            (aabb, aabb.center())
+        })
+        .collect::<Vec<_>>();
+    let mut indices = (0..triangles.len()).collect::<Vec<_>>();
+
+    let mut nodes = Vec::with_capacity(triangles.len() * 2);
+    if !indices.is_empty() {
+        let (root, _) = build_node(&primitives, &mut indices);
+        flatten(root, &mut nodes);
+    }
+
+    Bvh { nodes }
+}
+
+fn build_node(primitives: &[Primitive], indices: &mut [usize]) -> (BuildNode, Aabb) {
+    if let [index] = indices {
+        return (BuildNode::Leaf(*index), primitives[*index].0);
+    }
+
+    let centroids = indices
+        .iter()
+        .fold(Aabb::empty(), |bounds, &i| bounds.grow(&primitives[i].1));
+    let extent = centroids.size();
+    let axis = extent.imax();
+
+    let mid = if extent[axis] > f32::EPSILON {
+        let bin = |i: usize| {
+            let offset = (primitives[i].1[axis] - centroids.min[axis]) / extent[axis];
+            ((offset * SAH_BINS as f32) as usize).min(SAH_BINS - 1)
+        };
+
+        let mut bins = [(Aabb::empty(), 0_usize); SAH_BINS];
+        for &i in indices.iter() {
+            let (bounds, count) = &mut bins[bin(i)];
+            *bounds = bounds.join(&primitives[i].0);
+            *count += 1;
+        }
+
+        // cost of splitting after each bin, from the areas and counts on both sides,
+        // a split with an empty side is never taken
+        let cost = |bounds: &Aabb, count: usize| {
+            if count > 0 {
+                bounds.surface_area() * count as f32
+            } else {
+                f32::INFINITY
+            }
+        };
+        let mut costs = [0.0; SAH_BINS - 1];
+        let (mut bounds, mut count) = (Aabb::empty(), 0);
+        for (split, (b, c)) in bins[..SAH_BINS - 1].iter().enumerate() {
+            bounds = bounds.join(b);
+            count += c;
+            costs[split] = cost(&bounds, count);
+        }
+        let (mut bounds, mut count) = (Aabb::empty(), 0);
+        for (split, (b, c)) in bins[1..].iter().enumerate().rev() {
+            bounds = bounds.join(b);
+            count += c;
+            costs[split] += cost(&bounds, count);
+        }
+        let split = (0..SAH_BINS - 1)
+            .min_by_key(|&split| OrderedFloat(costs[split]))
+            .unwrap_or_default();
+
+        partition(indices, |i| bin(i) <= split)
+    } else {
+        0
+    };
+
+    // triangles that can't be told apart by their centroids are split in halves
+    let len = indices.len();
+    let mid = if mid == 0 || mid == len { len / 2 } else { mid };
+
+    let (left, right) = indices.split_at_mut(mid);
+    let (left, right) = if len > PARALLEL_BUILD {
+        rayon::join(
+            || build_node(primitives, left),
+            || build_node(primitives, right),
+        )
+    } else {
+        (build_node(primitives, left), build_node(primitives, right))
+    };
+
+    let bounds = left.1.join(&right.1);
+    (BuildNode::Node(Box::new(left), Box::new(right)), bounds)
+}
+
+/// BVH over the triangles with them sorted into the order of its leaves,
+/// so neighbouring leaves test neighbouring triangles
+fn build_ordered(triangles: Vec<Triangle>) -> (Vec<Triangle>, Bvh) {
+    let mut bvh = build_bvh(&triangles);
+    let mut triangles = triangles.into_iter().map(Some).collect::<Vec<_>>();
+    let mut ordered = Vec::with_capacity(triangles.len());
+
+    for node in &mut bvh.nodes {
+        if let BvhNode::Leaf { shape_index, .. } = node {
+            // every triangle is in exactly one leaf
+            ordered.extend(triangles[*shape_index].take());
+            *shape_index = ordered.len() - 1;
+        }
+    }
+
+    (ordered, bvh)
+}
+
+/// The nodes of a BVH with their bounds fitted to moved triangles,
+/// the children come after their parent so they are updated first
+fn refit_bvh(bvh: &Bvh, triangles: &[Triangle]) -> Bvh {
+    let mut nodes = bvh.nodes.clone();
+    let mut bounds = vec![Aabb::empty(); nodes.len()];
+
+    for index in (0..nodes.len()).rev() {
+        bounds[index] = match &mut nodes[index] {
+            BvhNode::Leaf { shape_index, .. } => {
+                let t = &triangles[*shape_index];
+                Aabb::empty().grow(&t.a).grow(&t.b).grow(&t.c)
+            }
+            BvhNode::Node {
+                left,
+                left_aabb,
+                right,
+                right_aabb,
+                ..
+            } => {
+                *left_aabb = bounds[*left];
+                *right_aabb = bounds[*right];
+                left_aabb.join(right_aabb)
+            }
+        };
+    }
+
+    Bvh { nodes }
+}
+
+/// Move the indices the predicate holds for to the front, returns how many there are
+fn partition(indices: &mut [usize], predicate: impl Fn(usize) -> bool) -> usize {
+    let mut mid = 0;
+    for i in 0..indices.len() {
+        if predicate(indices[i]) {
+            indices.swap(i, mid);
+            mid += 1;
+        }
+    }
+    mid
+}
+
+/// Append a subtree to the nodes depth first, returns the index of its root
+fn flatten(node: BuildNode, nodes: &mut Vec<BvhNode>) -> usize {
+    let index = nodes.len();
+    match node {
+        BuildNode::Leaf(shape_index) => nodes.push(BvhNode::Leaf { shape_index }),
+        BuildNode::Node(left, right) => {
+            // the children are filled in once their indices are known
+            nodes.push(BvhNode::Leaf { shape_index: 0 });
+            let (left, left_aabb) = *left;
+            let (right, right_aabb) = *right;
+            let left = flatten(left, nodes);
+            let right = flatten(right, nodes);
+            nodes[index] = BvhNode::Node {
+                left,
+                left_aabb,
+                right,
+                right_aabb,
+            };
+        }
+    }
+    index
+}
+
+/// Smoothing groups of the faces of an OBJ, `s <n>` before the faces,
+/// and the normals averaged over the faces of each group at a vertex
+struct Smoothing {
+    /// Group of every face in the order of the loaded groups, 0 if smoothing is off
+    groups: Vec<u32>,
+    /// Sum of the normals of the faces around a vertex position, by position index and group
+    normals: HashMap<(usize, u32), Vector3<f32>>,
+}
+
+impl Smoothing {
+    fn new(obj: &obj::Obj, faces: HashMap<GroupKey, Vec<u32>>) -> Self {
+        let mut faces = faces
+            .into_iter()
+            .map(|(key, groups)| (key, groups.into_iter()))
+            .collect::<HashMap<_, _>>();
+
+        let mut polys = Vec::new();
+        let mut groups = Vec::new();
+        for object in &obj.data.objects {
+            for group in &object.groups {
+                // a group reopened later in the file continues where it stopped
+                let mut faces =
+                    faces.get_mut(&(object.name.clone(), group.name.clone(), group.index));
+                for poly in &group.polys {
+                    polys.push(poly);
+                    groups.push(faces.as_mut().and_then(Iterator::next).unwrap_or(0));
+                }
+            }
+        }
+
+        let mut normals = HashMap::<(usize, u32), Vector3<f32>>::new();
+        for (poly, &group) in polys.iter().zip(&groups).filter(|(_, &g)| g != 0) {
+            let position = |i: usize| Point3::from(obj.data.position[poly.0[i].0]);
+            // not normalized, so larger faces weigh more
+            let normal = (1..poly.0.len() - 1)
+                .map(|i| (position(i) - position(0)).cross(&(position(i + 1) - position(0))))
+                .sum::<Vector3<f32>>();
+            for vertex in &poly.0 {
+                *normals.entry((vertex.0, group)).or_default() += normal;
+            }
+        }
+
+        Self { groups, normals }
+    }
+
+    fn group(&self, face: usize) -> u32 {
+        self.groups.get(face).copied().unwrap_or(0)
+    }
+
+    /// Smooth normal at a vertex position of a face in the group
+    fn normal(&self, position: usize, group: u32) -> Option<Vector3<f32>> {
+        if group == 0 {
+            return None;
+        }
+        self.normals
+            .get(&(position, group))
+            .and_then(|n| n.try_normalize(f32::EPSILON))
+    }
+}
+
+/// Object name, group name and group index of a group of an OBJ,
+/// the group index counts the splits of a group at material changes like the obj crate
+type GroupKey = (String, String, usize);
+
+/// Name of objects and groups the obj crate uses when the file doesn't name them
+const OBJ_DEFAULT_NAME: &str = "default";
+
+/// Statements of an OBJ the obj crate skips, read in one pass over the same source
+struct ObjExtras {
+    /// Colors some exporters write after the position of a vertex, `v x y z r g b`,
+    /// by vertex index, empty if no vertex has a color
+    colors: Vec<Color>,
+    /// Smoothing group of the faces of every group, `s <n>` before the faces,
+    /// in the order of the faces in the file
+    smoothing: HashMap<GroupKey, Vec<u32>>,
+}
+
+impl ObjExtras {
+    fn parse(source: &str) -> Self {
+        let mut extras = Self {
+            colors: Vec::new(),
+            smoothing: HashMap::new(),
+        };
+        let mut colors = Vec::new();
+        let mut object = OBJ_DEFAULT_NAME.to_owned();
+        // name and index of the current group and if it has a material, split like the obj crate
+        let mut group: Option<(String, usize, bool)> = None;
+        let mut smoothing_group = 0;
+        // smoothing groups of the faces since the group last changed
+        let mut faces = Vec::new();
+
+        for line in source.lines() {
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                Some("v") => colors.push(vertex_color(tokens)),
+                Some("s") => {
+                    // "off" and 0 turn smoothing off
+                    smoothing_group = tokens.next().and_then(|t| t.parse().ok()).unwrap_or(0);
+                }
+                Some("f") => {
+                    group.get_or_insert_with(|| (OBJ_DEFAULT_NAME.to_owned(), 0, false));
+                    faces.push(smoothing_group);
+                }
+                Some("o") => {
+                    if let Some((name, index, _)) = group.take() {
+                        extras.add_faces(&object, &name, index, &mut faces);
+                    }
+                    object = match line.get(1..) {
+                        Some(name) if line.len() > 2 => name.trim().to_owned(),
+                        _ => OBJ_DEFAULT_NAME.to_owned(),
+                    };
+                }
+                Some("g") => {
+                    if let Some((name, index, _)) = group.take() {
+                        extras.add_faces(&object, &name, index, &mut faces);
+                    }
+                    group = line
+                        .get(2..)
+                        .filter(|_| line.len() > 2)
+                        .map(|name| (name.trim().to_owned(), 0, false));
+                }
+                Some("usemtl") => {
+                    let (name, index, material) =
+                        group.get_or_insert_with(|| (OBJ_DEFAULT_NAME.to_owned(), 0, false));
+                    // a new material splits a group that has one already
+                    if *material {
+                        extras.add_faces(&object, name, *index, &mut faces);
+                        *index += 1;
+                    }
+                    *material = tokens.next().is_some();
+                }
+                _ => (),
+            }
+        }
+        if let Some((name, index, _)) = group {
+            extras.add_faces(&object, &name, index, &mut faces);
+        }
+
+        // vertices without a color don't tint the surface
+        if colors.iter().any(Option::is_some) {
+            extras.colors = colors
+                .into_iter()
+                .map(|c| c.unwrap_or_else(|| Color::from_element(1.0)))
+                .collect();
+        }
+
+        extras
+    }
+
+    /// Append the faces read since the group last changed to the group
+    fn add_faces(&mut self, object: &str, group: &str, index: usize, faces: &mut Vec<u32>) {
+        if !faces.is_empty() {
+            self.smoothing
+                .entry((object.to_owned(), group.to_owned(), index))
+                .or_default()
+                .append(faces);
+        }
+    }
+}
+
+/// Triangulate a polygon and compute normals and uv coordinates if they are missing,
+/// missing normals are smoothed over the faces of the same smoothing group
+fn triangulate(
+    obj: &obj::Obj,
+    colors: &[Color],
+    smoothing: &Smoothing,
+    group: u32,
+    poly: &SimplePolygon,
+    material_index: Option<usize>,
+    (computed_normals_zero, no_normals, no_uv): &mut (u32, u32, u32),
+) -> Vec<Triangle> {
+    let mut triangles = Vec::new();
+
+    for i in 1..poly.0.len() - 1 {
+        let a = Point3::from(obj.data.position[poly.0[0].0]);
+        let b = Point3::from(obj.data.position[poly.0[i].0]);
+        let c = Point3::from(obj.data.position[poly.0[i + 1].0]);
+
+        let computed_normal = (a - b)
+            .cross(&(a - c))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(|| {
+                *computed_normals_zero += 1;
+                Vector3::new(0.0, 0.0, 0.0)
+            });
+
+        let triangle = Triangle::new(
+            a,
+            b,
+            c,
+            poly.0[0].2.map_or_else(
+                || {
+                    *no_normals += 1;
+                    smoothing
+                        .normal(poly.0[0].0, group)
+                        .unwrap_or(computed_normal)
+                },
+                |i| Vector3::from(obj.data.normal[i]),
+            ),
+            poly.0[i].2.map_or_else(
+                || {
+                    *no_normals += 1;
+                    smoothing
+                        .normal(poly.0[i].0, group)
+                        .unwrap_or(computed_normal)
+                },
+                |i| Vector3::from(obj.data.normal[i]),
+            ),
+            poly.0[i + 1].2.map_or_else(
+                || {
+                    *no_normals += 1;
+                    smoothing
+                        .normal(poly.0[i + 1].0, group)
+                        .unwrap_or(computed_normal)
+                },
+                |i| Vector3::from(obj.data.normal[i]),
+            ),
+            poly.0[0].1.map_or_else(
+                || {
+                    *no_uv += 1;
+                    Vector2::new(0.0, 0.0)
+                },
+                |i| Vector2::from(obj.data.texture[i]),
+            ),
+            poly.0[i].1.map_or_else(
+                || {
+                    *no_uv += 1;
+                    Vector2::new(0.0, 0.0)
+                },
+                |i| Vector2::from(obj.data.texture[i]),
+            ),
+            poly.0[i + 1].1.map_or_else(
+                || {
+                    *no_uv += 1;
+                    Vector2::new(0.0, 0.0)
+                },
+                |i| Vector2::from(obj.data.texture[i]),
+            ),
+            material_index,
+        );
+
+        triangles.push(
+            match (
+                colors.get(poly.0[0].0),
+                colors.get(poly.0[i].0),
+                colors.get(poly.0[i + 1].0),
+            ) {
+                (Some(&a), Some(&b), Some(&c)) => triangle.with_colors([a, b, c]),
+                _ => triangle,
+            },
+        );
+    }
+
+    triangles
+}
+
+/// Index of the material of a group, by its name.
+/// References are left when no material library defines the name,
+/// the group falls back to the default material then and the name is collected
+fn material_index(
+    material: Option<&ObjMaterial>,
+    materials: &[Material],
+    unknown_materials: &mut Vec<String>,
+) -> Option<usize> {
+    let name = match material? {
+        ObjMaterial::Ref(name) => name,
+        ObjMaterial::Mtl(m) => &m.name,
+    };
+
+    let index = materials.iter().position(|mat| mat.name == *name);
+    if index.is_none() && !unknown_materials.contains(name) {
+        warn!("Material not found, using the default: {name}");
+        unknown_materials.push(name.clone());
+    }
+
+    index
+}
+
+/// Load an OBJ with its materials and return it with its source,
+/// read once so the statements the obj crate skips are parsed from the same source
+fn load_obj(path: &Path) -> Result<(obj::Obj, String), String> {
+    let source = files::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut obj = obj::Obj {
+        data: obj::ObjData::load_buf(source.as_bytes()).map_err(|e| e.to_string())?,
+        path: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    obj.load_mtls_fn(|directory, mtl| files::read(&directory.join(mtl)).map(Cursor::new))
+        .map_err(|e| format!("Failed to load materials: {e}"))?;
+
+    Ok((obj, source))
+}
+
+/// Color after the position of a `v` statement, the tokens after `v`
+fn vertex_color<'a>(tokens: impl Iterator<Item = &'a str>) -> Option<Color> {
+    let values = tokens
+        .skip(3)
+        .map(str::parse::<f32>)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    // a single value after the position is the weight, not a color
+    (values.len() == 3).then(|| Color::new(values[0], values[1], values[2]))
+}
+
+/// Statements of an MTL file that reference a texture
+fn is_texture_statement(keyword: &str) -> bool {
+    keyword.starts_with("map_") || ["bump", "disp", "decal", "refl"].contains(&keyword)
+}
+
+/// Files referenced by an OBJ or MTL file, relative to its directory
+pub(super) fn references(path: &Path) -> Vec<PathBuf> {
+    let Ok(content) = files::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            let keyword = tokens.next()?;
+
+            match keyword {
+                // a single mtllib statement may reference several files
+                "mtllib" => Some(tokens.map(PathBuf::from).collect::<Vec<_>>()),
+                // the file name is the last token after the texture options
+                k if is_texture_statement(k) => {
+                    Some(tokens.last().map(PathBuf::from).into_iter().collect())
+                }
+                _ => None,
+            }
+        })
+        .flatten()
+        .collect()
+}
+
+/// Content of an OBJ or MTL file with the referenced files renamed,
+/// the lines without renamed references are kept as they are
+pub(super) fn rename_references(content: &str, renamed: &HashMap<PathBuf, PathBuf>) -> String {
+    let rename = |token: &str| {
+        renamed.get(Path::new(token)).map_or_else(
+            || token.to_string(),
+            |p| p.to_string_lossy().replace('\\', "/"),
+        )
+    };
+
+    content
+        .lines()
+        .map(|line| {
+            let mut tokens = line.split_whitespace();
+            let keyword = tokens.next().unwrap_or_default();
+            let tokens = tokens.collect::<Vec<_>>();
+
+            match (keyword, tokens.split_last()) {
+                ("mtllib", _) if tokens.iter().any(|t| renamed.contains_key(Path::new(t))) => {
+                    let files = tokens.iter().map(|t| rename(t)).collect::<Vec<_>>();
+                    format!("mtllib {}", files.join(" "))
+                }
+                (k, Some((file, options)))
+                    if is_texture_statement(k) && renamed.contains_key(Path::new(file)) =>
+                {
+                    let statement = std::iter::once(k.to_string())
+                        .chain(options.iter().map(ToString::to_string))
+                        .chain(std::iter::once(rename(file)));
+                    statement.collect::<Vec<_>>().join(" ")
+                }
+                _ => line.to_string(),
+            }
+        })
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Model file and the material libraries and textures it references,
+/// relative to the directory of the model
+#[must_use]
+pub fn model_files(model: &Path) -> Vec<PathBuf> {
+    let directory = model.parent().unwrap_or_else(|| Path::new(""));
+    let mut files = vec![PathBuf::from(model.file_name().unwrap_or_default())];
+
+    for mtl in references(model) {
+        let textures = references(&directory.join(&mtl));
+        files.push(mtl);
+        files.extend(textures);
+    }
+
+    files
+}
+
+/// Deserializes the models of a scene relative to the scene path.
+/// Every OBJ or STL file is only loaded once per import settings, entries referencing
+/// the same file are instances sharing its mesh with their own transform.
+pub struct WithRelativePath<P: AsRef<std::path::Path>>(pub P);
+
+mod yaml {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use log::info;
+    use nalgebra::{Point3, Scale3, Translation3, UnitQuaternion, Vector3};
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+    use serde::{Deserialize, Serialize};
+
+    use super::{error, filename, Import, MaterialOverride, Object, WithRelativePath};
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ObjectDef {
+        /// Unique in the scene, objects without one get one when the scene is loaded
+        #[serde(default)]
+        pub id: u64,
+        /// Overrides the name taken from the file
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub name: Option<String>,
+        /// Index of the group the object is parented to
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub parent: Option<usize>,
+        #[serde(rename = "filePath")]
+        pub file_path: PathBuf,
+        #[serde(with = "super::super::yaml::point")]
+        pub position: Point3<f32>,
+        #[serde(with = "super::super::yaml::vector")]
+        pub rotation: Vector3<f32>,
+        #[serde(with = "super::super::yaml::vector")]
+        pub scale: Vector3<f32>,
+        /// Up axis and unit of the model file
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub import: Option<Import>,
+        /// Named material of the material library
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub material: Option<String>,
+        /// Overrides of the MTL materials, by material name
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        pub materials: BTreeMap<String, MaterialOverride>,
+        #[serde(
+            rename = "hiddenInPreview",
+            default,
+            skip_serializing_if = "std::ops::Not::not"
+        )]
+        pub hidden_in_preview: bool,
+        #[serde(
+            rename = "hiddenInRender",
+            default,
+            skip_serializing_if = "std::ops::Not::not"
+        )]
+        pub hidden_in_render: bool,
+    }
+
+    impl<'de, P: AsRef<std::path::Path> + Sync> serde::de::DeserializeSeed<'de>
+        for WithRelativePath<P>
+    {
+        type Value = Vec<Object>;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let yaml_objects = Vec::<ObjectDef>::deserialize(deserializer)?;
+
+            let parent = self
+                .0
+                .as_ref()
+                .parent()
+                .ok_or_else(|| serde::de::Error::custom("Failed to get parent path"))?;
+
+            if let Some(import) = yaml_objects
+                .iter()
+                .filter_map(|o| o.import)
+                .find(|i| i.unit_scale <= 0.0)
+            {
+                return Err(serde::de::Error::custom(format!(
+                    "The unit scale must be positive, got {}",
+                    import.unit_scale
+                )));
+            }
+
+            // load every referenced file only once per import settings
+            let mut imports = Vec::<(PathBuf, Import)>::new();
+            for o in &yaml_objects {
+                let key = (o.file_path.clone(), o.import.unwrap_or_default());
+                if !imports.contains(&key) {
+                    imports.push(key);
+                }
+            }
+
+            let meshes = imports
+                .into_par_iter()
+                .map(|(file_path, import)| {
+                    Object::load(
+                        parent.join(file_path.as_path()),
+                        import,
+                        Translation3::identity(),
+                        UnitQuaternion::identity(),
+                        Scale3::identity(),
+                    )
+                    .map(|o| ((file_path, import), o))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    let message = e.to_string();
+                    error::keep(e);
+                    serde::de::Error::custom(message)
+                })?;
+
+            info!(
+                "Loaded {} models from {} files",
+                yaml_objects.len(),
+                meshes.len()
+            );
+
+            yaml_objects
+                .into_iter()
+                .map(|yaml_object| {
+                    let translation = Translation3::from(yaml_object.position.coords);
+                    let rotation = UnitQuaternion::from_euler_angles(
+                        yaml_object.rotation.x * std::f32::consts::PI * 2.0 / 360.0,
+                        yaml_object.rotation.y * std::f32::consts::PI * 2.0 / 360.0,
+                        yaml_object.rotation.z * std::f32::consts::PI * 2.0 / 360.0,
+                    );
+                    let scale = Scale3::from(yaml_object.scale);
+
+                    let key = (
+                        yaml_object.file_path.clone(),
+                        yaml_object.import.unwrap_or_default(),
+                    );
+                    meshes
+                        .iter()
+                        .find(|(k, _)| *k == key)
+                        .map(|(_, o)| {
+                            let mut o = o.instance(translation, rotation, scale);
+                            o.id = yaml_object.id;
+                            if let Some(name) = yaml_object.name {
+                                o.name = name;
+                            }
+                            o.path = yaml_object.file_path;
+                            o.parent = yaml_object.parent;
+                            // applied once the material library of the scene is loaded
+                            o.material_overrides = yaml_object.materials;
+                            o.material = yaml_object.material;
+                            o.visible_in_preview = !yaml_object.hidden_in_preview;
+                            o.visible_in_render = !yaml_object.hidden_in_render;
+                            o
+                        })
+                        .ok_or_else(|| serde::de::Error::custom("Failed to get loaded model"))
+                })
+                .collect()
+        }
+    }
+
+    impl Serialize for Object {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            let rotation = self.rotation.euler_angles();
+
+            ObjectDef {
+                id: self.id,
+                name: (self.name != filename(&self.path)).then(|| self.name.clone()),
+                parent: self.parent,
+                file_path: self.path.clone(),
+                position: self.translation.vector.into(),
+                rotation: Vector3::new(
+                    rotation.0.to_degrees(),
+                    rotation.1.to_degrees(),
+                    rotation.2.to_degrees(),
+                ),
+                scale: self.scale.vector,
+                import: (self.import != Import::default()).then_some(self.import),
+                material: self.material.clone(),
+                materials: self.material_overrides.clone(),
+                hidden_in_preview: !self.visible_in_preview,
+                hidden_in_render: !self.visible_in_render,
+            }
+            .serialize(serializer)
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use super::{material::Material, object::Object, triangle::Triangle};
+use nalgebra::{Point3, Vector2, Vector3};
+use std::f32::consts::PI;
+
+type Vertex = (Point3<f32>, Vector3<f32>, Vector2<f32>);
+
+const fn triangle(a: Vertex, b: Vertex, c: Vertex, material_index: Option<usize>) -> Triangle {
+    Triangle::new(a.0, b.0, c.0, a.1, b.1, c.1, a.2, b.2, c.2, material_index)
+}
+
+impl Object {
+    /// Unit UV-sphere centered at the origin using a single material
+    #[must_use]
+    pub fn sphere(name: &str, material: Option<Material>) -> Self {
+        const RINGS: u32 = 24;
+        const SEGMENTS: u32 = 48;
+
+        let vertex = |ring: u32, segment: u32| -> Vertex {
+            let theta = ring as f32 / RINGS as f32 * PI;
+            let phi = segment as f32 / SEGMENTS as f32 * 2.0 * PI;
+            let normal = Vector3::new(
+                theta.sin() * phi.cos(),
+                theta.cos(),
+                theta.sin() * phi.sin(),
+            );
+            let uv = Vector2::new(
+                segment as f32 / SEGMENTS as f32,
+                1.0 - ring as f32 / RINGS as f32,
+            );
+
+            (Point3::from(normal), normal, uv)
+        };
+
+        let material_index = material.is_some().then_some(0);
+
+        let triangles = (0..RINGS)
+            .flat_map(|ring| (0..SEGMENTS).map(move |segment| (ring, segment)))
+            .flat_map(|(ring, segment)| {
+                let a = vertex(ring, segment);
+                let b = vertex(ring + 1, segment);
+                let c = vertex(ring + 1, segment + 1);
+                let d = vertex(ring, segment + 1);
+
+                // skip the degenerate triangles at the poles
+                [
+                    (ring != RINGS - 1).then(|| triangle(a, b, c, material_index)),
+                    (ring != 0).then(|| triangle(a, c, d, material_index)),
+                ]
+            })
+            .flatten()
+            .collect();
+
+        Self::from_triangles(name, triangles, material.into_iter().collect())
+    }
+
+    /// Unit square in the XZ plane centered at the origin, facing +Y
+    #[must_use]
+    pub fn quad(name: &str, material: Option<Material>) -> Self {
+        let material_index = material.is_some().then_some(0);
+        let vertex = |x: f32, z: f32| -> Vertex {
+            (
+                Point3::new(x, 0.0, z),
+                Vector3::y(),
+                Vector2::new(x + 0.5, 0.5 - z),
+            )
+        };
+
+        let (a, b, c, d) = (
+            vertex(-0.5, -0.5),
+            vertex(-0.5, 0.5),
+            vertex(0.5, 0.5),
+            vertex(0.5, -0.5),
+        );
+
+        Self::from_triangles(
+            name,
+            vec![
+                triangle(a, b, c, material_index),
+                triangle(a, c, d, material_index),
+            ],
+            material.into_iter().collect(),
+        )
+    }
+
+    /// Unit cube centered at the origin with flat shaded faces
+    #[must_use]
+    pub fn cuboid(name: &str, material: Option<Material>) -> Self {
+        let material_index = material.is_some().then_some(0);
+
+        let triangles = [
+            Vector3::x(),
+            -Vector3::x(),
+            Vector3::y(),
+            -Vector3::y(),
+            Vector3::z(),
+            -Vector3::z(),
+        ]
+        .into_iter()
+        .flat_map(|normal| {
+            // two axes spanning the face, ordered for counter-clockwise winding
+            let tangent = Vector3::new(normal.y, normal.z, normal.x);
+            let bitangent = normal.cross(&tangent);
+            let center = Point3::from(normal * 0.5);
+            let corners = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)].map(
+                |(s, t): (f32, f32)| -> Vertex {
+                    (
+                        center + tangent * s + bitangent * t,
+                        normal,
+                        Vector2::new(s + 0.5, t + 0.5),
+                    )
+                },
+            );
+
+            [
+                triangle(corners[0], corners[1], corners[2], material_index),
+                triangle(corners[0], corners[2], corners[3], material_index),
+            ]
+        })
+        .collect();
+
+        Self::from_triangles(name, triangles, material.into_iter().collect())
+    }
+}
@@ -0,0 +1,285 @@
+use super::{Color, Skybox};
+use nalgebra::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+
+/// How hits are shaded, the debug modes skip lighting entirely
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShadingMode {
+    #[default]
+    Full,
+    /// World space normal mapped to RGB
+    Normal,
+    /// Texture coordinates mapped to red and green
+    Uv,
+    /// Distance to the camera, normalized to the scene extent
+    Depth,
+}
+
+/// Weighting of the anti-aliasing samples of a pixel by their distance to its center
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PixelFilter {
+    /// Plain average of the samples
+    #[default]
+    Box,
+    /// Weight falling linearly to the radius
+    Tent,
+    Gaussian,
+    /// Mitchell-Netravali (B = C = 1/3), sharper with slightly negative lobes
+    Mitchell,
+}
+
+impl PixelFilter {
+    pub const ALL: [Self; 4] = [Self::Box, Self::Tent, Self::Gaussian, Self::Mitchell];
+
+    /// Radius in pixels the filter is usually used with
+    #[must_use]
+    pub const fn default_radius(self) -> f32 {
+        match self {
+            Self::Box => 0.5,
+            Self::Tent => 1.0,
+            Self::Gaussian => 1.5,
+            Self::Mitchell => 2.0,
+        }
+    }
+
+    /// Weight of a sample at the offset in pixels from the center, 0 outside of the radius
+    #[must_use]
+    pub fn weight(self, (dx, dy): (f32, f32), radius: f32) -> f32 {
+        let weight = |d: f32| {
+            let t = (d.abs() / radius).min(1.0);
+            match self {
+                Self::Box => 1.0,
+                Self::Tent => 1.0 - t,
+                // standard deviation of a third of the radius, shifted to 0 at the radius
+                Self::Gaussian => (-4.5 * t * t).exp() - (-4.5f32).exp(),
+                Self::Mitchell => {
+                    let x = 2.0 * t;
+                    // cubic pieces in Horner form
+                    if x < 1.0 {
+                        (7.0f32.mul_add(x, -12.0) * x).mul_add(x, 16.0 / 3.0) / 6.0
+                    } else {
+                        (-7.0f32 / 3.0)
+                            .mul_add(x, 12.0)
+                            .mul_add(x, -20.0)
+                            .mul_add(x, 32.0 / 3.0)
+                            / 6.0
+                    }
+                }
+            }
+        };
+
+        weight(dx) * weight(dy)
+    }
+}
+
+/// Plane cutting away the geometry on the side its normal points to,
+/// to look into interiors
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SectionPlane {
+    #[serde(with = "super::yaml::point")]
+    pub position: Point3<f32>,
+    #[serde(with = "super::yaml::vector")]
+    pub normal: Vector3<f32>,
+}
+
+impl SectionPlane {
+    /// The preview cuts with this many planes, the render with all of them
+    pub const PREVIEW_MAX: usize = 4;
+
+    /// Whether the point is cut away
+    #[must_use]
+    pub fn clips(&self, point: &Point3<f32>) -> bool {
+        (point - self.position).dot(&self.normal) > 0.0
+    }
+}
+
+// render toggles, not a state machine
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Reflections followed before the skybox is used
+    pub max_bounces: u32,
+    pub samples: u32,
+    pub ambient_color: Color,
+    pub ambient_intensity: f32,
+    pub skybox: Skybox,
+    pub anti_aliasing: bool,
+    /// Stop sampling a pixel once its noise is below the threshold
+    pub adaptive_sampling: bool,
+    /// Relative standard error at which adaptive sampling stops
+    pub noise_threshold: f32,
+    /// Weighting of the anti-aliasing samples
+    pub pixel_filter: PixelFilter,
+    /// Radius of the pixel filter in pixels, samples are spread over this radius
+    pub filter_radius: f32,
+    /// Shade with one light per hit, picked by its contribution, instead of all lights
+    pub light_sampling: bool,
+    /// Render all objects with a neutral material, ignoring textures and colors
+    pub clay: bool,
+    pub shading_mode: ShadingMode,
+    /// Name of the registered integrator that shades hits in the full shading mode
+    pub integrator: String,
+    /// Name of the registered shading model of the materials
+    pub bsdf: String,
+    /// Record an alpha channel that is 0 where only the skybox is seen
+    pub transparent_background: bool,
+    /// Also render the ambient light and each light alone, for balancing them in compositing
+    pub light_passes: bool,
+    /// Copy meshes into world space for rendering, faster but holds a second copy
+    /// of every baked mesh while rendering
+    pub bake_meshes: bool,
+    pub section_planes: Vec<SectionPlane>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            max_bounces: 4,
+            samples: 1,
+            ambient_color: Color::new(0.34, 0.14, 0.04).normalize(),
+            ambient_intensity: 0.2,
+            skybox: Skybox::default(),
+            anti_aliasing: false,
+            adaptive_sampling: false,
+            noise_threshold: 0.02,
+            pixel_filter: PixelFilter::Box,
+            filter_radius: PixelFilter::Box.default_radius(),
+            light_sampling: false,
+            clay: false,
+            shading_mode: ShadingMode::default(),
+            integrator: String::from("whitted"),
+            bsdf: String::from("phong"),
+            transparent_background: false,
+            light_passes: false,
+            bake_meshes: true,
+            section_planes: Vec::new(),
+        }
+    }
+}
+
+mod yaml {
+    use crate::scene::{Color, Skybox};
+
+    use super::{PixelFilter, SectionPlane, Settings, ShadingMode};
+    use serde::{Deserialize, Serialize};
+
+    #[allow(clippy::struct_excessive_bools)]
+    #[derive(Serialize, Deserialize)]
+    pub struct SettingsDef {
+        pub max_bounces: u32,
+        pub samples: u32,
+        #[serde(with = "super::super::yaml::color")]
+        pub ambient_color: Color,
+        pub skybox: Skybox,
+        pub anti_aliasing: bool,
+        #[serde(default)]
+        pub adaptive_sampling: bool,
+        #[serde(default = "default_noise_threshold")]
+        pub noise_threshold: f32,
+        #[serde(default)]
+        pub pixel_filter: PixelFilter,
+        #[serde(default = "default_filter_radius")]
+        pub filter_radius: f32,
+        #[serde(default)]
+        pub light_sampling: bool,
+        #[serde(default)]
+        pub clay: bool,
+        #[serde(default)]
+        pub shading_mode: ShadingMode,
+        #[serde(default = "default_integrator")]
+        pub integrator: String,
+        #[serde(default = "default_bsdf")]
+        pub bsdf: String,
+        #[serde(default)]
+        pub transparent_background: bool,
+        #[serde(default)]
+        pub light_passes: bool,
+        #[serde(default = "default_bake_meshes")]
+        pub bake_meshes: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub section_planes: Vec<SectionPlane>,
+    }
+
+    fn default_noise_threshold() -> f32 {
+        Settings::default().noise_threshold
+    }
+
+    fn default_filter_radius() -> f32 {
+        Settings::default().filter_radius
+    }
+
+    fn default_integrator() -> String {
+        Settings::default().integrator
+    }
+
+    fn default_bsdf() -> String {
+        Settings::default().bsdf
+    }
+
+    fn default_bake_meshes() -> bool {
+        Settings::default().bake_meshes
+    }
+
+    impl<'de> Deserialize<'de> for Settings {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            SettingsDef::deserialize(deserializer).map(|yaml_extras| Self {
+                max_bounces: yaml_extras.max_bounces,
+                samples: yaml_extras.samples,
+                ambient_color: yaml_extras
+                    .ambient_color
+                    .try_normalize(0.0)
+                    .unwrap_or_default(),
+                ambient_intensity: yaml_extras.ambient_color.norm(),
+                skybox: yaml_extras.skybox,
+                anti_aliasing: yaml_extras.anti_aliasing,
+                adaptive_sampling: yaml_extras.adaptive_sampling,
+                noise_threshold: yaml_extras.noise_threshold,
+                pixel_filter: yaml_extras.pixel_filter,
+                filter_radius: yaml_extras.filter_radius,
+                light_sampling: yaml_extras.light_sampling,
+                clay: yaml_extras.clay,
+                shading_mode: yaml_extras.shading_mode,
+                integrator: yaml_extras.integrator,
+                bsdf: yaml_extras.bsdf,
+                transparent_background: yaml_extras.transparent_background,
+                light_passes: yaml_extras.light_passes,
+                bake_meshes: yaml_extras.bake_meshes,
+                section_planes: yaml_extras.section_planes,
+            })
+        }
+    }
+
+    impl Serialize for Settings {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            SettingsDef {
+                max_bounces: self.max_bounces,
+                samples: self.samples,
+                ambient_color: self.ambient_color * self.ambient_intensity,
+                skybox: self.skybox.clone(),
+                anti_aliasing: self.anti_aliasing,
+                adaptive_sampling: self.adaptive_sampling,
+                noise_threshold: self.noise_threshold,
+                pixel_filter: self.pixel_filter,
+                filter_radius: self.filter_radius,
+                light_sampling: self.light_sampling,
+                clay: self.clay,
+                shading_mode: self.shading_mode,
+                integrator: self.integrator.clone(),
+                bsdf: self.bsdf.clone(),
+                transparent_background: self.transparent_background,
+                light_passes: self.light_passes,
+                bake_meshes: self.bake_meshes,
+                section_planes: self.section_planes.clone(),
+            }
+            .serialize(serializer)
+        }
+    }
+}
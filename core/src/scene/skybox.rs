@@ -1,10 +1,10 @@
-use super::Color;
+use super::{files, object::texture_error, Color, SceneError};
 use image::RgbImage;
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum Skybox {
-    Image { path: PathBuf, image: RgbImage },
+    Image { path: PathBuf, image: Arc<RgbImage> },
     Color(Color),
 }
 
@@ -16,7 +16,7 @@ impl Default for Skybox {
 
 mod yaml {
     use super::Skybox;
-    use crate::scene::Color;
+    use crate::scene::{error, Color};
     use serde::{Deserialize, Serialize};
 
     #[derive(Serialize, Deserialize)]
@@ -31,8 +31,11 @@ mod yaml {
             D: serde::Deserializer<'de>,
         {
             SkyboxDef::deserialize(deserializer).and_then(|yaml_extras| match yaml_extras {
-                SkyboxDef::Path(path) => Self::load_from_path(path)
-                    .map_err(|e| serde::de::Error::custom(format!("Failed to load skybox: {e}"))),
+                SkyboxDef::Path(path) => Self::load_from_path(path).map_err(|e| {
+                    let message = format!("Failed to load skybox: {e}");
+                    error::keep(e);
+                    serde::de::Error::custom(message)
+                }),
                 SkyboxDef::Color(color) => Ok(Self::Color(color)),
             })
         }
@@ -53,12 +56,19 @@ mod yaml {
 }
 
 impl Skybox {
-    fn load_from_path<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
-        let image = image::open(path.as_ref())?.into_rgb8();
+    /// Load an image as skybox
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`SceneError::UnsupportedTexture`] if the image can't be read
+    pub fn load_from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self, SceneError> {
+        let image = files::open_image(path.as_ref())
+            .map_err(|e| texture_error(path.as_ref(), e))?
+            .into_rgb8();
 
         Ok(Self::Image {
             path: path.as_ref().to_path_buf(),
-            image,
+            image: Arc::new(image),
         })
     }
 }
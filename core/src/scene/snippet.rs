@@ -0,0 +1,140 @@
+use super::Camera;
+use anyhow::Context;
+use nalgebra::{Point3, Scale3, Translation3, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+/// Transform of an object or group, copied to the clipboard as YAML
+/// with the same fields as in the scene file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Translation3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub scale: Scale3<f32>,
+}
+
+/// Position, direction and field of view of a camera, without its name and resolution
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub position: Point3<f32>,
+    pub look_at: Point3<f32>,
+    pub up: Vector3<f32>,
+    pub fov: f32,
+}
+
+impl Transform {
+    /// Serialize the transform for the clipboard
+    ///
+    /// # Errors
+    ///
+    /// Fails if the transform can't be serialized
+    pub fn to_yaml(&self) -> anyhow::Result<String> {
+        let rotation = self.rotation.euler_angles();
+
+        serde_yml::to_string(&TransformDef {
+            position: self.translation.vector.into(),
+            rotation: Vector3::new(
+                rotation.0.to_degrees(),
+                rotation.1.to_degrees(),
+                rotation.2.to_degrees(),
+            ),
+            scale: self.scale.vector,
+        })
+        .context("Failed to serialize transform")
+    }
+
+    /// Parse a transform copied to the clipboard
+    ///
+    /// # Errors
+    ///
+    /// Fails if the text isn't a transform
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        let transform = serde_yml::from_str::<TransformDef>(yaml)
+            .context("Clipboard doesn't contain a transform")?;
+
+        Ok(Self {
+            translation: Translation3::from(transform.position.coords),
+            rotation: UnitQuaternion::from_euler_angles(
+                transform.rotation.x.to_radians(),
+                transform.rotation.y.to_radians(),
+                transform.rotation.z.to_radians(),
+            ),
+            scale: Scale3::from(transform.scale),
+        })
+    }
+}
+
+impl CameraPose {
+    #[must_use]
+    pub const fn of(camera: &Camera) -> Self {
+        Self {
+            position: camera.position,
+            look_at: camera.look_at,
+            up: camera.up,
+            fov: camera.fov,
+        }
+    }
+
+    pub const fn apply(self, camera: &mut Camera) {
+        camera.position = self.position;
+        camera.look_at = self.look_at;
+        camera.up = self.up;
+        camera.fov = self.fov;
+    }
+
+    /// Serialize the camera pose for the clipboard
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pose can't be serialized
+    pub fn to_yaml(&self) -> anyhow::Result<String> {
+        serde_yml::to_string(&CameraPoseDef {
+            position: self.position,
+            look_at: self.look_at,
+            up_vec: self.up,
+            field_of_view: self.fov.to_degrees(),
+        })
+        .context("Failed to serialize camera pose")
+    }
+
+    /// Parse a camera pose copied to the clipboard
+    ///
+    /// # Errors
+    ///
+    /// Fails if the text isn't a camera pose
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        let pose = serde_yml::from_str::<CameraPoseDef>(yaml)
+            .context("Clipboard doesn't contain a camera pose")?;
+
+        Ok(Self {
+            position: pose.position,
+            look_at: pose.look_at,
+            up: pose.up_vec,
+            fov: pose.field_of_view.to_radians(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TransformDef {
+    #[serde(with = "super::yaml::point")]
+    position: Point3<f32>,
+    /// Euler angles in degrees
+    #[serde(with = "super::yaml::vector")]
+    rotation: Vector3<f32>,
+    #[serde(with = "super::yaml::vector")]
+    scale: Vector3<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CameraPoseDef {
+    #[serde(with = "super::yaml::point")]
+    position: Point3<f32>,
+    #[serde(rename = "lookAt")]
+    #[serde(with = "super::yaml::point")]
+    look_at: Point3<f32>,
+    #[serde(rename = "upVec")]
+    #[serde(with = "super::yaml::vector")]
+    up_vec: Vector3<f32>,
+    #[serde(rename = "fieldOfView")]
+    field_of_view: f32,
+}
@@ -0,0 +1,112 @@
+use super::{files, triangle::Triangle};
+use anyhow::Context;
+use nalgebra::{Point3, Vector2};
+use std::path::Path;
+
+/// Size of the binary header and the triangle count
+const BINARY_HEADER: usize = 84;
+/// Size of a binary triangle: normal, 3 vertices and attribute byte count
+const BINARY_TRIANGLE: usize = 50;
+
+/// Load the triangles of a binary or ASCII STL file.
+/// Normals are computed from the vertices since the stored
+/// facet normals of many exporters are zero or unreliable.
+pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<Triangle>> {
+    let data = files::read(path.as_ref()).context(format!(
+        "Failed to read stl from path: {}",
+        path.as_ref().display()
+    ))?;
+
+    let faces = if is_binary(&data) {
+        parse_binary(&data)
+    } else {
+        parse_ascii(&String::from_utf8_lossy(&data))
+    }
+    .context(format!(
+        "Failed to parse stl from path: {}",
+        path.as_ref().display()
+    ))?;
+
+    Ok(faces
+        .into_iter()
+        .filter_map(|[a, b, c]| {
+            // skip degenerate triangles
+            let normal = (b - a).cross(&(c - a)).try_normalize(f32::EPSILON)?;
+
+            Some(Triangle::new(
+                a,
+                b,
+                c,
+                normal,
+                normal,
+                normal,
+                Vector2::zeros(),
+                Vector2::zeros(),
+                Vector2::zeros(),
+                None,
+            ))
+        })
+        .collect())
+}
+
+/// ASCII files start with "solid", but so do some binary files,
+/// so the size announced by the binary header decides
+fn is_binary(data: &[u8]) -> bool {
+    data.get(80..BINARY_HEADER).is_some_and(|count| {
+        let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize;
+        data.len() == BINARY_HEADER + count * BINARY_TRIANGLE
+    }) || !data.starts_with(b"solid")
+}
+
+fn parse_binary(data: &[u8]) -> anyhow::Result<Vec<[Point3<f32>; 3]>> {
+    let body = data
+        .get(BINARY_HEADER..)
+        .context("File is too short for a binary stl header")?;
+
+    let float = |bytes: &[u8], i: usize| {
+        f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+    };
+    let point = |bytes: &[u8], i: usize| {
+        Point3::new(float(bytes, i), float(bytes, i + 4), float(bytes, i + 8))
+    };
+
+    Ok(body
+        .chunks_exact(BINARY_TRIANGLE)
+        // skip the facet normal
+        .map(|t| [point(t, 12), point(t, 24), point(t, 36)])
+        .collect())
+}
+
+fn parse_ascii(text: &str) -> anyhow::Result<Vec<[Point3<f32>; 3]>> {
+    let mut faces = Vec::new();
+    let mut vertices = Vec::with_capacity(3);
+
+    for (i, line) in text.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("vertex") => {
+                let coords = tokens
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("Invalid vertex in line {}", i + 1))?;
+                let [x, y, z] = coords[..] else {
+                    anyhow::bail!("Expected 3 coordinates in line {}", i + 1);
+                };
+
+                vertices.push(Point3::new(x, y, z));
+            }
+            Some("endloop") => {
+                let [a, b, c] = vertices[..] else {
+                    anyhow::bail!("Expected 3 vertices per facet in line {}", i + 1);
+                };
+
+                faces.push([a, b, c]);
+                vertices.clear();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(faces)
+}
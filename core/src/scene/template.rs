@@ -0,0 +1,329 @@
+use super::{
+    material::{IlluminationModel, Material},
+    Camera, Color, Light, Object, Scene, Settings, Skybox,
+};
+use anyhow::Context;
+use nalgebra::{Point3, Scale3, Translation3, UnitQuaternion, Vector3};
+use std::{
+    collections::BTreeMap,
+    f32::consts::{FRAC_PI_2, PI},
+    path::Path,
+};
+
+/// Procedurally built example scenes to get started without own models
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    CornellBox,
+    /// Sphere on a floor lit by a key, fill and rim light
+    ThreePointSphere,
+    /// Spheres with diffuse, specular and reflective materials
+    MaterialGrid,
+}
+
+fn material(name: &str, diffuse: Color, illumination_model: i32) -> Material {
+    Material {
+        name: name.to_string(),
+        diffuse_color: Some(diffuse),
+        specular_color: Some(Color::from_element(1.0)),
+        illumination_model: IlluminationModel::from_i32(illumination_model).unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+fn place(
+    mut object: Object,
+    translation: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    scale: Vector3<f32>,
+) -> Object {
+    object.translation = Translation3::from(translation);
+    object.rotation = rotation;
+    object.scale = Scale3::from(scale);
+    object
+}
+
+impl Template {
+    pub const ALL: [Self; 3] = [Self::CornellBox, Self::ThreePointSphere, Self::MaterialGrid];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::CornellBox => "cornell_box",
+            Self::ThreePointSphere => "three_point_sphere",
+            Self::MaterialGrid => "material_grid",
+        }
+    }
+
+    fn cornell_box() -> (Vec<Object>, Vec<Light>, Camera) {
+        let white = Color::new(0.73, 0.73, 0.73);
+        let wall = |name: &str, color: Color, translation, rotation| {
+            place(
+                Object::quad(name, Some(material(name, color, 1))),
+                translation,
+                rotation,
+                Vector3::from_element(2.0),
+            )
+        };
+
+        let objects = vec![
+            wall("Floor", white, Vector3::zeros(), UnitQuaternion::identity()),
+            wall(
+                "Ceiling",
+                white,
+                Vector3::new(0.0, 2.0, 0.0),
+                UnitQuaternion::from_euler_angles(PI, 0.0, 0.0),
+            ),
+            wall(
+                "Back",
+                white,
+                Vector3::new(0.0, 1.0, -1.0),
+                UnitQuaternion::from_euler_angles(FRAC_PI_2, 0.0, 0.0),
+            ),
+            wall(
+                "Left",
+                Color::new(0.65, 0.05, 0.05),
+                Vector3::new(-1.0, 1.0, 0.0),
+                UnitQuaternion::from_euler_angles(0.0, 0.0, -FRAC_PI_2),
+            ),
+            wall(
+                "Right",
+                Color::new(0.12, 0.45, 0.15),
+                Vector3::new(1.0, 1.0, 0.0),
+                UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2),
+            ),
+            place(
+                Object::cuboid("Tall box", Some(material("Tall box", white, 1))),
+                Vector3::new(-0.35, 0.6, -0.3),
+                UnitQuaternion::from_euler_angles(0.0, 15.0_f32.to_radians(), 0.0),
+                Vector3::new(0.6, 1.2, 0.6),
+            ),
+            place(
+                Object::cuboid("Short box", Some(material("Short box", white, 1))),
+                Vector3::new(0.4, 0.3, 0.3),
+                UnitQuaternion::from_euler_angles(0.0, -18.0_f32.to_radians(), 0.0),
+                Vector3::from_element(0.6),
+            ),
+        ];
+
+        let lights = vec![Light {
+            name: Some("Ceiling light".to_string()),
+            position: Point3::new(0.0, 1.9, 0.0),
+            color: Color::from_element(1.0),
+            intensity: 3.0,
+            enabled: true,
+            ..Light::default()
+        }];
+
+        let camera = Camera {
+            position: Point3::new(0.0, 1.0, 3.5),
+            look_at: Point3::new(0.0, 1.0, 0.0),
+            fov: 40.0_f32.to_radians(),
+            ..Camera::default()
+        };
+
+        (objects, lights, camera)
+    }
+
+    fn three_point_sphere() -> (Vec<Object>, Vec<Light>, Camera) {
+        let objects = vec![
+            place(
+                Object::quad(
+                    "Floor",
+                    Some(material("Floor", Color::new(0.5, 0.5, 0.5), 1)),
+                ),
+                Vector3::zeros(),
+                UnitQuaternion::identity(),
+                Vector3::from_element(10.0),
+            ),
+            place(
+                Object::sphere(
+                    "Sphere",
+                    Some(Material {
+                        specular_exponent: Some(50.0),
+                        ..material("Sphere", Color::new(0.8, 0.3, 0.2), 2)
+                    }),
+                ),
+                Vector3::new(0.0, 1.0, 0.0),
+                UnitQuaternion::identity(),
+                Vector3::from_element(1.0),
+            ),
+        ];
+
+        let lights = vec![
+            Light {
+                name: Some("Key light".to_string()),
+                position: Point3::new(3.0, 4.0, 3.0),
+                color: Color::new(1.0, 0.95, 0.85),
+                intensity: 30.0,
+                enabled: true,
+                ..Light::default()
+            },
+            Light {
+                name: Some("Fill light".to_string()),
+                position: Point3::new(-4.0, 2.0, 2.0),
+                color: Color::new(0.8, 0.85, 1.0),
+                intensity: 10.0,
+                enabled: true,
+                ..Light::default()
+            },
+            Light {
+                name: Some("Rim light".to_string()),
+                position: Point3::new(0.0, 3.0, -4.0),
+                color: Color::from_element(1.0),
+                intensity: 20.0,
+                enabled: true,
+                ..Light::default()
+            },
+        ];
+
+        let camera = Camera {
+            position: Point3::new(0.0, 1.5, 5.0),
+            look_at: Point3::new(0.0, 1.0, 0.0),
+            ..Camera::default()
+        };
+
+        (objects, lights, camera)
+    }
+
+    fn material_grid() -> (Vec<Object>, Vec<Light>, Camera) {
+        const COLUMNS: usize = 5;
+
+        let mut objects = vec![place(
+            Object::quad(
+                "Floor",
+                Some(material("Floor", Color::new(0.4, 0.4, 0.4), 1)),
+            ),
+            Vector3::zeros(),
+            UnitQuaternion::identity(),
+            Vector3::from_element(10.0),
+        )];
+
+        for column in 0..COLUMNS {
+            let t = column as f32 / (COLUMNS - 1) as f32;
+            let x = column as f32 - (COLUMNS - 1) as f32 / 2.0;
+
+            let rows = [
+                // diffuse with varying hue
+                material(
+                    &format!("Diffuse {}", column + 1),
+                    Color::new(1.0 - t, 0.3, t),
+                    1,
+                ),
+                // specular with increasing exponent
+                Material {
+                    specular_exponent: Some(5.0 * 100.0_f32.powf(t)),
+                    ..material(
+                        &format!("Specular {}", column + 1),
+                        Color::new(0.2, 0.4, 0.8),
+                        2,
+                    )
+                },
+                // reflective with increasing brightness
+                material(
+                    &format!("Reflective {}", column + 1),
+                    Color::from_element(t.mul_add(0.8, 0.2)),
+                    3,
+                ),
+            ];
+
+            for (row, m) in rows.into_iter().enumerate() {
+                let name = m.name.clone();
+                objects.push(place(
+                    Object::sphere(&name, Some(m)),
+                    Vector3::new(x, row as f32 + 0.5, 0.0),
+                    UnitQuaternion::identity(),
+                    Vector3::from_element(0.4),
+                ));
+            }
+        }
+
+        let lights = vec![
+            Light {
+                name: Some("Key light".to_string()),
+                position: Point3::new(0.0, 5.0, 4.0),
+                color: Color::from_element(1.0),
+                intensity: 40.0,
+                enabled: true,
+                ..Light::default()
+            },
+            Light {
+                name: Some("Fill light".to_string()),
+                position: Point3::new(-5.0, 3.0, 2.0),
+                color: Color::from_element(1.0),
+                intensity: 10.0,
+                enabled: true,
+                ..Light::default()
+            },
+        ];
+
+        let camera = Camera {
+            position: Point3::new(0.0, 1.5, 7.0),
+            look_at: Point3::new(0.0, 1.5, 0.0),
+            ..Camera::default()
+        };
+
+        (objects, lights, camera)
+    }
+
+    /// Build the template scene at the given path.
+    /// The generated geometry is saved as OBJ files in a folder next to the scene,
+    /// so the scene can be saved and loaded like any other.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the model directory or a model file can't be written
+    pub fn create(self, path: &Path) -> anyhow::Result<Scene> {
+        let mut scene = self.build(path);
+
+        let stem = path.file_stem().map_or_else(
+            || self.name().to_string(),
+            |s| s.to_string_lossy().to_string(),
+        );
+        let models = std::path::PathBuf::from(format!("{stem}_models"));
+        let directory = path.parent().unwrap_or_else(|| Path::new("")).join(&models);
+
+        std::fs::create_dir_all(&directory).context(format!(
+            "Failed to create model directory: {}",
+            directory.display()
+        ))?;
+
+        for (i, object) in scene.objects.iter_mut().enumerate() {
+            let file = format!("{i}_{}.obj", object.name.to_lowercase().replace(' ', "_"));
+            object.save_obj(directory.join(&file))?;
+            object.path = models.join(file);
+        }
+
+        Ok(scene)
+    }
+
+    /// Build the template scene in memory without saving the geometry,
+    /// e.g. where there is no file system
+    #[must_use]
+    pub fn build(self, path: &Path) -> Scene {
+        let (objects, lights, camera) = match self {
+            Self::CornellBox => Self::cornell_box(),
+            Self::ThreePointSphere => Self::three_point_sphere(),
+            Self::MaterialGrid => Self::material_grid(),
+        };
+
+        let mut scene = Scene {
+            path: path.to_path_buf(),
+            objects,
+            groups: Vec::new(),
+            lights,
+            cameras: vec![camera],
+            active_camera: 0,
+            settings: Settings {
+                skybox: Skybox::Color(Color::zeros()),
+                ambient_intensity: 0.05,
+                ..Settings::default()
+            },
+            materials: BTreeMap::new(),
+            material_library: None,
+            library_materials: BTreeMap::new(),
+            solo_light: None,
+        };
+        scene.assign_object_ids();
+        scene
+    }
+}
@@ -0,0 +1,174 @@
+use super::Color;
+use crate::raytracer::Ray;
+use nalgebra::{Affine3, Matrix3, Point3, Similarity3, Vector2, Vector3};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    pub a: Point3<f32>,
+    pub b: Point3<f32>,
+    pub c: Point3<f32>,
+    pub a_normal: Vector3<f32>,
+    pub b_normal: Vector3<f32>,
+    pub c_normal: Vector3<f32>,
+    pub a_uv: Vector2<f32>,
+    pub b_uv: Vector2<f32>,
+    pub c_uv: Vector2<f32>,
+    pub material_index: Option<usize>,
+    /// Vertex colors of the model, the diffuse color where the material has none
+    pub colors: Option<[Color; 3]>,
+}
+
+/// Everything a ray needs to test a triangle, derived from its positions once.
+/// Meshes keep these apart from the shading data of the triangles,
+/// so the tests during traversal read one cache line per triangle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Intersector {
+    a: Point3<f32>,
+    ab: Vector3<f32>,
+    bc: Vector3<f32>,
+    ca: Vector3<f32>,
+    /// Unit normal, zero for degenerate triangles
+    normal: Vector3<f32>,
+    /// Length of the cross product of the edges, twice the area
+    area: f32,
+}
+
+impl Intersector {
+    pub fn new(a: &Point3<f32>, b: &Point3<f32>, c: &Point3<f32>) -> Self {
+        let ab = b - a;
+        let ac = c - a;
+        let cross = ab.cross(&ac);
+        let area = cross.norm();
+
+        Self {
+            a: *a,
+            ab,
+            bc: c - b,
+            ca: a - c,
+            normal: if area > 0.0 {
+                cross / area
+            } else {
+                Vector3::zeros()
+            },
+            area,
+        }
+    }
+
+    /// Distance along the ray and barycentric coordinates if the ray hits the triangle
+    pub fn intersect(&self, ray: Ray, delta: f32) -> Option<(f32, (f32, f32, f32))> {
+        let Self {
+            a,
+            ab,
+            bc,
+            ca,
+            normal,
+            area,
+        } = self;
+        if *area <= delta {
+            return None;
+        }
+
+        let t = (a - ray.origin).dot(normal) / ray.direction.dot(normal);
+
+        if t < delta {
+            return None;
+        }
+
+        let p = ray.origin + ray.direction * t;
+
+        // the vectors from b and c follow from the one from a and the edges
+        let ap = p - a;
+        let bp = ap - ab;
+        let cp = ap + ca;
+
+        let ab_ap = ab.cross(&ap);
+        let bc_bp = bc.cross(&bp);
+        let ca_cp = ca.cross(&cp);
+
+        let ab_ap_dot = ab_ap.dot(normal);
+        let bc_bp_dot = bc_bp.dot(normal);
+        let ca_cp_dot = ca_cp.dot(normal);
+
+        if ab_ap_dot < 0.0 || bc_bp_dot < 0.0 || ca_cp_dot < 0.0 {
+            return None;
+        }
+
+        let area = ab_ap_dot + bc_bp_dot + ca_cp_dot;
+
+        Some((t, (bc_bp_dot / area, ca_cp_dot / area, ab_ap_dot / area)))
+    }
+}
+
+impl Triangle {
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
+        a: Point3<f32>,
+        b: Point3<f32>,
+        c: Point3<f32>,
+        a_normal: Vector3<f32>,
+        b_normal: Vector3<f32>,
+        c_normal: Vector3<f32>,
+        a_uv: Vector2<f32>,
+        b_uv: Vector2<f32>,
+        c_uv: Vector2<f32>,
+        material_index: Option<usize>,
+    ) -> Self {
+        Self {
+            a,
+            b,
+            c,
+            a_normal,
+            b_normal,
+            c_normal,
+            a_uv,
+            b_uv,
+            c_uv,
+            material_index,
+            colors: None,
+        }
+    }
+
+    pub const fn with_colors(mut self, colors: [Color; 3]) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Move the triangle, normals are only rotated as the scale is uniform
+    pub fn transformed(&self, transform: &Similarity3<f32>) -> Self {
+        let rotation = transform.isometry.rotation;
+        let triangle = Self::new(
+            transform.transform_point(&self.a),
+            transform.transform_point(&self.b),
+            transform.transform_point(&self.c),
+            rotation * self.a_normal,
+            rotation * self.b_normal,
+            rotation * self.c_normal,
+            self.a_uv,
+            self.b_uv,
+            self.c_uv,
+            self.material_index,
+        );
+        Self {
+            colors: self.colors,
+            ..triangle
+        }
+    }
+
+    /// Move the triangle by any transform, normals are transformed by the normal matrix of it
+    pub fn transformed_affine(&self, transform: &Affine3<f32>, normals: &Matrix3<f32>) -> Self {
+        let normal = |n: Vector3<f32>| (normals * n).try_normalize(f32::EPSILON).unwrap_or(n);
+        Self {
+            a: transform.transform_point(&self.a),
+            b: transform.transform_point(&self.b),
+            c: transform.transform_point(&self.c),
+            a_normal: normal(self.a_normal),
+            b_normal: normal(self.b_normal),
+            c_normal: normal(self.c_normal),
+            ..self.clone()
+        }
+    }
+
+    pub fn intersector(&self) -> Intersector {
+        Intersector::new(&self.a, &self.b, &self.c)
+    }
+}
@@ -0,0 +1,333 @@
+use super::{files, model_files, Camera, Scene, SceneError, Skybox};
+use crate::raytracer::Ray;
+use nalgebra::Vector3;
+use ordered_float::OrderedFloat;
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+
+/// Triangles with a smaller area or normals with a smaller length count as degenerate
+const EPSILON: f32 = 1e-9;
+
+/// Aspect ratios outside of this range are most likely a typo in the resolution
+const ASPECT_RATIO: std::ops::RangeInclusive<f32> = 0.25..=4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    /// The scene can't be rendered as intended
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Object, light or camera the issue was found in
+    pub subject: String,
+    pub message: String,
+}
+
+/// Problems found in a scene that would otherwise only show up in the render
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    /// Check the files, geometry, lights and camera of the scene
+    #[must_use]
+    pub fn run(scene: &Scene) -> Self {
+        let mut report = Self::default();
+
+        report.files(scene);
+        report.geometry(scene);
+        report.lights(scene);
+        report.cameras(scene);
+
+        report.issues.sort_by_key(|i| std::cmp::Reverse(i.severity));
+        report
+    }
+
+    #[must_use]
+    pub fn count(&self, severity: Severity) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == severity)
+            .count()
+    }
+
+    fn push(&mut self, severity: Severity, subject: &str, message: String) {
+        self.issues.push(Issue {
+            severity,
+            subject: subject.to_string(),
+            message,
+        });
+    }
+
+    /// Models, materials and textures that don't exist
+    fn files(&mut self, scene: &Scene) {
+        let scene_directory = scene.path.parent().unwrap_or_else(|| Path::new(""));
+
+        for object in scene
+            .objects
+            .iter()
+            .filter(|o| !o.path.as_os_str().is_empty())
+        {
+            let model = scene_directory.join(&object.path);
+            let directory = model.parent().unwrap_or_else(|| Path::new(""));
+
+            for file in model_files(&model) {
+                if !files::exists(&directory.join(&file)) {
+                    self.push(
+                        Severity::Error,
+                        &object.name,
+                        format!("missing file {}", file.display()),
+                    );
+                }
+            }
+
+            for (material, texture) in object
+                .material_overrides
+                .iter()
+                .filter_map(|(name, o)| o.diffuse_texture.as_ref().map(|t| (name, t)))
+            {
+                if !files::exists(&scene_directory.join(texture)) {
+                    self.push(
+                        Severity::Error,
+                        &object.name,
+                        format!(
+                            "missing texture {} of material {}",
+                            texture.display(),
+                            material
+                        ),
+                    );
+                }
+            }
+        }
+
+        for error in scene.material_errors() {
+            if let SceneError::UnknownMaterial {
+                path,
+                object,
+                material,
+            } = &error
+            {
+                self.push(
+                    Severity::Warning,
+                    object,
+                    format!("material {material} not defined in {}", path.display()),
+                );
+            }
+        }
+
+        if let Some(library) = &scene.material_library {
+            if !files::exists(&scene_directory.join(library)) {
+                self.push(
+                    Severity::Error,
+                    "Scene",
+                    format!("missing material library {}", library.display()),
+                );
+            }
+        }
+
+        for (name, material) in scene.library() {
+            if let Some(texture) = material.diffuse_texture {
+                if !files::exists(&scene_directory.join(&texture)) {
+                    self.push(
+                        Severity::Error,
+                        "Scene",
+                        format!("missing texture {} of material {}", texture.display(), name),
+                    );
+                }
+            }
+        }
+
+        if let Skybox::Image { path, .. } = &scene.settings.skybox {
+            if !files::exists(path) {
+                self.push(
+                    Severity::Warning,
+                    "Scene",
+                    format!("skybox {} was moved or deleted", path.display()),
+                );
+            }
+        }
+    }
+
+    /// Empty meshes, degenerate triangles and zero normals
+    fn geometry(&mut self, scene: &Scene) {
+        for object in &scene.objects {
+            let triangles = &object.mesh.triangles;
+
+            if triangles.is_empty() {
+                self.push(Severity::Warning, &object.name, "has no triangles".into());
+                continue;
+            }
+
+            let degenerate = triangles
+                .iter()
+                .filter(|t| (t.b - t.a).cross(&(t.c - t.a)).norm() <= EPSILON)
+                .count();
+            if degenerate > 0 {
+                self.push(
+                    Severity::Warning,
+                    &object.name,
+                    format!("{degenerate} degenerate triangles"),
+                );
+            }
+
+            let zero_normals = triangles
+                .iter()
+                .filter(|t| {
+                    [t.a_normal, t.b_normal, t.c_normal]
+                        .iter()
+                        .any(|n| n.norm() <= EPSILON)
+                })
+                .count();
+            if zero_normals > 0 {
+                self.push(
+                    Severity::Warning,
+                    &object.name,
+                    format!("{zero_normals} triangles with zero normals"),
+                );
+            }
+        }
+    }
+
+    /// Lights enclosed by geometry, seen from inside in every direction
+    fn lights(&mut self, scene: &Scene) {
+        let directions = [
+            Vector3::x(),
+            -Vector3::x(),
+            Vector3::y(),
+            -Vector3::y(),
+            Vector3::z(),
+            -Vector3::z(),
+        ];
+
+        for (i, light) in scene.lights.iter().enumerate() {
+            let name = light.name.clone().unwrap_or_else(|| format!("Light {i}"));
+
+            let enclosing = directions
+                .iter()
+                .map(|&direction| {
+                    let ray = Ray {
+                        origin: light.position,
+                        direction,
+                    };
+
+                    scene
+                        .objects
+                        .iter()
+                        .filter_map(|o| o.intersect(ray, 1e-5, None).map(|hit| (o, hit)))
+                        .min_by_key(|(_, hit)| {
+                            OrderedFloat((hit.point - light.position).norm_squared())
+                        })
+                        // a back face is hit from inside of a closed mesh
+                        .filter(|(_, hit)| hit.normal.dot(&direction) > 0.0)
+                        .map(|(o, _)| o.name.as_str())
+                })
+                .collect::<Option<Vec<_>>>();
+
+            if let Some(object) = enclosing.and_then(|o| o.first().copied()) {
+                self.push(
+                    Severity::Warning,
+                    &name,
+                    format!("is inside of {object} and lights nothing outside of it"),
+                );
+            }
+
+            if light.intensity <= 0.0 {
+                self.push(Severity::Warning, &name, "has no intensity".into());
+            }
+        }
+
+        if scene.active_lights().next().is_none() && scene.settings.ambient_intensity <= 0.0 {
+            self.push(
+                Severity::Warning,
+                "Scene",
+                "has no enabled lights and no ambient light".into(),
+            );
+        }
+    }
+
+    /// Resolution, aspect ratio, clipping planes and a camera orientation that can't be computed
+    fn cameras(&mut self, scene: &Scene) {
+        for (i, camera) in scene.cameras.iter().enumerate() {
+            let name = camera.name.clone().unwrap_or_else(|| format!("Camera {i}"));
+            self.camera(camera, &name);
+        }
+    }
+
+    fn camera(&mut self, camera: &Camera, name: &str) {
+        let (width, height) = camera.resolution;
+
+        if width == 0 || height == 0 {
+            self.push(
+                Severity::Error,
+                name,
+                format!("resolution {width}x{height} is empty"),
+            );
+        } else if !ASPECT_RATIO.contains(&(width as f32 / height as f32)) {
+            self.push(
+                Severity::Warning,
+                name,
+                format!("resolution {width}x{height} has an unusual aspect ratio"),
+            );
+        }
+
+        if camera.fov <= 0.0 || camera.fov >= std::f32::consts::PI {
+            self.push(
+                Severity::Error,
+                name,
+                format!(
+                    "field of view {:.1}° is outside of (0°, 180°)",
+                    camera.fov.to_degrees()
+                ),
+            );
+        }
+
+        if camera.near <= 0.0 || camera.far <= camera.near {
+            self.push(
+                Severity::Error,
+                name,
+                format!(
+                    "clipping planes near {} and far {} are not 0 < near < far",
+                    camera.near, camera.far
+                ),
+            );
+        }
+
+        let direction = camera.look_at - camera.position;
+        if direction.norm() <= EPSILON {
+            self.push(Severity::Error, name, "looks at its own position".into());
+        } else if direction.cross(&camera.up).norm() <= EPSILON {
+            self.push(
+                Severity::Error,
+                name,
+                "up vector is parallel to the view direction".into(),
+            );
+        }
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} errors, {} warnings",
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        )?;
+
+        for issue in &self.issues {
+            let icon = match issue.severity {
+                Severity::Error => "⛔",
+                Severity::Warning => "⚠",
+            };
+            write!(f, "\n{icon} {}: {}", issue.subject, issue.message)?;
+        }
+
+        Ok(())
+    }
+}
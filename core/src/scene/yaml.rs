@@ -32,6 +32,42 @@ pub mod color {
     }
 }
 
+/// provide (de)serialization for Option<Color> from r,g,b fields
+pub mod optional_color {
+    use serde::{Deserialize, Serialize};
+
+    use crate::scene::Color;
+
+    #[derive(Serialize, Deserialize)]
+    struct Yaml {
+        r: f32,
+        g: f32,
+        b: f32,
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let color = Option::<Yaml>::deserialize(deserializer)?;
+        Ok(color.map(|color| Color::new(color.r, color.g, color.b)))
+    }
+
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S>(color: &Option<Color>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        color
+            .map(|color| Yaml {
+                r: color.x,
+                g: color.y,
+                b: color.z,
+            })
+            .serialize(serializer)
+    }
+}
+
 /// provide (de)serialization for Point3<f32> from x,y,z fields
 pub mod point {
     use nalgebra::Point3;
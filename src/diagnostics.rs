@@ -0,0 +1,126 @@
+use anyhow::Context;
+use log::{info, warn, LevelFilter};
+use simplelog::{
+    ColorChoice, CombinedLogger, ConfigBuilder, TermLogger, TerminalMode, WriteLogger,
+};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use trayracer_core::scene::Scene;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// Log files kept, the one of this session and the ones of the sessions before
+const LOG_FILES: usize = 5;
+
+/// Graphics adapter the preview renders with, known once the app has started
+static GPU: OnceLock<String> = OnceLock::new();
+
+fn log_directory() -> PathBuf {
+    std::env::temp_dir().join("trayracer").join("logs")
+}
+
+/// Log file of the session that many sessions ago, 0 is the current one
+fn log_file(age: usize) -> PathBuf {
+    match age {
+        0 => log_directory().join("trayracer.log"),
+        age => log_directory().join(format!("trayracer.{age}.log")),
+    }
+}
+
+/// Shift the log files of earlier sessions by one, dropping the oldest
+fn rotate() -> anyhow::Result<()> {
+    std::fs::create_dir_all(log_directory()).context("Failed to create log directory")?;
+    for age in (0..LOG_FILES - 1).rev() {
+        let file = log_file(age);
+        if file.exists() {
+            std::fs::rename(&file, log_file(age + 1))
+                .context(format!("Failed to rotate log file {}", file.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Log to the terminal and to a log file, the file logger is skipped if it can't be created
+pub fn init_logger() -> anyhow::Result<()> {
+    #[cfg(debug_assertions)]
+    let level = LevelFilter::Trace;
+    #[cfg(not(debug_assertions))]
+    let level = LevelFilter::Info;
+
+    let config = ConfigBuilder::new()
+        // suppress all logs from dependencies, the core crate is prefixed the same
+        .add_filter_allow_str("trayracer")
+        .build();
+
+    let file =
+        rotate().and_then(|()| File::create(log_file(0)).context("Failed to create log file"));
+    let mut loggers: Vec<Box<dyn simplelog::SharedLogger>> = vec![TermLogger::new(
+        level,
+        config.clone(),
+        TerminalMode::Mixed,
+        ColorChoice::Auto,
+    )];
+    let file = file.map(|file| loggers.push(WriteLogger::new(level, config, file)));
+    CombinedLogger::init(loggers).context("Failed to initialize logger")?;
+
+    match file {
+        Ok(()) => info!("Logging to {}", log_file(0).display()),
+        Err(e) => warn!("Logging to the terminal only: {e:#}"),
+    }
+    Ok(())
+}
+
+/// Remember the graphics adapter for the diagnostics
+pub fn set_gpu(info: &eframe::wgpu::AdapterInfo) {
+    GPU.set(format!(
+        "{} ({:?}, {:?}, driver {} {})",
+        info.name, info.device_type, info.backend, info.driver, info.driver_info
+    ))
+    .ok();
+}
+
+/// Version, system and graphics adapter, as text for a bug report
+fn system_info() -> String {
+    format!(
+        "TrayRacer {}\nOS: {} {}\nThreads: {}\nLocale: {}\nGPU: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::thread::available_parallelism().map_or(1, usize::from),
+        sys_locale::get_locale().unwrap_or_default(),
+        GPU.get().map_or("unknown", String::as_str),
+    )
+}
+
+/// Zip the recent logs, the scene and the system information to attach them to a bug report
+pub fn export(path: &Path, scene: Option<&Scene>) -> anyhow::Result<()> {
+    let mut zip =
+        ZipWriter::new(File::create(path).context(format!("Failed to create {}", path.display()))?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("system.txt", options)?;
+    zip.write_all(system_info().as_bytes())?;
+
+    if let Some(scene) = scene {
+        zip.start_file("scene.yaml", options)?;
+        zip.write_all(scene.to_yaml()?.as_bytes())?;
+    }
+
+    for file in (0..LOG_FILES).map(log_file).filter(|f| f.exists()) {
+        let name = file
+            .file_name()
+            .map(|n| format!("logs/{}", n.to_string_lossy()))
+            .unwrap_or_default();
+        zip.start_file(name, options)?;
+        zip.write_all(
+            &std::fs::read(&file).context(format!("Failed to read {}", file.display()))?,
+        )?;
+    }
+
+    zip.finish().context("Failed to write diagnostics")?;
+    info!("Exported diagnostics to {}", path.display());
+    Ok(())
+}
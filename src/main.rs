@@ -1,42 +1,70 @@
 extern crate rust_i18n;
 
-use anyhow::Context;
-use eframe::Renderer;
-use egui::ViewportBuilder;
-use log::{error, info, LevelFilter};
+use log::error;
 use rust_i18n::i18n;
-use scene::Scene;
-use simplelog::{ColorChoice, ConfigBuilder, TerminalMode};
 use sys_locale::get_locale;
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    anyhow::Context,
+    eframe::Renderer,
+    egui::ViewportBuilder,
+    log::info,
+    server::RemoteControl,
+    std::time::Instant,
+    trayracer_core::{
+        raytracer::{audit::AuditReport, benchmark::Benchmark},
+        scene::Scene,
+    },
+};
 
-mod raytracer;
-mod scene;
+#[cfg(not(target_arch = "wasm32"))]
+mod diagnostics;
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
 mod ui;
 i18n!("locales", fallback = "en");
 
+#[cfg(not(target_arch = "wasm32"))]
 fn main() -> anyhow::Result<()> {
     rust_i18n::set_locale(
         get_locale()
             .unwrap_or_else(|| String::from("en-US"))
             .as_str(),
     );
-    simplelog::TermLogger::init(
-        #[cfg(debug_assertions)]
-        LevelFilter::Trace,
-        #[cfg(not(debug_assertions))]
-        LevelFilter::Info,
-        ConfigBuilder::new()
-            // suppress all logs from dependencies
-            .add_filter_allow_str("raytracing")
-            .build(),
-        TerminalMode::Mixed,
-        ColorChoice::Auto,
-    )
-    .context("Failed to initialize logger")?;
+    diagnostics::init_logger()?;
     info!(
         "available translations: {:?}",
         rust_i18n::available_locales!()
     );
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let scene_arg = |flag: &str| {
+        args.iter()
+            .position(|a| a == flag)
+            .map(|i| {
+                args.get(i + 1)
+                    .with_context(|| format!("{flag} requires a path to a scene file"))
+            })
+            .transpose()
+    };
+
+    if let Some(i) = args.iter().position(|a| a == "--serve") {
+        let port = args
+            .get(i + 1)
+            .context("--serve requires a port")?
+            .parse()
+            .context("Invalid port")?;
+        return RemoteControl::serve(port);
+    }
+
+    if let Some(path) = scene_arg("--benchmark")? {
+        return benchmark(path);
+    }
+
+    if let Some(path) = scene_arg("--audit")? {
+        return audit(path);
+    }
+
     let viewport = ViewportBuilder::default()
         .with_title("Trayracer")
         .with_app_id("raytracer")
@@ -57,7 +85,7 @@ fn main() -> anyhow::Result<()> {
         },
         Box::new(|cc| {
             Ok(Box::new(ui::App::new(cc).unwrap_or_else(|e| {
-                error!("Failed to create app: {}", e);
+                error!("Failed to create app: {e}");
                 std::process::exit(1);
             })))
         }),
@@ -65,3 +93,64 @@ fn main() -> anyhow::Result<()> {
     .map_err(|e| anyhow::anyhow!(e.to_string()))
     .context("Failed to run native")
 }
+
+/// Start the app in the canvas of the web page, see index.html
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use eframe::wasm_bindgen::JsCast;
+
+    rust_i18n::set_locale(
+        get_locale()
+            .unwrap_or_else(|| String::from("en-US"))
+            .as_str(),
+    );
+    eframe::WebLogger::init(log::LevelFilter::Info).ok();
+
+    wasm_bindgen_futures::spawn_local(async {
+        let Some(canvas) = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id("trayracer"))
+            .and_then(|canvas| canvas.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+        else {
+            error!("The page has no canvas with the id trayracer");
+            return;
+        };
+
+        eframe::WebRunner::new()
+            .start(
+                canvas,
+                eframe::WebOptions {
+                    depth_buffer: 32,
+                    ..Default::default()
+                },
+                Box::new(|cc| Ok(Box::new(ui::App::new(cc)?))),
+            )
+            .await
+            .unwrap_or_else(|e| error!("Failed to start the app: {e:?}"));
+    });
+}
+
+/// Run the benchmark headless on the given scene and print the report
+#[cfg(not(target_arch = "wasm32"))]
+fn benchmark(path: &str) -> anyhow::Result<()> {
+    let start = Instant::now();
+    let scene = Scene::load(path)?;
+    let load = start.elapsed();
+
+    let report = Benchmark::default().run(&scene);
+
+    println!("scene load (incl. BVH build): {load:.2?}");
+    println!("{report}");
+
+    Ok(())
+}
+
+/// Run the energy conservation audit on the given scene and print the report
+#[cfg(not(target_arch = "wasm32"))]
+fn audit(path: &str) -> anyhow::Result<()> {
+    let scene = Scene::load(path)?;
+
+    println!("{}", AuditReport::run(&scene));
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use anyhow::Context;
+use image::Rgba32FImage;
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    path::PathBuf,
+    sync::{atomic::Ordering, Arc},
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+use trayracer_core::{
+    raytracer::{
+        export::{ExportFormat, ExportOptions},
+        render::{Render, RenderThread},
+    },
+    scene::Scene,
+};
+
+/// Body of the request to load a scene
+#[derive(Deserialize)]
+struct LoadScene {
+    path: PathBuf,
+}
+
+enum Reply {
+    Json(u16, Value),
+    File(Vec<u8>, &'static str),
+}
+
+impl Reply {
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::Json(status, json!({ "error": message.into() }))
+    }
+}
+
+/// Headless renderer controlled over HTTP, to orchestrate renders from scripts and CI
+///
+/// - `POST /scene` with `{"path": "..."}` loads a scene file or bundle
+/// - `POST /render` starts rendering the loaded scene
+/// - `POST /cancel` cancels the running render
+/// - `GET /progress` reports the progress, whether it is rendering and the render time
+/// - `GET /result?format=png|png16|jpeg|exr` downloads the finished render
+pub struct RemoteControl {
+    render: Render,
+    scene: Option<Scene>,
+    /// Whether the last render completed without being cancelled
+    finished: bool,
+}
+
+impl RemoteControl {
+    fn new() -> Self {
+        Self {
+            render: Render::new(Arc::new(())),
+            scene: None,
+            finished: false,
+        }
+    }
+
+    /// Answer requests on the port of localhost until the process is stopped
+    pub fn serve(port: u16) -> anyhow::Result<()> {
+        let server = Server::http(("127.0.0.1", port))
+            .map_err(|e| anyhow::anyhow!(e.to_string()))
+            .context(format!("Failed to listen on port {port}"))?;
+        info!("Listening on http://127.0.0.1:{port}");
+
+        let mut control = Self::new();
+        for mut request in server.incoming_requests() {
+            control.update();
+
+            let reply = control.handle(&mut request).unwrap_or_else(|e| {
+                warn!(
+                    "Request {} {} failed: {:?}",
+                    request.method(),
+                    request.url(),
+                    e
+                );
+                Reply::error(400, format!("{e:#}"))
+            });
+            let (status, data, content_type) = match reply {
+                Reply::Json(status, value) => {
+                    (status, value.to_string().into_bytes(), "application/json")
+                }
+                Reply::File(data, content_type) => (200, data, content_type),
+            };
+            let mut response = Response::from_data(data).with_status_code(status);
+            if let Ok(header) = Header::from_bytes("Content-Type", content_type) {
+                response.add_header(header);
+            }
+
+            if let Err(e) = request.respond(response) {
+                warn!("Failed to respond: {e}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reset the rendering thread once it has finished
+    fn update(&mut self) {
+        if self
+            .render
+            .thread
+            .as_ref()
+            .is_some_and(RenderThread::is_finished)
+        {
+            self.render.thread = None;
+            // only completed renders can be downloaded
+            self.finished = !self.render.cancel.swap(false, Ordering::Relaxed);
+            info!("Render finished, completed: {}", self.finished);
+        }
+    }
+
+    fn handle(&mut self, request: &mut Request) -> anyhow::Result<Reply> {
+        let method = request.method().clone();
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((url.as_str(), ""));
+
+        Ok(match (&method, path) {
+            (Method::Post, "/scene") => {
+                if self.render.thread.is_some() {
+                    return Ok(Reply::error(409, "A render is running"));
+                }
+
+                let body: LoadScene =
+                    serde_json::from_reader(request.as_reader()).context("Invalid request body")?;
+                let scene = Scene::open(&body.path)?;
+                info!("Loaded scene {}", body.path.display());
+
+                let reply = json!({
+                    "path": body.path,
+                    "resolution": scene.camera().resolution,
+                    "samples": scene.settings.samples,
+                });
+                self.scene = Some(scene);
+                // the render of the previous scene can't be downloaded for this one
+                self.finished = false;
+                *self.render.raw.lock() = Rgba32FImage::new(0, 0);
+                Reply::Json(200, reply)
+            }
+            (Method::Post, "/render") => match &self.scene {
+                None => Reply::error(409, "No scene is loaded"),
+                Some(_) if self.render.thread.is_some() => Reply::error(409, "A render is running"),
+                Some(scene) => {
+                    self.finished = false;
+                    self.render.render(scene);
+                    Reply::Json(202, json!({ "rendering": true }))
+                }
+            },
+            (Method::Post, "/cancel") => {
+                if self.render.thread.is_some() {
+                    self.render.cancel.store(true, Ordering::Relaxed);
+                }
+                Reply::Json(200, json!({ "rendering": self.render.thread.is_some() }))
+            }
+            (Method::Get, "/progress") => Reply::Json(
+                200,
+                json!({
+                    "rendering": self.render.thread.is_some(),
+                    "finished": self.finished,
+                    "progress": f32::from(self.render.progress.load(Ordering::Relaxed))
+                        / f32::from(u16::MAX),
+                    "time": self.render.time.load(Ordering::Relaxed),
+                }),
+            ),
+            (Method::Get, "/result") => {
+                if !self.finished {
+                    return Ok(Reply::error(409, "No finished render"));
+                }
+                self.result(query)?
+            }
+            _ => Reply::error(404, format!("Unknown endpoint {path}")),
+        })
+    }
+
+    /// The finished render exported in the format of the query
+    fn result(&self, query: &str) -> anyhow::Result<Reply> {
+        let format = query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("format="))
+            .unwrap_or("png");
+        let (format, content_type) = match format {
+            "png" => (ExportFormat::Png8, "image/png"),
+            "png16" => (ExportFormat::Png16, "image/png"),
+            "jpeg" | "jpg" => (ExportFormat::Jpeg, "image/jpeg"),
+            "exr" => (ExportFormat::Exr, "image/x-exr"),
+            _ => return Ok(Reply::error(400, format!("Unknown format {format}"))),
+        };
+        let options = ExportOptions {
+            format,
+            ..ExportOptions::default()
+        };
+
+        // the encoders write files, the export is read back from a temporary one
+        let path = std::env::temp_dir().join(format!(
+            "trayracer-{}.{}",
+            std::process::id(),
+            format.extension()
+        ));
+        options.export(
+            &self.render.raw.lock(),
+            &self.render.post.read(),
+            &self.render.metadata(self.scene.as_ref()),
+            &path,
+        )?;
+        let data = std::fs::read(&path).context("Failed to read the exported render")?;
+        std::fs::remove_file(&path).ok();
+
+        Ok(Reply::File(data, content_type))
+    }
+}
@@ -0,0 +1,128 @@
+use super::{background, toasts};
+use egui::{Checkbox, Context, Ui};
+use egui_file::FileDialog;
+use log::{info, warn};
+use rust_i18n::t;
+use std::path::PathBuf;
+use trayracer_core::raytracer::{export::ExportOptions, render::Render};
+use trayracer_core::scene::Scene;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Saves every finished render to a directory with a timestamped filename,
+/// for batch and overnight renders nobody is around to export
+pub struct AutoExport {
+    pub enabled: bool,
+    /// Directory the renders are saved to, nothing is saved until one is chosen
+    pub directory: Option<PathBuf>,
+    dialog: Option<FileDialog>,
+}
+
+impl AutoExport {
+    pub const fn new() -> Self {
+        Self {
+            enabled: false,
+            directory: None,
+            dialog: None,
+        }
+    }
+
+    pub fn options(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add_enabled(
+                self.directory.is_some(),
+                Checkbox::new(&mut self.enabled, t!("auto_export")),
+            )
+            .on_hover_text(t!("auto_export_hint"));
+
+            let directory = self.directory.as_ref().map_or_else(
+                || t!("no_directory").to_string(),
+                |directory| directory.display().to_string(),
+            );
+            if ui.button("📁").on_hover_text(directory).clicked() {
+                let mut dialog = FileDialog::select_folder(self.directory.clone());
+                dialog.open();
+                self.dialog = Some(dialog);
+            }
+        });
+
+        if let Some(dialog) = self.dialog.as_mut() {
+            if dialog.show(ui.ctx()).selected() {
+                if let Some(path) = dialog.path() {
+                    self.directory = Some(path.to_path_buf());
+                    self.enabled = true;
+                }
+                self.dialog = None;
+            }
+        }
+    }
+
+    /// Save the render that just completed with the export options, in the background
+    pub fn export(
+        &self,
+        ctx: &Context,
+        render: &Render,
+        options: ExportOptions,
+        scene: Option<&Scene>,
+    ) {
+        let Some(directory) = self.directory.clone().filter(|_| self.enabled) else {
+            return;
+        };
+
+        let path = directory.join(format!(
+            "render_{}.{}",
+            timestamp(SystemTime::now()),
+            options.format.extension()
+        ));
+        let raw = render.raw.lock().clone();
+        let post = render.post.read().clone();
+        let metadata = render.metadata(scene);
+        let aovs = options.id_mattes.then(|| render.aovs.lock().clone());
+        let ctx = ctx.clone();
+
+        background(move || {
+            let result = options
+                .export(&raw, &post, &metadata, &path)
+                .and_then(|()| {
+                    aovs.map_or(Ok(()), |aovs| {
+                        ExportOptions::export_mattes(&aovs, &metadata, &path)
+                    })
+                });
+            match result {
+                Ok(()) => info!("Exported finished render to {}", path.display()),
+                Err(e) => {
+                    warn!("Failed to export finished render: {e:?}");
+                    toasts::error(&ctx, t!("auto_export_failed", error = format!("{e:#}")));
+                }
+            }
+        });
+    }
+}
+
+/// UTC date and time, sortable and usable in filenames
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+
+    // civil date from the days since 1970-01-01, shifted to eras starting in March
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}_{:02}-{:02}-{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
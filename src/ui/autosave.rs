@@ -0,0 +1,232 @@
+use super::toasts;
+use anyhow::Context;
+use egui::{Align2, DragValue, Ui, Window};
+use log::{info, warn};
+use rust_i18n::t;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use trayracer_core::scene::{Scene, Skybox};
+use web_time::Instant;
+
+/// Periodically saves the open scene to a temporary file,
+/// which is offered for recovery on the next launch if the
+/// app was not closed cleanly before the scene was saved
+pub struct Autosave {
+    /// Minutes between autosaves, 0 disables autosaving
+    pub interval: u32,
+    last_save: Instant,
+    /// Autosave found on launch, with the path of the original scene
+    recovery: Option<(PathBuf, SystemTime)>,
+}
+
+fn directory() -> PathBuf {
+    std::env::temp_dir().join("trayracer")
+}
+
+fn scene_file() -> PathBuf {
+    directory().join("autosave.yaml")
+}
+
+/// Holds the path of the scene the autosave belongs to
+fn origin_file() -> PathBuf {
+    directory().join("autosave.origin")
+}
+
+/// Remove the autosave, e.g. after the scene has been saved or on a clean exit
+pub fn clear() {
+    for file in [scene_file(), origin_file()] {
+        if file.exists() {
+            std::fs::remove_file(&file).unwrap_or_else(|e| {
+                warn!("Failed to remove autosave {}: {}", file.display(), e);
+            });
+        }
+    }
+}
+
+/// Write the scene with absolute asset paths, so it can be loaded from the temp directory
+fn write(scene: &Scene) -> anyhow::Result<()> {
+    let scene_directory = scene.path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut autosave = scene.clone();
+    for object in &mut autosave.objects {
+        if !object.path.as_os_str().is_empty() {
+            object.path = scene_directory.join(&object.path);
+        }
+        for texture in object
+            .material_overrides
+            .values_mut()
+            .filter_map(|o| o.diffuse_texture.as_mut())
+        {
+            *texture = scene_directory.join(&*texture);
+        }
+    }
+    if let Some(library) = &mut autosave.material_library {
+        *library = scene_directory.join(&*library);
+    }
+    for texture in autosave
+        .materials
+        .values_mut()
+        .filter_map(|m| m.diffuse_texture.as_mut())
+    {
+        *texture = scene_directory.join(&*texture);
+    }
+    if let Skybox::Image { path, .. } = &mut autosave.settings.skybox {
+        *path = std::path::absolute(&*path).unwrap_or_else(|_| path.clone());
+    }
+
+    std::fs::create_dir_all(directory()).context("Failed to create autosave directory")?;
+    std::fs::write(scene_file(), autosave.to_yaml()?).context("Failed to write autosave")?;
+    std::fs::write(origin_file(), scene.path.to_string_lossy().as_bytes())
+        .context("Failed to write autosave origin")?;
+
+    Ok(())
+}
+
+/// Load the autosave and make asset paths relative to the original scene again
+fn restore(origin: &Path) -> anyhow::Result<Scene> {
+    let mut scene = Scene::load(scene_file())?;
+    let scene_directory = origin.parent().unwrap_or_else(|| Path::new(""));
+    let relative = |path: &mut PathBuf| {
+        if let Ok(r) = path.strip_prefix(scene_directory) {
+            *path = r.to_path_buf();
+        }
+    };
+
+    for object in &mut scene.objects {
+        relative(&mut object.path);
+        object
+            .material_overrides
+            .values_mut()
+            .filter_map(|o| o.diffuse_texture.as_mut())
+            .for_each(relative);
+    }
+    if let Some(library) = &mut scene.material_library {
+        relative(library);
+    }
+    scene
+        .materials
+        .values_mut()
+        .filter_map(|m| m.diffuse_texture.as_mut())
+        .for_each(relative);
+    scene.path = origin.to_path_buf();
+
+    Ok(scene)
+}
+
+impl Autosave {
+    pub fn new() -> Self {
+        let recovery = std::fs::read_to_string(origin_file())
+            .ok()
+            .zip(
+                std::fs::metadata(scene_file())
+                    .and_then(|m| m.modified())
+                    .ok(),
+            )
+            .map(|(origin, modified)| (PathBuf::from(origin), modified));
+
+        if let Some((origin, _)) = &recovery {
+            info!("Found autosave of {}", origin.display());
+        }
+
+        Self {
+            interval: 2,
+            last_save: Instant::now(),
+            recovery,
+        }
+    }
+
+    /// Autosave the scene if the interval has passed
+    /// and offer to restore an autosave found on launch
+    pub fn update(&mut self, ui: &Ui, scene: &mut Option<Scene>) {
+        self.recovery_window(ui, scene);
+
+        if self.interval == 0 {
+            return;
+        }
+
+        let interval = Duration::from_secs(u64::from(self.interval) * 60);
+        if let Some(remaining) = interval.checked_sub(self.last_save.elapsed()) {
+            // wake up for the next autosave even if there is no input
+            ui.ctx().request_repaint_after(remaining);
+            return;
+        }
+        self.last_save = Instant::now();
+
+        // don't overwrite an autosave that has not been restored or discarded yet
+        if self.recovery.is_some() {
+            return;
+        }
+
+        if let Some(scene) = scene.as_ref() {
+            match write(scene) {
+                Ok(()) => info!("Autosaved {}", scene.path.display()),
+                Err(e) => {
+                    warn!("Failed to autosave scene: {e:?}");
+                    toasts::warning(ui.ctx(), t!("autosave_failed", error = format!("{e:#}")));
+                }
+            }
+        }
+    }
+
+    pub fn options(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("autosave")));
+            ui.add(
+                DragValue::new(&mut self.interval)
+                    .range(0..=60)
+                    .suffix(" min"),
+            )
+            .on_hover_text(t!("autosave_hint"));
+        });
+    }
+
+    fn recovery_window(&mut self, ui: &Ui, scene: &mut Option<Scene>) {
+        let Some((origin, modified)) = &self.recovery else {
+            return;
+        };
+
+        let age = modified.elapsed().unwrap_or_default().as_secs() / 60;
+        let mut close = false;
+
+        Window::new(t!("restore_scene"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+            .show(ui.ctx(), |ui| {
+                ui.label(t!(
+                    "restore_scene_hint",
+                    path = origin.display(),
+                    minutes = age
+                ));
+
+                ui.horizontal(|ui| {
+                    if ui.button(t!("restore")).clicked() {
+                        match restore(origin) {
+                            Ok(s) => {
+                                scene.replace(s);
+                            }
+                            Err(e) => {
+                                warn!("Failed to restore autosave: {e:?}");
+                                toasts::error(
+                                    ui.ctx(),
+                                    t!("restore_failed", error = format!("{e:#}")),
+                                );
+                            }
+                        }
+                        close = true;
+                    }
+
+                    if ui.button(t!("discard")).clicked() {
+                        clear();
+                        close = true;
+                    }
+                });
+            });
+
+        if close {
+            self.recovery = None;
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use eframe::wasm_bindgen::JsCast;
+use log::{info, warn};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+use trayracer_core::scene::{model_files, provide_file};
+use wasm_bindgen_futures::JsFuture;
+
+/// Content of a file on the server of the page, the path is relative to the page
+// the futures of the browser are run with spawn_local, which doesn't need them to be Send
+#[allow(clippy::future_not_send)]
+async fn fetch(path: &Path) -> Result<Vec<u8>, String> {
+    let url = path.to_string_lossy().replace('\\', "/");
+    let window = web_sys::window().ok_or("The page has no window")?;
+
+    let response = JsFuture::from(window.fetch_with_str(&url))
+        .await
+        .map_err(|e| format!("{e:?}"))?
+        .dyn_into::<web_sys::Response>()
+        .map_err(|e| format!("{e:?}"))?;
+    if !response.ok() {
+        return Err(format!("{} {}", response.status(), response.status_text()));
+    }
+
+    let buffer = JsFuture::from(response.array_buffer().map_err(|e| format!("{e:?}"))?)
+        .await
+        .map_err(|e| format!("{e:?}"))?;
+
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+/// Fetch a model with its material libraries and their textures
+/// and provide them to the loader of the core.
+/// The referenced files are only known once the file referencing them is fetched,
+/// files that can't be fetched are left to the loader to report as missing.
+#[allow(clippy::future_not_send)]
+pub async fn model(model: &Path) {
+    let directory = model.parent().unwrap_or_else(|| Path::new(""));
+    let mut fetched = HashSet::<PathBuf>::new();
+
+    loop {
+        let missing = model_files(model)
+            .into_iter()
+            .map(|file| directory.join(file))
+            .filter(|file| !fetched.contains(file))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            break;
+        }
+
+        for file in missing {
+            match fetch(&file).await {
+                Ok(content) => {
+                    info!("Fetched {} ({} bytes)", file.display(), content.len());
+                    provide_file(file.clone(), content);
+                }
+                Err(e) => warn!("Failed to fetch {}: {}", file.display(), e),
+            }
+            fetched.insert(file);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use super::toasts;
+use egui::{
+    Button, CollapsingHeader, ColorImage, Context, Image, ImageButton, RichText, ScrollArea,
+    SidePanel, TextureHandle, TextureOptions, Ui,
+};
+use egui_file::FileDialog;
+use image::Rgba32FImage;
+use log::{info, warn};
+use rust_i18n::t;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use std::{sync::atomic::Ordering, time::Duration};
+use trayracer_core::raytracer::render::{Render, RenderInfo};
+
+/// Width of the thumbnails in pixels
+const THUMBNAIL_SIZE: u32 = 160;
+
+/// Holds the renders of this session, separate per process
+#[cfg(not(target_arch = "wasm32"))]
+fn directory() -> PathBuf {
+    std::env::temp_dir()
+        .join("trayracer")
+        .join(format!("history_{}", std::process::id()))
+}
+
+/// Remove the renders of this session, e.g. on exit
+#[cfg(not(target_arch = "wasm32"))]
+pub fn clear() {
+    let directory = directory();
+    if directory.exists() {
+        std::fs::remove_dir_all(&directory).unwrap_or_else(|e| {
+            warn!(
+                "Failed to remove render history {}: {}",
+                directory.display(),
+                e
+            );
+        });
+    }
+}
+
+/// A completed render, the raw colors are kept on disk,
+/// or in memory on the web without a file system
+struct Entry {
+    thumbnail: TextureHandle,
+    /// Raw colors of the render as EXR, so it can be post processed again
+    #[cfg(not(target_arch = "wasm32"))]
+    path: PathBuf,
+    #[cfg(target_arch = "wasm32")]
+    raw: Rgba32FImage,
+    info: RenderInfo,
+    duration: Duration,
+}
+
+impl Entry {
+    /// Pass the raw colors of the render to `f`, unless they fail to load
+    #[cfg(not(target_arch = "wasm32"))]
+    fn with_raw(&self, f: impl FnOnce(Rgba32FImage)) {
+        match image::open(&self.path) {
+            Ok(image) => f(image.to_rgba32f()),
+            Err(e) => warn!("Failed to load render {}: {e}", self.path.display()),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn with_raw(&self, f: impl FnOnce(Rgba32FImage)) {
+        f(self.raw.clone());
+    }
+}
+
+/// Completed renders of this session, to reopen or export earlier attempts
+pub struct History {
+    /// Width of the panel
+    pub width: f32,
+    entries: Vec<Entry>,
+    /// Dialog to export the entry with the index
+    export_dialog: Option<(usize, FileDialog)>,
+}
+
+impl History {
+    pub const fn new() -> Self {
+        Self {
+            width: 200.0,
+            entries: Vec::new(),
+            export_dialog: None,
+        }
+    }
+
+    /// Add the render that just completed
+    pub fn add(&mut self, ctx: &Context, render: &Render) {
+        let raw = render.raw.lock().clone();
+
+        let image = render.image.lock();
+        let thumbnail = image::imageops::thumbnail(
+            &*image,
+            THUMBNAIL_SIZE,
+            THUMBNAIL_SIZE * image.height() / image.width().max(1),
+        );
+        drop(image);
+
+        // writing the EXR takes a while for large renders
+        #[cfg(not(target_arch = "wasm32"))]
+        let path = {
+            let path = directory().join(format!("render_{}.exr", self.entries.len()));
+            let file = path.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = std::fs::create_dir_all(directory()) {
+                    warn!("Failed to create render history directory: {e}");
+                }
+                match raw.save(&file) {
+                    Ok(()) => info!("Added render to history {}", file.display()),
+                    Err(e) => warn!("Failed to save render to history: {e}"),
+                }
+            });
+            path
+        };
+
+        self.entries.push(Entry {
+            thumbnail: ctx.load_texture(
+                format!("history_{}", self.entries.len()),
+                ColorImage::from_rgb(
+                    [thumbnail.width() as usize, thumbnail.height() as usize],
+                    thumbnail.as_raw(),
+                ),
+                TextureOptions::LINEAR,
+            ),
+            #[cfg(not(target_arch = "wasm32"))]
+            path,
+            #[cfg(target_arch = "wasm32")]
+            raw,
+            info: render.info,
+            duration: Duration::from_millis(u64::from(render.time.load(Ordering::Relaxed))),
+        });
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, render: &mut Render) {
+        self.export_dialog(ui, render);
+
+        let panel = SidePanel::right("history")
+            .show_separator_line(true)
+            .default_width(self.width)
+            .show_inside(ui, |ui| {
+                let title = format!("{} ({})", t!("history"), self.entries.len());
+                CollapsingHeader::new(RichText::new(title).size(16.0))
+                    .default_open(true)
+                    .show_unindented(ui, |ui| {
+                        if self.entries.is_empty() {
+                            ui.label(t!("no_history"));
+                        }
+
+                        ScrollArea::vertical().show(ui, |ui| {
+                            // newest first
+                            for i in (0..self.entries.len()).rev() {
+                                ui.separator();
+                                self.entry(ui, render, i);
+                            }
+                        });
+                    });
+            });
+        self.width = panel.response.rect.width();
+    }
+
+    fn entry(&mut self, ui: &mut Ui, render: &mut Render, index: usize) {
+        let entry = &self.entries[index];
+        let idle = render.thread.is_none();
+
+        let reopen = ui
+            .add_enabled(
+                idle,
+                ImageButton::new(Image::new(&entry.thumbnail).max_width(THUMBNAIL_SIZE as f32)),
+            )
+            .on_hover_text(t!("reopen_render_hint"))
+            .clicked();
+
+        let (width, height) = entry.info.resolution;
+        ui.label(format!(
+            "#{index}  {width}x{height}  {} spp  {:.2} s",
+            entry.info.samples,
+            entry.duration.as_secs_f32()
+        ));
+        let hash = format!("{}: {:016x}", t!("scene_hash"), entry.info.scene_hash);
+        ui.label(RichText::new(hash).small())
+            .on_hover_text(t!("scene_hash_hint"));
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(idle, Button::new(t!("reopen"))).clicked() || reopen {
+                entry.with_raw(|raw| {
+                    info!("Reopening render {index} from history");
+                    render.open(raw, entry.info, entry.duration.as_millis() as u32);
+                });
+            }
+
+            if ui.button(t!("export")).clicked() {
+                let mut dialog = FileDialog::save_file(None)
+                    .default_filename(format!("render_{index}_{width}x{height}.png"))
+                    .filename_filter(Box::new(|name| {
+                        [".png", ".jpg", ".jpeg", ".exr"]
+                            .into_iter()
+                            .any(|ext| name.ends_with(ext))
+                    }));
+                dialog.open();
+                self.export_dialog = Some((index, dialog));
+            }
+        });
+    }
+
+    /// Save the selected entry with the current post processing, EXR files keep the raw colors
+    fn export_dialog(&mut self, ui: &Ui, render: &Render) {
+        let Some((index, dialog)) = &mut self.export_dialog else {
+            return;
+        };
+
+        if !dialog.show(ui.ctx()).selected() {
+            return;
+        }
+
+        match (dialog.path(), self.entries.get(*index)) {
+            (Some(path), Some(entry)) => {
+                entry.with_raw(|raw| {
+                    info!("Exporting render {index} to {}", path.display());
+
+                    let exr = path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("exr"));
+                    let result = if exr {
+                        raw.save(path)
+                    } else {
+                        render.post.read().apply_image(&raw).save(path)
+                    };
+
+                    result.unwrap_or_else(|e| {
+                        warn!("Failed to export render: {e}");
+                        toasts::error(ui.ctx(), t!("export_image_failed", error = e));
+                    });
+                });
+            }
+            _ => warn!("Export dialog selected but returned no path"),
+        }
+
+        self.export_dialog = None;
+    }
+}
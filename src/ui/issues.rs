@@ -0,0 +1,90 @@
+use super::toasts;
+use egui::{hex_color, Button, CollapsingHeader, Color32, Context, RichText, Ui};
+use log::{info, warn};
+use rust_i18n::t;
+use std::path::PathBuf;
+use trayracer_core::scene::{Scene, Severity, ValidationReport};
+
+/// Validation report of the current scene, checked whenever a scene is opened
+pub struct Issues {
+    report: Option<ValidationReport>,
+    /// Scene the report belongs to
+    checked: Option<PathBuf>,
+}
+
+impl Issues {
+    pub const fn new() -> Self {
+        Self {
+            report: None,
+            checked: None,
+        }
+    }
+
+    fn check(&mut self, ctx: &Context, scene: &Scene) {
+        let report = ValidationReport::run(scene);
+        if report.issues.is_empty() {
+            info!("Scene validation found no issues");
+        } else {
+            warn!("Scene validation finished:\n{report}");
+        }
+
+        // e.g. missing models or textures, which are easy to overlook in the render
+        let errors = report.count(Severity::Error);
+        if errors > 0 {
+            toasts::warning(ctx, t!("scene_has_errors", count = errors));
+        }
+
+        self.report = Some(report);
+        self.checked = Some(scene.path.clone());
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, scene: &Scene) {
+        if self.checked.as_ref() != Some(&scene.path) {
+            self.check(ui.ctx(), scene);
+        }
+
+        let count = self.report.as_ref().map_or(0, |r| r.issues.len());
+
+        ui.vertical(|ui| {
+            ui.group(|ui| {
+                CollapsingHeader::new(
+                    RichText::new(format!("{} ({})", t!("issues"), count)).size(16.0),
+                )
+                .default_open(count > 0)
+                .show_unindented(ui, |ui| {
+                    ui.separator();
+
+                    if let Some(report) = &self.report {
+                        if report.issues.is_empty() {
+                            ui.label(t!("no_issues"));
+                        }
+
+                        for issue in &report.issues {
+                            let (icon, color) = match issue.severity {
+                                Severity::Error => ("⛔", hex_color!("#cc0000")),
+                                Severity::Warning => ("⚠", Color32::YELLOW),
+                            };
+
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(RichText::new(icon).color(color));
+                                ui.label(RichText::new(&issue.subject).strong());
+                                ui.label(&issue.message);
+                            });
+                        }
+                    }
+
+                    ui.separator();
+                    ui.vertical_centered(|ui| {
+                        if ui
+                            .add(Button::new(RichText::new(t!("check_scene"))).frame(false))
+                            .on_hover_text(t!("check_scene_hint"))
+                            .clicked()
+                        {
+                            self.check(ui.ctx(), scene);
+                        }
+                    });
+                });
+            });
+        });
+    }
+}
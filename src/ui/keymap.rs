@@ -0,0 +1,249 @@
+use egui::{
+    os::OperatingSystem, Button, Context, Grid, InputState, Key, ModifierNames, Modifiers, Ui,
+};
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Something the keyboard can trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    Render,
+    CancelRender,
+    SaveScene,
+    /// Show the next tab
+    SwitchTab,
+    /// Let the preview fill the window
+    FullscreenPreview,
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    SlowerMovement,
+    FasterMovement,
+    LessSensitive,
+    MoreSensitive,
+    LookAtOrigin,
+    ExitCamera,
+}
+
+impl Action {
+    pub const ALL: [Self; 17] = [
+        Self::Render,
+        Self::CancelRender,
+        Self::SaveScene,
+        Self::SwitchTab,
+        Self::FullscreenPreview,
+        Self::MoveForward,
+        Self::MoveBackward,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::SlowerMovement,
+        Self::FasterMovement,
+        Self::LessSensitive,
+        Self::MoreSensitive,
+        Self::LookAtOrigin,
+        Self::ExitCamera,
+    ];
+
+    fn name(self) -> String {
+        match self {
+            Self::Render => t!("render"),
+            Self::CancelRender => t!("cancel_render"),
+            Self::SaveScene => t!("save_scene"),
+            Self::SwitchTab => t!("switch_tab"),
+            Self::FullscreenPreview => t!("fullscreen_preview"),
+            Self::MoveForward => t!("move_forward"),
+            Self::MoveBackward => t!("move_backward"),
+            Self::MoveLeft => t!("move_left"),
+            Self::MoveRight => t!("move_right"),
+            Self::MoveUp => t!("move_up"),
+            Self::MoveDown => t!("move_down"),
+            Self::SlowerMovement => t!("slower_movement"),
+            Self::FasterMovement => t!("faster_movement"),
+            Self::LessSensitive => t!("less_sensitive"),
+            Self::MoreSensitive => t!("more_sensitive"),
+            Self::LookAtOrigin => t!("look_at_origin"),
+            Self::ExitCamera => t!("exit_camera"),
+        }
+        .to_string()
+    }
+
+    const fn default_binding(self) -> Binding {
+        match self {
+            Self::Render => Binding::key(Key::F12),
+            Self::CancelRender => Binding::shortcut(Modifiers::COMMAND, Key::Period),
+            Self::SaveScene => Binding::shortcut(Modifiers::COMMAND, Key::S),
+            Self::SwitchTab => Binding::shortcut(Modifiers::COMMAND, Key::Tab),
+            Self::FullscreenPreview => Binding::key(Key::Tab),
+            Self::MoveForward => Binding::key(Key::W),
+            Self::MoveBackward => Binding::key(Key::S),
+            Self::MoveLeft => Binding::key(Key::A),
+            Self::MoveRight => Binding::key(Key::D),
+            Self::MoveUp => Binding::key(Key::Space),
+            Self::MoveDown => Binding::modifiers(Modifiers::SHIFT),
+            Self::SlowerMovement => Binding::key(Key::Q),
+            Self::FasterMovement => Binding::key(Key::E),
+            Self::LessSensitive => Binding::key(Key::Y),
+            Self::MoreSensitive => Binding::key(Key::C),
+            Self::LookAtOrigin => Binding::key(Key::F),
+            Self::ExitCamera => Binding::key(Key::Escape),
+        }
+    }
+}
+
+/// A key with the modifiers held with it, or only modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    pub key: Option<Key>,
+    pub modifiers: Modifiers,
+}
+
+impl Binding {
+    const fn key(key: Key) -> Self {
+        Self::shortcut(Modifiers::NONE, key)
+    }
+
+    const fn shortcut(modifiers: Modifiers, key: Key) -> Self {
+        Self {
+            key: Some(key),
+            modifiers,
+        }
+    }
+
+    const fn modifiers(modifiers: Modifiers) -> Self {
+        Self {
+            key: None,
+            modifiers,
+        }
+    }
+
+    /// Pressed in this frame with exactly these modifiers
+    fn pressed(self, input: &InputState) -> bool {
+        self.key.is_some_and(|key| {
+            input.key_pressed(key) && input.modifiers.matches_exact(self.modifiers)
+        })
+    }
+
+    /// Held down, other modifiers may be held as well, e.g. to move diagonally while moving down
+    fn down(self, input: &InputState) -> bool {
+        self.key.map_or_else(
+            || !self.modifiers.is_none() && input.modifiers.contains(self.modifiers),
+            |key| input.key_down(key) && input.modifiers.matches_logically(self.modifiers),
+        )
+    }
+
+    pub fn label(self, ctx: &Context) -> String {
+        let modifiers =
+            ModifierNames::NAMES.format(&self.modifiers, ctx.os() == OperatingSystem::Mac);
+
+        match (self.key, modifiers.is_empty()) {
+            (Some(key), true) => key.name().to_string(),
+            (Some(key), false) => format!("{modifiers}+{}", key.name()),
+            (None, _) => modifiers,
+        }
+    }
+}
+
+/// Keyboard bindings of the actions, remapped in the settings and persisted with the preferences
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    /// Bindings that differ from the defaults
+    bindings: BTreeMap<Action, Binding>,
+    /// Action waiting for a key to be bound to
+    #[serde(skip)]
+    recording: Option<Action>,
+}
+
+impl Keymap {
+    pub fn binding(&self, action: Action) -> Binding {
+        self.bindings
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    pub fn pressed(&self, input: &InputState, action: Action) -> bool {
+        self.recording.is_none() && self.binding(action).pressed(input)
+    }
+
+    pub fn down(&self, input: &InputState, action: Action) -> bool {
+        self.recording.is_none() && self.binding(action).down(input)
+    }
+
+    pub fn label(&self, ctx: &Context, action: Action) -> String {
+        self.binding(action).label(ctx)
+    }
+
+    /// Table of the actions with buttons to record a new binding
+    pub fn show(&mut self, ui: &mut Ui) {
+        if let Some(action) = self.recording {
+            let pressed = ui.input(|i| {
+                i.events.iter().find_map(|e| match e {
+                    egui::Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Some(Binding::shortcut(*modifiers, *key)),
+                    _ => None,
+                })
+            });
+
+            if let Some(binding) = pressed {
+                self.set(action, binding);
+                self.recording = None;
+            }
+        }
+
+        Grid::new("keymap")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for action in Action::ALL {
+                    ui.label(action.name());
+
+                    let text = if self.recording == Some(action) {
+                        t!("press_key").to_string()
+                    } else {
+                        self.label(ui.ctx(), action)
+                    };
+                    if ui
+                        .add(Button::new(text).selected(self.recording == Some(action)))
+                        .on_hover_text(t!("record_binding_hint"))
+                        .clicked()
+                    {
+                        self.recording = (self.recording != Some(action)).then_some(action);
+                    }
+
+                    if ui
+                        .add_enabled(self.bindings.contains_key(&action), Button::new("⟲"))
+                        .on_hover_text(t!("reset_binding_hint"))
+                        .clicked()
+                    {
+                        self.bindings.remove(&action);
+                    }
+
+                    ui.end_row();
+                }
+            });
+
+        if ui.button(t!("reset_all_bindings")).clicked() {
+            self.bindings.clear();
+            self.recording = None;
+        }
+    }
+
+    fn set(&mut self, action: Action, binding: Binding) {
+        if binding == action.default_binding() {
+            self.bindings.remove(&action);
+        } else {
+            self.bindings.insert(action, binding);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use super::toasts;
+use egui::{Context, Ui};
+use log::{info, warn};
+use nalgebra::{Scale3, Translation3, UnitQuaternion};
+use rust_i18n::t;
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+use trayracer_core::scene::{Import, Object, Scene, SceneError};
+use web_time::Instant;
+
+/// An object loaded on a background thread, for the scene it was requested for
+type Loaded = (PathBuf, PathBuf, Result<Object, SceneError>);
+
+/// Loads models and builds their BVH on background threads,
+/// so large files don't freeze the UI. On the web they are fetched first.
+pub struct ObjectLoader {
+    sender: Sender<Loaded>,
+    receiver: Receiver<Loaded>,
+    /// Files being loaded and when loading started
+    pending: Vec<(PathBuf, Instant)>,
+    /// Place loaded objects in front of the camera instead of at the origin
+    pub auto_frame: bool,
+}
+
+impl ObjectLoader {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+
+        Self {
+            sender,
+            receiver,
+            pending: Vec::new(),
+            auto_frame: true,
+        }
+    }
+
+    /// Start loading a model, it is added to the scene once it is ready
+    pub fn load(&mut self, ctx: &Context, scene: &Scene, path: &Path) {
+        info!("Loading object from {}", path.display());
+        self.pending.push((path.to_path_buf(), Instant::now()));
+
+        let sender = self.sender.clone();
+        let ctx = ctx.clone();
+        let scene = scene.path.clone();
+        let path = path.to_path_buf();
+        #[cfg(target_arch = "wasm32")]
+        let model = path.clone();
+
+        let load = move || {
+            let object = Object::load(
+                &path,
+                Import::default(),
+                Translation3::identity(),
+                UnitQuaternion::identity(),
+                Scale3::identity(),
+            );
+            sender.send((scene, path, object)).ok();
+            ctx.request_repaint();
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        super::background(load);
+
+        // the model and its files are fetched from the server of the page first
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(async move {
+            super::fetch::model(&model).await;
+            load();
+        });
+    }
+
+    /// Add finished objects to the scene they were loaded for
+    pub fn update(&mut self, ctx: &Context, scene: &mut Option<Scene>) {
+        while let Ok((scene_path, path, object)) = self.receiver.try_recv() {
+            if let Some(i) = self.pending.iter().position(|(p, _)| *p == path) {
+                self.pending.remove(i);
+            }
+
+            match (object, scene.as_mut()) {
+                (Ok(mut object), Some(scene)) if scene.path == scene_path => {
+                    info!("Loaded object from {}", path.display());
+                    if self.auto_frame {
+                        object.frame(scene.camera());
+                    }
+                    scene.objects.push(object);
+                    scene.assign_object_ids();
+                }
+                (Ok(_), _) => {
+                    warn!(
+                        "Discarding {}, the scene it was loaded for is closed",
+                        path.display()
+                    );
+                }
+                (Err(e), _) => {
+                    warn!("Failed to load object: {e}");
+                    toasts::error(
+                        ctx,
+                        t!(
+                            "load_object_failed",
+                            name = path.display(),
+                            error = format!("{e:#}")
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Spinner for every file still loading
+    pub fn show(&self, ui: &mut Ui) {
+        for (path, started) in &self.pending {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(t!(
+                    "loading_object",
+                    name = path.file_name().unwrap_or_default().to_string_lossy(),
+                    seconds = started.elapsed().as_secs()
+                ));
+            });
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use super::outliner::Selection;
+use egui::{
+    color_picker, CollapsingHeader, ColorImage, ComboBox, DragValue, FontFamily, Image, RichText,
+    Slider, TextureHandle, TextureOptions, Ui,
+};
+use egui_file::FileDialog;
+use image::RgbImage;
+use log::warn;
+use rust_i18n::t;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::{Arc, Weak},
+};
+use trayracer_core::scene::{Color, MaterialOverride, Object, Scene};
+
+/// Size of the texture thumbnails in pixels
+const THUMBNAIL_SIZE: u32 = 64;
+
+/// Inspector for the materials of the selected object,
+/// changes are stored as overrides in the scene
+pub struct MaterialEditor {
+    /// Dialog to select an override texture, for the object index and material name
+    texture_dialog: Option<(usize, String, FileDialog)>,
+    /// Thumbnails of the textures, by the address of the decoded image
+    thumbnails: HashMap<usize, (Weak<RgbImage>, TextureHandle)>,
+}
+
+impl MaterialEditor {
+    pub fn new() -> Self {
+        Self {
+            texture_dialog: None,
+            thumbnails: HashMap::new(),
+        }
+    }
+
+    /// Show the materials of the selected object
+    pub fn show(&mut self, ui: &mut Ui, scene: &mut Scene, selection: Option<Selection>) {
+        let Some(Selection::Object(n)) = selection else {
+            return;
+        };
+
+        let scene_directory = scene
+            .path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
+
+        self.texture_dialog(ui, scene, &scene_directory);
+        let library = scene.library();
+        let object = &mut scene.objects[n];
+
+        // textures of removed materials don't need a thumbnail anymore
+        self.thumbnails
+            .retain(|_, (image, _)| image.strong_count() > 0);
+
+        ui.vertical(|ui| {
+            ui.group(|ui| {
+                CollapsingHeader::new(
+                    RichText::new(format!("{} ({})", t!("materials"), object.materials.len()))
+                        .size(16.0),
+                )
+                .default_open(true)
+                .show_unindented(ui, |ui| {
+                    ui.separator();
+
+                    if !library.is_empty() {
+                        Self::library_material(ui, object, n, &library, &scene_directory);
+                    }
+
+                    for i in 0..object.materials.len() {
+                        ui.separator();
+                        self.material(ui, object, n, i, &library, &scene_directory);
+                    }
+                })
+                .header_response
+                .on_hover_text(t!("material_override_hint"));
+            });
+        });
+    }
+
+    /// Select a named material of the library instead of the materials of the model
+    fn library_material(
+        ui: &mut Ui,
+        object: &mut Object,
+        n: usize,
+        library: &BTreeMap<String, MaterialOverride>,
+        scene_directory: &Path,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("library_material")));
+
+            let mut selected = object.material.clone();
+            ComboBox::from_id_salt(("library_material", n))
+                .selected_text(
+                    selected
+                        .clone()
+                        .unwrap_or_else(|| t!("model_materials").to_string()),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut selected, None, t!("model_materials"));
+                    for name in library.keys() {
+                        ui.selectable_value(&mut selected, Some(name.clone()), name);
+                    }
+                })
+                .response
+                .on_hover_text(t!("library_material_hint"));
+
+            if selected != object.material {
+                object.material = selected;
+                object.apply_materials(
+                    object.material.as_ref().and_then(|m| library.get(m)),
+                    scene_directory,
+                );
+            }
+        });
+    }
+
+    /// Edit one material of an object
+    #[allow(clippy::too_many_lines)]
+    fn material(
+        &mut self,
+        ui: &mut Ui,
+        object: &mut Object,
+        object_index: usize,
+        index: usize,
+        library: &BTreeMap<String, MaterialOverride>,
+        scene_directory: &Path,
+    ) {
+        let name = object.materials[index].name.clone();
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&name).family(FontFamily::Monospace));
+
+            if object.material_overrides.contains_key(&name)
+                && ui
+                    .small_button(t!("reset"))
+                    .on_hover_text(t!("reset_material_hint"))
+                    .clicked()
+            {
+                object.material_overrides.remove(&name);
+                object.apply_materials(
+                    object.material.as_ref().and_then(|m| library.get(m)),
+                    scene_directory,
+                );
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("diffuse_color")));
+            let mut color = object.materials[index]
+                .diffuse_color
+                .unwrap_or_else(|| Color::from_element(0.9));
+            if color_picker::color_edit_button_rgb(ui, color.as_mut()).changed() {
+                object
+                    .material_overrides
+                    .entry(name.clone())
+                    .or_default()
+                    .diffuse_color = Some(color);
+                object.materials[index].diffuse_color = Some(color);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("specular_color")));
+            let mut color = object.materials[index].specular_color.unwrap_or_default();
+            if color_picker::color_edit_button_rgb(ui, color.as_mut()).changed() {
+                object
+                    .material_overrides
+                    .entry(name.clone())
+                    .or_default()
+                    .specular_color = Some(color);
+                object.materials[index].specular_color = Some(color);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("specular_exponent")));
+            let mut exponent = object.materials[index]
+                .specular_exponent
+                .unwrap_or_default();
+            if ui
+                .add(DragValue::new(&mut exponent).range(0.0..=1000.0))
+                .on_hover_text(t!("specular_exponent_hint"))
+                .changed()
+            {
+                object
+                    .material_overrides
+                    .entry(name.clone())
+                    .or_default()
+                    .specular_exponent = Some(exponent);
+                object.materials[index].specular_exponent = Some(exponent);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("opacity")));
+            // the material stores the transparency, the MTL and the overrides the opacity
+            let mut opacity = 1.0 - object.materials[index].dissolve.unwrap_or_default();
+            if ui
+                .add(Slider::new(&mut opacity, 0.0..=1.0))
+                .on_hover_text(t!("opacity_hint"))
+                .changed()
+            {
+                object
+                    .material_overrides
+                    .entry(name.clone())
+                    .or_default()
+                    .dissolve = Some(opacity);
+                object.materials[index].dissolve = Some(1.0 - opacity);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("diffuse_texture")));
+
+            match object.materials[index].diffuse_texture.clone() {
+                Some(texture) => {
+                    let thumbnail = self.thumbnail(ui, &texture);
+                    ui.add(Image::new(&thumbnail).max_height(THUMBNAIL_SIZE as f32))
+                        .on_hover_text(
+                            object
+                                .material_overrides
+                                .get(&name)
+                                .and_then(|o| o.diffuse_texture.as_ref())
+                                .map_or_else(|| "MTL".to_string(), |p| p.display().to_string()),
+                        );
+                }
+                None => {
+                    ui.label(t!("none"));
+                }
+            }
+
+            if ui.small_button(t!("choose_texture")).clicked() {
+                let mut dialog = FileDialog::open_file(None).filename_filter(Box::new(|p| {
+                    Path::new(p).extension().is_some_and(|ext| {
+                        ["png", "jpg", "jpeg", "bmp", "tga", "exr"]
+                            .iter()
+                            .any(|e| ext.eq_ignore_ascii_case(e))
+                    })
+                }));
+                dialog.open();
+                self.texture_dialog = Some((object_index, name.clone(), dialog));
+            }
+        });
+    }
+
+    /// Downscaled copy of a texture uploaded to the GPU, cached while the texture is in use
+    fn thumbnail(&mut self, ui: &Ui, texture: &Arc<RgbImage>) -> TextureHandle {
+        let key = Arc::as_ptr(texture) as usize;
+
+        self.thumbnails
+            .entry(key)
+            .or_insert_with(|| {
+                let thumbnail = image::imageops::thumbnail(
+                    texture.as_ref(),
+                    THUMBNAIL_SIZE,
+                    THUMBNAIL_SIZE * texture.height() / texture.width().max(1),
+                );
+                let handle = ui.ctx().load_texture(
+                    format!("thumbnail_{key}"),
+                    ColorImage::from_rgb(
+                        [thumbnail.width() as usize, thumbnail.height() as usize],
+                        thumbnail.as_raw(),
+                    ),
+                    TextureOptions::LINEAR,
+                );
+                (Arc::downgrade(texture), handle)
+            })
+            .1
+            .clone()
+    }
+
+    /// Apply the texture selected for a material override
+    fn texture_dialog(&mut self, ui: &Ui, scene: &mut Scene, scene_directory: &Path) {
+        let Some((object, material, dialog)) = &mut self.texture_dialog else {
+            return;
+        };
+        let library = scene.library();
+
+        if !dialog.show(ui.ctx()).selected() {
+            return;
+        }
+
+        match (dialog.path(), scene.objects.get_mut(*object)) {
+            (Some(path), Some(object)) => {
+                // keep the texture relative to the scene if possible
+                let texture = path
+                    .strip_prefix(scene_directory)
+                    .unwrap_or(path)
+                    .to_path_buf();
+
+                object
+                    .material_overrides
+                    .entry(material.clone())
+                    .or_default()
+                    .diffuse_texture = Some(texture);
+                object.apply_materials(
+                    object.material.as_ref().and_then(|m| library.get(m)),
+                    scene_directory,
+                );
+            }
+            _ => warn!("Texture dialog selected but returned no path"),
+        }
+
+        self.texture_dialog = None;
+    }
+}
@@ -1,27 +1,79 @@
+use self::autoexport::AutoExport;
+#[cfg(not(target_arch = "wasm32"))]
+use self::autosave::Autosave;
+use self::history::History;
+use self::issues::Issues;
+use self::keymap::Action;
+use self::loader::ObjectLoader;
+use self::materials::MaterialEditor;
+use self::outliner::{Outliner, Selection};
+use self::preferences::Preferences;
 use self::preview::Preview;
 use self::renderresult::RenderResult;
+use self::rendertexture::RenderTexture;
 use self::statusbar::StatusBar;
-use self::yamlmenu::YamlMenu;
-use crate::raytracer::render::Render;
-use crate::scene::Scene;
+#[cfg(not(target_arch = "wasm32"))]
+use self::watcher::SceneWatcher;
+use self::yamlmenu::{RecentScenes, YamlMenu};
 use crate::ui::properties::Properties;
 use anyhow::Context;
 use eframe::CreationContext;
-use egui::mutex::{Mutex, RwLock};
+use egui::mutex::RwLock;
 use egui::{
-    vec2, CentralPanel, ColorImage, ImageData, ScrollArea, SidePanel, TextStyle, TextureOptions,
+    vec2, CentralPanel, ColorImage, ImageData, Key, ScrollArea, SidePanel, TextStyle,
+    TextureOptions, Ui, Window,
 };
-use image::ImageBuffer;
+#[cfg(not(target_arch = "wasm32"))]
+use egui_file::FileDialog;
+use log::{info, warn};
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::thread::JoinHandle;
+use std::time::Duration;
+use trayracer_core::raytracer::render::{Render, RenderThread};
+use trayracer_core::scene::Scene;
 
+mod autoexport;
+#[cfg(not(target_arch = "wasm32"))]
+mod autosave;
+#[cfg(target_arch = "wasm32")]
+mod fetch;
+mod history;
+mod issues;
+mod keymap;
+mod loader;
+mod materials;
+mod outliner;
+mod preferences;
+mod presets;
 mod preview;
 mod properties;
 mod renderresult;
+mod rendertexture;
+mod snap;
 mod statusbar;
+mod toasts;
+#[cfg(not(target_arch = "wasm32"))]
+mod watcher;
 mod yamlmenu;
 
+/// Time a render may take of each frame on the web, where it runs on the UI thread
+const RENDER_STEP: Duration = Duration::from_millis(30);
+
+/// Run a task on a background thread
+#[cfg(not(target_arch = "wasm32"))]
+fn background(task: impl FnOnce() + Send + 'static) {
+    std::thread::spawn(task);
+}
+
+/// The web has no threads, the task runs before returning
+#[cfg(target_arch = "wasm32")]
+fn background(task: impl FnOnce() + Send + 'static) {
+    task();
+}
+
 /// Main application
 /// This holds all the UI elements and application state
 pub struct App {
@@ -31,34 +83,233 @@ pub struct App {
     statusbar: StatusBar,
     preview: Preview,
     render_result: RenderResult,
+    history: History,
     yaml_menu: YamlMenu,
+    #[cfg(not(target_arch = "wasm32"))]
+    autosave: Autosave,
+    auto_export: AutoExport,
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: SceneWatcher,
+    loader: ObjectLoader,
+    issues: Issues,
+    outliner: Outliner,
+    materials: MaterialEditor,
+    /// Item shown in the properties, shared between the outliner and the preview
+    selection: Option<Selection>,
+    /// Objects selected together with the selected object, edited at once
+    selected_objects: BTreeSet<usize>,
+    preferences: Preferences,
+    /// Dialog to save the diagnostics for a bug report
+    #[cfg(not(target_arch = "wasm32"))]
+    diagnostics_dialog: Option<FileDialog>,
     scene: Arc<RwLock<Option<Scene>>>,
 }
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum Tab {
     Preview,
     RenderResult,
+    /// Preview and render result next to each other
+    Split,
 }
 
 impl App {
+    /// Scene menu, outliner, properties, materials and issues next to the preview
+    fn side_panel(&mut self, ui: &mut Ui, scene: &mut Option<Scene>) {
+        let panel = SidePanel::right("panel")
+            .show_separator_line(true)
+            .default_width(self.preferences.side_panel_width)
+            .show_inside(ui, |ui| {
+                ScrollArea::new([false, true]).show(ui, |ui| {
+                    self.yaml_menu.show(scene, ui);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    self.autosave.options(ui);
+                    self.auto_export.options(ui);
+                    self.loader.show(ui);
+
+                    ui.separator();
+
+                    if let Some(scene) = scene.as_mut() {
+                        self.outliner.show(
+                            ui,
+                            scene,
+                            &mut self.selection,
+                            &mut self.selected_objects,
+                            &mut self.loader,
+                        );
+
+                        ui.add_space(5.0);
+
+                        self.properties.show(
+                            scene,
+                            ui,
+                            &self.render,
+                            &mut self.selection,
+                            &mut self.selected_objects,
+                            &mut self.preferences,
+                        );
+
+                        ui.add_space(5.0);
+
+                        self.materials.show(ui, scene, self.selection);
+
+                        ui.add_space(5.0);
+
+                        self.issues.show(ui, scene);
+                    }
+                });
+            });
+        self.preferences.side_panel_width = panel.response.rect.width();
+    }
+
+    /// Global keyboard shortcuts, the camera movement is handled by the preview
+    fn shortcuts(&mut self, ctx: &egui::Context, scene: Option<&Scene>) {
+        // typing into a text field doesn't trigger shortcuts
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let keymap = &self.preferences.keymap;
+        let [render, cancel, save, switch_tab, fullscreen] = ctx.input(|i| {
+            [
+                Action::Render,
+                Action::CancelRender,
+                Action::SaveScene,
+                Action::SwitchTab,
+                Action::FullscreenPreview,
+            ]
+            .map(|action| keymap.pressed(i, action))
+        });
+        // escape cancels as well, unless it leaves the camera movement of the preview
+        let escape = !self.preview.active
+            && ctx.input(|i| i.key_pressed(Key::Escape) && i.modifiers.is_none());
+
+        if render && self.render.thread.is_none() {
+            if let Some(scene) = scene {
+                StatusBar::start_render(&mut self.render, scene, &mut self.current_tab);
+            }
+        }
+
+        if (cancel || escape) && self.render.thread.is_some() {
+            self.render.cancel.store(true, Ordering::Relaxed);
+        }
+
+        if save {
+            self.yaml_menu.save_scene(ctx, scene);
+        }
+
+        if fullscreen {
+            self.preview.fullscreen = !self.preview.fullscreen;
+        }
+
+        if switch_tab {
+            self.current_tab = match self.current_tab {
+                Tab::Preview => Tab::RenderResult,
+                Tab::RenderResult => Tab::Split,
+                Tab::Split => Tab::Preview,
+            };
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn settings_window(&mut self, ctx: &egui::Context, scene: Option<&Scene>) {
+        Window::new(t!("settings"))
+            .collapsible(false)
+            .open(&mut self.statusbar.show_settings)
+            .show(ctx, |ui| {
+                ScrollArea::vertical().show(ui, |ui| {
+                    ui.heading(t!("appearance"));
+                    self.preferences.appearance(ui);
+
+                    ui.separator();
+                    ui.heading(t!("navigation"));
+                    self.preview.navigation.show(ui);
+
+                    ui.separator();
+                    ui.heading(t!("keyboard_shortcuts"));
+                    self.preferences.keymap.show(ui);
+
+                    #[cfg(not(target_arch = "wasm32"))]
+                    Self::diagnostics(ui, &mut self.diagnostics_dialog);
+                });
+            });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dialog) = self.diagnostics_dialog.as_mut() {
+            if dialog.show(ctx).selected() {
+                if let Some(path) = dialog.path() {
+                    if let Err(e) = crate::diagnostics::export(path, scene) {
+                        warn!("Failed to export diagnostics: {e:?}");
+                        toasts::error(
+                            ctx,
+                            t!("export_diagnostics_failed", error = format!("{e:#}")),
+                        );
+                    }
+                }
+                self.diagnostics_dialog = None;
+            }
+        }
+    }
+
+    /// Button to export the logs, the scene and the system information for a bug report
+    #[cfg(not(target_arch = "wasm32"))]
+    fn diagnostics(ui: &mut Ui, dialog: &mut Option<FileDialog>) {
+        ui.separator();
+        ui.heading(t!("diagnostics"));
+        if ui
+            .button(t!("export_diagnostics"))
+            .on_hover_text(t!("export_diagnostics_hint"))
+            .clicked()
+        {
+            let mut save = FileDialog::save_file(None)
+                .default_filename("trayracer-diagnostics.zip")
+                .filename_filter(Box::new(|name| {
+                    std::path::Path::new(name)
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+                }));
+            save.open();
+            *dialog = Some(save);
+        }
+    }
+
+    /// Collect the preferences from the parts of the app they belong to
+    fn update_preferences(&mut self) {
+        self.preferences.tab = self.current_tab;
+        self.preferences.history_width = self.history.width;
+        self.preferences.preview_speed = self.preview.speed;
+        self.preferences.preview_sensitivity = self.preview.sensitivity;
+        self.preferences.preview_scale_speed = self.preview.scale_speed;
+        self.preferences.preview_navigation = self.preview.navigation;
+        self.preferences.auto_frame = self.loader.auto_frame;
+        self.preferences
+            .export_directory
+            .clone_from(&self.statusbar.export_directory);
+        self.preferences.export_options = self.statusbar.export_options;
+        self.preferences.auto_export = self.auto_export.enabled;
+        self.preferences
+            .auto_export_directory
+            .clone_from(&self.auto_export.directory);
+    }
+
     pub fn new(cc: &CreationContext) -> anyhow::Result<Self> {
         egui_extras::install_image_loaders(&cc.egui_ctx);
 
         // Initialize the preview renderer with the wgpu context
-        preview::gpu::init_wgpu(
-            cc.wgpu_render_state
-                .as_ref()
-                .context("Failed to get wgpu context")?,
-        );
+        let render_state = cc
+            .wgpu_render_state
+            .as_ref()
+            .context("Failed to get wgpu context")?;
+        preview::gpu::init_wgpu(render_state);
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::diagnostics::set_gpu(&render_state.adapter.get_info());
 
-        // create initial render texture (GPU exclusive) and image buffer (CPU exclusive)
+        // create initial render texture (GPU exclusive), the render holds the image (CPU exclusive)
         let render_texture = cc.egui_ctx.load_texture(
             "render",
             ImageData::Color(Arc::new(ColorImage::example())),
             TextureOptions::default(),
         );
-        let image_buffer = Arc::new(Mutex::new(ImageBuffer::new(0, 0)));
 
         cc.egui_ctx.style_mut(|s| {
             s.text_styles.insert(
@@ -70,16 +321,91 @@ impl App {
             s.spacing.item_spacing = vec2(10.0, std::f32::consts::PI * 1.76643);
         });
 
-        let scene = Arc::new(RwLock::new(None));
+        let recent = cc
+            .storage
+            .and_then(|s| eframe::get_value::<RecentScenes>(s, RecentScenes::STORAGE_KEY))
+            .unwrap_or_default();
+
+        let scene = recent
+            .paths
+            .first()
+            .filter(|_| recent.open_last)
+            .and_then(|path| {
+                info!("Opening last scene {}", path.display());
+                Scene::open(path)
+                    .map_err(|e| {
+                        warn!("Failed to open last scene: {e}");
+                        toasts::open_failed(&cc.egui_ctx, &e);
+                    })
+                    .ok()
+            });
+        // the web has no file system to open scenes from, it starts with an example instead
+        #[cfg(target_arch = "wasm32")]
+        let scene = scene.or_else(|| {
+            Some(
+                trayracer_core::scene::Template::MaterialGrid
+                    .build(std::path::Path::new("demo.yaml")),
+            )
+        });
+        let scene = Arc::new(RwLock::new(scene));
+
+        let preferences = cc
+            .storage
+            .and_then(|s| eframe::get_value::<Preferences>(s, Preferences::STORAGE_KEY))
+            .unwrap_or_default();
+        cc.egui_ctx.set_theme(preferences.theme);
+        cc.egui_ctx.set_zoom_factor(preferences.ui_scale);
+
+        let mut preview = Preview::new(scene.clone());
+        preview.speed = preferences.preview_speed;
+        preview.sensitivity = preferences.preview_sensitivity;
+        preview.scale_speed = preferences.preview_scale_speed;
+        preview.navigation = preferences.preview_navigation;
+
+        let mut statusbar = StatusBar::new();
+        statusbar
+            .export_directory
+            .clone_from(&preferences.export_directory);
+        statusbar.export_options = preferences.export_options;
+
+        let mut history = History::new();
+        history.width = preferences.history_width;
+
+        let mut auto_export = AutoExport::new();
+        auto_export.enabled = preferences.auto_export;
+        auto_export
+            .directory
+            .clone_from(&preferences.auto_export_directory);
+
+        let mut loader = ObjectLoader::new();
+        loader.auto_frame = preferences.auto_frame;
 
         Ok(Self {
-            current_tab: Tab::Preview,
-            render: Render::new(render_texture, image_buffer),
+            current_tab: preferences.tab,
+            render: Render::new(Arc::new(RenderTexture::new(
+                render_texture.clone(),
+                cc.egui_ctx.clone(),
+            ))),
             properties: Properties::new(),
-            statusbar: StatusBar::new(),
-            preview: Preview::new(scene.clone()),
-            render_result: RenderResult::new(),
-            yaml_menu: YamlMenu::new(),
+            statusbar,
+            preview,
+            render_result: RenderResult::new(render_texture),
+            history,
+            yaml_menu: YamlMenu::new(recent),
+            #[cfg(not(target_arch = "wasm32"))]
+            autosave: Autosave::new(),
+            auto_export,
+            #[cfg(not(target_arch = "wasm32"))]
+            watcher: SceneWatcher::new(),
+            loader,
+            issues: Issues::new(),
+            outliner: Outliner::new(),
+            materials: MaterialEditor::new(),
+            selection: None,
+            selected_objects: BTreeSet::new(),
+            preferences,
+            #[cfg(not(target_arch = "wasm32"))]
+            diagnostics_dialog: None,
             scene,
         })
     }
@@ -87,22 +413,87 @@ impl App {
 
 /// Main application loop (called every frame)
 impl eframe::App for App {
+    // the scene stays locked for the whole frame on purpose
+    #[allow(clippy::significant_drop_tightening)]
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // without threads on the web the render continues for a part of each frame
+        if let Some(thread) = &self.render.thread {
+            thread.step(RENDER_STEP);
+        }
+
         // check if the render thread has finished and reset it
         self.render
             .thread
             .as_ref()
-            .is_some_and(JoinHandle::is_finished)
+            .is_some_and(RenderThread::is_finished)
             .then(|| {
                 self.render.thread = None;
-                self.render.cancel.store(false, Ordering::Relaxed);
+                // only completed renders are kept
+                if !self.render.cancel.swap(false, Ordering::Relaxed) {
+                    self.history.add(ctx, &self.render);
+                    self.auto_export.export(
+                        ctx,
+                        &self.render,
+                        self.statusbar.export_options,
+                        self.scene.read().as_ref(),
+                    );
+                }
             });
 
-        // lock the scene for the duration of the frame
-        let mut scene = self.scene.write();
+        self.update_preferences();
+
+        // lock the scene for the duration of the frame,
+        // through its own handle so the side panel can borrow the app
+        let scene = self.scene.clone();
+        let mut scene = scene.write();
+
+        self.shortcuts(ctx, scene.as_ref());
+        self.settings_window(ctx, scene.as_ref());
+        toasts::show(ctx);
+
+        // hot reload files changed outside of the app
+        #[cfg(not(target_arch = "wasm32"))]
+        self.watcher.update(ctx, &mut scene);
+
+        // add objects that finished loading in the background
+        self.loader.update(ctx, &mut scene);
+
+        // the selected item may have been removed or the scene closed
+        self.selection = self
+            .selection
+            .filter(|s| scene.as_ref().is_some_and(|scene| s.exists(scene)));
+        // the multi selection only lasts while one of its objects is selected
+        match self.selection {
+            Some(Selection::Object(o)) if self.selected_objects.contains(&o) => {
+                let count = scene.as_ref().map_or(0, |scene| scene.objects.len());
+                self.selected_objects.retain(|&o| o < count);
+            }
+            _ => self.selected_objects.clear(),
+        }
+
         CentralPanel::default().show(ctx, |ui| {
-            self.statusbar
-                .show(ui, scene.as_mut(), &mut self.render, &mut self.current_tab);
+            #[cfg(not(target_arch = "wasm32"))]
+            self.autosave.update(ui, &mut scene);
+
+            if self.preview.fullscreen {
+                self.preview.show(
+                    ui,
+                    &mut scene,
+                    &mut self.loader,
+                    &mut self.selection,
+                    &self.preferences.keymap,
+                    &mut self.preferences.snap,
+                );
+                return;
+            }
+
+            self.statusbar.show(
+                ui,
+                scene.as_mut(),
+                &mut self.render,
+                &mut self.current_tab,
+                &self.preferences.keymap,
+            );
 
             ui.vertical_centered(|ui| {
                 ui.separator();
@@ -110,40 +501,54 @@ impl eframe::App for App {
 
             match self.current_tab {
                 Tab::Preview => {
-                    SidePanel::right("panel")
-                        .show_separator_line(true)
-                        .show_inside(ui, |ui| {
-                            ScrollArea::new([false, true]).show(ui, |ui| {
-                                self.yaml_menu.show(&mut scene, ui);
-
-                                ui.separator();
-
-                                if let Some(scene) = scene.as_mut() {
-                                    self.properties.show(scene, ui, &self.render);
-                                }
-                            });
-                        });
-
-                    //if let Some(scene) = scene.as_mut() {
-                    //    self.preview.show(ui, scene);
-                    //} else {
-                    //    ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
-                    //        ui.horizontal(|ui| {
-                    //            ui.vertical_centered(|ui| {
-                    //                ui.heading(t!("no_scene_loaded"));
-                    //                ui.label(RichText::new(t!("drop_yaml")));
-                    //            });
-                    //        });
-                    //    });
-                    //}
-                    self.preview.show(ui, &mut scene);
+                    self.side_panel(ui, &mut scene);
+                    self.preview.show(
+                        ui,
+                        &mut scene,
+                        &mut self.loader,
+                        &mut self.selection,
+                        &self.preferences.keymap,
+                        &mut self.preferences.snap,
+                    );
                 }
                 Tab::RenderResult => {
+                    self.history.show(ui, &mut self.render);
                     if let Some(scene) = scene.as_ref() {
-                        self.render_result.show(ui, scene, &self.render);
+                        self.render_result.show(ui, scene, &mut self.render);
                     }
                 }
+                Tab::Split => {
+                    self.side_panel(ui, &mut scene);
+                    ui.columns(2, |columns| {
+                        self.preview.show(
+                            &mut columns[0],
+                            &mut scene,
+                            &mut self.loader,
+                            &mut self.selection,
+                            &self.preferences.keymap,
+                            &mut self.preferences.snap,
+                        );
+                        if let Some(scene) = scene.as_ref() {
+                            self.render_result
+                                .show(&mut columns[1], scene, &mut self.render);
+                        }
+                    });
+                }
             }
         });
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, RecentScenes::STORAGE_KEY, &self.yaml_menu.recent);
+        eframe::set_value(storage, Preferences::STORAGE_KEY, &self.preferences);
+    }
+
+    fn on_exit(&mut self) {
+        // a clean exit needs no recovery
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            autosave::clear();
+            history::clear();
+        }
+    }
 }
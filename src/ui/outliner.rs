@@ -0,0 +1,567 @@
+use super::loader::ObjectLoader;
+use egui::{
+    collapsing_header::CollapsingState, Button, CollapsingHeader, ComboBox, FontFamily, RichText,
+    TextEdit, Ui,
+};
+use egui_file::FileDialog;
+use rust_i18n::t;
+use std::collections::BTreeSet;
+use trayracer_core::scene::{Camera, Group, Light, Scene};
+
+/// Item of the scene shown in the properties and highlighted in the preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Camera(usize),
+    Light(usize),
+    Object(usize),
+    Group(usize),
+}
+
+impl Selection {
+    /// Whether the selected item still exists in the scene
+    pub const fn exists(self, scene: &Scene) -> bool {
+        match self {
+            Self::Camera(c) => c < scene.cameras.len(),
+            Self::Light(l) => l < scene.lights.len(),
+            Self::Object(o) => o < scene.objects.len(),
+            Self::Group(g) => g < scene.groups.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Node {
+    Group(usize),
+    Object(usize),
+}
+
+/// Changes to the hierarchy collected while drawing the tree, applied afterwards
+enum Action {
+    Reparent(Node, Option<usize>),
+    ShowInPreview(usize, bool),
+    ShowInRender(usize, bool),
+}
+
+/// Order of the lights and objects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sort {
+    /// As in the scene file, objects in the tree of their groups
+    Scene,
+    Name,
+    /// Objects with the most triangles first
+    Triangles,
+}
+
+impl Sort {
+    const ALL: [Self; 3] = [Self::Scene, Self::Name, Self::Triangles];
+
+    fn name(self) -> String {
+        match self {
+            Self::Scene => t!("sort_scene"),
+            Self::Name => t!("sort_name"),
+            Self::Triangles => t!("sort_triangles"),
+        }
+        .to_string()
+    }
+}
+
+/// Tree of the camera, lights, groups and the objects parented to them
+pub struct Outliner {
+    /// Dialog to add a new object
+    object_dialog: Option<FileDialog>,
+    /// Path of the model to add relative to the page, the web has no file dialog
+    #[cfg(target_arch = "wasm32")]
+    object_url: String,
+    /// Only lights and objects with this in their name are listed
+    filter: String,
+    sort: Sort,
+}
+
+impl Outliner {
+    pub const fn new() -> Self {
+        Self {
+            object_dialog: None,
+            #[cfg(target_arch = "wasm32")]
+            object_url: String::new(),
+            filter: String::new(),
+            sort: Sort::Scene,
+        }
+    }
+
+    /// Whether the name contains the filter, ignoring case
+    fn matches(&self, name: &str) -> bool {
+        name.to_lowercase().contains(&self.filter.to_lowercase())
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        scene: &mut Scene,
+        selection: &mut Option<Selection>,
+        objects: &mut BTreeSet<usize>,
+        loader: &mut ObjectLoader,
+    ) {
+        ui.vertical(|ui| {
+            ui.group(|ui| {
+                CollapsingHeader::new(RichText::new(t!("outliner")).size(16.0))
+                    .default_open(true)
+                    .show_unindented(ui, |ui| {
+                        ui.separator();
+
+                        Self::cameras(ui, scene, selection);
+
+                        self.filter_bar(ui);
+
+                        self.lights(ui, scene, selection);
+
+                        let mut actions = Vec::new();
+                        CollapsingHeader::new(format!(
+                            "{} ({})",
+                            t!("objects"),
+                            scene.objects.len()
+                        ))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            if self.filter.is_empty() && self.sort == Sort::Scene {
+                                tree(ui, scene, None, selection, objects, &mut actions);
+                            } else {
+                                self.object_list(ui, scene, selection, objects, &mut actions);
+                            }
+                        })
+                        .header_response
+                        .on_hover_text(t!("multi_selection_hint"));
+
+                        for action in actions {
+                            match action {
+                                Action::Reparent(Node::Group(g), parent) => {
+                                    scene.groups[g].parent = parent;
+                                }
+                                Action::Reparent(Node::Object(o), parent) => {
+                                    scene.objects[o].parent = parent;
+                                }
+                                Action::ShowInPreview(o, visible) => {
+                                    scene.objects[o].visible_in_preview = visible;
+                                }
+                                Action::ShowInRender(o, visible) => {
+                                    scene.objects[o].visible_in_render = visible;
+                                }
+                            }
+                        }
+
+                        ui.separator();
+                        self.add_buttons(ui, scene, selection, loader);
+                    });
+            });
+        });
+
+        // the hierarchy may have been edited
+        scene.update_transforms();
+    }
+
+    /// Cameras and the selector for the one used by the preview and the render
+    fn cameras(ui: &mut Ui, scene: &mut Scene, selection: &mut Option<Selection>) {
+        CollapsingHeader::new(format!("{} ({})", t!("cameras"), scene.cameras.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}:", t!("active_camera")));
+                    ComboBox::from_id_salt("active_camera")
+                        .selected_text(camera_name(scene, scene.active_camera))
+                        .show_ui(ui, |ui| {
+                            for c in 0..scene.cameras.len() {
+                                let name = camera_name(scene, c);
+                                ui.selectable_value(&mut scene.active_camera, c, name);
+                            }
+                        })
+                        .response
+                        .on_hover_text(t!("active_camera_hint"));
+                });
+
+                for c in 0..scene.cameras.len() {
+                    let active = if c == scene.active_camera { " ●" } else { "" };
+                    select(
+                        ui,
+                        selection,
+                        Selection::Camera(c),
+                        format!("📷 {}{active}", camera_name(scene, c)),
+                    );
+                }
+            });
+    }
+
+    /// Filter text box and sort order of the lights and objects
+    fn filter_bar(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.filter)
+                    .hint_text(t!("filter"))
+                    .desired_width(120.0),
+            )
+            .on_hover_text(t!("filter_hint"));
+
+            if !self.filter.is_empty() && ui.small_button("✖").clicked() {
+                self.filter.clear();
+            }
+
+            ComboBox::from_id_salt("outliner_sort")
+                .selected_text(self.sort.name())
+                .show_ui(ui, |ui| {
+                    for sort in Sort::ALL {
+                        ui.selectable_value(&mut self.sort, sort, sort.name());
+                    }
+                })
+                .response
+                .on_hover_text(t!("sort_hint"));
+        });
+    }
+
+    fn lights(&self, ui: &mut Ui, scene: &Scene, selection: &mut Option<Selection>) {
+        let mut lights = scene
+            .lights
+            .iter()
+            .enumerate()
+            .map(|(n, light)| {
+                let name = light
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("{} {n}", t!("light")));
+                (n, light, name)
+            })
+            .filter(|(_, _, name)| self.matches(name))
+            .collect::<Vec<_>>();
+        // lights have no triangles, they are sorted by name instead
+        if self.sort != Sort::Scene {
+            lights.sort_by_key(|(_, _, name)| name.to_lowercase());
+        }
+
+        CollapsingHeader::new(format!("{} ({})", t!("lights"), scene.lights.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                for (n, light, name) in lights {
+                    let state = if scene.solo_light == Some(n) {
+                        " ◉"
+                    } else if light.enabled {
+                        ""
+                    } else {
+                        " ○"
+                    };
+                    select(
+                        ui,
+                        selection,
+                        Selection::Light(n),
+                        format!("💡 {name}{state}"),
+                    );
+                }
+            });
+    }
+
+    /// Flat list of the objects matching the filter in the sort order
+    fn object_list(
+        &self,
+        ui: &mut Ui,
+        scene: &Scene,
+        selection: &mut Option<Selection>,
+        objects: &mut BTreeSet<usize>,
+        actions: &mut Vec<Action>,
+    ) {
+        let mut list = (0..scene.objects.len())
+            .filter(|&o| self.matches(&scene.objects[o].name))
+            .collect::<Vec<_>>();
+        match self.sort {
+            Sort::Scene => {}
+            Sort::Name => list.sort_by_key(|&o| scene.objects[o].name.to_lowercase()),
+            Sort::Triangles => {
+                list.sort_by_key(|&o| std::cmp::Reverse(scene.objects[o].mesh.triangles.len()));
+            }
+        }
+
+        if list.is_empty() {
+            ui.label(t!("no_matches"));
+        }
+
+        for o in list {
+            object_row(ui, scene, o, selection, objects, actions);
+        }
+    }
+
+    fn add_buttons(
+        &mut self,
+        ui: &mut Ui,
+        scene: &mut Scene,
+        selection: &mut Option<Selection>,
+        loader: &mut ObjectLoader,
+    ) {
+        ui.vertical_centered(|ui| {
+            if ui
+                .add(Button::new(RichText::new(t!("add_camera"))).frame(false))
+                .on_hover_text(t!("add_camera_hint"))
+                .clicked()
+            {
+                let camera = Camera {
+                    name: None,
+                    ..scene.camera().clone()
+                };
+                scene.cameras.push(camera);
+                *selection = Some(Selection::Camera(scene.cameras.len() - 1));
+            }
+
+            if ui
+                .add(Button::new(RichText::new(t!("add_light"))).frame(false))
+                .clicked()
+            {
+                scene.lights.push(Light {
+                    name: None,
+                    position: nalgebra::Point3::new(5.0, 2.0, 2.0),
+                    intensity: 3.0,
+                    color: nalgebra::Vector3::new(1.0, 1.0, 1.0),
+                    enabled: true,
+                    ..Light::default()
+                });
+                *selection = Some(Selection::Light(scene.lights.len() - 1));
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            ui.horizontal(|ui| {
+                ui.add(
+                    TextEdit::singleline(&mut self.object_url)
+                        .hint_text("models/car.obj")
+                        .desired_width(120.0),
+                )
+                .on_hover_text(t!("object_url_hint"));
+                if ui
+                    .add_enabled(
+                        !self.object_url.trim().is_empty(),
+                        Button::new(RichText::new(t!("add_object"))).frame(false),
+                    )
+                    .clicked()
+                {
+                    let path = std::path::PathBuf::from(self.object_url.trim());
+                    loader.load(ui.ctx(), scene, &path);
+                }
+            });
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .add(Button::new(RichText::new(t!("add_object"))).frame(false))
+                .clicked()
+            {
+                let mut dialog = FileDialog::open_file(None).show_files_filter(Box::new(|path| {
+                    path.extension().is_some_and(|ext| {
+                        ext.eq_ignore_ascii_case("obj") || ext.eq_ignore_ascii_case("stl")
+                    })
+                }));
+                dialog.open();
+                self.object_dialog = Some(dialog);
+            }
+            ui.checkbox(&mut loader.auto_frame, t!("auto_frame"))
+                .on_hover_text(t!("auto_frame_hint"));
+
+            if let Some(dialog) = &mut self.object_dialog {
+                if dialog.show(ui.ctx()).selected() {
+                    if let Some(file) = dialog.path() {
+                        loader.load(ui.ctx(), scene, file);
+                    }
+                }
+            }
+
+            if ui
+                .add(Button::new(RichText::new(t!("add_group"))).frame(false))
+                .clicked()
+            {
+                let name = format!("{} {}", t!("group"), scene.groups.len());
+                scene.groups.push(Group::new(&name));
+                *selection = Some(Selection::Group(scene.groups.len() - 1));
+            }
+        });
+    }
+}
+
+fn camera_name(scene: &Scene, index: usize) -> String {
+    scene.cameras[index]
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("{} {index}", t!("camera")))
+}
+
+/// Label that selects the item when clicked
+fn select(ui: &mut Ui, selection: &mut Option<Selection>, item: Selection, text: String) {
+    if ui
+        .selectable_label(
+            *selection == Some(item),
+            RichText::new(text).family(FontFamily::Monospace),
+        )
+        .clicked()
+    {
+        *selection = Some(item);
+    }
+}
+
+/// Label of an object, Ctrl adds it to or removes it from the selection
+/// and Shift selects the objects between it and the selected object
+fn select_object(
+    ui: &mut Ui,
+    selection: &mut Option<Selection>,
+    objects: &mut BTreeSet<usize>,
+    o: usize,
+    text: String,
+) {
+    let selected = *selection == Some(Selection::Object(o)) || objects.contains(&o);
+    if !ui
+        .selectable_label(selected, RichText::new(text).family(FontFamily::Monospace))
+        .clicked()
+    {
+        return;
+    }
+
+    let modifiers = ui.input(|i| i.modifiers);
+    match *selection {
+        Some(Selection::Object(current)) if modifiers.command => {
+            objects.insert(current);
+            if objects.insert(o) {
+                *selection = Some(Selection::Object(o));
+            } else {
+                objects.remove(&o);
+                if current == o {
+                    *selection = objects.first().map(|&o| Selection::Object(o));
+                }
+            }
+        }
+        Some(Selection::Object(current)) if modifiers.shift => {
+            objects.extend(current.min(o)..=current.max(o));
+            *selection = Some(Selection::Object(o));
+        }
+        _ => {
+            objects.clear();
+            *selection = Some(Selection::Object(o));
+        }
+    }
+}
+
+/// Groups and objects below the parent, groups can be expanded recursively
+fn tree(
+    ui: &mut Ui,
+    scene: &Scene,
+    parent: Option<usize>,
+    selection: &mut Option<Selection>,
+    objects: &mut BTreeSet<usize>,
+    actions: &mut Vec<Action>,
+) {
+    let groups = (0..scene.groups.len()).filter(|&g| scene.groups[g].parent == parent);
+
+    for g in groups {
+        CollapsingState::load_with_default_open(ui.ctx(), ui.id().with(Node::Group(g)), true)
+            .show_header(ui, |ui| {
+                select(
+                    ui,
+                    selection,
+                    Selection::Group(g),
+                    format!("📁 {}", scene.groups[g].name),
+                );
+                parent_combo(ui, scene, Node::Group(g), actions);
+            })
+            .body(|ui| tree(ui, scene, Some(g), selection, objects, actions));
+    }
+
+    let children = (0..scene.objects.len()).filter(|&o| scene.objects[o].parent == parent);
+
+    for o in children {
+        object_row(ui, scene, o, selection, objects, actions);
+    }
+}
+
+/// Name, parent and visibility of an object
+fn object_row(
+    ui: &mut Ui,
+    scene: &Scene,
+    o: usize,
+    selection: &mut Option<Selection>,
+    objects: &mut BTreeSet<usize>,
+    actions: &mut Vec<Action>,
+) {
+    ui.horizontal(|ui| {
+        select_object(
+            ui,
+            selection,
+            objects,
+            o,
+            format!("▲ {}", scene.objects[o].name),
+        );
+        parent_combo(ui, scene, Node::Object(o), actions);
+        visibility_toggles(ui, scene, o, objects, actions);
+    });
+}
+
+/// Eye icons to hide the object in the preview and the render,
+/// toggling a selected object toggles all selected objects
+fn visibility_toggles(
+    ui: &mut Ui,
+    scene: &Scene,
+    o: usize,
+    objects: &BTreeSet<usize>,
+    actions: &mut Vec<Action>,
+) {
+    let targets = if objects.contains(&o) {
+        objects.iter().copied().collect()
+    } else {
+        vec![o]
+    };
+    let object = &scene.objects[o];
+
+    if ui
+        .selectable_label(object.visible_in_preview, "👁")
+        .on_hover_text(t!("show_in_preview_hint"))
+        .clicked()
+    {
+        let visible = !object.visible_in_preview;
+        actions.extend(targets.iter().map(|&t| Action::ShowInPreview(t, visible)));
+    }
+
+    if ui
+        .selectable_label(object.visible_in_render, "🖼")
+        .on_hover_text(t!("show_in_render_hint"))
+        .clicked()
+    {
+        let visible = !object.visible_in_render;
+        actions.extend(targets.iter().map(|&t| Action::ShowInRender(t, visible)));
+    }
+}
+
+/// Select the parent group, groups can't be parented to themselves or their descendants
+fn parent_combo(ui: &mut Ui, scene: &Scene, node: Node, actions: &mut Vec<Action>) {
+    let current = match node {
+        Node::Group(g) => scene.groups[g].parent,
+        Node::Object(o) => scene.objects[o].parent,
+    };
+
+    let options = std::iter::once((None, t!("no_parent").to_string()))
+        .chain(
+            scene
+                .groups
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| match node {
+                    Node::Group(g) => !scene.is_descendant(i, g),
+                    Node::Object(_) => true,
+                })
+                .map(|(i, group)| (Some(i), group.name.clone())),
+        )
+        .collect::<Vec<_>>();
+
+    let selected = options
+        .iter()
+        .find(|(parent, _)| *parent == current)
+        .map(|(_, name)| name.clone())
+        .unwrap_or_default();
+
+    ComboBox::from_id_salt(("parent", node))
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (parent, name) in options {
+                if ui.selectable_label(parent == current, name).clicked() && parent != current {
+                    actions.push(Action::Reparent(node, parent));
+                }
+            }
+        })
+        .response
+        .on_hover_text(t!("parent_hint"));
+}
@@ -0,0 +1,109 @@
+use super::{
+    keymap::Keymap, presets::RenderPreset, preview::navigation::Navigation, snap::Snap, Tab,
+};
+use egui::{Slider, ThemePreference, Ui};
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use trayracer_core::raytracer::export::ExportOptions;
+
+/// UI state that isn't part of a scene, persisted between sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Tab shown on launch
+    pub tab: Tab,
+    /// Width of the side panel with the outliner and properties
+    pub side_panel_width: f32,
+    /// Width of the render history
+    pub history_width: f32,
+    /// Camera movement speed of the preview
+    pub preview_speed: f32,
+    /// Mouse look sensitivity of the preview
+    pub preview_sensitivity: f32,
+    /// Scale the movement speed of the preview with the size of the scene
+    pub preview_scale_speed: bool,
+    /// Mouse controls of the preview camera
+    pub preview_navigation: Navigation,
+    pub theme: ThemePreference,
+    /// Zoom of the whole UI on top of the scale of the display
+    pub ui_scale: f32,
+    /// Directory the last render was exported to
+    pub export_directory: Option<PathBuf>,
+    /// Format and tone mapping of exported renders
+    pub export_options: ExportOptions,
+    /// Save finished renders to the auto export directory
+    pub auto_export: bool,
+    pub auto_export_directory: Option<PathBuf>,
+    /// Place imported objects in front of the camera
+    pub auto_frame: bool,
+    pub keymap: Keymap,
+    pub snap: Snap,
+    /// Render settings saved by the user
+    pub render_presets: Vec<RenderPreset>,
+}
+
+impl Preferences {
+    pub const STORAGE_KEY: &'static str = "preferences";
+    const UI_SCALE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+
+    /// Theme and UI scale
+    pub fn appearance(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("theme")));
+            for (theme, name) in [
+                (ThemePreference::System, t!("theme_system")),
+                (ThemePreference::Light, t!("theme_light")),
+                (ThemePreference::Dark, t!("theme_dark")),
+            ] {
+                if ui.radio_value(&mut self.theme, theme, name).changed() {
+                    ui.ctx().set_theme(theme);
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("ui_scale")));
+            let response = ui
+                .add(
+                    Slider::new(&mut self.ui_scale, Self::UI_SCALE)
+                        .step_by(0.05)
+                        .fixed_decimals(2),
+                )
+                .on_hover_text(t!("ui_scale_hint"));
+            let reset = ui.button(t!("reset")).clicked();
+            if reset {
+                self.ui_scale = 1.0;
+            }
+
+            // scaling while dragging would move the slider away from the pointer
+            if reset || response.drag_stopped() || (response.changed() && !response.dragged()) {
+                ui.ctx().set_zoom_factor(self.ui_scale);
+            }
+        });
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            tab: Tab::Preview,
+            side_panel_width: 200.0,
+            history_width: 200.0,
+            preview_speed: 0.1,
+            preview_sensitivity: 0.001,
+            preview_scale_speed: true,
+            preview_navigation: Navigation::default(),
+            theme: ThemePreference::System,
+            ui_scale: 1.0,
+            export_directory: None,
+            export_options: ExportOptions::default(),
+            auto_export: false,
+            auto_export_directory: None,
+            auto_frame: true,
+            keymap: Keymap::default(),
+            snap: Snap::default(),
+            render_presets: Vec::new(),
+        }
+    }
+}
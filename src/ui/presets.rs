@@ -0,0 +1,82 @@
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+use trayracer_core::scene::Scene;
+
+/// Render settings selected together, the built-in ones and the presets saved by the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderPreset {
+    pub name: String,
+    pub resolution: (u32, u32),
+    /// Samples per pixel, anti-aliasing is used for more than one
+    pub samples: u32,
+    pub max_bounces: u32,
+    pub adaptive_sampling: bool,
+    pub noise_threshold: f32,
+}
+
+impl RenderPreset {
+    /// Draft, preview and final quality
+    pub fn builtin() -> [Self; 3] {
+        [
+            Self {
+                name: t!("preset_draft").to_string(),
+                resolution: (640, 360),
+                samples: 1,
+                max_bounces: 2,
+                adaptive_sampling: false,
+                noise_threshold: 0.05,
+            },
+            Self {
+                name: t!("preset_preview").to_string(),
+                resolution: (1280, 720),
+                samples: 8,
+                max_bounces: 4,
+                adaptive_sampling: true,
+                noise_threshold: 0.05,
+            },
+            Self {
+                name: t!("preset_final").to_string(),
+                resolution: (1920, 1080),
+                samples: 64,
+                max_bounces: 8,
+                adaptive_sampling: true,
+                noise_threshold: 0.01,
+            },
+        ]
+    }
+
+    /// The current settings of the scene
+    pub fn of(name: &str, scene: &Scene) -> Self {
+        let settings = &scene.settings;
+        Self {
+            name: name.to_string(),
+            resolution: scene.camera().resolution,
+            samples: if settings.anti_aliasing {
+                settings.samples
+            } else {
+                1
+            },
+            max_bounces: settings.max_bounces,
+            adaptive_sampling: settings.adaptive_sampling,
+            noise_threshold: settings.noise_threshold,
+        }
+    }
+
+    pub fn apply(&self, scene: &mut Scene) {
+        scene.camera_mut().resolution = self.resolution;
+        let settings = &mut scene.settings;
+        settings.anti_aliasing = self.samples > 1;
+        settings.samples = self.samples;
+        settings.max_bounces = self.max_bounces;
+        settings.adaptive_sampling = self.adaptive_sampling;
+        settings.noise_threshold = self.noise_threshold;
+    }
+
+    /// Whether the scene uses the settings of the preset
+    pub fn matches(&self, scene: &Scene) -> bool {
+        Self {
+            name: self.name.clone(),
+            ..Self::of("", scene)
+        } == *self
+    }
+}
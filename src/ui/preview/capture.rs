@@ -0,0 +1,104 @@
+use crate::ui::toasts;
+use anyhow::Context;
+use egui::{Color32, ColorImage, Event, Rect, Ui, UserData, ViewportCommand};
+use egui_file::FileDialog;
+use image::{ImageFormat, RgbaImage};
+use log::{info, warn};
+use rust_i18n::t;
+use std::path::Path;
+
+/// Screenshot of the preview as drawn by the GPU, without the toolbars, saved as PNG
+pub struct Capture {
+    /// The next frame is drawn without overlays and taken as screenshot
+    requested: bool,
+    /// Part of the window the preview was drawn to, while waiting for the screenshot
+    rect: Option<Rect>,
+    image: Option<ColorImage>,
+    dialog: Option<FileDialog>,
+}
+
+impl Capture {
+    pub const fn new() -> Self {
+        Self {
+            requested: false,
+            rect: None,
+            image: None,
+            dialog: None,
+        }
+    }
+
+    pub const fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Whether the toolbars and overlays are left out of this frame
+    pub const fn hides_overlays(&self) -> bool {
+        self.requested
+    }
+
+    /// Take the screenshot of the preview drawn to the rect and save it once it arrives
+    pub fn update(&mut self, ui: &Ui, rect: Rect) {
+        let ctx = ui.ctx();
+
+        if self.requested {
+            self.requested = false;
+            self.rect = Some(rect);
+            ctx.send_viewport_cmd(ViewportCommand::Screenshot(UserData::default()));
+        }
+
+        if let Some(rect) = self.rect {
+            let screenshot = ctx.input(|i| {
+                i.events.iter().find_map(|e| match e {
+                    Event::Screenshot { image, .. } => Some(image.clone()),
+                    _ => None,
+                })
+            });
+
+            if let Some(screenshot) = screenshot {
+                info!("Captured the preview");
+                self.rect = None;
+                self.image = Some(screenshot.region(&rect, Some(ctx.pixels_per_point())));
+
+                let mut dialog = FileDialog::save_file(None)
+                    .default_filename("preview.png")
+                    .filename_filter(Box::new(|name| {
+                        Path::new(name)
+                            .extension()
+                            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+                    }));
+                dialog.open();
+                self.dialog = Some(dialog);
+            }
+        }
+
+        if let Some(dialog) = self.dialog.as_mut() {
+            if dialog.show(ctx).selected() {
+                if let (Some(path), Some(image)) = (dialog.path(), &self.image) {
+                    Self::save(image, path).unwrap_or_else(|e| {
+                        warn!("Failed to save the preview: {e:?}");
+                        toasts::error(ctx, t!("capture_preview_failed", error = format!("{e:#}")));
+                    });
+                }
+                self.dialog = None;
+                self.image = None;
+            }
+        }
+    }
+
+    fn save(image: &ColorImage, path: &Path) -> anyhow::Result<()> {
+        let pixels = image
+            .pixels
+            .iter()
+            .flat_map(Color32::to_srgba_unmultiplied)
+            .collect();
+        let [width, height] = image.size;
+
+        RgbaImage::from_raw(width as u32, height as u32, pixels)
+            .context("Screenshot has the wrong size")?
+            .save_with_format(path, ImageFormat::Png)
+            .context("Failed to save PNG")?;
+
+        info!("Saved the preview to {}", path.display());
+        Ok(())
+    }
+}
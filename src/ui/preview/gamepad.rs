@@ -0,0 +1,65 @@
+use egui::Vec2;
+use gilrs::{ev::state::ButtonData, Axis, Button, Gilrs};
+use log::warn;
+
+/// Sticks and triggers of a gamepad, zero inside the dead zone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadInput {
+    /// Left stick, right and forward are positive
+    pub movement: Vec2,
+    /// Right stick, right and up are positive
+    pub look: Vec2,
+    /// Right trigger minus left trigger
+    pub speed: f32,
+}
+
+/// Flies the preview camera with the first connected gamepad
+pub struct Gamepad {
+    /// None if gamepads aren't supported on this system
+    gilrs: Option<Gilrs>,
+}
+
+impl Gamepad {
+    const DEAD_ZONE: f32 = 0.15;
+
+    pub fn new() -> Self {
+        Self {
+            gilrs: Gilrs::new()
+                .map_err(|e| warn!("Gamepads are not available: {e}"))
+                .ok(),
+        }
+    }
+
+    pub fn connected(&self) -> bool {
+        self.gilrs
+            .as_ref()
+            .is_some_and(|gilrs| gilrs.gamepads().next().is_some())
+    }
+
+    /// Input of the first connected gamepad, None while it isn't touched
+    pub fn input(&mut self) -> Option<GamepadInput> {
+        let gilrs = self.gilrs.as_mut()?;
+        // the state of the gamepads is updated while handling their events
+        while gilrs.next_event().is_some() {}
+
+        let (_, gamepad) = gilrs.gamepads().next()?;
+        let axis = |axis| {
+            let value = gamepad.value(axis);
+            if value.abs() < Self::DEAD_ZONE {
+                0.0
+            } else {
+                value
+            }
+        };
+        let trigger = |button| gamepad.button_data(button).map_or(0.0, ButtonData::value);
+
+        let input = GamepadInput {
+            movement: Vec2::new(axis(Axis::LeftStickX), axis(Axis::LeftStickY)),
+            look: Vec2::new(axis(Axis::RightStickX), axis(Axis::RightStickY)),
+            speed: trigger(Button::RightTrigger2) - trigger(Button::LeftTrigger2),
+        };
+
+        (input.movement != Vec2::ZERO || input.look != Vec2::ZERO || input.speed != 0.0)
+            .then_some(input)
+    }
+}
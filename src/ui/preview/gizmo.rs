@@ -0,0 +1,352 @@
+use super::{super::snap::Snap, gpu::view_projection};
+use egui::{pos2, Color32, Painter, Pos2, Rect, Response, Shape, Stroke, Ui, Vec2};
+use nalgebra::{Matrix4, Point3, Scale3, Translation3, UnitQuaternion, Vector3};
+use ordered_float::OrderedFloat;
+use rust_i18n::t;
+use trayracer_core::{
+    raytracer::Ray,
+    scene::{Scene, SectionPlane},
+};
+
+/// Length of the handles and radius of the rings in points
+const HANDLE_LENGTH: f32 = 80.0;
+/// Distance in points within which the pointer grabs a handle
+const GRAB_DISTANCE: f32 = 8.0;
+/// Segments of a rotation ring
+const RING_SEGMENTS: usize = 48;
+const AXIS_COLORS: [Color32; 3] = [
+    Color32::from_rgb(220, 50, 50),
+    Color32::from_rgb(60, 190, 60),
+    Color32::from_rgb(60, 110, 230),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+impl GizmoMode {
+    pub const ALL: [Self; 3] = [Self::Translate, Self::Rotate, Self::Scale];
+
+    pub fn name(self) -> String {
+        match self {
+            Self::Translate => t!("gizmo_move"),
+            Self::Rotate => t!("gizmo_rotate"),
+            Self::Scale => t!("gizmo_scale"),
+        }
+        .to_string()
+    }
+}
+
+/// Screen space handles to move, rotate and scale the selected object
+#[derive(Clone)]
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    /// Axis of the handle being dragged
+    dragging: Option<usize>,
+    /// Transform of the object when the drag started
+    start: Option<(Translation3<f32>, UnitQuaternion<f32>, Scale3<f32>)>,
+    /// Movement along the handle since the drag started, units for translation,
+    /// radians for rotation and a factor for scale
+    along: f32,
+}
+
+/// Converts between world space and the screen space of the preview
+pub struct Projection {
+    matrix: Matrix4<f32>,
+    rect: Rect,
+}
+
+impl Projection {
+    pub fn new(scene: &Scene, rect: Rect) -> Self {
+        Self {
+            matrix: view_projection(scene.camera()),
+            rect,
+        }
+    }
+
+    /// Position of a point on the screen, none if it is behind the camera
+    fn project(&self, point: &Point3<f32>) -> Option<Pos2> {
+        let clip = self.matrix * point.to_homogeneous();
+        (clip.w > 0.0).then(|| {
+            let ndc = clip.xyz() / clip.w;
+            pos2(
+                (self.rect.width() / 2.0).mul_add(ndc.x, self.rect.center().x),
+                (self.rect.height() / 2.0).mul_add(-ndc.y, self.rect.center().y),
+            )
+        })
+    }
+
+    /// Ray from the camera through a position on the screen
+    fn ray(&self, pos: Pos2) -> Option<Ray> {
+        let inverse = self.matrix.try_inverse()?;
+        let x = (pos.x - self.rect.center().x) / (self.rect.width() / 2.0);
+        let y = (self.rect.center().y - pos.y) / (self.rect.height() / 2.0);
+
+        let near = inverse.transform_point(&Point3::new(x, y, -1.0));
+        let far = inverse.transform_point(&Point3::new(x, y, 1.0));
+
+        Some(Ray {
+            origin: near,
+            direction: (far - near).normalize(),
+        })
+    }
+
+    /// Screen space movement of one unit along a direction starting at a point
+    fn screen_direction(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<Vec2> {
+        // a small step keeps the point in front of the camera for directions towards it
+        let step = 0.01;
+        Some((self.project(&(origin + direction * step))? - self.project(origin)?) / step)
+    }
+}
+
+/// Index of the object under the pointer
+pub fn pick(scene: &Scene, projection: &Projection, pos: Pos2) -> Option<usize> {
+    let ray = projection.ray(pos)?;
+    // only what the preview draws can be clicked
+    let planes = &scene.settings.section_planes;
+    let planes = &planes[..planes.len().min(SectionPlane::PREVIEW_MAX)];
+
+    scene
+        .objects
+        .iter()
+        .enumerate()
+        .filter(|(_, o)| o.visible_in_preview)
+        .filter_map(|(i, o)| {
+            o.intersect_clipped(ray, 1e-5, f32::INFINITY, None, planes)
+                .map(|hit| (i, hit))
+        })
+        .min_by_key(|(_, hit)| OrderedFloat((hit.point - ray.origin).norm_squared()))
+        .map(|(i, _)| i)
+}
+
+/// A handle for one axis, drawn as polyline
+struct Handle {
+    axis: usize,
+    points: Vec<Pos2>,
+    /// Screen space movement of one unit along the axis
+    screen_direction: Vec2,
+}
+
+impl Handle {
+    fn distance(&self, pos: Pos2) -> f32 {
+        self.points
+            .windows(2)
+            .map(|s| {
+                let segment = s[1] - s[0];
+                let t = ((pos - s[0]).dot(segment) / segment.length_sq().max(f32::EPSILON))
+                    .clamp(0.0, 1.0);
+                (s[0] + segment * t).distance(pos)
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+}
+
+impl Gizmo {
+    pub const fn new() -> Self {
+        Self {
+            mode: GizmoMode::Translate,
+            dragging: None,
+            start: None,
+            along: 0.0,
+        }
+    }
+
+    /// Handles of the object for the current mode
+    fn handles(&self, scene: &Scene, projection: &Projection, object: usize) -> Vec<Handle> {
+        let object = &scene.objects[object];
+        let origin = object.transform() * Point3::origin();
+        let Some(center) = projection.project(&origin) else {
+            return Vec::new();
+        };
+
+        (0..3)
+            .filter_map(|axis| {
+                let unit = Vector3::ith(axis, 1.0);
+                // translation and rotation happen in the space of the parent,
+                // scale along the axes of the object
+                let direction = match self.mode {
+                    GizmoMode::Translate | GizmoMode::Rotate => object.parent_transform() * unit,
+                    GizmoMode::Scale => object.transform() * unit,
+                };
+                let screen_direction = projection.screen_direction(&origin, &direction)?;
+
+                let points = match self.mode {
+                    GizmoMode::Translate | GizmoMode::Scale => {
+                        // axes pointing at the camera can't be grabbed
+                        if screen_direction.length() < f32::EPSILON {
+                            return None;
+                        }
+                        vec![
+                            center,
+                            center + screen_direction.normalized() * HANDLE_LENGTH,
+                        ]
+                    }
+                    GizmoMode::Rotate => {
+                        let normal = direction.try_normalize(f32::EPSILON)?;
+                        let helper = if normal.x.abs() < 0.9 {
+                            Vector3::x()
+                        } else {
+                            Vector3::y()
+                        };
+                        let u = normal.cross(&helper).normalize();
+                        let v = normal.cross(&u);
+
+                        // radius in world space that appears as long as the other handles
+                        let right = (scene.camera().look_at - scene.camera().position)
+                            .cross(&scene.camera().up)
+                            .try_normalize(f32::EPSILON)?;
+                        let radius = HANDLE_LENGTH
+                            / projection
+                                .screen_direction(&origin, &right)?
+                                .length()
+                                .max(1.0);
+
+                        (0..=RING_SEGMENTS)
+                            .filter_map(|s| {
+                                let angle = s as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                                projection.project(
+                                    &(origin + (u * angle.cos() + v * angle.sin()) * radius),
+                                )
+                            })
+                            .collect()
+                    }
+                };
+
+                Some(Handle {
+                    axis,
+                    points,
+                    screen_direction,
+                })
+            })
+            .collect()
+    }
+
+    /// Draw the handles of the selected object and apply drags to its transform,
+    /// returns whether the pointer is on a handle
+    pub fn show(
+        &mut self,
+        ui: &Ui,
+        response: &Response,
+        painter: &Painter,
+        scene: &mut Scene,
+        selected: usize,
+        snap: &Snap,
+    ) -> bool {
+        let projection = Projection::new(scene, response.rect);
+        let handles = self.handles(scene, &projection, selected);
+
+        let grabbed = |pos: Option<Pos2>| {
+            pos.and_then(|pos| {
+                handles
+                    .iter()
+                    .map(|h| (h.axis, h.distance(pos)))
+                    .filter(|(_, d)| *d <= GRAB_DISTANCE)
+                    .min_by_key(|(_, d)| OrderedFloat(*d))
+                    .map(|(axis, _)| axis)
+            })
+        };
+
+        if response.drag_started() {
+            self.dragging = grabbed(ui.input(|i| i.pointer.press_origin()));
+            let object = &scene.objects[selected];
+            self.start = Some((object.translation, object.rotation, object.scale));
+            self.along = match self.mode {
+                GizmoMode::Translate | GizmoMode::Rotate => 0.0,
+                GizmoMode::Scale => 1.0,
+            };
+        }
+        let hovered = self.dragging.or_else(|| grabbed(response.hover_pos()));
+
+        if let Some(handle) = self
+            .dragging
+            .and_then(|axis| handles.iter().find(|h| h.axis == axis))
+            .filter(|_| response.dragged())
+        {
+            self.drag(response, &projection, handle, scene, selected, snap);
+        }
+
+        if response.drag_stopped() {
+            self.dragging = None;
+            self.start = None;
+        }
+
+        for handle in &handles {
+            let (color, width) = if hovered == Some(handle.axis) {
+                (Color32::YELLOW, 3.0)
+            } else {
+                (AXIS_COLORS[handle.axis], 2.0)
+            };
+
+            painter.add(Shape::line(
+                handle.points.clone(),
+                Stroke::new(width, color),
+            ));
+            if let (GizmoMode::Scale, Some(end)) = (self.mode, handle.points.last()) {
+                painter.rect_filled(Rect::from_center_size(*end, Vec2::splat(8.0)), 0.0, color);
+            }
+        }
+
+        hovered.is_some()
+    }
+
+    /// Change the transform of the object by the pointer movement along a handle,
+    /// the movement since the drag started is snapped
+    fn drag(
+        &mut self,
+        response: &Response,
+        projection: &Projection,
+        handle: &Handle,
+        scene: &mut Scene,
+        selected: usize,
+        snap: &Snap,
+    ) {
+        let Some((translation, rotation, scale)) = self.start else {
+            return;
+        };
+        let delta = response.drag_delta();
+        let camera = scene.camera().position;
+        let object = &mut scene.objects[selected];
+        let axis = handle.axis;
+
+        match self.mode {
+            GizmoMode::Translate => {
+                let along = delta.dot(handle.screen_direction.normalized());
+                self.along += along / handle.screen_direction.length();
+                object.translation.vector[axis] =
+                    snap.translation(translation.vector[axis] + self.along);
+            }
+            GizmoMode::Scale => {
+                let along = delta.dot(handle.screen_direction.normalized());
+                self.along *= (1.0 + along / HANDLE_LENGTH).max(0.1);
+                object.scale.vector[axis] = snap.scale(scale.vector[axis] * self.along);
+            }
+            GizmoMode::Rotate => {
+                let Some(center) = projection.project(&(object.transform() * Point3::origin()))
+                else {
+                    return;
+                };
+                let Some(pos) = response.interact_pointer_pos() else {
+                    return;
+                };
+
+                let (from, to) = (pos - delta - center, pos - center);
+                // clockwise on the screen, as the y axis points down
+                let angle = from.x.mul_add(to.y, -(from.y * to.x)).atan2(from.dot(to));
+
+                // the ring is seen from the side the axis points to
+                let normal = object.parent_transform() * Vector3::ith(axis, 1.0);
+                let towards_camera =
+                    normal.dot(&(camera - object.transform() * Point3::origin())) > 0.0;
+                self.along += if towards_camera { -angle } else { angle };
+
+                object.rotation = UnitQuaternion::from_axis_angle(
+                    &Vector3::ith_axis(axis),
+                    snap.rotation(self.along),
+                ) * rotation;
+            }
+        }
+    }
+}
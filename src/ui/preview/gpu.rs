@@ -1,58 +1,564 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+    sync::Arc,
+    time::Duration,
+};
+use web_time::Instant;
 
-use crate::scene::Scene;
 use eframe::wgpu::PipelineCompilationOptions;
-use egui::mutex::RwLock;
+use egui::mutex::{Mutex, RwLock};
 use egui_wgpu::{
     wgpu::{
         self,
         util::{BufferInitDescriptor, DeviceExt},
-        BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
-        BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferDescriptor,
-        BufferUsages, ColorTargetState, ColorWrites, CompareFunction, DepthBiasState,
-        DepthStencilState, FragmentState, FrontFace, MultisampleState, PipelineLayoutDescriptor,
-        PolygonMode, PrimitiveState, PrimitiveTopology, RenderPipeline, RenderPipelineDescriptor,
-        ShaderModuleDescriptor, ShaderSource, ShaderStages, StencilState, TextureFormat,
-        VertexAttribute, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
+        BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+        BindGroupLayoutEntry, BindingResource, BindingType, BlendState, Buffer, BufferBinding,
+        BufferBindingType, BufferDescriptor, BufferSize, BufferUsages, ColorTargetState,
+        ColorWrites, CompareFunction, DepthBiasState, DepthStencilState, Extent3d, FilterMode,
+        FragmentState, FrontFace, IndexFormat, LoadOp, MultisampleState, Operations,
+        PipelineLayoutDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology,
+        RenderPassDepthStencilAttachment, RenderPassDescriptor, RenderPipeline,
+        RenderPipelineDescriptor, SamplerBindingType, SamplerDescriptor, ShaderModuleDescriptor,
+        ShaderSource, ShaderStages, StencilState, StoreOp, TextureDescriptor, TextureDimension,
+        TextureFormat, TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor,
+        TextureViewDimension, VertexAttribute, VertexBufferLayout, VertexFormat, VertexState,
+        VertexStepMode,
     },
     CallbackTrait,
 };
 use log::debug;
-use nalgebra::{Isometry3, Perspective3};
+use nalgebra::{Isometry3, Matrix4, Perspective3, Point3, Vector2, Vector3, Vector4};
+use trayracer_core::{
+    profiler,
+    scene::{Camera, Object, Scene, SectionPlane},
+};
 
 struct Resources {
+    bind_group_layout: BindGroupLayout,
     bind_group: BindGroup,
     pipeline: RenderPipeline,
+    /// Pipeline for the grid and axis lines
+    line_pipeline: RenderPipeline,
+    /// Pipeline for the bounding boxes, lines that don't fade out
+    bounds_pipeline: RenderPipeline,
+    /// Depth only pipeline rendering the scene from the lights
+    shadow_pipeline: RenderPipeline,
+    /// One layer of the shadow map for each cube face of each light
+    shadow_views: Vec<TextureView>,
+    /// Shadow map and face matrices sampled by the main pipeline
+    shadow_bind_group: BindGroup,
+    /// Matrix of the face rendered by the shadow pipeline, selected with a dynamic offset
+    shadow_face_bind_group: BindGroup,
+    shadow_buffer: Buffer,
     vertex_buffer: Buffer,
+    index_buffer: Buffer,
+    vertex_layout: VertexLayout,
+    /// Index ranges of the visible objects
+    draws: Vec<Range<u32>>,
+    /// Index ranges drawn into the shadow maps, objects outside of the view cast shadows as well
+    shadow_draws: Vec<Range<u32>>,
     uniform_buffer: Buffer,
     lights_buffer: Buffer,
     transforms_buffer: Buffer,
 }
 
+// overlay toggles of the preview, not a state machine
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Clone)]
 pub struct WgpuPainter {
     scene: Arc<RwLock<Option<Scene>>>,
+    /// Index of the object highlighted as selected
+    pub selected: Option<usize>,
+    /// Draw the ground grid and the axes
+    pub show_grid: bool,
+    /// Draw the bounding boxes of all objects, not only the selected one
+    pub show_bounds: bool,
+    /// Cast shadows from the first lights
+    pub show_shadows: bool,
+    /// Draw large meshes that cover a small part of the view decimated
+    pub use_lod: bool,
+    /// Measured by the last prepare
+    draw_stats: Arc<Mutex<DrawStats>>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DrawStats {
+    /// Time to upload the buffers and record the shadow passes
+    pub time: Duration,
+    /// Vertices in the vertex buffer, shared between triangles
+    pub vertices: usize,
+    /// Triangles drawn after culling, some with their level of detail
+    pub triangles: usize,
 }
 
 impl WgpuPainter {
-    const MAX_LIGHTS: usize = 255;
-    const MAX_OBJECTS: usize = 255;
+    /// Initial capacity of the storage buffers, they grow with the scene
+    const LIGHTS_CAPACITY: usize = 16;
+    const OBJECTS_CAPACITY: usize = 64;
+    /// Lights that cast shadows, each one renders the scene six times
+    const SHADOW_LIGHTS: usize = 4;
+    /// Width and height of a shadow map face in pixels
+    const SHADOW_SIZE: u32 = 512;
+
+    pub fn new(scene: Arc<RwLock<Option<Scene>>>) -> Self {
+        Self {
+            scene,
+            selected: None,
+            show_grid: true,
+            show_bounds: false,
+            show_shadows: true,
+            use_lod: true,
+            draw_stats: Arc::new(Mutex::new(DrawStats::default())),
+        }
+    }
+
+    pub fn draw_stats(&self) -> DrawStats {
+        *self.draw_stats.lock()
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    color: [f32; 3],
+    /// Index of the object, used to look up its transform
+    object: u32,
+}
+
+/// Identifies the geometry of an object, it is uploaded again when it changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ObjectKey {
+    /// Address of the shared mesh
+    mesh: usize,
+    /// Hash of the diffuse colors of the materials
+    colors: u64,
+}
+
+impl ObjectKey {
+    fn new(object: &Object) -> Self {
+        let mut hasher = DefaultHasher::new();
+        for material in (0..object.materials.len())
+            .map(Some)
+            .chain([None])
+            .map(|i| object.material(i))
+        {
+            material
+                .and_then(|m| m.diffuse_color)
+                .map(|c| c.map(f32::to_bits))
+                .hash(&mut hasher);
+        }
+
+        Self {
+            mesh: Arc::as_ptr(&object.mesh) as usize,
+            colors: hasher.finish(),
+        }
+    }
+}
+
+/// Range of an object in the vertex and index buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ObjectSlot {
+    key: ObjectKey,
+    first_vertex: usize,
+    vertex_count: usize,
+    first_index: usize,
+    index_count: usize,
+    /// Indices of the level of detail at the end of the range of the object
+    lod_count: usize,
+}
+
+impl ObjectSlot {
+    const fn full(&self) -> Range<u32> {
+        self.first_index as u32..(self.first_index + self.index_count - self.lod_count) as u32
+    }
+
+    const fn lod(&self) -> Range<u32> {
+        (self.first_index + self.index_count - self.lod_count) as u32
+            ..(self.first_index + self.index_count) as u32
+    }
+}
+
+/// Objects in the vertex and index buffer
+#[derive(Default)]
+struct VertexLayout {
+    objects: Vec<ObjectSlot>,
+    vertex_count: usize,
+}
+
+impl VertexLayout {
+    /// Index ranges of the objects in the view of the camera,
+    /// with their level of detail when they cover only a small part of it
+    fn visible(&self, scene: &Scene, use_lod: bool) -> Vec<Range<u32>> {
+        let view = view_projection(scene.camera());
+
+        merge(
+            self.objects
+                .iter()
+                .zip(&scene.objects)
+                .filter(|(_, object)| object.visible_in_preview)
+                .filter_map(|(slot, object)| {
+                    let bounds = &object.mesh.bounds;
+                    let transform = view * object.transform().to_homogeneous();
+                    let corners = (0..8)
+                        .map(|c| {
+                            transform
+                                * Point3::new(
+                                    if c & 1 == 0 {
+                                        bounds.min.x
+                                    } else {
+                                        bounds.max.x
+                                    },
+                                    if c & 2 == 0 {
+                                        bounds.min.y
+                                    } else {
+                                        bounds.max.y
+                                    },
+                                    if c & 4 == 0 {
+                                        bounds.min.z
+                                    } else {
+                                        bounds.max.z
+                                    },
+                                )
+                                .to_homogeneous()
+                        })
+                        .collect::<Vec<_>>();
+
+                    // outside if all corners are on the outer side of one of the clip planes
+                    let outside = (0..3).any(|axis| {
+                        corners.iter().all(|c| c[axis] < -c.w)
+                            || corners.iter().all(|c| c[axis] > c.w)
+                    });
+                    if outside {
+                        return None;
+                    }
+
+                    let small = use_lod
+                        && slot.lod_count > 0
+                        && corners.iter().all(|c| c.w > 0.0)
+                        && screen_coverage(&corners) < LOD_COVERAGE;
+                    Some(if small { slot.lod() } else { slot.full() })
+                }),
+        )
+    }
+
+    /// Index ranges of all shown objects in full detail
+    fn all(&self, scene: &Scene) -> Vec<Range<u32>> {
+        merge(
+            self.objects
+                .iter()
+                .zip(&scene.objects)
+                .filter(|(_, object)| object.visible_in_preview)
+                .map(|(slot, _)| slot.full()),
+        )
+    }
+}
+
+/// Objects covering less of the screen are drawn with their level of detail
+const LOD_COVERAGE: f32 = 0.01;
+
+/// Part of the screen covered by the bounding rectangle of points in clip space in front of the camera
+fn screen_coverage(corners: &[Vector4<f32>]) -> f32 {
+    let (min, max) = corners.iter().fold(
+        (
+            Vector2::repeat(f32::INFINITY),
+            Vector2::repeat(f32::NEG_INFINITY),
+        ),
+        |(min, max), c| {
+            let ndc = Vector2::new(c.x, c.y) / c.w;
+            (min.inf(&ndc), max.sup(&ndc))
+        },
+    );
+    let size = max.sup(&Vector2::repeat(-1.0)).inf(&Vector2::repeat(1.0))
+        - min.sup(&Vector2::repeat(-1.0)).inf(&Vector2::repeat(1.0));
+
+    // the screen is two units wide and high in normalized device coordinates
+    size.x * size.y / 4.0
+}
+
+/// Join adjacent ranges to draw them at once, empty ones are dropped
+fn merge(ranges: impl Iterator<Item = Range<u32>>) -> Vec<Range<u32>> {
+    let mut merged = Vec::<Range<u32>>::new();
+    for range in ranges.filter(|r| !r.is_empty()) {
+        match merged.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Vertices of an object, shared between the triangles with the same position, normal and color,
+/// and the indices of the triangles into them, followed by the indices of the level of detail
+fn object_geometry(index: usize, object: &Object) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(object.mesh.triangles.len() * 3);
+    let mut lookup = HashMap::new();
+
+    for t in object
+        .mesh
+        .triangles
+        .iter()
+        .chain(object.mesh.lod.iter().flatten())
+    {
+        let diffuse = object
+            .material(t.material_index)
+            .and_then(|m| m.diffuse_color);
+        // the vertex colors are only used where the material has no diffuse color
+        let colors = diffuse.map_or_else(
+            || t.colors.map_or([[0.9; 3]; 3], |c| c.map(Into::into)),
+            |c| [c.into(); 3],
+        );
+
+        for (position, normal, color) in [
+            (t.a, t.a_normal, colors[0]),
+            (t.b, t.b_normal, colors[1]),
+            (t.c, t.c_normal, colors[2]),
+        ] {
+            let vertex = Vertex {
+                position: position.into(),
+                normal: normal.into(),
+                color,
+                object: index as u32,
+            };
+            // compare the bits, floats are neither Eq nor Hash
+            let key: [u32; 10] = bytemuck::cast(vertex);
+
+            indices.push(*lookup.entry(key).or_insert_with(|| {
+                vertices.push(vertex);
+                (vertices.len() - 1) as u32
+            }));
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Grow a buffer to at least the size, returns whether it was recreated and lost its contents
+fn reserve(device: &wgpu::Device, buffer: &mut Buffer, size: u64, label: &str) -> bool {
+    if buffer.size() >= size {
+        return false;
+    }
+
+    let usage = buffer.usage();
+    buffer.destroy();
+    // grow in steps to not recreate it for every added object
+    *buffer = device.create_buffer(&BufferDescriptor {
+        label: Some(label),
+        usage,
+        size: size.next_power_of_two(),
+        mapped_at_creation: false,
+    });
+    true
+}
+
+/// Bind group of the uniforms, lights and transforms, recreated when a buffer grows
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &BindGroupLayout,
+    uniform_buffer: &Buffer,
+    lights_buffer: &Buffer,
+    transforms_buffer: &Buffer,
+) -> BindGroup {
+    device.create_bind_group(&BindGroupDescriptor {
+        label: Some("preview bind group"),
+        layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: lights_buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 2,
+                resource: transforms_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+/// Half the size of the ground grid, it fades out towards its border
+const GRID_EXTENT: i32 = 50;
+
+/// Lines of the ground grid around the camera, rebuilt when the camera moves to another cell
+struct Grid {
+    buffer: Buffer,
+    vertex_count: usize,
+    center: Option<(i32, i32)>,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct LineVertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+impl Grid {
+    const COLOR: [f32; 3] = [0.45; 3];
+    const AXIS_COLORS: [[f32; 3]; 3] = [[0.86, 0.2, 0.2], [0.24, 0.75, 0.24], [0.24, 0.43, 0.9]];
+
+    /// Grid lines at y = 0 every unit, with the x and z axes on it and the y axis from the origin
+    fn vertices((x, z): (i32, i32)) -> Vec<LineVertex> {
+        let line = |from: [f32; 3], to: [f32; 3], color: [f32; 3]| {
+            [
+                LineVertex {
+                    position: from,
+                    color,
+                },
+                LineVertex {
+                    position: to,
+                    color,
+                },
+            ]
+        };
+        let (min_x, max_x) = ((x - GRID_EXTENT) as f32, (x + GRID_EXTENT) as f32);
+        let (min_z, max_z) = ((z - GRID_EXTENT) as f32, (z + GRID_EXTENT) as f32);
+
+        let lines_x = (z - GRID_EXTENT..=z + GRID_EXTENT).map(|i| {
+            let color = if i == 0 {
+                Self::AXIS_COLORS[0]
+            } else {
+                Self::COLOR
+            };
+            line([min_x, 0.0, i as f32], [max_x, 0.0, i as f32], color)
+        });
+        let lines_z = (x - GRID_EXTENT..=x + GRID_EXTENT).map(|i| {
+            let color = if i == 0 {
+                Self::AXIS_COLORS[2]
+            } else {
+                Self::COLOR
+            };
+            line([i as f32, 0.0, min_z], [i as f32, 0.0, max_z], color)
+        });
+        let axis_y = line(
+            [0.0; 3],
+            [0.0, GRID_EXTENT as f32, 0.0],
+            Self::AXIS_COLORS[1],
+        );
 
-    pub const fn new(scene: Arc<RwLock<Option<Scene>>>) -> Self {
-        Self { scene }
+        lines_x
+            .chain(lines_z)
+            .chain(std::iter::once(axis_y))
+            .flatten()
+            .collect()
     }
 }
 
-struct VertexCount(usize);
+/// Edges of the bounding boxes, rebuilt every frame as the objects move
+struct Bounds {
+    buffer: Buffer,
+    vertex_count: usize,
+}
+
+impl Bounds {
+    const COLOR: [f32; 3] = [0.6; 3];
+    /// Same as the tint of the selected object
+    const SELECTED_COLOR: [f32; 3] = [1.0, 0.6, 0.1];
+
+    /// Edges of the transformed bounding box of the selected object, or of all objects
+    fn vertices(scene: &Scene, selected: Option<usize>, all: bool) -> Vec<LineVertex> {
+        scene
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(i, o)| {
+                // the selected object is outlined even when hidden, to find it
+                ((all && o.visible_in_preview) || selected == Some(*i))
+                    && !o.mesh.triangles.is_empty()
+            })
+            .flat_map(|(i, o)| {
+                let color = if selected == Some(i) {
+                    Self::SELECTED_COLOR
+                } else {
+                    Self::COLOR
+                };
+                let (min, max) = (o.mesh.bounds.min, o.mesh.bounds.max);
+                let transform = o.transform();
+
+                // the bits of the index choose between the minimum and maximum of each axis
+                let corner = move |c: usize| {
+                    let point = Point3::new(
+                        if c & 1 == 0 { min.x } else { max.x },
+                        if c & 2 == 0 { min.y } else { max.y },
+                        if c & 4 == 0 { min.z } else { max.z },
+                    );
+                    LineVertex {
+                        position: (transform * point).into(),
+                        color,
+                    }
+                };
+
+                // an edge connects corners that differ in one bit
+                (0..8)
+                    .flat_map(|c| [1, 2, 4].map(move |bit| (c, bit)))
+                    .filter(|(c, bit)| c & bit == 0)
+                    .flat_map(move |(c, bit)| [corner(c), corner(c | bit)])
+            })
+            .collect()
+    }
+}
 
 #[repr(C, align(16))]
 #[derive(Debug, Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
 struct ShaderUniforms {
     view: [[f32; 4]; 4],
     lights_count: u32,
-    _pad: [u32; 3],
+    /// Index of the selected object, `u32::MAX` if there is none
+    selected: u32,
+    /// Number of lights with a shadow map
+    shadow_lights: u32,
+    _pad: u32,
     ambient_color: [f32; 3],
     ambient_intensity: f32,
+    camera_position: [f32; 3],
+    _pad2: f32,
+    /// Normal and negative distance to the origin of each section plane
+    section_planes: [[f32; 4]; SectionPlane::PREVIEW_MAX],
+    section_count: u32,
+    _pad3: [u32; 3],
+}
+
+/// View projection of a cube face of a light, padded to the uniform offset alignment
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowFace {
+    view: [[f32; 4]; 4],
+    _pad: [[f32; 4]; 12],
+}
+
+impl ShadowFace {
+    const NEAR: f32 = 0.05;
+    const FAR: f32 = 100.0;
+
+    /// Faces looking along +x, -x, +y, -y, +z and -z from the light,
+    /// the shader chooses the face by the largest component of the direction
+    fn cube(light: &Point3<f32>) -> [Self; 6] {
+        let projection = Perspective3::new(1.0, std::f32::consts::FRAC_PI_2, Self::NEAR, Self::FAR)
+            .to_homogeneous();
+
+        [
+            (Vector3::x(), Vector3::y()),
+            (-Vector3::x(), Vector3::y()),
+            (Vector3::y(), Vector3::z()),
+            (-Vector3::y(), Vector3::z()),
+            (Vector3::z(), Vector3::y()),
+            (-Vector3::z(), Vector3::y()),
+        ]
+        .map(|(direction, up)| Self {
+            view: (projection
+                * Isometry3::look_at_rh(light, &(light + direction), &up).to_homogeneous())
+            .into(),
+            ..Default::default()
+        })
+    }
 }
 
 #[repr(C, align(16))]
@@ -72,133 +578,301 @@ impl CallbackTrait for WgpuPainter {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         _screen_descriptor: &egui_wgpu::ScreenDescriptor,
-        _egui_encoder: &mut wgpu::CommandEncoder,
+        egui_encoder: &mut wgpu::CommandEncoder,
         callback_resources: &mut egui_wgpu::CallbackResources,
     ) -> Vec<wgpu::CommandBuffer> {
+        let _scope = profiler::scope("preview prepare");
+        let start = Instant::now();
         let Some(scene) = &*self.scene.read() else {
             return vec![];
         };
 
-        let vertex_count = callback_resources
-            .get::<VertexCount>()
-            .expect("Failed to get vertex count");
+        let resources = callback_resources
+            .get_mut::<Resources>()
+            .expect("Failed to get preview resources");
 
-        let vertices = scene
+        // unchanged objects keep their vertex and index count, the others are triangulated again
+        let old = std::mem::take(&mut resources.vertex_layout.objects);
+        let geometries = scene
             .objects
             .iter()
-            .map(|o| o.triangles.len())
-            .sum::<usize>()
-            * 3;
+            .enumerate()
+            .map(|(i, object)| {
+                let key = ObjectKey::new(object);
+                old.get(i).filter(|slot| slot.key == key).map_or_else(
+                    || {
+                        let (vertices, indices) = object_geometry(i, object);
+                        (
+                            key,
+                            vertices.len(),
+                            indices.len(),
+                            Some((vertices, indices)),
+                        )
+                    },
+                    |slot| (key, slot.vertex_count, slot.index_count, None),
+                )
+            })
+            .collect::<Vec<_>>();
 
-        // TODO: recreate the vertex buffer if the scene has changed
-        // this only compares the vertex count
-        if vertex_count.0 != vertices {
-            debug!("New vertex buffer from {} to {}", vertex_count.0, vertices);
+        let vertex_count = geometries.iter().map(|g| g.1).sum::<usize>();
+        let index_count = geometries.iter().map(|g| g.2).sum::<usize>();
 
-            let resources = callback_resources
-                .get_mut::<Resources>()
-                .expect("Failed to get preview resources");
+        // everything is uploaded again into new buffers
+        let grown = reserve(
+            device,
+            &mut resources.vertex_buffer,
+            (vertex_count * std::mem::size_of::<Vertex>()) as u64,
+            "preview vertex buffer",
+        ) | reserve(
+            device,
+            &mut resources.index_buffer,
+            (index_count * std::mem::size_of::<u32>()) as u64,
+            "preview index buffer",
+        );
+        if grown {
+            debug!("New geometry buffers for {vertex_count} vertices and {index_count} indices");
+        }
 
-            resources.vertex_buffer.destroy();
+        // only objects that changed or moved within the buffers are uploaded
+        let mut objects = Vec::with_capacity(scene.objects.len());
+        let (mut first_vertex, mut first_index) = (0, 0);
+        let mut uploaded = 0;
+        for (i, (object, (key, vertices, indices, geometry))) in
+            scene.objects.iter().zip(geometries).enumerate()
+        {
+            let slot = ObjectSlot {
+                key,
+                first_vertex,
+                vertex_count: vertices,
+                first_index,
+                index_count: indices,
+                lod_count: object.mesh.lod.as_ref().map_or(0, |lod| lod.len() * 3),
+            };
 
-            resources.vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
-                label: Some("preview vertex buffer"),
-                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
-                // convert vertices to array of bytes
-                // maybe use some conversion crate?
-                contents: scene
-                    .objects
-                    .iter()
-                    .enumerate()
-                    .flat_map(|(i, o)| o.triangles.iter().map(move |t| (i, o, t)))
-                    .map(|(i, o, t)| (i, t.material_index.and_then(|i| o.materials.get(i)), t))
-                    .flat_map(|(i, m, t)| {
-                        let color = m
-                            .as_ref()
-                            .and_then(|m| m.diffuse_color)
-                            .map_or([0.9; 3], std::convert::Into::into);
-                        [
-                            bytemuck::bytes_of(&[t.a.into(), t.a_normal.into(), color]),
-                            bytemuck::bytes_of(&(i as u32)),
-                            bytemuck::bytes_of(&[t.b.into(), t.b_normal.into(), color]),
-                            bytemuck::bytes_of(&(i as u32)),
-                            bytemuck::bytes_of(&[t.c.into(), t.c_normal.into(), color]),
-                            bytemuck::bytes_of(&(i as u32)),
-                        ]
-                        .into_iter()
-                        .flatten()
-                        .copied()
-                        .collect::<Vec<u8>>()
-                    })
-                    .collect::<Vec<u8>>()
-                    .as_slice(),
+            if (grown || geometry.is_some() || old.get(i) != Some(&slot)) && indices > 0 {
+                let (vertices, indices) = geometry.unwrap_or_else(|| object_geometry(i, object));
+                // the indices are relative to the first vertex of the object
+                let indices = indices
+                    .into_iter()
+                    .map(|index| index + first_vertex as u32)
+                    .collect::<Vec<_>>();
+
+                queue.write_buffer(
+                    &resources.vertex_buffer,
+                    (first_vertex * std::mem::size_of::<Vertex>()) as u64,
+                    bytemuck::cast_slice(&vertices),
+                );
+                queue.write_buffer(
+                    &resources.index_buffer,
+                    (first_index * std::mem::size_of::<u32>()) as u64,
+                    bytemuck::cast_slice(&indices),
+                );
+                uploaded += 1;
+            }
+
+            objects.push(slot);
+            first_vertex += vertices;
+            first_index += indices;
+        }
+        if uploaded > 0 {
+            debug!(
+                "Uploaded {} of {} objects to the geometry buffers",
+                uploaded,
+                scene.objects.len()
+            );
+        }
+        resources.vertex_layout = VertexLayout {
+            objects,
+            vertex_count,
+        };
+        resources.draws = resources.vertex_layout.visible(scene, self.use_lod);
+        resources.shadow_draws = resources.vertex_layout.all(scene);
+
+        // the grid follows the camera in steps of one cell
+        let center = (
+            scene.camera().position.x.round() as i32,
+            scene.camera().position.z.round() as i32,
+        );
+        let grid = callback_resources
+            .get_mut::<Grid>()
+            .expect("Failed to get preview grid");
+        if self.show_grid && grid.center != Some(center) {
+            let vertices = Grid::vertices(center);
+
+            grid.buffer.destroy();
+            grid.buffer = device.create_buffer_init(&BufferInitDescriptor {
+                label: Some("preview grid buffer"),
+                usage: BufferUsages::VERTEX,
+                contents: bytemuck::cast_slice(&vertices),
             });
+            grid.vertex_count = vertices.len();
+            grid.center = Some(center);
+        }
 
-            callback_resources.insert(VertexCount(vertices));
+        let vertices = Bounds::vertices(scene, self.selected, self.show_bounds);
+        let bounds = callback_resources
+            .get_mut::<Bounds>()
+            .expect("Failed to get preview bounds");
+        let size = std::mem::size_of_val(vertices.as_slice()) as u64;
+        if bounds.buffer.size() < size {
+            bounds.buffer.destroy();
+            bounds.buffer = device.create_buffer(&BufferDescriptor {
+                label: Some("preview bounds buffer"),
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                size,
+                mapped_at_creation: false,
+            });
+        }
+        if !vertices.is_empty() {
+            queue.write_buffer(&bounds.buffer, 0, bytemuck::cast_slice(&vertices));
         }
+        bounds.vertex_count = vertices.len();
 
         let resources = callback_resources
-            .get::<Resources>()
+            .get_mut::<Resources>()
             .expect("Failed to get preview resources");
 
+        let lights = scene
+            .active_lights()
+            .map(|l| ShaderLight {
+                position: l.position.into(),
+                color: l.color.into(),
+                intensity: l.intensity,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let shadow_lights = if self.show_shadows {
+            lights.len().min(Self::SHADOW_LIGHTS)
+        } else {
+            0
+        };
+
         queue.write_buffer(
             &resources.uniform_buffer,
             0,
             bytemuck::cast_slice(&[ShaderUniforms {
-                view: (Perspective3::new(
-                    scene.camera.resolution.0 as f32 / scene.camera.resolution.1 as f32,
-                    scene.camera.fov,
-                    0.1,
-                    1000.0,
-                )
-                .to_homogeneous()
-                    * Isometry3::look_at_rh(
-                        &scene.camera.position,
-                        &scene.camera.look_at,
-                        &scene.camera.up,
-                    )
-                    .to_homogeneous())
-                .into(),
-                lights_count: scene.lights.len() as u32,
+                view: view_projection(scene.camera()).into(),
+                lights_count: lights.len() as u32,
+                selected: self.selected.map_or(u32::MAX, |o| o as u32),
+                shadow_lights: shadow_lights as u32,
                 ambient_color: scene.settings.ambient_color.into(),
                 ambient_intensity: scene.settings.ambient_intensity,
+                camera_position: scene.camera().position.into(),
+                section_planes: std::array::from_fn(|i| {
+                    scene.settings.section_planes.get(i).map_or([0.0; 4], |p| {
+                        let [x, y, z] = p.normal.into();
+                        [x, y, z, -p.normal.dot(&p.position.coords)]
+                    })
+                }),
+                section_count: scene
+                    .settings
+                    .section_planes
+                    .len()
+                    .min(SectionPlane::PREVIEW_MAX) as u32,
                 ..Default::default()
             }]),
         );
 
-        queue.write_buffer(
-            &resources.lights_buffer,
-            0,
-            scene
-                .lights
-                .iter()
-                .map(|l| ShaderLight {
-                    position: l.position.into(),
-                    color: l.color.into(),
-                    intensity: l.intensity,
-                    ..Default::default()
-                })
-                .chain(std::iter::repeat(ShaderLight::default()))
-                .take(Self::MAX_LIGHTS)
-                .flat_map(|x| bytemuck::bytes_of(&x).to_vec())
-                .collect::<Vec<u8>>()
-                .as_slice(),
+        let transforms = scene
+            .objects
+            .iter()
+            .map(|o| o.transform().to_homogeneous().into())
+            .collect::<Vec<[[f32; 4]; 4]>>();
+
+        // storage buffers can't be empty, they keep room for at least one element
+        let grown = reserve(
+            device,
+            &mut resources.lights_buffer,
+            std::mem::size_of_val(lights.as_slice()).max(std::mem::size_of::<ShaderLight>()) as u64,
+            "preview lights buffer",
+        ) | reserve(
+            device,
+            &mut resources.transforms_buffer,
+            std::mem::size_of_val(transforms.as_slice()).max(std::mem::size_of::<[[f32; 4]; 4]>())
+                as u64,
+            "preview transforms buffer",
         );
+        if grown {
+            debug!(
+                "New storage buffers for {} lights and {} objects",
+                lights.len(),
+                transforms.len()
+            );
+            resources.bind_group = create_bind_group(
+                device,
+                &resources.bind_group_layout,
+                &resources.uniform_buffer,
+                &resources.lights_buffer,
+                &resources.transforms_buffer,
+            );
+        }
 
+        queue.write_buffer(&resources.lights_buffer, 0, bytemuck::cast_slice(&lights));
         queue.write_buffer(
             &resources.transforms_buffer,
             0,
-            scene
-                .objects
-                .iter()
-                .map(|o| o.transform().to_homogeneous())
-                .chain(std::iter::repeat(Isometry3::identity().to_homogeneous()))
-                .take(Self::MAX_OBJECTS)
-                .flat_map(|m| bytemuck::cast_slice(m.as_slice()).to_vec())
-                .collect::<Vec<u8>>()
-                .as_slice(),
+            bytemuck::cast_slice(&transforms),
         );
 
+        if shadow_lights > 0 {
+            let faces = scene
+                .active_lights()
+                .take(shadow_lights)
+                .flat_map(|l| ShadowFace::cube(&l.position))
+                .collect::<Vec<_>>();
+            queue.write_buffer(&resources.shadow_buffer, 0, bytemuck::cast_slice(&faces));
+        }
+
+        // the buffer writes above are applied before the encoder is submitted
+        for (layer, view) in resources
+            .shadow_views
+            .iter()
+            .enumerate()
+            .take(shadow_lights * 6)
+        {
+            let mut render_pass = egui_encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("preview shadow pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            if !resources.shadow_draws.is_empty() {
+                render_pass.set_pipeline(&resources.shadow_pipeline);
+                render_pass.set_bind_group(0, &resources.bind_group, &[]);
+                render_pass.set_bind_group(
+                    1,
+                    &resources.shadow_face_bind_group,
+                    &[(layer * std::mem::size_of::<ShadowFace>()) as u32],
+                );
+                render_pass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(resources.index_buffer.slice(..), IndexFormat::Uint32);
+                for range in &resources.shadow_draws {
+                    render_pass.draw_indexed(range.clone(), 0, 0..1);
+                }
+            }
+        }
+
+        *self.draw_stats.lock() = DrawStats {
+            time: start.elapsed(),
+            vertices: resources.vertex_layout.vertex_count,
+            triangles: resources
+                .draws
+                .iter()
+                .map(ExactSizeIterator::len)
+                .sum::<usize>()
+                / 3,
+        };
+
         vec![]
     }
 
@@ -208,22 +882,115 @@ impl CallbackTrait for WgpuPainter {
         render_pass: &mut wgpu::RenderPass<'static>,
         callback_resources: &'a egui_wgpu::CallbackResources,
     ) {
+        let _scope = profiler::scope("preview paint");
         let resources = callback_resources
             .get::<Resources>()
             .expect("Failed to get preview resources");
 
-        let vertex_count = callback_resources
-            .get::<VertexCount>()
-            .expect("Failed to get vertex count")
-            .0;
-
         render_pass.set_pipeline(&resources.pipeline);
         render_pass.set_bind_group(0, &resources.bind_group, &[]);
-        render_pass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
-        render_pass.draw(0..vertex_count as u32, 0..1);
+        render_pass.set_bind_group(1, &resources.shadow_bind_group, &[]);
+        if !resources.draws.is_empty() {
+            render_pass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(resources.index_buffer.slice(..), IndexFormat::Uint32);
+            for range in &resources.draws {
+                render_pass.draw_indexed(range.clone(), 0, 0..1);
+            }
+        }
+
+        if self.show_grid {
+            let grid = callback_resources
+                .get::<Grid>()
+                .expect("Failed to get preview grid");
+
+            render_pass.set_pipeline(&resources.line_pipeline);
+            render_pass.set_vertex_buffer(0, grid.buffer.slice(..));
+            render_pass.draw(0..grid.vertex_count as u32, 0..1);
+        }
+
+        let bounds = callback_resources
+            .get::<Bounds>()
+            .expect("Failed to get preview bounds");
+        if bounds.vertex_count > 0 {
+            render_pass.set_pipeline(&resources.bounds_pipeline);
+            render_pass.set_vertex_buffer(0, bounds.buffer.slice(..));
+            render_pass.draw(0..bounds.vertex_count as u32, 0..1);
+        }
     }
 }
 
+/// Projection from world space into clip space of the preview
+pub fn view_projection(camera: &Camera) -> Matrix4<f32> {
+    Perspective3::new(camera.aspect_ratio(), camera.fov, camera.near, camera.far).to_homogeneous()
+        * Isometry3::look_at_rh(&camera.position, &camera.look_at, &camera.up).to_homogeneous()
+}
+
+/// Pipeline drawing line lists of `LineVertex`, with the fragment shader of the entry point
+fn create_line_pipeline(
+    render_state: &egui_wgpu::RenderState,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    fragment_entry: &str,
+    label: &str,
+) -> RenderPipeline {
+    render_state
+        .device
+        .create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(layout),
+            vertex: VertexState {
+                module: shader,
+                entry_point: Some("vs_line"),
+                buffers: &[VertexBufferLayout {
+                    // 3x f32 for position, 3x f32 for color
+                    array_stride: std::mem::size_of::<LineVertex>() as u64,
+                    step_mode: VertexStepMode::Vertex,
+                    attributes: &[
+                        // position
+                        VertexAttribute {
+                            format: VertexFormat::Float32x3,
+                            offset: 0,
+                            shader_location: 0,
+                        },
+                        // color
+                        VertexAttribute {
+                            format: VertexFormat::Float32x3,
+                            offset: std::mem::size_of::<f32>() as u64 * 3,
+                            shader_location: 1,
+                        },
+                    ],
+                }],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(FragmentState {
+                module: shader,
+                entry_point: Some(fragment_entry),
+                targets: &[Some(ColorTargetState {
+                    format: render_state.target_format,
+                    // the grid fades out with the distance to the camera
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::LineList,
+                ..PrimitiveState::default()
+            },
+            // hidden behind objects, but doesn't hide anything itself
+            depth_stencil: Some(DepthStencilState {
+                format: TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Less,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+}
+
 // setup the wgpu pipeline
 #[allow(clippy::too_many_lines)]
 pub fn init_wgpu(render_state: &egui_wgpu::RenderState) {
@@ -234,6 +1001,11 @@ pub fn init_wgpu(render_state: &egui_wgpu::RenderState) {
         source: ShaderSource::Wgsl(Cow::from(include_str!("shader.wgsl"))),
     });
 
+    let shadow_shader = device.create_shader_module(ShaderModuleDescriptor {
+        label: Some("preview shadow shader"),
+        source: ShaderSource::Wgsl(Cow::from(include_str!("shadow.wgsl"))),
+    });
+
     let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
         label: Some("preview bind group layout"),
         entries: &[
@@ -270,9 +1042,62 @@ pub fn init_wgpu(render_state: &egui_wgpu::RenderState) {
         ],
     });
 
+    let shadow_bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("preview shadow bind group layout"),
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Texture {
+                    sample_type: TextureSampleType::Depth,
+                    view_dimension: TextureViewDimension::D2Array,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Sampler(SamplerBindingType::Comparison),
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 2,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    });
+
+    let shadow_face_bind_group_layout =
+        device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("preview shadow face bind group layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
     let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
         label: Some("preview pipeline layout"),
-        bind_group_layouts: &[&bind_group_layout],
+        bind_group_layouts: &[&bind_group_layout, &shadow_bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let shadow_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some("preview shadow pipeline layout"),
+        bind_group_layouts: &[&bind_group_layout, &shadow_face_bind_group_layout],
         push_constant_ranges: &[],
     });
 
@@ -284,7 +1109,7 @@ pub fn init_wgpu(render_state: &egui_wgpu::RenderState) {
             entry_point: Some("vs_main"),
             buffers: &[VertexBufferLayout {
                 // 3x f32 for position, 3x f32 for normal, 3x f32 for color, 1x u32 for transform index
-                array_stride: std::mem::size_of::<f32>() as u64 * (3 + 3 + 3 + 1),
+                array_stride: std::mem::size_of::<Vertex>() as u64,
                 step_mode: VertexStepMode::Vertex,
                 attributes: &[
                     // position
@@ -346,6 +1171,71 @@ pub fn init_wgpu(render_state: &egui_wgpu::RenderState) {
         cache: None,
     });
 
+    let shadow_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("preview shadow pipeline"),
+        layout: Some(&shadow_pipeline_layout),
+        vertex: VertexState {
+            module: &shadow_shader,
+            entry_point: Some("vs_shadow"),
+            buffers: &[VertexBufferLayout {
+                // same layout as the main pipeline, only position and transform index are used
+                array_stride: std::mem::size_of::<Vertex>() as u64,
+                step_mode: VertexStepMode::Vertex,
+                attributes: &[
+                    // position
+                    VertexAttribute {
+                        format: VertexFormat::Float32x3,
+                        offset: 0,
+                        shader_location: 0,
+                    },
+                    // transform index
+                    VertexAttribute {
+                        format: VertexFormat::Uint32,
+                        offset: std::mem::size_of::<f32>() as u64 * 9,
+                        shader_location: 3,
+                    },
+                ],
+            }],
+            compilation_options: PipelineCompilationOptions::default(),
+        },
+        fragment: None,
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            ..PrimitiveState::default()
+        },
+        depth_stencil: Some(DepthStencilState {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            stencil: StencilState::default(),
+            // against shadow acne on surfaces facing the light
+            bias: DepthBiasState {
+                constant: 2,
+                slope_scale: 2.0,
+                clamp: 0.0,
+            },
+        }),
+        multisample: MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+
+    let line_pipeline = create_line_pipeline(
+        render_state,
+        &pipeline_layout,
+        &shader,
+        "fs_line",
+        "preview line pipeline",
+    );
+    let bounds_pipeline = create_line_pipeline(
+        render_state,
+        &pipeline_layout,
+        &shader,
+        "fs_bounds",
+        "preview bounds pipeline",
+    );
+
     let uniform_buffer = device.create_buffer(&BufferDescriptor {
         label: Some("preview uniform buffer"),
         usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
@@ -356,36 +1246,104 @@ pub fn init_wgpu(render_state: &egui_wgpu::RenderState) {
     let lights_buffer = device.create_buffer(&BufferDescriptor {
         label: Some("preview lights buffer"),
         usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
-        size: std::mem::size_of::<ShaderLight>() as u64 * WgpuPainter::MAX_LIGHTS as u64,
+        size: std::mem::size_of::<ShaderLight>() as u64 * WgpuPainter::LIGHTS_CAPACITY as u64,
         mapped_at_creation: false,
     });
 
     let transforms_buffer = device.create_buffer(&BufferDescriptor {
         label: Some("preview transforms buffer"),
         usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
-        size: std::mem::size_of::<[[f32; 4]; 4]>() as u64 * WgpuPainter::MAX_OBJECTS as u64,
+        size: std::mem::size_of::<[[f32; 4]; 4]>() as u64 * WgpuPainter::OBJECTS_CAPACITY as u64,
         mapped_at_creation: false,
     });
 
-    let bind_group = device.create_bind_group(&BindGroupDescriptor {
-        label: Some("preview bind group"),
-        layout: &bind_group_layout,
+    let shadow_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("preview shadow buffer"),
+        usage: BufferUsages::UNIFORM | BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        size: std::mem::size_of::<ShadowFace>() as u64 * WgpuPainter::SHADOW_LIGHTS as u64 * 6,
+        mapped_at_creation: false,
+    });
+
+    let shadow_texture = device.create_texture(&TextureDescriptor {
+        label: Some("preview shadow map"),
+        size: Extent3d {
+            width: WgpuPainter::SHADOW_SIZE,
+            height: WgpuPainter::SHADOW_SIZE,
+            depth_or_array_layers: WgpuPainter::SHADOW_LIGHTS as u32 * 6,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Depth32Float,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+
+    let shadow_views = (0..WgpuPainter::SHADOW_LIGHTS as u32 * 6)
+        .map(|layer| {
+            shadow_texture.create_view(&TextureViewDescriptor {
+                label: Some("preview shadow face"),
+                dimension: Some(TextureViewDimension::D2),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    let shadow_sampler = device.create_sampler(&SamplerDescriptor {
+        label: Some("preview shadow sampler"),
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        compare: Some(CompareFunction::LessEqual),
+        ..Default::default()
+    });
+
+    let shadow_bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("preview shadow bind group"),
+        layout: &shadow_bind_group_layout,
         entries: &[
             BindGroupEntry {
                 binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
+                resource: BindingResource::TextureView(&shadow_texture.create_view(
+                    &TextureViewDescriptor {
+                        dimension: Some(TextureViewDimension::D2Array),
+                        ..Default::default()
+                    },
+                )),
             },
             BindGroupEntry {
                 binding: 1,
-                resource: lights_buffer.as_entire_binding(),
+                resource: BindingResource::Sampler(&shadow_sampler),
             },
             BindGroupEntry {
                 binding: 2,
-                resource: transforms_buffer.as_entire_binding(),
+                resource: shadow_buffer.as_entire_binding(),
             },
         ],
     });
 
+    let shadow_face_bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("preview shadow face bind group"),
+        layout: &shadow_face_bind_group_layout,
+        entries: &[BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer: &shadow_buffer,
+                offset: 0,
+                size: BufferSize::new(std::mem::size_of::<ShadowFace>() as u64),
+            }),
+        }],
+    });
+
+    let bind_group = create_bind_group(
+        device,
+        &bind_group_layout,
+        &uniform_buffer,
+        &lights_buffer,
+        &transforms_buffer,
+    );
+
     let vertex_buffer = device.create_buffer(&BufferDescriptor {
         label: Some("preview vertex buffer"),
         usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
@@ -393,10 +1351,40 @@ pub fn init_wgpu(render_state: &egui_wgpu::RenderState) {
         mapped_at_creation: false,
     });
 
+    let index_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("preview index buffer"),
+        usage: BufferUsages::INDEX | BufferUsages::COPY_DST,
+        size: 0,
+        mapped_at_creation: false,
+    });
+
+    let grid = Grid {
+        buffer: device.create_buffer(&BufferDescriptor {
+            label: Some("preview grid buffer"),
+            usage: BufferUsages::VERTEX,
+            size: 0,
+            mapped_at_creation: false,
+        }),
+        vertex_count: 0,
+        center: None,
+    };
+
     let resources = Resources {
+        bind_group_layout,
         bind_group,
         pipeline,
+        line_pipeline,
+        bounds_pipeline,
+        shadow_pipeline,
+        shadow_views,
+        shadow_bind_group,
+        shadow_face_bind_group,
+        shadow_buffer,
         vertex_buffer,
+        index_buffer,
+        vertex_layout: VertexLayout::default(),
+        draws: Vec::new(),
+        shadow_draws: Vec::new(),
         uniform_buffer,
         lights_buffer,
         transforms_buffer,
@@ -412,5 +1400,21 @@ pub fn init_wgpu(render_state: &egui_wgpu::RenderState) {
         .renderer
         .write()
         .callback_resources
-        .insert(VertexCount(0));
+        .insert(grid);
+
+    let bounds = Bounds {
+        buffer: device.create_buffer(&BufferDescriptor {
+            label: Some("preview bounds buffer"),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            size: 0,
+            mapped_at_creation: false,
+        }),
+        vertex_count: 0,
+    };
+
+    render_state
+        .renderer
+        .write()
+        .callback_resources
+        .insert(bounds);
 }
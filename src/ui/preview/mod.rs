@@ -1,39 +1,92 @@
+use self::capture::Capture;
+use self::gamepad::{Gamepad, GamepadInput};
+use self::gizmo::{Gizmo, Projection};
 use self::gpu::WgpuPainter;
-use crate::scene::{Object, Scene, Skybox};
+use self::navigation::Navigation;
+use self::overlay::{overlay, Guides, Stats};
+use super::{
+    keymap::{Action, Keymap},
+    loader::ObjectLoader,
+    outliner::Selection,
+    snap::Snap,
+    toasts,
+};
 use egui::{
-    mutex::RwLock, pos2, Align, Align2, Color32, Context, CursorGrab, DroppedFile, Event, Frame,
-    Id, Key, LayerId, Layout, Order, Pos2, Rect, RichText, Sense, Shape, TextStyle, Ui, Vec2,
-    ViewportCommand,
+    mutex::RwLock, Align, Align2, Color32, Context, CursorGrab, DroppedFile, Event, Frame, Id,
+    LayerId, Layout, Order, Pos2, Rect, RichText, Sense, Shape, Slider, TextStyle, Ui, UiBuilder,
+    Vec2, ViewportCommand,
 };
 use egui_wgpu::Callback;
 use log::warn;
-use nalgebra::{OPoint, Scale3, Translation3, UnitQuaternion};
+use nalgebra::{OPoint, Point3};
 use rust_i18n::t;
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use trayracer_core::scene::{Camera, Scene, Skybox};
 
+mod capture;
+mod gamepad;
+mod gizmo;
 pub mod gpu;
+pub mod navigation;
+mod overlay;
 
-#[derive(Clone)]
+// modes and options of the navigation, not a state machine
+#[allow(clippy::struct_excessive_bools)]
 pub struct Preview {
     // whether the preview is in movement mode
-    active: bool,
-    speed: f32,
-    sensitivity: f32,
+    pub active: bool,
+    pub speed: f32,
+    pub sensitivity: f32,
+    /// Multiply the speed with the size of the scene
+    pub scale_speed: bool,
+    pub navigation: Navigation,
+    /// Hide the rest of the app so the preview fills the window
+    pub fullscreen: bool,
     gpu: WgpuPainter,
+    gizmo: Gizmo,
+    stats: Stats,
+    guides: Guides,
+    /// Show the performance overlay
+    show_stats: bool,
+    capture: Capture,
+    gamepad: Gamepad,
     dropped_files: Vec<DroppedFile>,
 }
 
 impl Preview {
-    pub const fn new(scene: Arc<RwLock<Option<Scene>>>) -> Self {
+    pub fn new(scene: Arc<RwLock<Option<Scene>>>) -> Self {
         Self {
             active: false,
             speed: 0.1,
             sensitivity: 0.001,
+            scale_speed: true,
+            navigation: Navigation::default(),
+            fullscreen: false,
             gpu: gpu::WgpuPainter::new(scene),
+            gizmo: Gizmo::new(),
+            stats: Stats::new(),
+            guides: Guides::new(),
+            show_stats: false,
+            capture: Capture::new(),
+            gamepad: Gamepad::new(),
             dropped_files: Vec::new(),
         }
     }
 
+    /// Distance moved per scrolled point, relative to the movement speed
+    const SCROLL_STEP: f32 = 0.2;
+    /// Mouse movement in points per frame a fully tilted stick looks around as fast as
+    const STICK_LOOK: f32 = 20.0;
+
+    /// Factor of the movement speed, from the size of the scene if enabled
+    fn speed_scale(&self, scene: &Scene) -> f32 {
+        if self.scale_speed {
+            speed_scale(scene)
+        } else {
+            1.0
+        }
+    }
+
     fn change_preview_movement(&mut self, ui: &Ui, response: &egui::Response, active: bool) {
         self.active = active;
 
@@ -50,14 +103,23 @@ impl Preview {
             .send_viewport_cmd(ViewportCommand::CursorVisible(!active));
     }
 
-    pub fn show(&mut self, ui: &mut Ui, scene: &mut Option<Scene>) {
+    #[allow(clippy::too_many_lines)]
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        scene: &mut Option<Scene>,
+        loader: &mut ObjectLoader,
+        selection: &mut Option<Selection>,
+        keymap: &Keymap,
+        snap: &mut Snap,
+    ) {
         Self::show_hover_overlay(ui.ctx(), scene.as_ref(), ui.available_rect_before_wrap());
         ui.ctx().input(|i| {
             if !i.raw.dropped_files.is_empty() {
                 //self.dropped_files = i.raw.dropped_files.clone();
                 self.dropped_files.clone_from(&i.raw.dropped_files);
                 if let Some(path) = self.dropped_files.first().and_then(|p| p.path.as_ref()) {
-                    Self::handle_file(path, scene);
+                    Self::handle_file(ui.ctx(), path, scene, loader);
                 }
                 self.dropped_files.clear();
             }
@@ -74,8 +136,11 @@ impl Preview {
             return;
         };
         ui.vertical(|ui| {
-            let available_size = ui.available_size();
-            let aspect_ratio = scene.camera.resolution.0 as f32 / scene.camera.resolution.1 as f32;
+            // the margin of the frame is left out so the preview has exactly the aspect ratio
+            // of the render, the remaining space is letterboxed
+            let available_size = ui.available_size() - Vec2::splat(20.0);
+            // stereo renders are previewed with the view of one eye
+            let aspect_ratio = scene.camera().aspect_ratio();
 
             // compute largest rectangle with aspect_ratio that fits in available_size
             let (width, height) = if available_size.x / available_size.y > aspect_ratio {
@@ -98,50 +163,148 @@ impl Preview {
                 .show(ui, |ui| {
                     let (response, painter) = ui.allocate_painter(
                         Vec2 {
-                            x: width - 20.0,
-                            y: height - 20.0,
+                            x: width,
+                            y: height,
                         },
                         Sense::click_and_drag(),
                     );
+                    let selected = match *selection {
+                        Some(Selection::Object(o)) => Some(o),
+                        _ => None,
+                    };
+
+                    self.gpu.selected = selected;
                     painter.add(Shape::Callback(Callback::new_paint_callback(
                         response.rect,
                         self.gpu.clone(),
                     )));
 
-                    if response.hover_pos().is_some() && !self.active {
+                    // a screenshot of the preview leaves out everything drawn over it
+                    let overlays = !self.capture.hides_overlays();
+                    self.capture.update(ui, response.rect);
+
+                    if overlays {
+                        self.guides
+                            .show(&painter, response.rect, scene.camera().resolution);
+                    }
+
+                    if !self.active && overlays {
+                        self.view_toolbar(ui, response.rect);
+                    }
+
+                    let on_gizmo = match selected.filter(|_| !self.active && overlays) {
+                        Some(selected) => {
+                            self.gizmo_toolbar(ui, response.rect, snap);
+                            self.gizmo
+                                .show(ui, &response, &painter, scene, selected, snap)
+                        }
+                        None => false,
+                    };
+
+                    if response.hover_pos().is_some() && !self.active && !on_gizmo && overlays {
                         egui::show_tooltip(
                             ui.ctx(),
                             ui.layer_id(),
                             egui::Id::new("preview_tooltip"),
                             |ui| {
-                                ui.label(t!("change_camera_pos"));
+                                ui.label(if self.navigation.hold_to_fly {
+                                    t!("change_camera_pos_hold")
+                                } else {
+                                    t!("change_camera_pos")
+                                });
                             },
                         );
                     }
 
-                    if response.clicked() {
-                        self.change_preview_movement(ui, &response, true);
+                    if response.clicked() && !self.active && !on_gizmo {
+                        // select the object under the pointer, or look around when there is none
+                        match response.interact_pointer_pos().and_then(|pos| {
+                            gizmo::pick(scene, &Projection::new(scene, response.rect), pos)
+                        }) {
+                            Some(o) => *selection = Some(Selection::Object(o)),
+                            None if !self.navigation.hold_to_fly => {
+                                self.change_preview_movement(ui, &response, true);
+                            }
+                            None => {}
+                        }
+                    }
+
+                    if self.navigation.hold_to_fly {
+                        let (pressed, down) = ui
+                            .input(|i| (i.pointer.secondary_pressed(), i.pointer.secondary_down()));
+                        if !self.active && pressed && response.hovered() {
+                            self.change_preview_movement(ui, &response, true);
+                        } else if self.active && !down {
+                            self.change_preview_movement(ui, &response, false);
+                        }
+                    }
+
+                    if self.navigation.scroll_moves && !self.active && response.hovered() {
+                        let scroll = ui.input(|i| i.raw_scroll_delta.y);
+                        if scroll != 0.0 {
+                            let speed = self.speed * self.speed_scale(scene);
+                            dolly(scene.camera_mut(), scroll * Self::SCROLL_STEP * speed);
+                        }
+                    }
+
+                    if let Some(input) = self.gamepad.input() {
+                        let speed_scale = self.speed_scale(scene);
+                        self.fly(scene.camera_mut(), input, speed_scale);
+                        ui.ctx().request_repaint();
+                    } else if self.gamepad.connected() {
+                        // gamepad events don't wake up egui
+                        ui.ctx().request_repaint_after(Duration::from_millis(50));
+                    }
+
+                    if self.show_stats && overlays {
+                        self.stats
+                            .show(ui, response.rect, scene, self.gpu.draw_stats());
                     }
 
                     if self.active {
-                        // TODO: do not use debug_text
-                        painter.debug_text(
-                            pos2(response.rect.left(), response.rect.top()),
-                            Align2::LEFT_TOP,
-                            Color32::WHITE,
-                            format!(
-                                "{}\n{} {:.2}\n{} {:.4}\n{}\n{}",
-                                t!("wasd"),
-                                t!("qe"),
-                                self.speed,
-                                t!("yc"),
-                                self.sensitivity,
-                                t!("f"),
-                                t!("esc")
-                            ),
-                        );
+                        overlay(ui, response.rect, Layout::top_down(Align::Min), |ui| {
+                            let ctx = ui.ctx().clone();
+                            let keys = |actions: &[Action]| {
+                                actions
+                                    .iter()
+                                    .map(|&a| keymap.label(&ctx, a))
+                                    .collect::<Vec<_>>()
+                                    .join("/")
+                            };
+
+                            ui.label(t!(
+                                "wasd",
+                                keys = keys(&[
+                                    Action::MoveForward,
+                                    Action::MoveLeft,
+                                    Action::MoveBackward,
+                                    Action::MoveRight,
+                                    Action::MoveUp,
+                                    Action::MoveDown
+                                ])
+                            ));
+                            ui.label(format!(
+                                "{} {:.2}",
+                                t!(
+                                    "qe",
+                                    keys = keys(&[Action::SlowerMovement, Action::FasterMovement])
+                                ),
+                                self.speed
+                            ));
+                            ui.label(format!(
+                                "{} {:.4}",
+                                t!(
+                                    "yc",
+                                    keys = keys(&[Action::LessSensitive, Action::MoreSensitive])
+                                ),
+                                self.sensitivity
+                            ));
+                            ui.label(t!("f", keys = keys(&[Action::LookAtOrigin])));
+                            ui.label(t!("esc", keys = keys(&[Action::ExitCamera])));
+                        });
 
-                        self.move_camera(ui, &response, scene);
+                        let speed_scale = self.speed_scale(scene);
+                        self.move_camera(ui, &response, scene.camera_mut(), keymap, speed_scale);
                     }
 
                     if !response.has_focus() && self.active {
@@ -152,29 +315,130 @@ impl Preview {
         });
     }
 
-    fn handle_file(path: &PathBuf, scene: &mut Option<Scene>) {
+    /// Toggles for the overlays of the preview
+    fn view_toolbar(&mut self, ui: &mut Ui, rect: Rect) {
+        let mut toolbar = ui.new_child(
+            UiBuilder::new()
+                .max_rect(rect.shrink(5.0))
+                .layout(Layout::left_to_right(Align::Min)),
+        );
+
+        toolbar
+            .toggle_value(&mut self.gpu.show_grid, t!("grid"))
+            .on_hover_text(t!("grid_hint"));
+        toolbar
+            .toggle_value(&mut self.gpu.show_bounds, t!("bounds"))
+            .on_hover_text(t!("bounds_hint"));
+        toolbar
+            .toggle_value(&mut self.gpu.show_shadows, t!("shadows"))
+            .on_hover_text(t!("shadows_hint"));
+        toolbar
+            .toggle_value(&mut self.gpu.use_lod, t!("lod"))
+            .on_hover_text(t!("lod_hint"));
+        toolbar
+            .toggle_value(&mut self.show_stats, t!("performance"))
+            .on_hover_text(t!("performance_hint"));
+        toolbar.menu_button(t!("guides"), |ui| {
+            ui.checkbox(&mut self.guides.frame, t!("camera_frame"))
+                .on_hover_text(t!("camera_frame_hint"));
+            ui.checkbox(&mut self.guides.thirds, t!("rule_of_thirds"));
+            ui.checkbox(&mut self.guides.center, t!("center_guide"));
+        });
+        toolbar
+            .toggle_value(&mut self.fullscreen, "⛶")
+            .on_hover_text(t!("fullscreen_preview_hint"));
+        toolbar
+            .menu_button("🎥", |ui| self.navigation(ui))
+            .response
+            .on_hover_text(t!("navigation_hint"));
+        if toolbar
+            .button("📷")
+            .on_hover_text(t!("capture_preview_hint"))
+            .clicked()
+        {
+            self.capture.request();
+        }
+    }
+
+    /// Move and look around with the sticks of a gamepad, the triggers change the speed
+    fn fly(&mut self, camera: &mut Camera, input: GamepadInput, speed_scale: f32) {
+        self.speed = input.speed.mul_add(0.005, self.speed).clamp(0.0, 1.0);
+
+        let direction = (camera.look_at - camera.position).normalize();
+        let right = direction.cross(&camera.up).normalize();
+        let up = right.cross(&direction).normalize();
+
+        let look_y = if self.navigation.invert_y {
+            -input.look.y
+        } else {
+            input.look.y
+        };
+        let new_point = camera.position
+            + direction
+            + (right * input.look.x + up * look_y) * Self::STICK_LOOK * self.sensitivity;
+        camera.look_at = camera.position + (new_point - camera.position).normalize();
+
+        let movement =
+            (direction * input.movement.y + right * input.movement.x) * self.speed * speed_scale;
+        camera.position += movement;
+        camera.look_at += movement;
+    }
+
+    /// Movement speed and look sensitivity of the camera mode
+    fn navigation(&mut self, ui: &mut Ui) {
+        ui.add(
+            Slider::new(&mut self.speed, 0.001..=1.0)
+                .logarithmic(true)
+                .text(t!("movement_speed")),
+        );
+        ui.add(
+            Slider::new(&mut self.sensitivity, 0.0001..=0.5)
+                .logarithmic(true)
+                .text(t!("look_sensitivity")),
+        );
+        ui.checkbox(&mut self.scale_speed, t!("scale_speed"))
+            .on_hover_text(t!("scale_speed_hint"));
+        ui.separator();
+        self.navigation.show(ui);
+    }
+
+    /// Buttons to switch between moving, rotating and scaling the selected object
+    fn gizmo_toolbar(&mut self, ui: &mut Ui, rect: Rect, snap: &mut Snap) {
+        let mut toolbar = ui.new_child(
+            UiBuilder::new()
+                .max_rect(rect.shrink(5.0))
+                .layout(Layout::right_to_left(Align::Min)),
+        );
+
+        for mode in gizmo::GizmoMode::ALL.into_iter().rev() {
+            toolbar.selectable_value(&mut self.gizmo.mode, mode, mode.name());
+        }
+
+        toolbar.separator();
+        snap.toolbar(&mut toolbar);
+    }
+
+    fn handle_file(
+        ctx: &Context,
+        path: &PathBuf,
+        scene: &mut Option<Scene>,
+        loader: &mut ObjectLoader,
+    ) {
         match path.extension().and_then(|ext| ext.to_str()) {
-            Some("yaml" | "yml") => {
-                Scene::load(path).map_or_else(
+            Some("yaml" | "yml" | "json" | "zip") => {
+                Scene::open(path).map_or_else(
                     |e| {
-                        warn!("Failed to load scene: {}", e);
+                        warn!("Failed to load scene: {e}");
+                        toasts::open_failed(ctx, &e);
                     },
                     |s| {
                         scene.replace(s);
                     },
                 );
             }
-            Some("obj") => {
-                if let Some(scene) = scene.as_mut() {
-                    match Object::from_obj(
-                        path,
-                        Translation3::identity(),
-                        UnitQuaternion::identity(),
-                        Scale3::identity(),
-                    ) {
-                        Ok(object) => scene.objects.push(object),
-                        Err(e) => warn!("Failed to load object: {}", e),
-                    }
+            Some("obj" | "stl") => {
+                if let Some(scene) = scene.as_ref() {
+                    loader.load(ctx, scene, path);
                 }
             }
             _ => {}
@@ -197,8 +461,8 @@ impl Preview {
                     rect.center(),
                     Align2::CENTER_CENTER,
                     match extension {
-                        Some("yaml" | "yml") => t!("hov_yaml"),
-                        Some("obj") if scene.is_some() => t!("hov_obj"),
+                        Some("yaml" | "yml" | "json" | "zip") => t!("hov_yaml"),
+                        Some("obj" | "stl") if scene.is_some() => t!("hov_obj"),
                         _ => t!("hov_unknown"),
                     },
                     TextStyle::Heading.resolve(&ctx.style()),
@@ -208,8 +472,15 @@ impl Preview {
         }
     }
 
-    fn move_camera(&mut self, ui: &Ui, response: &egui::Response, scene: &mut Scene) {
-        if ui.input(|i| i.key_pressed(egui::Key::Escape)) && self.active {
+    fn move_camera(
+        &mut self,
+        ui: &Ui,
+        response: &egui::Response,
+        camera: &mut Camera,
+        keymap: &Keymap,
+        speed_scale: f32,
+    ) {
+        if ui.input(|i| keymap.pressed(i, Action::ExitCamera)) && self.active {
             // exit movement mode using ESC
             self.change_preview_movement(ui, response, false);
         }
@@ -225,8 +496,8 @@ impl Preview {
                 .fold(Pos2::ZERO, |acc, x| acc + x)
         });
 
-        let direction = (scene.camera.look_at - scene.camera.position).normalize();
-        let right = direction.cross(&scene.camera.up).normalize();
+        let direction = (camera.look_at - camera.position).normalize();
+        let right = direction.cross(&camera.up).normalize();
         let up = right.cross(&direction).normalize();
 
         // move mouse to center
@@ -235,64 +506,120 @@ impl Preview {
                 response.rect.center(),
             ));
 
+        let delta_y = if self.navigation.invert_y {
+            -delta.y
+        } else {
+            delta.y
+        };
+
         // move look_at point in a sphere around camera with constant distance 1 using mouse
-        let new_point = scene.camera.position + direction - (right * delta.x * self.sensitivity)
-            + (up * delta.y * self.sensitivity);
-        scene.camera.look_at =
-            scene.camera.position + (new_point - scene.camera.position).normalize();
+        let new_point = camera.position + direction - (right * delta.x * self.sensitivity)
+            + (up * delta_y * self.sensitivity);
+        camera.look_at = camera.position + (new_point - camera.position).normalize();
 
-        scene.camera.fov = ui
-            .input(|i| i.raw_scroll_delta.y)
-            .mul_add(-0.001, scene.camera.fov)
-            .clamp(0.0_f32.to_radians(), 180.0_f32.to_radians());
+        let scroll = ui.input(|i| i.raw_scroll_delta.y);
+        if self.navigation.scroll_moves {
+            dolly(
+                camera,
+                scroll * Self::SCROLL_STEP * self.speed * speed_scale,
+            );
+        } else {
+            camera.fov = scroll
+                .mul_add(-0.001, camera.fov)
+                .clamp(0.0_f32.to_radians(), 180.0_f32.to_radians());
+        }
 
         // compute movement
         ui.input(|i| {
-            i.key_down(Key::F).then(|| {
-                scene.camera.look_at = OPoint::origin();
+            keymap.down(i, Action::LookAtOrigin).then(|| {
+                camera.look_at = OPoint::origin();
             });
 
-            i.key_pressed(Key::Y).then(|| {
+            keymap.pressed(i, Action::LessSensitive).then(|| {
                 self.sensitivity = (self.sensitivity - 0.0001_f32).max(0.0);
                 warn!("Look sensitivity: {}", self.sensitivity);
             });
-            i.key_pressed(Key::C).then(|| {
+            keymap.pressed(i, Action::MoreSensitive).then(|| {
                 self.sensitivity = (self.sensitivity + 0.0001_f32).min(0.5);
                 warn!("Look sensitivity: {}", self.sensitivity);
             });
-            i.key_down(Key::Q).then(|| {
+            keymap.down(i, Action::SlowerMovement).then(|| {
                 self.speed = (self.speed - 0.005_f32).max(0.0);
                 warn!("Movement speed: {}", self.speed);
             });
-            i.key_down(Key::E).then(|| {
+            keymap.down(i, Action::FasterMovement).then(|| {
                 self.speed = (self.speed + 0.005_f32).min(1.0);
                 warn!("Movement speed: {}", self.speed);
             });
 
-            i.key_down(Key::W).then(|| {
-                scene.camera.position += direction * self.speed;
-                scene.camera.look_at += direction * self.speed;
+            let speed = self.speed * speed_scale;
+            keymap.down(i, Action::MoveForward).then(|| {
+                camera.position += direction * speed;
+                camera.look_at += direction * speed;
             });
-            i.key_down(Key::S).then(|| {
-                scene.camera.position -= direction * self.speed;
-                scene.camera.look_at -= direction * self.speed;
+            keymap.down(i, Action::MoveBackward).then(|| {
+                camera.position -= direction * speed;
+                camera.look_at -= direction * speed;
             });
-            i.key_down(Key::A).then(|| {
-                scene.camera.position -= right * self.speed;
-                scene.camera.look_at -= right * self.speed;
+            keymap.down(i, Action::MoveLeft).then(|| {
+                camera.position -= right * speed;
+                camera.look_at -= right * speed;
             });
-            i.key_down(Key::D).then(|| {
-                scene.camera.position += right * self.speed;
-                scene.camera.look_at += right * self.speed;
+            keymap.down(i, Action::MoveRight).then(|| {
+                camera.position += right * speed;
+                camera.look_at += right * speed;
             });
-            i.key_down(Key::Space).then(|| {
-                scene.camera.position += scene.camera.up * self.speed;
-                scene.camera.look_at += scene.camera.up * self.speed;
+            keymap.down(i, Action::MoveUp).then(|| {
+                camera.position += camera.up * speed;
+                camera.look_at += camera.up * speed;
             });
-            i.modifiers.shift.then(|| {
-                scene.camera.position -= scene.camera.up * self.speed;
-                scene.camera.look_at -= scene.camera.up * self.speed;
+            keymap.down(i, Action::MoveDown).then(|| {
+                camera.position -= camera.up * speed;
+                camera.look_at -= camera.up * speed;
             });
         });
     }
 }
+
+/// Move the camera along its view direction
+fn dolly(camera: &mut Camera, distance: f32) {
+    let direction = (camera.look_at - camera.position).normalize();
+    camera.position += direction * distance;
+    camera.look_at += direction * distance;
+}
+
+/// Factor of the movement speed, 1 for scenes about 10 units across
+fn speed_scale(scene: &Scene) -> f32 {
+    const REFERENCE_SIZE: f32 = 10.0;
+
+    let (min, max) = scene
+        .objects
+        .iter()
+        .filter(|o| o.visible_in_preview && !o.mesh.triangles.is_empty())
+        .flat_map(|o| {
+            let (min, max) = (o.mesh.bounds.min, o.mesh.bounds.max);
+            let transform = o.transform();
+            (0..8).map(move |c| {
+                transform
+                    * Point3::new(
+                        if c & 1 == 0 { min.x } else { max.x },
+                        if c & 2 == 0 { min.y } else { max.y },
+                        if c & 4 == 0 { min.z } else { max.z },
+                    )
+            })
+        })
+        .fold(
+            (
+                Point3::from([f32::INFINITY; 3]),
+                Point3::from([f32::NEG_INFINITY; 3]),
+            ),
+            |(min, max), p| (min.inf(&p), max.sup(&p)),
+        );
+
+    let size = (max - min).norm();
+    if size.is_finite() {
+        (size / REFERENCE_SIZE).max(0.01)
+    } else {
+        1.0
+    }
+}
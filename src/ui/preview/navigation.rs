@@ -0,0 +1,25 @@
+use egui::Ui;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+
+/// How the mouse controls the camera of the preview
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Navigation {
+    /// Moving the mouse up looks down
+    pub invert_y: bool,
+    /// Fly while the right mouse button is held, instead of from a click until Esc
+    pub hold_to_fly: bool,
+    /// Scrolling moves the camera forward and backward instead of changing the field of view
+    pub scroll_moves: bool,
+}
+
+impl Navigation {
+    pub fn show(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.invert_y, t!("invert_y"));
+        ui.checkbox(&mut self.hold_to_fly, t!("hold_to_fly"))
+            .on_hover_text(t!("hold_to_fly_hint"));
+        ui.checkbox(&mut self.scroll_moves, t!("scroll_moves"))
+            .on_hover_text(t!("scroll_moves_hint"));
+    }
+}
@@ -0,0 +1,125 @@
+use super::gpu::DrawStats;
+use egui::{
+    pos2, vec2, Align, Align2, Color32, FontId, Frame, Layout, Painter, Rect, Stroke, Ui, UiBuilder,
+};
+use rust_i18n::t;
+use std::collections::VecDeque;
+use trayracer_core::scene::Scene;
+
+/// Frames the frame rate is averaged over
+const FRAMES: usize = 60;
+
+/// Frame rate, size of the scene and time to prepare the draw calls of the preview
+#[derive(Clone)]
+pub struct Stats {
+    frame_times: VecDeque<f32>,
+}
+
+impl Stats {
+    pub const fn new() -> Self {
+        Self {
+            frame_times: VecDeque::new(),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, rect: Rect, scene: &Scene, draw: DrawStats) {
+        self.frame_times.push_back(ui.input(|i| i.unstable_dt));
+        while self.frame_times.len() > FRAMES {
+            self.frame_times.pop_front();
+        }
+
+        let frame_time = self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32;
+        let fps = if frame_time > 0.0 {
+            1.0 / frame_time
+        } else {
+            0.0
+        };
+        let triangles = scene
+            .objects
+            .iter()
+            .map(|o| o.mesh.triangles.len())
+            .sum::<usize>();
+
+        overlay(ui, rect, Layout::bottom_up(Align::Min), |ui| {
+            ui.label(format!("{fps:.0} FPS ({:.1} ms)", frame_time * 1000.0));
+            ui.label(format!(
+                "{}: {} / {triangles}",
+                t!("triangles"),
+                draw.triangles
+            ));
+            ui.label(format!("{}: {}", t!("vertices"), draw.vertices));
+            ui.label(format!(
+                "{}: {:.2} ms",
+                t!("draw_time"),
+                draw.time.as_secs_f64() * 1000.0
+            ));
+        });
+
+        // egui only repaints on input, keep drawing to measure the actual frame rate
+        ui.ctx().request_repaint();
+    }
+}
+
+/// Composition guides drawn over the preview, which has the aspect ratio of the render
+#[derive(Clone)]
+pub struct Guides {
+    /// Outline and resolution of the rendered frame
+    pub frame: bool,
+    pub thirds: bool,
+    pub center: bool,
+}
+
+impl Guides {
+    pub const fn new() -> Self {
+        Self {
+            frame: true,
+            thirds: false,
+            center: false,
+        }
+    }
+
+    pub fn show(&self, painter: &Painter, rect: Rect, resolution: (u32, u32)) {
+        let stroke = Stroke::new(1.0, Color32::from_white_alpha(100));
+
+        if self.frame {
+            painter.rect_stroke(rect, 0.0, stroke);
+            painter.text(
+                rect.right_bottom() - vec2(5.0, 5.0),
+                Align2::RIGHT_BOTTOM,
+                format!("{}×{}", resolution.0, resolution.1),
+                FontId::monospace(12.0),
+                Color32::from_white_alpha(160),
+            );
+        }
+
+        if self.thirds {
+            for t in [1.0 / 3.0, 2.0 / 3.0] {
+                let x = rect.width().mul_add(t, rect.left());
+                let y = rect.height().mul_add(t, rect.top());
+                painter.line_segment([pos2(x, rect.top()), pos2(x, rect.bottom())], stroke);
+                painter.line_segment([pos2(rect.left(), y), pos2(rect.right(), y)], stroke);
+            }
+        }
+
+        if self.center {
+            let center = rect.center();
+            let size = rect.width().min(rect.height()) * 0.05;
+            painter.line_segment([center - vec2(size, 0.0), center + vec2(size, 0.0)], stroke);
+            painter.line_segment([center - vec2(0.0, size), center + vec2(0.0, size)], stroke);
+        }
+    }
+}
+
+/// Box with a dark background in a corner of the preview, positioned by the layout
+pub fn overlay(ui: &mut Ui, rect: Rect, layout: Layout, add_contents: impl FnOnce(&mut Ui)) {
+    let mut child = ui.new_child(UiBuilder::new().max_rect(rect.shrink(5.0)).layout(layout));
+
+    Frame::none()
+        .fill(Color32::from_black_alpha(160))
+        .rounding(4.0)
+        .inner_margin(6.0)
+        .show(&mut child, |ui| {
+            ui.style_mut().visuals.override_text_color = Some(Color32::WHITE);
+            ui.vertical(add_contents);
+        });
+}
@@ -3,13 +3,20 @@ struct VertexOut {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) color: vec3<f32>,
+    @location(3) @interpolate(flat) object: u32,
 }
 
 struct Uniforms {
     view: mat4x4<f32>,
     lights_count: u32,
+    selected: u32,
+    shadow_lights: u32,
     ambient_color: vec3<f32>,
     ambient_intensity: f32,
+    camera_position: vec3<f32>,
+    // normal and negative distance to the origin, keep the size in sync with SectionPlane::PREVIEW_MAX
+    section_planes: array<vec4<f32>, 4>,
+    section_count: u32,
 }
 
 @group(0) @binding(0)
@@ -27,6 +34,49 @@ var<storage, read> lights: array<Light>;
 @group(0) @binding(2)
 var<storage, read> transforms: array<mat4x4<f32>>;
 
+// padded to the uniform offset alignment of 256 bytes
+struct ShadowFace {
+    view: mat4x4<f32>,
+    pad: array<vec4<f32>, 12>,
+}
+
+@group(1) @binding(0)
+var shadow_map: texture_depth_2d_array;
+
+@group(1) @binding(1)
+var shadow_sampler: sampler_comparison;
+
+@group(1) @binding(2)
+var<storage, read> shadow_faces: array<ShadowFace>;
+
+// visibility of a position from a light with a shadow map, one face of its cube is sampled
+fn shadow(light: u32, position: vec3<f32>) -> f32 {
+    var direction: vec3<f32> = position - lights[light].position;
+    var size: vec3<f32> = abs(direction);
+
+    // same order as the faces of the cube: +x, -x, +y, -y, +z, -z
+    var face: u32;
+    if (size.x >= size.y && size.x >= size.z) {
+        face = select(1u, 0u, direction.x > 0.0);
+    } else if (size.y >= size.z) {
+        face = select(3u, 2u, direction.y > 0.0);
+    } else {
+        face = select(5u, 4u, direction.z > 0.0);
+    }
+
+    var layer: u32 = light * 6u + face;
+    var clip: vec4<f32> = shadow_faces[layer].view * vec4<f32>(position, 1.0);
+    var ndc: vec3<f32> = clip.xyz / clip.w;
+
+    // beyond the far plane nothing was rendered into the shadow map
+    if (ndc.z > 1.0) {
+        return 1.0;
+    }
+
+    var uv: vec2<f32> = ndc.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5);
+    return textureSampleCompareLevel(shadow_map, shadow_sampler, uv, layer, ndc.z);
+}
+
 @vertex
 fn vs_main(
     @location(0) position: vec3<f32>,
@@ -40,22 +90,84 @@ fn vs_main(
 
     out.result = uniforms.view * transform * vec4<f32>(position, 1.0);
     out.position = (transform * vec4<f32>(position, 1.0)).xyz;
-    out.normal = (transform * vec4<f32>(normal, 0.0)).xyz;
+    // the cofactor matrix is the inverse transpose scaled by the determinant, it keeps normals
+    // perpendicular under non-uniform scales, the sign flips them back for mirrored objects
+    var linear: mat3x3<f32> = mat3x3<f32>(transform[0].xyz, transform[1].xyz, transform[2].xyz);
+    var cofactor: mat3x3<f32> = mat3x3<f32>(
+        cross(linear[1], linear[2]),
+        cross(linear[2], linear[0]),
+        cross(linear[0], linear[1]),
+    );
+    var world_normal: vec3<f32> = cofactor * normal * sign(determinant(linear));
+    out.normal = select(vec3<f32>(0.0), normalize(world_normal), length(world_normal) > 0.0);
     out.color = color;
+    out.object = transform_index;
 
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    // cut away by a section plane
+    for (var i = 0u; i < uniforms.section_count; i = i + 1u) {
+        if (dot(uniforms.section_planes[i].xyz, in.position) + uniforms.section_planes[i].w > 0.0) {
+            discard;
+        }
+    }
+
     var color: vec3<f32> = uniforms.ambient_color * uniforms.ambient_intensity * in.color;
 
     for (var i = 0u; i < uniforms.lights_count; i = i + 1u) {
         var light: Light = lights[i];
         var light_dir: vec3<f32> = normalize(light.position - in.position.xyz);
         var diff: f32 = max(dot(in.normal, light_dir), 0.0);
+        if (i < uniforms.shadow_lights) {
+            diff = diff * shadow(i, in.position);
+        }
         color = color + in.color * light.color * diff * light.intensity / pow(length(light.position - in.position.xyz), 2.0);
     }
 
+    // tint the selected object
+    if (in.object == uniforms.selected) {
+        color = mix(color, vec3<f32>(1.0, 0.6, 0.1), 0.35);
+    }
+
     return vec4<f32>(color, 1.0);
-}
\ No newline at end of file
+}
+
+// half the size of the ground grid, keep in sync with GRID_EXTENT
+const GRID_EXTENT: f32 = 50.0;
+
+struct LineOut {
+    @builtin(position) result: vec4<f32>,
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+}
+
+@vertex
+fn vs_line(
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+) -> LineOut {
+    var out: LineOut;
+
+    out.result = uniforms.view * vec4<f32>(position, 1.0);
+    out.position = position;
+    out.color = color;
+
+    return out;
+}
+
+@fragment
+fn fs_line(in: LineOut) -> @location(0) vec4<f32> {
+    // fade out towards the border of the grid
+    var distance: f32 = length(in.position.xz - uniforms.camera_position.xz);
+    var alpha: f32 = 1.0 - smoothstep(GRID_EXTENT * 0.5, GRID_EXTENT, distance);
+
+    return vec4<f32>(in.color, alpha);
+}
+
+@fragment
+fn fs_bounds(in: LineOut) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
@@ -0,0 +1,21 @@
+// depth only pass rendering the scene from one cube face of a light
+
+@group(0) @binding(2)
+var<storage, read> transforms: array<mat4x4<f32>>;
+
+// padded to the uniform offset alignment of 256 bytes
+struct ShadowFace {
+    view: mat4x4<f32>,
+    pad: array<vec4<f32>, 12>,
+}
+
+@group(1) @binding(0)
+var<uniform> shadow_face: ShadowFace;
+
+@vertex
+fn vs_shadow(
+    @location(0) position: vec3<f32>,
+    @location(3) transform_index: u32,
+) -> @builtin(position) vec4<f32> {
+    return shadow_face.view * transforms[transform_index] * vec4<f32>(position, 1.0);
+}
@@ -1,92 +1,372 @@
-use crate::{
-    raytracer::render::Render,
-    scene::{Color, Light, Object, Skybox},
-    Scene,
+use super::{
+    outliner::Selection, preferences::Preferences, presets::RenderPreset, snap::Snap, toasts,
 };
 use anyhow::Context;
 use egui::{
-    color_picker, hex_color, include_image, Align, Button, CollapsingHeader, DragValue, FontFamily,
-    ImageButton, Layout, RichText, Slider, SliderClamping, Ui,
+    color_picker, hex_color, include_image, Align, Button, CollapsingHeader, ComboBox, DragValue,
+    FontFamily, ImageButton, Layout, RichText, Slider, SliderClamping, TextEdit, TextStyle, Ui,
 };
 use egui_file::FileDialog;
 use log::warn;
-use nalgebra::{coordinates::XYZ, Scale3, Translation3, UnitQuaternion};
+use nalgebra::{coordinates::XYZ, Point3, UnitQuaternion, Vector3};
 use rust_i18n::t;
-use std::{f32::consts, path::Path};
+use std::{collections::BTreeSet, f32::consts, path::Path, sync::Arc};
+use trayracer_core::{
+    raytracer::render::Render,
+    scene::{
+        CameraPose, Color, Object, PixelFilter, Projection, Scene, SectionPlane, ShadingMode,
+        Skybox, Transform,
+    },
+};
 
-fn xyz_drag_value(ui: &mut Ui, value: &mut XYZ<f32>) {
+/// Units model files are commonly exported in, by their size in scene units (meters)
+const UNITS: [(&str, f32); 5] = [
+    ("mm", 0.001),
+    ("cm", 0.01),
+    ("m", 1.0),
+    ("in", 0.0254),
+    ("ft", 0.3048),
+];
+
+pub fn xyz_drag_value(ui: &mut Ui, value: &mut XYZ<f32>) {
+    snapped_xyz_drag_value(ui, value, |v| v);
+}
+
+/// Changed components are rounded by the snap function
+pub fn snapped_xyz_drag_value(ui: &mut Ui, value: &mut XYZ<f32>, snap: impl Fn(f32) -> f32) {
     ui.horizontal(|ui| {
-        ui.add(DragValue::new(&mut value.x).speed(0.1).prefix("x: "));
-        ui.add(DragValue::new(&mut value.y).speed(0.1).prefix("y: "));
-        ui.add(DragValue::new(&mut value.z).speed(0.1).prefix("z: "));
+        for (prefix, component) in [
+            ("x: ", &mut value.x),
+            ("y: ", &mut value.y),
+            ("z: ", &mut value.z),
+        ] {
+            if ui
+                .add(DragValue::new(component).speed(0.1).prefix(prefix))
+                .changed()
+            {
+                *component = snap(*component);
+            }
+        }
     });
 }
 
+/// Euler angles in degrees, the changed angle is rounded by the snap function
+pub fn rotation_drag_value(
+    ui: &mut Ui,
+    rotation: &mut UnitQuaternion<f32>,
+    snap: impl Fn(f32) -> f32,
+) {
+    ui.horizontal(|ui| {
+        let (mut x, mut y, mut z) = rotation.euler_angles();
+
+        [("x", &mut x), ("y", &mut y), ("z", &mut z)]
+            .iter_mut()
+            .any(|(prefix, angle)| {
+                let changed = ui
+                    .add(
+                        DragValue::new(*angle)
+                            .speed(0.01)
+                            .custom_formatter(|f, _| format!("{:.1}°", f.to_degrees()))
+                            .prefix(format!("{prefix}: ")),
+                    )
+                    .changed();
+                if changed {
+                    **angle = snap(**angle);
+                }
+                changed
+            })
+            .then(|| {
+                *rotation = UnitQuaternion::from_euler_angles(x, y, z);
+            })
+    });
+}
+
+/// Distance a duplicate is moved along x, so it doesn't hide the original
+const DUPLICATE_OFFSET: f32 = 0.5;
+
+/// Button of an item header that was clicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HeaderAction {
+    Remove,
+    Duplicate,
+}
+
 pub struct Properties {
     /// Dialog to select a skybox image
     skybox_dialog: Option<FileDialog>,
-    /// Dialog to add a new object
-    object_dialog: Option<FileDialog>,
+    /// Name the current render settings are saved as
+    preset_name: String,
 }
 
 impl Properties {
     pub const fn new() -> Self {
         Self {
             skybox_dialog: None,
-            object_dialog: None,
+            preset_name: String::new(),
         }
     }
 
-    pub fn show(&mut self, scene: &mut Scene, ui: &mut Ui, render: &Render) {
+    pub fn show(
+        &mut self,
+        scene: &mut Scene,
+        ui: &mut Ui,
+        render: &Render,
+        selection: &mut Option<Selection>,
+        objects: &mut BTreeSet<usize>,
+        preferences: &mut Preferences,
+    ) {
         ui.horizontal(|ui| {
             ui.heading(t!("properties"));
         });
 
-        Self::camera_settings(scene, ui);
+        let snap = &preferences.snap;
 
-        ui.add_space(5.0);
-
-        self.scene_settings(scene, ui, render);
+        match *selection {
+            Some(Selection::Camera(c)) => {
+                if Self::camera(ui, scene, c) {
+                    scene.remove_camera(c);
+                    *selection = None;
+                }
+            }
+            Some(Selection::Light(n)) => match Self::light(ui, scene, n) {
+                Some(HeaderAction::Remove) => {
+                    scene.lights.remove(n);
+                    // the soloed light keeps its index
+                    scene.solo_light = match scene.solo_light {
+                        Some(s) if s == n => None,
+                        Some(s) if s > n => Some(s - 1),
+                        solo => solo,
+                    };
+                    *selection = None;
+                }
+                Some(HeaderAction::Duplicate) => {
+                    let mut light = scene.lights[n].clone();
+                    light.name = light
+                        .name
+                        .map(|name| t!("copy_of", name = name).to_string());
+                    light.position.x += DUPLICATE_OFFSET;
+                    scene.lights.push(light);
+                    *selection = Some(Selection::Light(scene.lights.len() - 1));
+                }
+                None => {}
+            },
+            Some(Selection::Object(n)) if objects.len() > 1 => {
+                if Self::objects(ui, scene, n, objects, snap) {
+                    // back to front, so the indices stay valid
+                    for &o in objects.iter().rev() {
+                        scene.objects.remove(o);
+                    }
+                    objects.clear();
+                    *selection = None;
+                }
+            }
+            Some(Selection::Object(n)) => match Self::object(ui, scene, n, snap) {
+                Some(HeaderAction::Remove) => {
+                    scene.objects.remove(n);
+                    *selection = None;
+                }
+                Some(HeaderAction::Duplicate) => {
+                    // the clone shares the mesh with the original
+                    let mut object = scene.objects[n].clone();
+                    object.name = t!("copy_of", name = object.name).to_string();
+                    object.translation.x += DUPLICATE_OFFSET;
+                    scene.objects.push(object);
+                    scene.assign_object_ids();
+                    scene.update_transforms();
+                    *selection = Some(Selection::Object(scene.objects.len() - 1));
+                }
+                None => {}
+            },
+            Some(Selection::Group(g)) => {
+                if Self::group(ui, scene, g, snap) {
+                    scene.remove_group(g);
+                    *selection = None;
+                }
+            }
+            None => {
+                ui.group(|ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(t!("nothing_selected"));
+                    });
+                });
+            }
+        }
 
         ui.add_space(5.0);
 
-        Self::lights(ui, scene);
+        self.scene_settings(scene, ui, render, &mut preferences.render_presets);
+    }
 
-        ui.add_space(5.0);
+    /// Pose of a camera, returns whether it should be removed
+    #[allow(clippy::too_many_lines)]
+    fn camera(ui: &mut Ui, scene: &mut Scene, c: usize) -> bool {
+        let removable = scene.cameras.len() > 1;
+        let active = scene.active_camera == c;
+        let camera = &mut scene.cameras[c];
 
-        self.objects(ui, scene);
-    }
+        let (remove, activate) = ui
+            .group(|ui| {
+                let remove = Self::header(
+                    ui,
+                    |ui| {
+                        let mut name = camera.name.clone().unwrap_or_default();
+                        if ui
+                            .add(
+                                TextEdit::singleline(&mut name)
+                                    .hint_text(format!("{} {c}", t!("camera")))
+                                    .font(TextStyle::Monospace)
+                                    .desired_width(150.0),
+                            )
+                            .on_hover_text(t!("name_hint"))
+                            .changed()
+                        {
+                            // an empty name falls back to the numbered label
+                            camera.name = (!name.is_empty()).then_some(name);
+                        }
+                    },
+                    removable,
+                    &t!("remove_camera_hint"),
+                    None,
+                ) == Some(HeaderAction::Remove);
 
-    pub fn camera_settings(scene: &mut Scene, ui: &mut egui::Ui) {
-        ui.group(|ui| {
-            ui.vertical_centered(|ui| {
-                ui.label(RichText::new(t!("camera")).size(16.0));
-            });
+                let activate = ui
+                    .add_enabled(!active, Button::new(t!("use_camera")))
+                    .on_hover_text(t!("use_camera_hint"))
+                    .clicked();
 
-            ui.separator();
+                let pose = CameraPose::of(camera);
+                if let Some(pose) =
+                    Self::clipboard(ui, &pose, CameraPose::to_yaml, CameraPose::from_yaml)
+                {
+                    pose.apply(camera);
+                }
 
-            ui.vertical(|ui| {
                 ui.label(format!("{}:", t!("position")));
 
-                xyz_drag_value(ui, &mut scene.camera.position);
+                xyz_drag_value(ui, &mut camera.position);
 
                 ui.label(format!("{}:", t!("look_at")));
 
-                xyz_drag_value(ui, &mut scene.camera.look_at);
+                xyz_drag_value(ui, &mut camera.look_at);
 
                 ui.label(format!("{}:", t!("fov")));
 
-                ui.add(
-                    Slider::new(&mut scene.camera.fov, 0.0..=consts::PI)
-                        .step_by(0.01)
-                        .custom_formatter(|x, _| format!("{:.2}°", x.to_degrees()))
-                        .clamping(SliderClamping::Edits),
-                );
-            });
-        });
+                if ui
+                    .add(
+                        Slider::new(&mut camera.fov, 0.0..=consts::PI)
+                            .step_by(0.01)
+                            .custom_formatter(|x, _| format!("{:.2}°", x.to_degrees()))
+                            .clamping(SliderClamping::Edits),
+                    )
+                    .changed()
+                {
+                    camera.by_focal_length = false;
+                }
+
+                ui.horizontal(|ui| {
+                    let mut focal_length = camera.focal_length();
+                    if ui
+                        .add(
+                            DragValue::new(&mut focal_length)
+                                .speed(0.5)
+                                .range(1.0..=2000.0)
+                                .max_decimals(1)
+                                .suffix(" mm"),
+                        )
+                        .on_hover_text(t!("focal_length_hint"))
+                        .changed()
+                    {
+                        camera.set_focal_length(focal_length);
+                    }
+                    ui.label(t!("focal_length"));
+                });
+
+                ui.horizontal(|ui| {
+                    // the field of view stays, the focal length follows the sensor
+                    ui.add(
+                        DragValue::new(&mut camera.sensor_width)
+                            .speed(0.1)
+                            .range(1.0..=100.0)
+                            .max_decimals(1)
+                            .suffix(" mm"),
+                    )
+                    .on_hover_text(t!("sensor_width_hint"));
+                    ui.label(t!("sensor_width"));
+                });
+
+                ComboBox::from_label(t!("projection"))
+                    .selected_text(Self::format_projection(camera.projection))
+                    .show_ui(ui, |ui| {
+                        for projection in Projection::ALL {
+                            ui.selectable_value(
+                                &mut camera.projection,
+                                projection,
+                                Self::format_projection(projection),
+                            );
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("projection_hint"));
+                if camera.projection.is_stereo() {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            DragValue::new(&mut camera.eye_separation)
+                                .speed(0.001)
+                                .range(0.0..=1.0)
+                                .max_decimals(3),
+                        )
+                        .on_hover_text(t!("eye_separation_hint"));
+                        ui.label(t!("eye_separation"));
+                    });
+                }
+
+                ui.label(format!("{}:", t!("clipping_planes")))
+                    .on_hover_text(t!("clipping_planes_hint"));
+
+                ui.horizontal(|ui| {
+                    // the near plane has to stay in front of the far plane
+                    let far = camera.far;
+                    ui.add(
+                        DragValue::new(&mut camera.near)
+                            .speed(0.01)
+                            .range(0.001..=far * 0.99)
+                            .prefix(format!("{}: ", t!("near"))),
+                    );
+                    let near = camera.near;
+                    ui.add(
+                        DragValue::new(&mut camera.far)
+                            .speed(1.0)
+                            .range(near * 1.01..=f32::MAX)
+                            .prefix(format!("{}: ", t!("far"))),
+                    );
+                });
+
+                ui.label(format!("{}:", t!("render_size")));
+
+                let (x, y) = &mut camera.resolution;
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(x).speed(1.0).range(10..=8192).prefix("w: "));
+                    ui.add(DragValue::new(y).speed(1.0).range(10..=8192).prefix("h: "));
+                });
+
+                (remove, activate)
+            })
+            .inner;
+
+        if activate {
+            scene.active_camera = c;
+        }
+
+        remove
     }
 
-    fn scene_settings(&mut self, scene: &mut Scene, ui: &mut Ui, render: &Render) {
+    fn scene_settings(
+        &mut self,
+        scene: &mut Scene,
+        ui: &mut Ui,
+        render: &Render,
+        presets: &mut Vec<RenderPreset>,
+    ) {
         ui.vertical(|ui| {
             ui.group(|ui| {
                 CollapsingHeader::new(RichText::new(t!("scene_settings")).size(16.0))
@@ -94,11 +374,13 @@ impl Properties {
                     .show_unindented(ui, |ui| {
                         ui.separator();
 
-                        Self::render_options(ui, render, scene);
+                        self.render_options(ui, render, scene, presets);
 
                         self.skybox_options(ui, scene);
 
                         Self::ambient_options(ui, scene);
+
+                        Self::section_planes(ui, scene);
                     });
             });
         });
@@ -115,30 +397,307 @@ impl Properties {
         );
     }
 
-    fn render_options(ui: &mut Ui, render: &Render, scene: &mut Scene) {
+    /// Planes cutting away the geometry, added through the origin along an axis
+    fn section_planes(ui: &mut Ui, scene: &mut Scene) {
+        ui.label(format!("{}:", t!("section_planes")))
+            .on_hover_text(t!("section_planes_hint"));
+
+        let planes = &mut scene.settings.section_planes;
+        let mut remove = None;
+        for (i, plane) in planes.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", t!("position")));
+                xyz_drag_value(ui, &mut plane.position);
+            });
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", t!("normal")));
+                xyz_drag_value(ui, &mut plane.normal);
+                if ui
+                    .small_button("⇄")
+                    .on_hover_text(t!("flip_section_plane_hint"))
+                    .clicked()
+                {
+                    plane.normal = -plane.normal;
+                }
+                if ui
+                    .small_button("🗑")
+                    .on_hover_text(t!("remove_section_plane_hint"))
+                    .clicked()
+                {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            planes.remove(i);
+        }
+
+        // the preview cuts with a fixed number of planes
+        ui.add_enabled_ui(planes.len() < SectionPlane::PREVIEW_MAX, |ui| {
+            ui.horizontal(|ui| {
+                for (name, normal) in [
+                    ("+X", Vector3::x()),
+                    ("+Y", Vector3::y()),
+                    ("+Z", Vector3::z()),
+                ] {
+                    if ui
+                        .button(name)
+                        .on_hover_text(t!("add_section_plane_hint"))
+                        .clicked()
+                    {
+                        planes.push(SectionPlane {
+                            position: Point3::origin(),
+                            normal,
+                        });
+                    }
+                }
+            });
+        });
+    }
+
+    /// Integrators and BSDFs registered with the render, by name
+    fn shading_models(ui: &mut Ui, render: &Render, scene: &mut Scene) {
+        egui::ComboBox::from_label(t!("integrator"))
+            .selected_text(scene.settings.integrator.clone())
+            .show_ui(ui, |ui| {
+                for name in render.registry.integrators() {
+                    ui.selectable_value(&mut scene.settings.integrator, name.to_owned(), name);
+                }
+            })
+            .response
+            .on_hover_text(t!("integrator_hint"));
+        egui::ComboBox::from_label(t!("bsdf"))
+            .selected_text(scene.settings.bsdf.clone())
+            .show_ui(ui, |ui| {
+                for name in render.registry.bsdfs() {
+                    ui.selectable_value(&mut scene.settings.bsdf, name.to_owned(), name);
+                }
+            })
+            .response
+            .on_hover_text(t!("bsdf_hint"));
+    }
+
+    fn format_shading_mode(mode: ShadingMode) -> String {
+        match mode {
+            ShadingMode::Full => t!("shading_full"),
+            ShadingMode::Normal => t!("shading_normal"),
+            ShadingMode::Uv => t!("shading_uv"),
+            ShadingMode::Depth => t!("shading_depth"),
+        }
+        .to_string()
+    }
+
+    /// Filter weighting the anti-aliasing samples and its radius
+    fn pixel_filter(ui: &mut Ui, scene: &mut Scene) {
+        let settings = &mut scene.settings;
+        egui::ComboBox::from_label(t!("pixel_filter"))
+            .selected_text(Self::format_pixel_filter(settings.pixel_filter))
+            .show_ui(ui, |ui| {
+                for filter in PixelFilter::ALL {
+                    // each filter starts with the radius it is usually used with
+                    if ui
+                        .selectable_value(
+                            &mut settings.pixel_filter,
+                            filter,
+                            Self::format_pixel_filter(filter),
+                        )
+                        .clicked()
+                    {
+                        settings.filter_radius = filter.default_radius();
+                    }
+                }
+            })
+            .response
+            .on_hover_text(t!("pixel_filter_hint"));
+        ui.add(
+            Slider::new(&mut settings.filter_radius, 0.5..=3.0)
+                .step_by(0.1)
+                .suffix(" px")
+                .text(t!("filter_radius")),
+        );
+    }
+
+    fn format_projection(projection: Projection) -> String {
+        match projection {
+            Projection::Perspective => t!("projection_perspective"),
+            Projection::Stereo => t!("projection_stereo"),
+            Projection::Ods => t!("projection_ods"),
+        }
+        .to_string()
+    }
+
+    fn format_pixel_filter(filter: PixelFilter) -> String {
+        match filter {
+            PixelFilter::Box => t!("filter_box"),
+            PixelFilter::Tent => t!("filter_tent"),
+            PixelFilter::Gaussian => t!("filter_gaussian"),
+            PixelFilter::Mitchell => t!("filter_mitchell"),
+        }
+        .to_string()
+    }
+
+    /// Select a built-in or saved preset, or save the current settings as one
+    fn presets(&mut self, ui: &mut Ui, scene: &mut Scene, presets: &mut Vec<RenderPreset>) {
+        let builtin = RenderPreset::builtin();
+        let current = builtin
+            .iter()
+            .chain(presets.iter())
+            .find(|p| p.matches(scene))
+            .map_or_else(|| t!("preset_custom").to_string(), |p| p.name.clone());
+        let mut remove = None;
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("preset")));
+            ComboBox::from_id_salt("render_preset")
+                .selected_text(current)
+                .show_ui(ui, |ui| {
+                    for preset in &builtin {
+                        if ui
+                            .selectable_label(preset.matches(scene), &preset.name)
+                            .clicked()
+                        {
+                            preset.apply(scene);
+                        }
+                    }
+
+                    if !presets.is_empty() {
+                        ui.separator();
+                    }
+                    for (i, preset) in presets.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .selectable_label(preset.matches(scene), &preset.name)
+                                .clicked()
+                            {
+                                preset.apply(scene);
+                            }
+                            if ui
+                                .small_button("🗑")
+                                .on_hover_text(t!("remove_preset_hint"))
+                                .clicked()
+                            {
+                                remove = Some(i);
+                            }
+                        });
+                    }
+                });
+        });
+
+        if let Some(i) = remove {
+            presets.remove(i);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.preset_name)
+                    .hint_text(t!("preset_name"))
+                    .desired_width(120.0),
+            );
+            if ui
+                .add_enabled(
+                    !self.preset_name.trim().is_empty(),
+                    Button::new(t!("save_preset")),
+                )
+                .on_hover_text(t!("save_preset_hint"))
+                .clicked()
+            {
+                let preset = RenderPreset::of(self.preset_name.trim(), scene);
+                // saving under an existing name replaces that preset
+                presets.retain(|p| p.name != preset.name);
+                presets.push(preset);
+                self.preset_name.clear();
+            }
+        });
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn render_options(
+        &mut self,
+        ui: &mut Ui,
+        render: &Render,
+        scene: &mut Scene,
+        presets: &mut Vec<RenderPreset>,
+    ) {
+        ui.add_enabled_ui(render.thread.is_none(), |ui| {
+            self.presets(ui, scene, presets);
+        });
+
         ui.label(format!("{}:", t!("render_size")));
         ui.vertical(|ui| {
             ui.add_enabled_ui(render.thread.is_none(), |ui| {
                 ui.vertical(|ui| {
-                    let text = Self::format_render_size(scene.camera.resolution);
+                    let text = Self::format_render_size(scene.camera().resolution);
                     egui::ComboBox::from_id_salt(0)
                         .selected_text(text)
                         .show_ui(ui, |ui| {
-                            ui.selectable_value(&mut scene.camera.resolution, (1280, 720), "HD");
                             ui.selectable_value(
-                                &mut scene.camera.resolution,
+                                &mut scene.camera_mut().resolution,
+                                (1280, 720),
+                                "HD",
+                            );
+                            ui.selectable_value(
+                                &mut scene.camera_mut().resolution,
                                 (1920, 1080),
                                 "Full HD",
                             );
-                            ui.selectable_value(&mut scene.camera.resolution, (2560, 1440), "2k");
-                            ui.selectable_value(&mut scene.camera.resolution, (3840, 2160), "4k");
-                            ui.selectable_value(&mut scene.camera.resolution, (7680, 4320), "8k");
+                            ui.selectable_value(
+                                &mut scene.camera_mut().resolution,
+                                (2560, 1440),
+                                "2k",
+                            );
+                            ui.selectable_value(
+                                &mut scene.camera_mut().resolution,
+                                (3840, 2160),
+                                "4k",
+                            );
+                            ui.selectable_value(
+                                &mut scene.camera_mut().resolution,
+                                (7680, 4320),
+                                "8k",
+                            );
                         });
                     ui.horizontal(|ui| {
-                        let (x, y) = &mut scene.camera.resolution;
+                        let (x, y) = &mut scene.camera_mut().resolution;
                         ui.add(DragValue::new(x).speed(1.0).range(10..=8192).prefix("w: "));
                         ui.add(DragValue::new(y).speed(1.0).range(10..=8192).prefix("h: "));
                     });
+                    egui::ComboBox::from_label(t!("shading_mode"))
+                        .selected_text(Self::format_shading_mode(scene.settings.shading_mode))
+                        .show_ui(ui, |ui| {
+                            for mode in [
+                                ShadingMode::Full,
+                                ShadingMode::Normal,
+                                ShadingMode::Uv,
+                                ShadingMode::Depth,
+                            ] {
+                                ui.selectable_value(
+                                    &mut scene.settings.shading_mode,
+                                    mode,
+                                    Self::format_shading_mode(mode),
+                                );
+                            }
+                        });
+                    if scene.settings.shading_mode == ShadingMode::Full {
+                        Self::shading_models(ui, render, scene);
+                    }
+                    ui.add(
+                        Slider::new(&mut scene.settings.max_bounces, 1..=16)
+                            .text(t!("max_bounces")),
+                    )
+                    .on_hover_text(t!("max_bounces_hint"));
+                    ui.checkbox(&mut scene.settings.clay, t!("clay"))
+                        .on_hover_text(t!("clay_hint"));
+                    ui.checkbox(&mut scene.settings.light_sampling, t!("light_sampling"))
+                        .on_hover_text(t!("light_sampling_hint"));
+                    ui.checkbox(
+                        &mut scene.settings.transparent_background,
+                        t!("transparent_background"),
+                    )
+                    .on_hover_text(t!("transparent_background_hint"));
+                    ui.checkbox(&mut scene.settings.light_passes, t!("light_passes"))
+                        .on_hover_text(t!("light_passes_hint"));
+                    ui.checkbox(&mut scene.settings.bake_meshes, t!("bake_meshes"))
+                        .on_hover_text(t!("bake_meshes_hint"));
                     ui.checkbox(&mut scene.settings.anti_aliasing, "Anti-Aliasing");
                     if scene.settings.anti_aliasing {
                         ui.label("Samples per pixel:");
@@ -146,6 +705,19 @@ impl Properties {
                             Slider::new(&mut scene.settings.samples, 1..=128)
                                 .clamping(SliderClamping::Edits),
                         );
+                        Self::pixel_filter(ui, scene);
+                        ui.checkbox(
+                            &mut scene.settings.adaptive_sampling,
+                            t!("adaptive_sampling"),
+                        )
+                        .on_hover_text(t!("adaptive_sampling_hint"));
+                        if scene.settings.adaptive_sampling {
+                            ui.add(
+                                Slider::new(&mut scene.settings.noise_threshold, 0.001..=0.2)
+                                    .logarithmic(true)
+                                    .text(t!("noise_threshold")),
+                            );
+                        }
                     }
                 });
             });
@@ -169,14 +741,15 @@ impl Properties {
 
                     Ok::<_, anyhow::Error>(Skybox::Image {
                         path: path.to_path_buf(),
-                        image,
+                        image: Arc::new(image),
                     })
                 })() {
                     Ok(skybox) => {
                         scene.settings.skybox = skybox;
                     }
                     Err(e) => {
-                        warn!("Failed to load skybox: {}", e);
+                        warn!("Failed to load skybox: {e}");
+                        toasts::error(ui.ctx(), t!("load_skybox_failed", error = format!("{e:#}")));
                     }
                 }
 
@@ -221,7 +794,7 @@ impl Properties {
         let mut dialog = FileDialog::open_file(None).filename_filter(Box::new(|p| {
             Path::new(p)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("exr"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("exr"))
         }));
 
         dialog.open();
@@ -229,191 +802,415 @@ impl Properties {
         self.skybox_dialog = Some(dialog);
     }
 
-    fn lights(ui: &mut Ui, scene: &mut Scene) {
-        ui.vertical(|ui| {
-            ui.group(|ui| {
-                CollapsingHeader::new(
-                    RichText::new(format!("{} ({})", t!("lights"), scene.lights.len())).size(16.0),
-                )
-                .default_open(true)
-                .show_unindented(ui, |ui| {
-                    scene
-                        .lights
-                        .iter_mut()
-                        .enumerate()
-                        .filter_map(|(n, light)| {
-                            let mut remove = false;
-
-                            ui.separator();
-
-                            ui.horizontal(|ui| {
-                                ui.label(
-                                    RichText::new(format!("{} {n}", t!("light")))
-                                        .size(14.0)
-                                        .family(FontFamily::Monospace),
-                                );
-                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                                    remove = ui
-                                        .add_sized(
-                                            [20.0, 20.0],
-                                            ImageButton::new(include_image!(
-                                                "../../res/icons/trash-solid.svg"
-                                            ))
-                                            .tint(hex_color!("#cc0000")),
-                                        )
-                                        .clicked();
-                                });
-                            });
+    /// Header with the name of the selected item, a remove button and
+    /// a duplicate button if there is a hint for it
+    fn header(
+        ui: &mut Ui,
+        add_name: impl FnOnce(&mut Ui),
+        removable: bool,
+        remove_hint: &str,
+        duplicate_hint: Option<&str>,
+    ) -> Option<HeaderAction> {
+        let mut action = None;
 
-                            ui.label(format!("{}:", t!("position")));
+        ui.horizontal(|ui| {
+            add_name(ui);
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui
+                    .add_enabled_ui(removable, |ui| {
+                        ui.add_sized(
+                            [20.0, 20.0],
+                            ImageButton::new(include_image!("../../res/icons/trash-solid.svg"))
+                                .tint(hex_color!("#cc0000")),
+                        )
+                    })
+                    .inner
+                    .on_hover_text(remove_hint)
+                    .clicked()
+                {
+                    action = Some(HeaderAction::Remove);
+                }
 
-                            xyz_drag_value(ui, &mut light.position);
+                if let Some(hint) = duplicate_hint {
+                    if ui
+                        .add_sized([20.0, 20.0], Button::new("⧉"))
+                        .on_hover_text(hint)
+                        .clicked()
+                    {
+                        action = Some(HeaderAction::Duplicate);
+                    }
+                }
+            });
+        });
 
-                            ui.label(format!("{}:", t!("intensity")));
+        ui.separator();
 
-                            ui.add(
-                                Slider::new(&mut light.intensity, 0.0..=100.0)
-                                    .clamping(SliderClamping::Edits),
-                            );
+        action
+    }
 
-                            ui.label(format!("{}:", t!("color")));
+    /// Buttons to copy the value as YAML and to paste one, returns the pasted value.
+    /// The web can't read the clipboard on request, it only has the copy button
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables, unused_mut))]
+    fn clipboard<T>(
+        ui: &mut Ui,
+        value: &T,
+        to_yaml: impl FnOnce(&T) -> anyhow::Result<String>,
+        from_yaml: impl FnOnce(&str) -> anyhow::Result<T>,
+    ) -> Option<T> {
+        let mut pasted = None;
 
-                            color_picker::color_edit_button_rgb(ui, light.color.as_mut());
+        ui.horizontal(|ui| {
+            if ui
+                .button(t!("copy"))
+                .on_hover_text(t!("copy_snippet_hint"))
+                .clicked()
+            {
+                match to_yaml(value) {
+                    Ok(yaml) => ui.ctx().copy_text(yaml),
+                    Err(e) => warn!("{e:?}"),
+                }
+            }
 
-                            remove.then_some(n)
-                        })
-                        .collect::<Vec<_>>()
-                        .into_iter()
-                        .for_each(|n| {
-                            scene.lights.remove(n);
-                        });
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .button(t!("paste"))
+                .on_hover_text(t!("paste_snippet_hint"))
+                .clicked()
+            {
+                let result = arboard::Clipboard::new()
+                    .and_then(|mut clipboard| clipboard.get_text())
+                    .context("Failed to read the clipboard")
+                    .and_then(|text| from_yaml(&text));
 
-                    ui.separator();
-                    ui.vertical_centered(|ui| {
-                        ui.add(Button::new(RichText::new(t!("add_light"))).frame(false))
-                            .clicked()
-                            .then(|| {
-                                scene.lights.push(Light {
-                                    position: nalgebra::Point3::new(5.0, 2.0, 2.0),
-                                    intensity: 3.0,
-                                    color: nalgebra::Vector3::new(1.0, 1.0, 1.0),
-                                });
-                            });
-                    });
-                });
-            });
+                match result {
+                    Ok(value) => pasted = Some(value),
+                    Err(e) => {
+                        warn!("Failed to paste: {e:?}");
+                        toasts::warning(ui.ctx(), t!("paste_failed", error = format!("{e:#}")));
+                    }
+                }
+            }
         });
+
+        pasted
     }
 
-    fn objects(&mut self, ui: &mut Ui, scene: &mut Scene) {
-        ui.vertical(|ui| {
-            ui.group(|ui| {
-                CollapsingHeader::new(
-                    RichText::new(format!("{} ({})", t!("objects"), scene.objects.len()))
-                        .size(16.0),
-                )
-                .default_open(true)
-                .show_unindented(ui, |ui| {
-                    let mut objects_to_remove = Vec::new();
+    /// Settings of a light, returns whether it should be removed or duplicated
+    fn light(ui: &mut Ui, scene: &mut Scene, n: usize) -> Option<HeaderAction> {
+        let objects = scene
+            .objects
+            .iter()
+            .map(|o| (o.id, o.name.clone()))
+            .collect::<Vec<_>>();
+        let solo = &mut scene.solo_light;
+        let light = &mut scene.lights[n];
 
-                    for (n, o) in scene.objects.iter_mut().enumerate() {
-                        ui.separator();
+        ui.group(|ui| {
+            let remove = Self::header(
+                ui,
+                |ui| {
+                    let mut name = light.name.clone().unwrap_or_default();
+                    if ui
+                        .add(
+                            TextEdit::singleline(&mut name)
+                                .hint_text(format!("{} {n}", t!("light")))
+                                .font(TextStyle::Monospace)
+                                .desired_width(150.0),
+                        )
+                        .on_hover_text(t!("name_hint"))
+                        .changed()
+                    {
+                        // an empty name falls back to the numbered label
+                        light.name = (!name.is_empty()).then_some(name);
+                    }
+                },
+                true,
+                &t!("remove_light_hint"),
+                Some(&t!("duplicate_light_hint")),
+            );
 
-                        ui.horizontal(|ui| {
-                            ui.label(
-                                RichText::new(format!("{} ({} ▲)", o.name, o.triangles.len()))
-                                    .size(14.0)
-                                    .family(FontFamily::Monospace),
-                            );
-                            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                                if ui
-                                    .add_sized(
-                                        [20.0, 20.0],
-                                        ImageButton::new(include_image!(
-                                            "../../res/icons/trash-solid.svg"
-                                        ))
-                                        .tint(hex_color!("#cc0000")),
-                                    )
-                                    .clicked()
-                                {
-                                    objects_to_remove.push(n);
-                                }
-                            });
-                        });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut light.enabled, t!("enabled"))
+                    .on_hover_text(t!("light_enabled_hint"));
 
-                        ui.label(format!("{}:", t!("position")));
+                let soloed = *solo == Some(n);
+                if ui
+                    .selectable_label(soloed, t!("solo"))
+                    .on_hover_text(t!("solo_hint"))
+                    .clicked()
+                {
+                    *solo = (!soloed).then_some(n);
+                }
+            });
 
-                        xyz_drag_value(ui, &mut o.translation);
+            ui.label(format!("{}:", t!("position")));
 
-                        ui.label(format!("{}:", t!("rotation")));
+            xyz_drag_value(ui, &mut light.position);
 
-                        ui.horizontal(|ui| {
-                            let (mut x, mut y, mut z) = o.rotation.euler_angles();
-
-                            [("x", &mut x), ("y", &mut y), ("z", &mut z)]
-                                .iter_mut()
-                                .any(|(prefix, angle)| {
-                                    ui.add(
-                                        DragValue::new(*angle)
-                                            .speed(0.01)
-                                            .custom_formatter(|f, _| {
-                                                format!("{:.1}°", f.to_degrees())
-                                            })
-                                            .prefix(format!("{prefix}: ")),
-                                    )
-                                    .changed()
-                                })
-                                .then(|| {
-                                    o.rotation =
-                                        nalgebra::UnitQuaternion::from_euler_angles(x, y, z);
-                                })
-                        });
+            ui.label(format!("{}:", t!("intensity")));
 
-                        ui.label(format!("{}:", t!("scale")));
+            ui.add(Slider::new(&mut light.intensity, 0.0..=100.0).clamping(SliderClamping::Edits));
 
-                        xyz_drag_value(ui, &mut o.scale);
-                    }
+            ui.label(format!("{}:", t!("color")));
 
-                    for o in objects_to_remove {
-                        scene.objects.remove(o);
+            color_picker::color_edit_button_rgb(ui, light.color.as_mut());
+
+            ui.label(format!("{}:", t!("light_linking")))
+                .on_hover_text(t!("light_linking_hint"));
+            ui.horizontal(|ui| {
+                Self::linked_objects(ui, &t!("light_include"), &mut light.include, &objects);
+                Self::linked_objects(ui, &t!("light_exclude"), &mut light.exclude, &objects);
+            });
+
+            remove
+        })
+        .inner
+    }
+
+    /// Menu to select the objects of a light linking list, by id and name
+    fn linked_objects(ui: &mut Ui, label: &str, linked: &mut Vec<u64>, objects: &[(u64, String)]) {
+        ui.menu_button(format!("{label} ({})", linked.len()), |ui| {
+            // ids of removed objects stay listed until they are unchecked
+            let missing = linked
+                .iter()
+                .filter(|id| !objects.iter().any(|(o, _)| o == *id))
+                .map(|&id| (id, format!("#{id}")))
+                .collect::<Vec<_>>();
+            for (id, name) in objects.iter().chain(&missing) {
+                let mut checked = linked.contains(id);
+                if ui.checkbox(&mut checked, name).changed() {
+                    if checked {
+                        linked.push(*id);
+                    } else {
+                        linked.retain(|o| o != id);
                     }
+                }
+            }
+        });
+    }
 
-                    ui.separator();
-                    ui.vertical_centered(|ui| {
-                        if ui
-                            .add(Button::new(RichText::new(t!("add_object"))).frame(false))
-                            .clicked()
-                        {
-                            let mut dialog =
-                                FileDialog::open_file(None).show_files_filter(Box::new(|path| {
-                                    path.extension()
-                                        .is_some_and(|ext| ext.eq_ignore_ascii_case("obj"))
-                                }));
-                            dialog.open();
-                            self.object_dialog = Some(dialog);
-                        }
+    /// Transform of a group, returns whether it should be removed
+    fn group(ui: &mut Ui, scene: &mut Scene, g: usize, snap: &Snap) -> bool {
+        let group = &mut scene.groups[g];
 
-                        if let Some(dialog) = &mut self.object_dialog {
-                            if dialog.show(ui.ctx()).selected() {
-                                if let Some(file) = dialog.path() {
-                                    match Object::from_obj(
-                                        file,
-                                        Translation3::identity(),
-                                        UnitQuaternion::identity(),
-                                        Scale3::identity(),
-                                    ) {
-                                        Ok(object) => {
-                                            scene.objects.push(object);
-                                        }
-                                        Err(e) => warn!("Failed to load object: {}", e),
-                                    }
-                                }
-                            }
-                        }
-                    });
-                });
+        let remove = ui
+            .group(|ui| {
+                let remove = Self::header(
+                    ui,
+                    |ui| {
+                        ui.add(
+                            TextEdit::singleline(&mut group.name)
+                                .font(TextStyle::Monospace)
+                                .desired_width(150.0),
+                        )
+                        .on_hover_text(t!("name_hint"));
+                    },
+                    true,
+                    &t!("remove_group_hint"),
+                    None,
+                ) == Some(HeaderAction::Remove);
+
+                let transform = Transform {
+                    translation: group.translation,
+                    rotation: group.rotation,
+                    scale: group.scale,
+                };
+                if let Some(transform) =
+                    Self::clipboard(ui, &transform, Transform::to_yaml, Transform::from_yaml)
+                {
+                    group.translation = transform.translation;
+                    group.rotation = transform.rotation;
+                    group.scale = transform.scale;
+                }
+
+                ui.label(format!("{}:", t!("position")));
+                snapped_xyz_drag_value(ui, &mut group.translation, |v| snap.translation(v));
+                ui.label(format!("{}:", t!("rotation")));
+                rotation_drag_value(ui, &mut group.rotation, |a| snap.rotation(a));
+                ui.label(format!("{}:", t!("scale")));
+                snapped_xyz_drag_value(ui, &mut group.scale, |v| snap.scale(v));
+
+                remove
+            })
+            .inner;
+
+        scene.update_transforms();
+
+        remove
+    }
+
+    /// Transform of an object, returns whether it should be removed or duplicated
+    fn object(ui: &mut Ui, scene: &mut Scene, n: usize, snap: &Snap) -> Option<HeaderAction> {
+        let o = &mut scene.objects[n];
+
+        ui.group(|ui| {
+            let remove = Self::header(
+                ui,
+                |ui| {
+                    ui.add(
+                        TextEdit::singleline(&mut o.name)
+                            .font(TextStyle::Monospace)
+                            .desired_width(150.0),
+                    )
+                    .on_hover_text(t!("name_hint"));
+                    ui.label(
+                        RichText::new(format!("({} ▲)", o.mesh.triangles.len()))
+                            .size(14.0)
+                            .family(FontFamily::Monospace),
+                    );
+                },
+                true,
+                &t!("remove_object_hint"),
+                Some(&t!("duplicate_object_hint")),
+            );
+
+            let transform = Transform {
+                translation: o.translation,
+                rotation: o.rotation,
+                scale: o.scale,
+            };
+            if let Some(transform) =
+                Self::clipboard(ui, &transform, Transform::to_yaml, Transform::from_yaml)
+            {
+                o.translation = transform.translation;
+                o.rotation = transform.rotation;
+                o.scale = transform.scale;
+            }
+
+            ui.horizontal_wrapped(|ui| {
+                if ui
+                    .button(t!("reset_transform"))
+                    .on_hover_text(t!("reset_transform_hint"))
+                    .clicked()
+                {
+                    o.reset_transform();
+                }
+                if ui
+                    .button(t!("center_at_origin"))
+                    .on_hover_text(t!("center_at_origin_hint"))
+                    .clicked()
+                {
+                    o.center_at_origin();
+                }
+                if ui
+                    .button(t!("normalize_scale"))
+                    .on_hover_text(t!("normalize_scale_hint"))
+                    .clicked()
+                {
+                    o.normalize_scale();
+                }
             });
+
+            ui.label(format!("{}:", t!("position")));
+
+            snapped_xyz_drag_value(ui, &mut o.translation, |v| snap.translation(v));
+
+            ui.label(format!("{}:", t!("rotation")));
+
+            rotation_drag_value(ui, &mut o.rotation, |a| snap.rotation(a));
+
+            ui.label(format!("{}:", t!("scale")));
+
+            snapped_xyz_drag_value(ui, &mut o.scale, |v| snap.scale(v));
+
+            // generated objects have no model file
+            if !o.path.as_os_str().is_empty() {
+                Self::import(ui, o);
+            }
+
+            remove
+        })
+        .inner
+    }
+
+    /// Up axis and unit of the model file, the mesh is converted again when they change
+    fn import(ui: &mut Ui, o: &mut Object) {
+        let mut import = o.import;
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut import.z_up, t!("z_up"))
+                .on_hover_text(t!("z_up_hint"));
+            ComboBox::from_label(t!("unit"))
+                .selected_text(
+                    UNITS
+                        .iter()
+                        .find(|(_, scale)| (scale - import.unit_scale).abs() < f32::EPSILON)
+                        .map_or_else(
+                            || format!("{}", import.unit_scale),
+                            |(unit, _)| (*unit).to_owned(),
+                        ),
+                )
+                .show_ui(ui, |ui| {
+                    for (unit, scale) in UNITS {
+                        ui.selectable_value(&mut import.unit_scale, scale, unit);
+                    }
+                })
+                .response
+                .on_hover_text(t!("unit_hint"));
         });
+
+        if import != o.import {
+            o.set_import(import);
+        }
+    }
+
+    /// Transform of the selected object, changes are applied to all selected objects
+    /// as offsets, returns whether they should be removed
+    fn objects(
+        ui: &mut Ui,
+        scene: &mut Scene,
+        n: usize,
+        objects: &BTreeSet<usize>,
+        snap: &Snap,
+    ) -> bool {
+        let o = &scene.objects[n];
+        let (mut translation, mut rotation, mut scale) = (o.translation, o.rotation, o.scale);
+
+        let remove = ui
+            .group(|ui| {
+                let remove = Self::header(
+                    ui,
+                    |ui| {
+                        ui.label(
+                            RichText::new(t!("objects_selected", count = objects.len()))
+                                .size(14.0)
+                                .family(FontFamily::Monospace),
+                        );
+                    },
+                    true,
+                    &t!("remove_objects_hint"),
+                    None,
+                ) == Some(HeaderAction::Remove);
+
+                ui.label(RichText::new(t!("batch_transform_hint")).small());
+
+                ui.label(format!("{}:", t!("position")));
+                snapped_xyz_drag_value(ui, &mut translation, |v| snap.translation(v));
+                ui.label(format!("{}:", t!("rotation")));
+                rotation_drag_value(ui, &mut rotation, |a| snap.rotation(a));
+                ui.label(format!("{}:", t!("scale")));
+                snapped_xyz_drag_value(ui, &mut scale, |v| snap.scale(v));
+
+                remove
+            })
+            .inner;
+
+        let o = &scene.objects[n];
+        let offset = translation.vector - o.translation.vector;
+        let turn = rotation * o.rotation.inverse();
+        let grow = scale.vector - o.scale.vector;
+        let rotated = rotation != o.rotation;
+
+        for &i in objects {
+            let object = &mut scene.objects[i];
+            object.translation.vector += offset;
+            if rotated {
+                object.rotation = turn * object.rotation;
+            }
+            object.scale.vector += grow;
+        }
+
+        remove
     }
 
     const fn format_render_size(size: (u32, u32)) -> &'static str {
@@ -1,40 +1,794 @@
-use crate::{raytracer::render::Render, scene::Scene};
-use egui::{pos2, Color32, CursorIcon, Frame, Rect, Rounding, Sense, Stroke, Ui, Vec2};
+use super::toasts;
+use egui::{
+    pos2, vec2, Align2, Button, Color32, ColorImage, ComboBox, CursorIcon, DragValue, FontId,
+    Frame, Painter, Pos2, Rect, Rounding, Sense, Shape, Slider, Stroke, TextureHandle,
+    TextureOptions, Ui, Vec2,
+};
+use egui_file::FileDialog;
+use image::{RgbImage, Rgba32FImage};
+use log::warn;
+use rayon::{
+    iter::{
+        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+    },
+    slice::ParallelSlice,
+};
+use rust_i18n::t;
+use std::{
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+};
+use trayracer_core::{
+    raytracer::{
+        lut::Lut,
+        post::{Clipping, Histogram, Post, HISTOGRAM_BINS},
+        render::{Region, Render, SampleImage},
+    },
+    scene::{Color, Scene},
+};
 
+/// Image shown in the render result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pass {
+    Beauty,
+    /// Samples taken per pixel as false colors
+    Samples,
+}
+
+impl Pass {
+    fn label(self) -> String {
+        match self {
+            Self::Beauty => t!("beauty").to_string(),
+            Self::Samples => t!("sample_heatmap").to_string(),
+        }
+    }
+}
+
+/// Comparison of the render with the previous one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compare {
+    Off,
+    /// Previous render left of a draggable wipe, current render right of it
+    Wipe,
+    /// Absolute difference of both renders
+    Difference,
+}
+
+impl Compare {
+    fn label(self) -> String {
+        match self {
+            Self::Off => t!("off").to_string(),
+            Self::Wipe => t!("wipe").to_string(),
+            Self::Difference => t!("difference").to_string(),
+        }
+    }
+}
+
+/// Amplification of the difference view, small differences would be invisible otherwise
+const DIFFERENCE_GAIN: u16 = 4;
+
+/// Zoom presets in percent of the image pixels to the screen pixels
+const ZOOM_PRESETS: [f32; 7] = [25.0, 50.0, 100.0, 200.0, 400.0, 800.0, 1600.0];
+
+/// Range of the zoom in percent
+const ZOOM_RANGE: std::ops::RangeInclusive<f32> = 1.0..=3200.0;
+
+/// Zoom change per point scrolled
+const ZOOM_SPEED: f32 = 0.002;
+
+/// Distance in points within which the pointer grabs the wipe
+const WIPE_GRAB_DISTANCE: f32 = 8.0;
+
+// view toggles and pointer interactions, not a state machine
+#[allow(clippy::struct_excessive_bools)]
 pub struct RenderResult {
-    // zoom factor where 0 is no zoom
-    zoom: f32,
+    /// Texture the render is shown in while rendering
+    texture: TextureHandle,
+    /// Screen points per image pixel, none to fit the image into the view
+    zoom: Option<f32>,
+    /// Offset of the image center from the center of the view
     position: Vec2,
+    /// Zoom that fits the image into the view in the last frame
+    fit_zoom: f32,
+    pass: Pass,
+    heatmap_texture: Option<TextureHandle>,
+    /// Progress the heatmap was computed for
+    heatmap_key: Option<u16>,
+    /// Dialog to select a LUT file
+    lut_dialog: Option<FileDialog>,
+    show_histogram: bool,
+    /// Show zebra stripes over blown out and crushed pixels
+    show_clipping: bool,
+    histogram: Option<Histogram>,
+    clipping_texture: Option<TextureHandle>,
+    /// Progress and post processing gain the analysis was computed for
+    analysis_key: Option<(u16, Color)>,
+    compare: Compare,
+    /// Position of the wipe in the range [0, 1] of the image width
+    wipe: f32,
+    dragging_wipe: bool,
+    /// Where the image was drawn in the last frame, to grab the wipe
+    image_rect: Option<Rect>,
+    /// Drag a region to render again instead of panning
+    selecting_region: bool,
+    /// Pixel where the region drag started
+    region_start: Option<(u32, u32)>,
+    region: Option<Region>,
+    /// Samples per pixel to render the region with
+    region_samples: u32,
+    /// Previous render uploaded to the GPU, by the address of the image
+    previous_texture: Option<(usize, TextureHandle)>,
+    difference_texture: Option<TextureHandle>,
+    /// Previous render, progress and post processing gain the difference was computed for
+    difference_key: Option<(usize, u16, Color)>,
+}
+
+/// Absolute difference of two renders, none if their resolutions differ
+fn difference(a: &RgbImage, b: &RgbImage) -> Option<ColorImage> {
+    if a.dimensions() != b.dimensions() {
+        return None;
+    }
+
+    let pixels = a
+        .as_raw()
+        .par_chunks(3)
+        .zip(b.as_raw().par_chunks(3))
+        .map(|(a, b)| {
+            let [r, g, b] = [0, 1, 2]
+                .map(|c| (u16::from(a[c].abs_diff(b[c])) * DIFFERENCE_GAIN).min(255) as u8);
+            Color32::from_rgb(r, g, b)
+        })
+        .collect();
+
+    Some(ColorImage {
+        size: [a.width() as usize, a.height() as usize],
+        pixels,
+    })
+}
+
+/// Zebra stripe mask of blown out (red) and crushed (blue) pixels
+fn clipping_mask(post: &Post, raw: &Rgba32FImage) -> ColorImage {
+    let (width, height) = raw.dimensions();
+
+    let pixels = (0..width * height)
+        .into_par_iter()
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+
+            // diagonal stripes
+            if (x + y) / 4 % 2 == 0 {
+                return Color32::TRANSPARENT;
+            }
+
+            let [red, green, blue, _] = raw.get_pixel(x, y).0;
+
+            match post.clipping(Color::new(red, green, blue)) {
+                Some(Clipping::Blown) => Color32::from_rgba_unmultiplied(255, 0, 0, 160),
+                Some(Clipping::Crushed) => Color32::from_rgba_unmultiplied(0, 0, 255, 160),
+                None => Color32::TRANSPARENT,
+            }
+        })
+        .collect();
+
+    ColorImage {
+        size: [width as usize, height as usize],
+        pixels,
+    }
+}
+
+/// False color ramp from blue (few samples) over cyan, green
+/// and yellow to red (max samples), unrendered pixels stay transparent
+fn sample_heatmap(samples: &SampleImage, max: u32) -> ColorImage {
+    const RAMP: [[f32; 3]; 5] = [
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ];
+
+    let (width, height) = samples.dimensions();
+
+    let pixels = samples
+        .par_iter()
+        .map(|&count| {
+            if count == 0 {
+                return Color32::TRANSPARENT;
+            }
+
+            let position =
+                (count as f32 / max.max(1) as f32).clamp(0.0, 1.0) * (RAMP.len() - 1) as f32;
+            let i = (position as usize).min(RAMP.len() - 2);
+            let fraction = position - i as f32;
+            let [red, green, blue] = [0, 1, 2]
+                .map(|c| (fraction.mul_add(RAMP[i + 1][c] - RAMP[i][c], RAMP[i][c]) * 255.0) as u8);
+
+            Color32::from_rgb(red, green, blue)
+        })
+        .collect();
+
+    ColorImage {
+        size: [width as usize, height as usize],
+        pixels,
+    }
+}
+
+fn paint_histogram(painter: &Painter, rect: Rect, histogram: &Histogram) {
+    let area = Rect::from_min_size(rect.right_top() + vec2(-266.0, 10.0), vec2(256.0, 110.0));
+    painter.rect_filled(area, 4.0, Color32::from_black_alpha(160));
+
+    let graph = area.shrink(6.0);
+    let max = histogram.max().max(1) as f32;
+
+    for (bins, color) in [
+        (
+            &histogram.red,
+            Color32::from_rgba_unmultiplied(255, 60, 60, 200),
+        ),
+        (
+            &histogram.green,
+            Color32::from_rgba_unmultiplied(60, 255, 60, 200),
+        ),
+        (
+            &histogram.blue,
+            Color32::from_rgba_unmultiplied(60, 60, 255, 200),
+        ),
+        (&histogram.luminance, Color32::WHITE),
+    ] {
+        let points = bins
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| {
+                pos2(
+                    (i as f32 / (HISTOGRAM_BINS - 1) as f32).mul_add(graph.width(), graph.left()),
+                    (count as f32 / max).mul_add(-graph.height(), graph.bottom()),
+                )
+            })
+            .collect();
+
+        painter.add(Shape::line(points, Stroke::new(1.5, color)));
+    }
+
+    painter.text(
+        graph.left_top(),
+        Align2::LEFT_TOP,
+        format!(
+            "{}: {}  {}: {}",
+            t!("blown"),
+            histogram.blown,
+            t!("crushed"),
+            histogram.crushed
+        ),
+        FontId::monospace(10.0),
+        Color32::WHITE,
+    );
 }
 
 impl RenderResult {
-    pub const fn new() -> Self {
+    pub const fn new(texture: TextureHandle) -> Self {
         Self {
-            zoom: 0.0,
+            texture,
+            zoom: None,
             position: Vec2::ZERO,
+            fit_zoom: 1.0,
+            pass: Pass::Beauty,
+            heatmap_texture: None,
+            heatmap_key: None,
+            lut_dialog: None,
+            show_histogram: false,
+            show_clipping: false,
+            histogram: None,
+            clipping_texture: None,
+            analysis_key: None,
+            compare: Compare::Off,
+            wipe: 0.5,
+            dragging_wipe: false,
+            image_rect: None,
+            selecting_region: false,
+            region_start: None,
+            region: None,
+            region_samples: 0,
+            previous_texture: None,
+            difference_texture: None,
+            difference_key: None,
+        }
+    }
+
+    /// Fit the image into the view and center it
+    const fn zoom_to_fit(&mut self) {
+        self.zoom = None;
+        self.position = Vec2::ZERO;
+    }
+
+    /// Zoom in percent of the image pixels to the screen pixels
+    fn zoom_percent(&self, ui: &Ui) -> f32 {
+        self.zoom.unwrap_or(self.fit_zoom) * ui.ctx().pixels_per_point() * 100.0
+    }
+
+    /// Change the zoom, keeping the image point at the anchor (from the view center) in place
+    fn zoom_at(&mut self, ui: &Ui, percent: f32, anchor: Vec2) {
+        let from = self.zoom.unwrap_or(self.fit_zoom);
+        let to = percent.clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end())
+            / 100.0
+            / ui.ctx().pixels_per_point();
+
+        self.position = anchor - (anchor - self.position) * (to / from);
+        self.zoom = Some(to);
+    }
+
+    fn zoom_options(&mut self, ui: &mut Ui) {
+        if ui
+            .selectable_label(self.zoom.is_none(), t!("zoom_fit"))
+            .on_hover_text(t!("zoom_fit_hint"))
+            .clicked()
+        {
+            self.zoom_to_fit();
+        }
+
+        if ui
+            .button("1:1")
+            .on_hover_text(t!("zoom_pixels_hint"))
+            .clicked()
+        {
+            self.zoom_at(ui, 100.0, Vec2::ZERO);
         }
+
+        let current = self.zoom_percent(ui);
+        ComboBox::from_id_salt("zoom")
+            .selected_text(format!("{current:.0}%"))
+            .width(70.0)
+            .show_ui(ui, |ui| {
+                for preset in ZOOM_PRESETS {
+                    if ui
+                        .selectable_label((current - preset).abs() < 0.5, format!("{preset}%"))
+                        .clicked()
+                    {
+                        self.zoom_at(ui, preset, Vec2::ZERO);
+                    }
+                }
+            })
+            .response
+            .on_hover_text(t!("zoom_hint"));
     }
 
-    pub fn show(&mut self, ui: &mut Ui, scene: &Scene, render: &Render) {
+    fn region_options(&mut self, ui: &mut Ui, scene: &Scene, render: &mut Render) {
+        ui.toggle_value(&mut self.selecting_region, t!("select_region"))
+            .on_hover_text(t!("select_region_hint"));
+
+        let Some(region) = self.region else {
+            return;
+        };
+
+        ui.label(format!("{}x{}", region.width, region.height));
+        ui.add(
+            DragValue::new(&mut self.region_samples)
+                .range(1..=u32::MAX)
+                .suffix(" spp"),
+        )
+        .on_hover_text(t!("region_samples_hint"));
+
+        let finished =
+            render.thread.is_none() && render.progress.load(Ordering::Relaxed) == u16::MAX;
+        if ui
+            .add_enabled(finished, Button::new(t!("render_region")))
+            .on_hover_text(t!("render_region_hint"))
+            .clicked()
+        {
+            render.render_region(scene, region, self.region_samples);
+        }
+
+        if ui.button("✖").on_hover_text(t!("clear_region")).clicked() {
+            self.region = None;
+        }
+    }
+
+    /// Pixel of the render under a position on the screen, clamped to the render
+    fn pixel(&self, pos: Pos2, size: Vec2) -> Option<(u32, u32)> {
+        let rect = self.image_rect?;
+        let uv = (pos - rect.min) / rect.size();
+        Some((
+            (uv.x * size.x).clamp(0.0, size.x - 1.0) as u32,
+            (uv.y * size.y).clamp(0.0, size.y - 1.0) as u32,
+        ))
+    }
+
+    /// Outline the region to render again with its resolution
+    fn paint_region(&self, painter: &Painter, image_rect: Rect, size: Vec2) {
+        let Some(region) = self.region else {
+            return;
+        };
+
+        let to_screen = |x: u32, y: u32| {
+            image_rect.min + vec2(x as f32 / size.x, y as f32 / size.y) * image_rect.size()
+        };
+        let rect = Rect::from_min_max(
+            to_screen(region.x, region.y),
+            to_screen(region.x + region.width, region.y + region.height),
+        );
+
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.5, Color32::YELLOW));
+        painter.text(
+            rect.left_top() + vec2(0.0, -2.0),
+            Align2::LEFT_BOTTOM,
+            format!("{}x{}", region.width, region.height),
+            FontId::monospace(10.0),
+            Color32::YELLOW,
+        );
+    }
+
+    /// Upload the previous render and recompute the difference
+    /// when new tiles have been rendered or the post processing has changed
+    fn update_comparison(&mut self, ui: &Ui, render: &Render) {
+        if self.compare == Compare::Off {
+            return;
+        }
+        let Some(previous) = &render.previous else {
+            return;
+        };
+        let address = Arc::as_ptr(previous) as usize;
+
+        if self.previous_texture.as_ref().map(|(a, _)| *a) != Some(address) {
+            let texture = ui.ctx().load_texture(
+                "previous_render",
+                ColorImage::from_rgb(
+                    [previous.width() as usize, previous.height() as usize],
+                    previous.as_raw(),
+                ),
+                TextureOptions::default(),
+            );
+            self.previous_texture = Some((address, texture));
+        }
+
+        if self.compare != Compare::Difference {
+            return;
+        }
+
+        let key = (
+            address,
+            render.progress.load(Ordering::Relaxed),
+            render.post.read().gain(),
+        );
+        if self.difference_key == Some(key) {
+            return;
+        }
+        self.difference_key = Some(key);
+
+        let Some(difference) = difference(previous, &render.image.lock()) else {
+            warn!("The previous render has a different resolution and can't be compared");
+            self.difference_texture = None;
+            return;
+        };
+
+        match &mut self.difference_texture {
+            Some(texture) => texture.set(difference, TextureOptions::NEAREST),
+            None => {
+                self.difference_texture = Some(ui.ctx().load_texture(
+                    "render_difference",
+                    difference,
+                    TextureOptions::NEAREST,
+                ));
+            }
+        }
+    }
+
+    /// Paint the previous render left of the wipe, or the difference over the image
+    fn paint_comparison(&self, painter: &Painter, image_rect: Rect) {
+        let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+
+        match self.compare {
+            Compare::Off => {}
+            Compare::Wipe => {
+                let Some((_, previous)) = &self.previous_texture else {
+                    return;
+                };
+                let x = image_rect.width().mul_add(self.wipe, image_rect.left());
+
+                painter
+                    .with_clip_rect(
+                        Rect::from_min_max(image_rect.min, pos2(x, image_rect.max.y))
+                            .intersect(painter.clip_rect()),
+                    )
+                    .image(previous.id(), image_rect, uv, Color32::WHITE);
+
+                let top = image_rect.top().max(painter.clip_rect().top());
+                let bottom = image_rect.bottom().min(painter.clip_rect().bottom());
+                painter.line_segment(
+                    [pos2(x, top), pos2(x, bottom)],
+                    Stroke::new(2.0, Color32::WHITE),
+                );
+                for (text, align, offset) in
+                    [("A", Align2::RIGHT_TOP, -6.0), ("B", Align2::LEFT_TOP, 6.0)]
+                {
+                    painter.text(
+                        pos2(x + offset, top + 6.0),
+                        align,
+                        text,
+                        FontId::proportional(14.0),
+                        Color32::WHITE,
+                    );
+                }
+            }
+            Compare::Difference => {
+                if let Some(texture) = &self.difference_texture {
+                    painter.image(texture.id(), image_rect, uv, Color32::WHITE);
+                }
+            }
+        }
+    }
+
+    /// Recompute histogram and clipping mask when new tiles
+    /// have been rendered or the post processing has changed
+    fn update_analysis(&mut self, ui: &Ui, render: &Render) {
+        if !self.show_histogram && !self.show_clipping {
+            return;
+        }
+
+        let post = render.post.read().clone();
+        let key = (render.progress.load(Ordering::Relaxed), post.gain());
+
+        if self.analysis_key == Some(key) {
+            return;
+        }
+        self.analysis_key = Some(key);
+
+        let raw = render.raw.lock();
+        self.histogram = Some(post.histogram(&raw));
+        let mask = clipping_mask(&post, &raw);
+        drop(raw);
+
+        match &mut self.clipping_texture {
+            Some(texture) => texture.set(mask, TextureOptions::NEAREST),
+            None => {
+                self.clipping_texture = Some(ui.ctx().load_texture(
+                    "clipping",
+                    mask,
+                    TextureOptions::NEAREST,
+                ));
+            }
+        }
+    }
+
+    /// Recompute the sample heatmap when new tiles have been rendered
+    fn update_heatmap(&mut self, ui: &Ui, scene: &Scene, render: &Render) {
+        if self.pass != Pass::Samples {
+            return;
+        }
+
+        let key = render.progress.load(Ordering::Relaxed);
+        if self.heatmap_key == Some(key) {
+            return;
+        }
+        self.heatmap_key = Some(key);
+
+        let heatmap = sample_heatmap(&render.samples.lock(), scene.settings.samples);
+
+        match &mut self.heatmap_texture {
+            Some(texture) => texture.set(heatmap, TextureOptions::NEAREST),
+            None => {
+                self.heatmap_texture = Some(ui.ctx().load_texture(
+                    "sample_heatmap",
+                    heatmap,
+                    TextureOptions::NEAREST,
+                ));
+            }
+        }
+    }
+
+    fn post_options(&mut self, ui: &mut Ui, render: &Render) {
+        if let Some(dialog) = &mut self.lut_dialog {
+            if dialog.show(ui.ctx()).selected() {
+                if let Some(path) = dialog.path() {
+                    match Lut::load(path) {
+                        Ok(lut) => {
+                            render.post.write().lut = Some(Arc::new(lut));
+                            render.apply_post();
+                        }
+                        Err(e) => {
+                            warn!("Failed to load LUT: {e:?}");
+                            toasts::error(
+                                ui.ctx(),
+                                t!("load_lut_failed", error = format!("{e:#}")),
+                            );
+                        }
+                    }
+                }
+
+                self.lut_dialog = None;
+            }
+        }
+
+        ui.horizontal(|ui| {
+            let changed = {
+                let mut post = render.post.write();
+
+                // the auto exposure sets the exposure when a render finishes
+                let exposure = ui
+                    .add_enabled(
+                        !post.auto_exposure,
+                        Slider::new(&mut post.exposure, -5.0..=5.0)
+                            .text(t!("exposure"))
+                            .suffix(" EV"),
+                    )
+                    .changed();
+                let auto_exposure = ui
+                    .checkbox(&mut post.auto_exposure, t!("auto_exposure"))
+                    .on_hover_text(t!("auto_exposure_hint"))
+                    .changed();
+                if auto_exposure && post.auto_exposure && render.thread.is_none() {
+                    post.expose(&render.raw.lock());
+                }
+                let temperature = ui
+                    .add(Slider::new(&mut post.temperature, -1.0..=1.0).text(t!("temperature")))
+                    .changed();
+                let tint = ui
+                    .add(Slider::new(&mut post.tint, -1.0..=1.0).text(t!("tint")))
+                    .changed();
+
+                drop(post);
+
+                exposure || auto_exposure || temperature || tint
+            };
+
+            let reset = ui.button(t!("reset")).clicked();
+            if reset {
+                let mut post = render.post.write();
+                post.exposure = 0.0;
+                post.auto_exposure = false;
+                post.temperature = 0.0;
+                post.tint = 0.0;
+            }
+
+            // re-tone-map the raw render without rendering again
+            if changed || reset {
+                render.apply_post();
+            }
+
+            ui.separator();
+
+            ui.label(format!("{}:", t!("lut")));
+
+            let lut = render.post.read().lut.clone();
+            match lut {
+                Some(lut) => {
+                    ui.label(
+                        lut.path
+                            .file_name()
+                            .map_or_else(String::new, |name| name.to_string_lossy().to_string()),
+                    );
+
+                    ui.button("✖")
+                        .on_hover_text(t!("clear_lut"))
+                        .clicked()
+                        .then(|| {
+                            render.post.write().lut = None;
+                            render.apply_post();
+                        });
+                }
+                None => {
+                    ui.label(t!("none"));
+                }
+            }
+
+            ui.button(t!("load_lut")).clicked().then(|| {
+                let mut dialog = FileDialog::open_file(None).filename_filter(Box::new(|p| {
+                    Path::new(p)
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("cube"))
+                }));
+                dialog.open();
+                self.lut_dialog = Some(dialog);
+            });
+        });
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn show(&mut self, ui: &mut Ui, scene: &Scene, render: &mut Render) {
+        self.post_options(ui, render);
+
+        ui.horizontal(|ui| {
+            ComboBox::from_label(t!("pass"))
+                .selected_text(self.pass.label())
+                .show_ui(ui, |ui| {
+                    for pass in [Pass::Beauty, Pass::Samples] {
+                        ui.selectable_value(&mut self.pass, pass, pass.label());
+                    }
+                });
+            ui.separator();
+            ui.checkbox(&mut self.show_histogram, t!("histogram"));
+            ui.checkbox(&mut self.show_clipping, t!("clipping"));
+            ui.separator();
+            ui.add_enabled_ui(render.previous.is_some(), |ui| {
+                ComboBox::from_label(t!("compare"))
+                    .selected_text(self.compare.label())
+                    .show_ui(ui, |ui| {
+                        for compare in [Compare::Off, Compare::Wipe, Compare::Difference] {
+                            ui.selectable_value(&mut self.compare, compare, compare.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("compare_hint"))
+                    .on_disabled_hover_text(t!("compare_disabled_hint"));
+            });
+            ui.separator();
+            self.zoom_options(ui);
+            ui.separator();
+            self.region_options(ui, scene, render);
+        });
+
+        self.update_analysis(ui, render);
+        self.update_heatmap(ui, scene, render);
+        self.update_comparison(ui, render);
+
         Frame::canvas(ui.style()).outer_margin(10.0).show(ui, |ui| {
             let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::drag());
 
             let response = response.on_hover_and_drag_cursor(CursorIcon::Grab);
 
-            // Check if the dialog is being hovered over or active
-            if response.has_focus() || response.hovered() {
-                self.zoom += ui.input(|i| i.raw_scroll_delta.y);
-                self.zoom = self.zoom.clamp(
-                    -response.rect.width().min(response.rect.height()) / 4.0,
-                    f32::INFINITY,
-                );
-                self.position += response.drag_delta();
+            // a render reopened from the history may have another resolution than the scene
+            let (width, height) = Some(render.info.resolution)
+                .filter(|&(w, h)| w > 0 && h > 0)
+                .unwrap_or_else(|| scene.camera().resolution);
+            let image_size = vec2(width as f32, height as f32);
+            self.fit_zoom = (response.rect.width() / image_size.x)
+                .min(response.rect.height() / image_size.y)
+                .max(f32::EPSILON);
+
+            if response.drag_started() {
+                let origin = ui.input(|i| i.pointer.press_origin());
+                self.dragging_wipe = self.compare == Compare::Wipe
+                    && self.previous_texture.is_some()
+                    && origin.zip(self.image_rect).is_some_and(|(origin, rect)| {
+                        (origin.x - rect.width().mul_add(self.wipe, rect.left())).abs()
+                            <= WIPE_GRAB_DISTANCE
+                    });
+                self.region_start = origin
+                    .filter(|_| self.selecting_region && !self.dragging_wipe)
+                    .and_then(|origin| self.pixel(origin, image_size));
+            }
+            if response.drag_stopped() {
+                self.dragging_wipe = false;
+                self.region_start = None;
             }
 
-            response.double_clicked().then(|| {
-                self.zoom = 0.0;
-                self.position = Vec2::ZERO;
-            });
+            if let Some((pos, rect)) = response
+                .interact_pointer_pos()
+                .zip(self.image_rect)
+                .filter(|_| self.dragging_wipe)
+            {
+                self.wipe = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+            } else if let Some(((x0, y0), (x1, y1))) = self.region_start.zip(
+                response
+                    .interact_pointer_pos()
+                    .and_then(|pos| self.pixel(pos, image_size)),
+            ) {
+                if self.region.is_none() {
+                    self.region_samples = scene.settings.samples.saturating_mul(4);
+                }
+                self.region = Some(Region {
+                    x: x0.min(x1),
+                    y: y0.min(y1),
+                    width: x0.abs_diff(x1) + 1,
+                    height: y0.abs_diff(y1) + 1,
+                });
+                ui.ctx().set_cursor_icon(CursorIcon::Crosshair);
+            } else if response.has_focus() || response.hovered() {
+                // Check if the dialog is being hovered over or active
+                let scroll = ui.input(|i| i.raw_scroll_delta.y);
+                if let Some(pointer) = response.hover_pos().filter(|_| scroll != 0.0) {
+                    // zoom towards the pointer
+                    let percent = self.zoom_percent(ui) * (scroll * ZOOM_SPEED).exp();
+                    self.zoom_at(ui, percent, pointer - response.rect.center());
+                }
+
+                if response.dragged() {
+                    // panning leaves the fit
+                    self.zoom = Some(self.zoom.unwrap_or(self.fit_zoom));
+                    self.position += response.drag_delta();
+                }
+            }
+
+            response.double_clicked().then(|| self.zoom_to_fit());
 
             // paint gray grid
             let cell_size = 25.0;
@@ -59,35 +813,43 @@ impl RenderResult {
                 }
             }
 
-            let render_aspect = scene.camera.resolution.0 as f32 / scene.camera.resolution.1 as f32;
-            let rect = Rect::from_min_size(
-                response.rect.min,
-                // keep aspect ratio
-                Vec2::new(
-                    response
-                        .rect
-                        .width()
-                        .min(response.rect.height() * render_aspect),
-                    response
-                        .rect
-                        .height()
-                        .min(response.rect.width() / render_aspect),
-                ),
+            let image_rect = Rect::from_center_size(
+                response.rect.center() + self.position,
+                image_size * self.zoom.unwrap_or(self.fit_zoom),
             );
 
-            // center rect
-            let rect = Rect::from_min_size(
-                rect.min + (response.rect.size() - rect.size()) / 2.0,
-                rect.size(),
-            );
+            let texture = match (self.pass, &self.heatmap_texture) {
+                (Pass::Samples, Some(heatmap)) => heatmap.id(),
+                _ => self.texture.id(),
+            };
 
             painter.image(
-                render.texture.id(),
-                rect.translate(self.position)
-                    .expand2(Vec2::new(self.zoom * render_aspect, self.zoom)),
+                texture,
+                image_rect,
                 Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
                 Color32::WHITE,
             );
+
+            self.paint_comparison(&painter, image_rect);
+            self.paint_region(&painter, image_rect, image_size);
+            self.image_rect = Some(image_rect);
+
+            if let Some(texture) = self
+                .clipping_texture
+                .as_ref()
+                .filter(|_| self.show_clipping)
+            {
+                painter.image(
+                    texture.id(),
+                    image_rect,
+                    Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+            }
+
+            if let Some(histogram) = self.histogram.as_ref().filter(|_| self.show_histogram) {
+                paint_histogram(&painter, response.rect, histogram);
+            }
         });
     }
 }
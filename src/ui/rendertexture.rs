@@ -0,0 +1,50 @@
+use egui::{Color32, ColorImage, Context, ImageData, TextureHandle, TextureOptions};
+use image::RgbImage;
+use std::sync::Arc;
+use trayracer_core::{
+    profiler,
+    raytracer::render::{Region, RenderOutput},
+};
+
+/// Shows the pixels of the render in an egui texture as they arrive
+pub struct RenderTexture {
+    /// egui Texture (GPU exclusive)
+    texture: TextureHandle,
+    ctx: Context,
+}
+
+impl RenderTexture {
+    pub const fn new(texture: TextureHandle, ctx: Context) -> Self {
+        Self { texture, ctx }
+    }
+}
+
+impl RenderOutput for RenderTexture {
+    fn show(&self, image: &RgbImage) {
+        let _scope = profiler::scope("texture upload");
+        self.texture.clone().set(
+            ImageData::Color(Arc::new(ColorImage::from_rgb(
+                [image.width() as usize, image.height() as usize],
+                image.as_raw(),
+            ))),
+            TextureOptions::default(),
+        );
+        self.ctx.request_repaint();
+    }
+
+    fn show_region(&self, region: Region, pixels: &[[u8; 3]]) {
+        let _scope = profiler::scope("texture upload");
+        self.texture.clone().set_partial(
+            [region.x as usize, region.y as usize],
+            ImageData::Color(Arc::new(ColorImage {
+                size: [region.width as usize, region.height as usize],
+                pixels: pixels
+                    .iter()
+                    .map(|&[r, g, b]| Color32::from_rgb(r, g, b))
+                    .collect(),
+            })),
+            TextureOptions::default(),
+        );
+        self.ctx.request_repaint();
+    }
+}
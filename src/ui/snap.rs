@@ -0,0 +1,86 @@
+use egui::{DragValue, Ui};
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+
+/// Increments the transforms snap to in the properties and with the gizmo
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Snap {
+    pub enabled: bool,
+    /// Grid size of positions
+    pub translation: f32,
+    /// Step of rotations in degrees
+    pub rotation: f32,
+    pub scale: f32,
+}
+
+impl Snap {
+    /// Position on the grid, unchanged while snapping is off
+    pub fn translation(&self, value: f32) -> f32 {
+        Self::round(self.enabled, value, self.translation)
+    }
+
+    /// Angle in radians rounded to the rotation step
+    pub fn rotation(&self, angle: f32) -> f32 {
+        Self::round(self.enabled, angle, self.rotation.to_radians())
+    }
+
+    pub fn scale(&self, value: f32) -> f32 {
+        Self::round(self.enabled, value, self.scale)
+    }
+
+    fn round(enabled: bool, value: f32, step: f32) -> f32 {
+        if enabled && step > 0.0 {
+            (value / step).round() * step
+        } else {
+            value
+        }
+    }
+
+    /// Toggle and a menu with the increments, for a right to left toolbar
+    pub fn toolbar(&mut self, ui: &mut Ui) {
+        ui.menu_button("⏷", |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", t!("position")));
+                ui.add(
+                    DragValue::new(&mut self.translation)
+                        .speed(0.01)
+                        .range(0.001..=100.0),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", t!("rotation")));
+                ui.add(
+                    DragValue::new(&mut self.rotation)
+                        .speed(0.5)
+                        .range(0.1..=180.0)
+                        .suffix("°"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", t!("scale")));
+                ui.add(
+                    DragValue::new(&mut self.scale)
+                        .speed(0.01)
+                        .range(0.001..=100.0),
+                );
+            });
+        })
+        .response
+        .on_hover_text(t!("snap_increments_hint"));
+
+        ui.toggle_value(&mut self.enabled, t!("snap"))
+            .on_hover_text(t!("snap_hint"));
+    }
+}
+
+impl Default for Snap {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            translation: 0.5,
+            rotation: 15.0,
+            scale: 0.1,
+        }
+    }
+}
@@ -1,29 +1,98 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread::JoinHandle;
+use std::time::Duration;
 
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::Context as _;
 use egui::special_emojis::GITHUB;
 use egui::{
-    vec2, Align, Align2, Button, Color32, Frame, Layout, ProgressBar, RichText, Ui, Window,
+    vec2, Align, Align2, Button, Color32, ComboBox, DragValue, Frame, Grid, Layout, ProgressBar,
+    Rect, RichText, ScrollArea, Sense, Slider, Ui, Vec2, Window,
 };
 use egui_file::FileDialog;
+#[cfg(not(target_arch = "wasm32"))]
+use image::DynamicImage;
 use log::{info, warn};
 use rust_i18n::t;
 
-use crate::raytracer::render::Render;
-use crate::scene::Scene;
+use trayracer_core::profiler;
+use trayracer_core::raytracer::audit::AuditReport;
+#[cfg(not(target_arch = "wasm32"))]
+use trayracer_core::raytracer::benchmark::Benchmark;
+use trayracer_core::raytracer::benchmark::BenchmarkReport;
+use trayracer_core::raytracer::export::{DepthRange, ExportFormat, ExportOptions, Pass};
+use trayracer_core::raytracer::post::ToneMapping;
+use trayracer_core::raytracer::render::{Render, TileState};
+use trayracer_core::scene::Scene;
 
-use super::Tab;
+use super::keymap::{Action, Keymap};
+use super::{toasts, Tab};
 
+// windows shown from the status bar, not a state machine
+#[allow(clippy::struct_excessive_bools)]
 pub struct StatusBar {
     save_render_dialog: Option<FileDialog>,
+    /// Directory the save dialog starts in
+    pub export_directory: Option<PathBuf>,
+    pub export_options: ExportOptions,
+    /// Whether the export options window should be shown
+    show_export: bool,
+    /// Image of the render the export writes
+    pass: Pass,
+    /// Depths mapped to black and white when exporting the depth pass
+    depth_range: DepthRange,
+    /// Kept open, on Linux the copied image is only available while it exists
+    #[cfg(not(target_arch = "wasm32"))]
+    clipboard: Option<arboard::Clipboard>,
     /// Whether the about window should be shown
     show_about: bool,
+    /// Running benchmark thread
+    #[cfg(not(target_arch = "wasm32"))]
+    benchmark: Option<JoinHandle<BenchmarkReport>>,
+    /// Report of the last finished benchmark, shown in a window
+    benchmark_report: Option<BenchmarkReport>,
+    /// Report of the last material audit, shown in a window
+    audit_report: Option<AuditReport>,
+    /// Whether the render statistics window should be shown
+    show_stats: bool,
+    /// Whether the profiler window should be shown, scopes are only timed while it is
+    show_profiler: bool,
+    /// Whether the settings window should be shown
+    pub show_settings: bool,
 }
 
 impl StatusBar {
     pub const fn new() -> Self {
         Self {
             save_render_dialog: None,
+            export_directory: None,
+            export_options: ExportOptions {
+                format: ExportFormat::Png8,
+                jpeg_quality: 90,
+                tone_mapping: ToneMapping::Clip,
+                alpha: false,
+                id_mattes: false,
+            },
+            show_export: false,
+            pass: Pass::Beauty,
+            depth_range: DepthRange {
+                near: 0.0,
+                far: 10.0,
+            },
+            #[cfg(not(target_arch = "wasm32"))]
+            clipboard: None,
             show_about: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            benchmark: None,
+            benchmark_report: None,
+            audit_report: None,
+            show_stats: false,
+            show_profiler: false,
+            show_settings: false,
         }
     }
 
@@ -33,6 +102,7 @@ impl StatusBar {
         scene: Option<&mut Scene>,
         render: &mut Render,
         current_tab: &mut Tab,
+        keymap: &Keymap,
     ) {
         ui.horizontal(|ui| {
             ui.selectable_label(*current_tab == Tab::Preview, t!("preview"))
@@ -47,16 +117,207 @@ impl StatusBar {
                     *current_tab = Tab::RenderResult;
                 });
 
+            ui.selectable_label(*current_tab == Tab::Split, t!("split_view"))
+                .on_hover_text(t!("split_view_hint"))
+                .clicked()
+                .then(|| {
+                    *current_tab = Tab::Split;
+                });
+
             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                 self.about_us_button(ui);
-                self.export_button(ui, render);
-                Self::render_button(ui, render, scene, current_tab);
+                ui.toggle_value(
+                    &mut self.show_settings,
+                    RichText::new(t!("settings")).size(14.0),
+                );
+                self.export_button(ui, render, scene.as_deref());
+                // the web has no clipboard for images and no clock for the benchmark
+                #[cfg(not(target_arch = "wasm32"))]
+                self.copy_button(ui, render);
+                #[cfg(not(target_arch = "wasm32"))]
+                self.benchmark_button(ui, render, scene.as_deref());
+                self.audit_button(ui, scene.as_deref());
+                ui.toggle_value(&mut self.show_stats, RichText::new(t!("stats")).size(14.0))
+                    .on_hover_text(t!("stats_hint"));
+                ui.toggle_value(
+                    &mut self.show_profiler,
+                    RichText::new(t!("profiler")).size(14.0),
+                )
+                .on_hover_text(t!("profiler_hint"));
+                Self::render_button(ui, render, scene, current_tab, keymap);
                 Self::progress_bar(ui, render);
             });
             self.about_window(ui);
+            self.benchmark_window(ui);
+            self.audit_window(ui);
+            self.stats_window(ui, render);
+            self.profiler_window(ui);
         });
     }
 
+    fn audit_button(&mut self, ui: &mut Ui, scene: Option<&Scene>) {
+        ui.add_enabled_ui(scene.is_some(), |ui| {
+            ui.button(RichText::new(t!("audit")).size(14.0))
+                .on_hover_text(t!("audit_hint"))
+                .clicked()
+                .then(|| {
+                    if let Some(scene) = scene {
+                        let report = AuditReport::run(scene);
+                        info!("Material audit finished:\n{report}");
+                        self.audit_report = Some(report);
+                    }
+                });
+        });
+    }
+
+    fn audit_window(&mut self, ui: &Ui) {
+        let mut open = self.audit_report.is_some();
+
+        if let Some(report) = &self.audit_report {
+            Window::new(t!("audit"))
+                .collapsible(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    ScrollArea::vertical().show(ui, |ui| {
+                        ui.label(RichText::new(report.to_string()).monospace());
+                    });
+                });
+        }
+
+        if !open {
+            self.audit_report = None;
+        }
+    }
+
+    fn stats_window(&mut self, ui: &Ui, render: &mut Render) {
+        Window::new(t!("stats"))
+            .resizable(false)
+            .collapsible(false)
+            .open(&mut self.show_stats)
+            .show(ui.ctx(), |ui| {
+                ui.checkbox(&mut render.gather_stats, t!("gather_stats"))
+                    .on_hover_text(t!("gather_stats_hint"));
+
+                match &render.stats {
+                    Some(stats) => {
+                        ui.label(RichText::new(stats.to_string()).monospace());
+                    }
+                    None => {
+                        ui.label(t!("no_stats"));
+                    }
+                }
+            });
+    }
+
+    fn profiler_window(&mut self, ui: &Ui) {
+        profiler::set_enabled(self.show_profiler);
+
+        Window::new(t!("profiler"))
+            .collapsible(false)
+            .open(&mut self.show_profiler)
+            .show(ui.ctx(), |ui| {
+                if ui.button(t!("reset")).clicked() {
+                    profiler::reset();
+                }
+
+                let mut timings = profiler::timings();
+                if timings.is_empty() {
+                    ui.label(t!("profiler_empty"));
+                    return;
+                }
+                timings.sort_by_key(|timing| std::cmp::Reverse(timing.1.total));
+
+                Grid::new("profiler")
+                    .striped(true)
+                    .num_columns(6)
+                    .show(ui, |ui| {
+                        for header in [
+                            t!("profiler_scope"),
+                            t!("profiler_calls"),
+                            t!("profiler_last"),
+                            t!("profiler_mean"),
+                            t!("profiler_max"),
+                            t!("profiler_total"),
+                        ] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+
+                        for (name, timing) in timings {
+                            ui.label(name);
+                            ui.label(timing.calls.to_string());
+                            for duration in [timing.last, timing.mean(), timing.max, timing.total] {
+                                ui.label(RichText::new(format!("{duration:.2?}")).monospace());
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                // rendering and loading threads don't repaint the window
+                ui.ctx().request_repaint_after(Duration::from_millis(500));
+            });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn benchmark_button(&mut self, ui: &mut Ui, render: &Render, scene: Option<&Scene>) {
+        // collect the report once the benchmark thread has finished
+        if self.benchmark.as_ref().is_some_and(JoinHandle::is_finished) {
+            if let Some(handle) = self.benchmark.take() {
+                match handle.join() {
+                    Ok(report) => {
+                        info!("Benchmark finished:\n{report}");
+                        self.benchmark_report = Some(report);
+                    }
+                    Err(_) => warn!("Benchmark thread panicked"),
+                }
+            }
+        }
+
+        let running = self.benchmark.is_some();
+
+        ui.add_enabled_ui(
+            !running && render.thread.is_none() && scene.is_some(),
+            |ui| {
+                ui.button(RichText::new(t!("benchmark")).size(14.0))
+                    .on_hover_text(t!("benchmark_hint"))
+                    .clicked()
+                    .then(|| {
+                        if let Some(scene) = scene {
+                            let scene = scene.clone();
+                            let ctx = ui.ctx().clone();
+                            self.benchmark = Some(std::thread::spawn(move || {
+                                let report = Benchmark::default().run(&scene);
+                                ctx.request_repaint();
+                                report
+                            }));
+                        }
+                    });
+            },
+        );
+
+        if running {
+            ui.spinner();
+        }
+    }
+
+    fn benchmark_window(&mut self, ui: &Ui) {
+        let mut open = self.benchmark_report.is_some();
+
+        if let Some(report) = &self.benchmark_report {
+            Window::new(t!("benchmark"))
+                .resizable(false)
+                .collapsible(false)
+                .open(&mut open)
+                .show(ui.ctx(), |ui| {
+                    ui.label(RichText::new(report.to_string()).monospace());
+                });
+        }
+
+        if !open {
+            self.benchmark_report = None;
+        }
+    }
+
     pub fn about_us_button(&mut self, ui: &mut Ui) {
         ui.add(Button::new(" ? ").rounding(40.0))
             .clicked()
@@ -93,7 +354,7 @@ impl StatusBar {
             });
     }
 
-    pub fn export_button(&mut self, ui: &mut Ui, render: &Render) {
+    pub fn export_button(&mut self, ui: &mut Ui, render: &Render, scene: Option<&Scene>) {
         if ui
             .add_enabled(
                 render.progress.load(Ordering::Relaxed) == u16::MAX,
@@ -101,64 +362,344 @@ impl StatusBar {
             )
             .clicked()
         {
-            info!("Exporting image");
-            self.save_render_dialog
-                .get_or_insert_with(|| {
-                    let (x, y) = render.image.lock().dimensions();
-                    FileDialog::save_file(None)
-                        .default_filename(format!("render_{x}x{y}.png"))
-                        .filename_filter(Box::new(|name| {
-                            [".png", ".jpg", ".jpeg"]
-                                .into_iter()
-                                .any(|ext| name.ends_with(ext))
-                        }))
-                })
-                .open();
+            self.show_export = true;
         }
 
+        self.export_window(ui, render);
+
         if let Some(dialog) = self.save_render_dialog.as_mut() {
             if dialog.show(ui.ctx()).selected() {
                 match dialog.path() {
                     Some(path) => {
-                        log::info!("Saving image to {:?}", path);
-                        render.image.lock().save(path).unwrap_or_else(|e| {
-                            warn!("Failed to save image: {}", e);
+                        log::info!("Saving image to {}", path.display());
+                        self.export_directory = path.parent().map(Path::to_path_buf);
+                        let metadata = render.metadata(scene);
+                        match self.pass {
+                            Pass::Beauty => self
+                                .export_options
+                                .export(&render.raw.lock(), &render.post.read(), &metadata, path)
+                                .and_then(|()| {
+                                    if self.export_options.id_mattes {
+                                        ExportOptions::export_mattes(
+                                            &render.aovs.lock(),
+                                            &metadata,
+                                            path,
+                                        )
+                                    } else {
+                                        Ok(())
+                                    }
+                                }),
+                            Pass::Depth => self.depth_range.export(
+                                &render.aovs.lock().depth,
+                                self.export_options.format,
+                                &metadata,
+                                path,
+                            ),
+                            Pass::Lights => self.export_options.export_lights(
+                                &render.aovs.lock().lights,
+                                &render.post.read(),
+                                &metadata,
+                                path,
+                            ),
+                        }
+                        .unwrap_or_else(|e| {
+                            warn!("Failed to save image: {e:?}");
+                            toasts::error(
+                                ui.ctx(),
+                                t!("export_image_failed", error = format!("{e:#}")),
+                            );
                         });
                     }
                     None => {
                         warn!("Save dialog returned no path");
                     }
                 }
+                self.save_render_dialog = None;
+            }
+        }
+    }
+
+    /// Copy the post processed render to the system clipboard
+    #[cfg(not(target_arch = "wasm32"))]
+    fn copy_button(&mut self, ui: &mut Ui, render: &Render) {
+        if ui
+            .add_enabled(
+                render.progress.load(Ordering::Relaxed) == u16::MAX,
+                Button::new(RichText::new(t!("copy_image")).size(14.0)),
+            )
+            .on_hover_text(t!("copy_image_hint"))
+            .clicked()
+        {
+            if let Err(e) = self.copy_image(render) {
+                warn!("Failed to copy image: {e:?}");
+                toasts::error(ui.ctx(), t!("copy_image_failed", error = format!("{e:#}")));
             }
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn copy_image(&mut self, render: &Render) -> anyhow::Result<()> {
+        let image = DynamicImage::ImageRgb8(render.image.lock().clone()).to_rgba8();
+
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => self
+                .clipboard
+                .insert(arboard::Clipboard::new().context("Failed to open the clipboard")?),
+        };
+        clipboard
+            .set_image(arboard::ImageData {
+                width: image.width() as usize,
+                height: image.height() as usize,
+                bytes: Cow::Owned(image.into_raw()),
+            })
+            .context("Failed to write the image to the clipboard")?;
+
+        info!("Copied image to the clipboard");
+        Ok(())
+    }
+
+    /// Pass, format, quality and tone mapping of the export, then the file to save to
+    fn export_window(&mut self, ui: &Ui, render: &Render) {
+        let mut save = false;
+        let options = &mut self.export_options;
+        let pass = &mut self.pass;
+        let depth_range = &mut self.depth_range;
+
+        Window::new(t!("export"))
+            .resizable(false)
+            .collapsible(false)
+            .open(&mut self.show_export)
+            .show(ui.ctx(), |ui| {
+                ComboBox::from_label(t!("export_pass"))
+                    .selected_text(Self::pass_name(*pass))
+                    .show_ui(ui, |ui| {
+                        for p in Pass::ALL {
+                            ui.selectable_value(pass, p, Self::pass_name(p));
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("export_pass_hint"));
+
+                if *pass == Pass::Depth && !options.format.has_depth() {
+                    options.format = ExportFormat::Png16;
+                }
+
+                ComboBox::from_label(t!("export_format"))
+                    .selected_text(Self::format_name(options.format))
+                    .show_ui(ui, |ui| {
+                        for format in ExportFormat::ALL {
+                            if *pass == Pass::Depth && !format.has_depth() {
+                                continue;
+                            }
+                            ui.selectable_value(
+                                &mut options.format,
+                                format,
+                                Self::format_name(format),
+                            );
+                        }
+                    });
+
+                if *pass == Pass::Depth {
+                    Self::depth_range(ui, depth_range, render);
+                    ui.separator();
+                    save = ui.button(t!("export_save")).clicked();
+                    return;
+                }
+                if *pass == Pass::Lights {
+                    ui.label(t!("export_lights_hint"));
+                }
+
+                match options.format {
+                    ExportFormat::Jpeg => {
+                        ui.add(
+                            Slider::new(&mut options.jpeg_quality, 1..=100)
+                                .text(t!("jpeg_quality")),
+                        );
+                    }
+                    ExportFormat::Exr => {
+                        ui.label(t!("exr_hint"));
+                    }
+                    ExportFormat::Png8 | ExportFormat::Png16 => {}
+                }
+
+                ui.add_enabled_ui(options.format != ExportFormat::Exr, |ui| {
+                    ComboBox::from_label(t!("tone_mapping"))
+                        .selected_text(Self::tone_mapping_name(options.tone_mapping))
+                        .show_ui(ui, |ui| {
+                            for tone_mapping in ToneMapping::ALL {
+                                ui.selectable_value(
+                                    &mut options.tone_mapping,
+                                    tone_mapping,
+                                    Self::tone_mapping_name(tone_mapping),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(t!("tone_mapping_hint"));
+                });
+
+                ui.add_enabled_ui(options.format.has_alpha(), |ui| {
+                    ui.checkbox(&mut options.alpha, t!("export_alpha"))
+                        .on_hover_text(t!("export_alpha_hint"));
+                });
+
+                if *pass == Pass::Beauty {
+                    ui.checkbox(&mut options.id_mattes, t!("export_id_mattes"))
+                        .on_hover_text(t!("export_id_mattes_hint"));
+                }
+
+                ui.separator();
+                save = ui.button(t!("export_save")).clicked();
+            });
+
+        if save {
+            info!("Exporting image");
+            self.show_export = false;
+
+            let (x, y) = render.image.lock().dimensions();
+            let extension = self.export_options.format.extension();
+            let name = match self.pass {
+                Pass::Beauty => "render",
+                Pass::Depth => "depth",
+                Pass::Lights => "lights",
+            };
+            let mut dialog = FileDialog::save_file(self.export_directory.clone())
+                .default_filename(format!("{name}_{x}x{y}.{extension}"))
+                .filename_filter(Box::new(move |name| name.ends_with(extension)));
+            dialog.open();
+            self.save_render_dialog = Some(dialog);
+        }
+    }
+
+    /// Near and far depth of the depth pass, fitted to the rendered depths on request
+    fn depth_range(ui: &mut Ui, range: &mut DepthRange, render: &Render) {
+        ui.horizontal(|ui| {
+            ui.label(t!("depth_near"));
+            ui.add(
+                DragValue::new(&mut range.near)
+                    .speed(0.1)
+                    .range(0.0..=range.far),
+            );
+            ui.label(t!("depth_far"));
+            ui.add(
+                DragValue::new(&mut range.far)
+                    .speed(0.1)
+                    .range(range.near..=f32::INFINITY),
+            );
+            if ui
+                .button(t!("depth_fit"))
+                .on_hover_text(t!("depth_fit_hint"))
+                .clicked()
+            {
+                let fitted = DepthRange::fit(&render.aovs.lock().depth);
+                match fitted {
+                    Some(fitted) => *range = fitted,
+                    None => toasts::warning(ui.ctx(), t!("depth_fit_failed")),
+                }
+            }
+        })
+        .response
+        .on_hover_text(t!("depth_range_hint"));
+    }
+
+    fn pass_name(pass: Pass) -> String {
+        match pass {
+            Pass::Beauty => t!("pass_beauty"),
+            Pass::Depth => t!("pass_depth"),
+            Pass::Lights => t!("pass_lights"),
+        }
+        .to_string()
+    }
+
+    fn format_name(format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Png8 => t!("png_8"),
+            ExportFormat::Png16 => t!("png_16"),
+            ExportFormat::Jpeg => t!("jpeg"),
+            ExportFormat::Exr => t!("exr"),
+        }
+        .to_string()
+    }
+
+    fn tone_mapping_name(tone_mapping: ToneMapping) -> String {
+        match tone_mapping {
+            ToneMapping::Clip => t!("tone_mapping_clip"),
+            ToneMapping::Reinhard => t!("tone_mapping_reinhard"),
+            ToneMapping::Aces => t!("tone_mapping_aces"),
+        }
+        .to_string()
+    }
+
+    /// Start or cancel the render, with a context menu showing the shortcuts
     pub fn render_button(
         ui: &mut Ui,
         render: &mut Render,
         scene: Option<&mut Scene>,
         current_tab: &mut Tab,
+        keymap: &Keymap,
     ) {
-        if render.thread.is_some() {
-            ui.button(t!("cancel")).clicked().then(|| {
-                render.cancel.store(true, Ordering::Relaxed);
-            });
+        let rendering = render.thread.is_some();
+        let render_shortcut = keymap.label(ui.ctx(), Action::Render);
+        let cancel_shortcut = format!("{} / Esc", keymap.label(ui.ctx(), Action::CancelRender));
+
+        let response = if rendering {
+            ui.button(t!("cancel")).on_hover_text(&cancel_shortcut)
         } else {
-            ui.add_enabled_ui(render.thread.is_none() && scene.is_some(), |ui| {
-                ui.button(RichText::new(t!("render")).size(14.0))
-                    .clicked()
-                    .then(|| {
-                        if let Some(scene) = scene {
-                            render.render(ui.ctx().clone(), scene);
-                            *current_tab = Tab::RenderResult;
-                        }
-                    })
-            });
+            ui.add_enabled(
+                scene.is_some(),
+                Button::new(RichText::new(t!("render")).size(14.0)),
+            )
+            .on_hover_text(&render_shortcut)
+        };
+        let mut start = !rendering && response.clicked();
+        let mut cancel = rendering && response.clicked();
+
+        response.context_menu(|ui| {
+            if ui
+                .add_enabled(
+                    !rendering && scene.is_some(),
+                    Button::new(t!("render")).shortcut_text(&render_shortcut),
+                )
+                .clicked()
+            {
+                start = true;
+                ui.close_menu();
+            }
+            if ui
+                .add_enabled(
+                    rendering,
+                    Button::new(t!("cancel_render")).shortcut_text(&cancel_shortcut),
+                )
+                .clicked()
+            {
+                cancel = true;
+                ui.close_menu();
+            }
+        });
+
+        if cancel {
+            render.cancel.store(true, Ordering::Relaxed);
+        }
+        if start {
+            if let Some(scene) = scene {
+                Self::start_render(render, scene, current_tab);
+            }
+        }
+    }
+
+    /// Render the scene and show the result
+    pub fn start_render(render: &mut Render, scene: &Scene, current_tab: &mut Tab) {
+        render.render(scene);
+        // the split view already shows the result
+        if *current_tab != Tab::Split {
+            *current_tab = Tab::RenderResult;
         }
     }
 
     pub fn progress_bar(ui: &mut Ui, render: &Render) {
         let progress = f32::from(render.progress.load(Ordering::Relaxed)) / f32::from(u16::MAX);
+        Self::tile_minimap(ui, render);
         ui.add(
             ProgressBar::new(progress)
                 .desired_width(ui.available_width() / 3.0)
@@ -184,4 +725,42 @@ impl StatusBar {
 
         ui.label(t!("render_progress"));
     }
+
+    /// Grid of the blocks of a running render, colored by whether they are queued,
+    /// rendering or done
+    fn tile_minimap(ui: &mut Ui, render: &Render) {
+        if render.progress.load(Ordering::Relaxed) == u16::MAX {
+            return;
+        }
+
+        let tiles = render.tiles.lock().clone();
+        if tiles.states.is_empty() {
+            return;
+        }
+
+        let height = ui.spacing().interact_size.y;
+        let cell = height / tiles.rows as f32;
+        let (rect, response) =
+            ui.allocate_exact_size(vec2(cell * tiles.columns as f32, height), Sense::hover());
+
+        let painter = ui.painter_at(rect);
+        for (i, state) in tiles.states.iter().enumerate() {
+            let (x, y) = (i as u32 % tiles.columns, i as u32 / tiles.columns);
+            let color = match state {
+                TileState::Queued => ui.visuals().extreme_bg_color,
+                TileState::Rendering => Color32::YELLOW,
+                TileState::Done => Color32::BLUE,
+            };
+            painter.rect_filled(
+                Rect::from_min_size(
+                    rect.min + vec2(x as f32, y as f32) * cell,
+                    Vec2::splat(cell),
+                ),
+                0.0,
+                color,
+            );
+        }
+
+        response.on_hover_text(t!("tile_progress_hint"));
+    }
 }
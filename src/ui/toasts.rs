@@ -0,0 +1,142 @@
+use egui::{
+    hex_color, vec2, Align, Align2, Area, Color32, Context, Frame, Id, Layout, Order, RichText,
+    Sense,
+};
+use rust_i18n::t;
+use std::time::Duration;
+use trayracer_core::scene::SceneError;
+
+/// Seconds a toast is shown
+const DURATION: f64 = 8.0;
+/// Width of a toast in points
+const WIDTH: f32 = 320.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+struct Toast {
+    level: Level,
+    text: String,
+    /// Input time the toast was added at
+    created: f64,
+}
+
+fn id() -> Id {
+    Id::new("toasts")
+}
+
+fn push(ctx: &Context, level: Level, text: String) {
+    let created = ctx.input(|i| i.time);
+    ctx.data_mut(|d| {
+        d.get_temp_mut_or_default::<Vec<Toast>>(id()).push(Toast {
+            level,
+            text,
+            created,
+        });
+    });
+    ctx.request_repaint();
+}
+
+/// Tell the user about a failure that would otherwise only be logged
+pub fn error(ctx: &Context, text: impl Into<String>) {
+    push(ctx, Level::Error, text.into());
+}
+
+/// Tell the user about a problem that doesn't stop the current action
+pub fn warning(ctx: &Context, text: impl Into<String>) {
+    push(ctx, Level::Warning, text.into());
+}
+
+/// Tell the user why a scene couldn't be opened and how to fix it
+pub fn open_failed(ctx: &Context, err: &anyhow::Error) {
+    let Some(e) = err.downcast_ref::<SceneError>() else {
+        error(ctx, t!("open_scene_failed", error = format!("{err:#}")));
+        return;
+    };
+
+    let name = e.path().display();
+    let text = match e {
+        SceneError::MissingFile { .. } => t!("scene_error_missing_file", name = name),
+        SceneError::Malformed {
+            line: Some(line),
+            message,
+            ..
+        } => t!(
+            "scene_error_malformed_line",
+            name = name,
+            line = line,
+            error = message
+        ),
+        SceneError::Malformed { message, .. } => {
+            t!("scene_error_malformed", name = name, error = message)
+        }
+        SceneError::Model { message, .. } => t!("scene_error_model", name = name, error = message),
+        SceneError::UnsupportedTexture { message, .. } => {
+            t!(
+                "scene_error_unsupported_texture",
+                name = name,
+                error = message
+            )
+        }
+        SceneError::UnknownMaterial {
+            object, material, ..
+        } => t!(
+            "scene_error_unknown_material",
+            name = name,
+            object = object,
+            material = material
+        ),
+    };
+    error(ctx, text);
+}
+
+/// Stack the toasts in the bottom right corner, they disappear after a while or when clicked
+pub fn show(ctx: &Context) {
+    let now = ctx.input(|i| i.time);
+    let mut toasts = ctx
+        .data(|d| d.get_temp::<Vec<Toast>>(id()))
+        .unwrap_or_default();
+    toasts.retain(|t| now - t.created < DURATION);
+
+    if toasts.is_empty() {
+        ctx.data_mut(|d| d.remove::<Vec<Toast>>(id()));
+        return;
+    }
+
+    Area::new(id())
+        .order(Order::Foreground)
+        .anchor(Align2::RIGHT_BOTTOM, vec2(-10.0, -30.0))
+        .show(ctx, |ui| {
+            ui.with_layout(Layout::bottom_up(Align::Max), |ui| {
+                // the oldest toast at the bottom
+                toasts.retain(|toast| {
+                    let (icon, color) = match toast.level {
+                        Level::Error => ("⛔", hex_color!("#cc0000")),
+                        Level::Warning => ("⚠", Color32::YELLOW),
+                    };
+
+                    let response = Frame::popup(ui.style())
+                        .show(ui, |ui| {
+                            ui.set_max_width(WIDTH);
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(RichText::new(icon).color(color));
+                                ui.label(&toast.text);
+                            });
+                        })
+                        .response
+                        .interact(Sense::click())
+                        .on_hover_text(t!("dismiss_hint"));
+
+                    !response.clicked()
+                });
+            });
+        });
+
+    ctx.data_mut(|d| d.insert_temp(id(), toasts));
+    // expire the toasts without other input
+    ctx.request_repaint_after(Duration::from_secs(1));
+}
@@ -0,0 +1,345 @@
+use super::toasts;
+use log::{debug, info, warn};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use rust_i18n::t;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+    time::{Duration, Instant},
+};
+use trayracer_core::scene::{model_files, Import, Object, Scene, Skybox};
+
+/// Wait for this long after the last change before reloading,
+/// editors often write a file in several steps
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watches the loaded scene file, its models, materials, textures, material library and skybox
+/// and reloads what changed on disk
+pub struct SceneWatcher {
+    watcher: Option<RecommendedWatcher>,
+    events: Receiver<notify::Result<Event>>,
+    /// Scene, model and skybox paths the watched files were collected for,
+    /// reading the referenced files of the models is too slow for every frame
+    sources: Option<Vec<PathBuf>>,
+    /// Watched files and the directories they are watched through
+    files: HashSet<PathBuf>,
+    directories: HashSet<PathBuf>,
+    /// Changed files waiting for the debounce to pass
+    changed: HashSet<PathBuf>,
+    last_change: Instant,
+}
+
+/// Absolute path without following a possibly removed file itself
+fn normalize(path: &Path) -> PathBuf {
+    path.parent()
+        .and_then(|p| {
+            let p = if p.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                p
+            };
+            p.canonicalize().ok()
+        })
+        .zip(path.file_name())
+        .map_or_else(|| path.to_path_buf(), |(p, name)| p.join(name))
+}
+
+/// All files of an object, absolute
+fn object_files(scene_directory: &Path, object: &Object) -> Vec<PathBuf> {
+    if object.path.as_os_str().is_empty() {
+        return Vec::new();
+    }
+
+    let model = scene_directory.join(&object.path);
+    let directory = model
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .to_path_buf();
+
+    model_files(&model)
+        .into_iter()
+        .map(|file| normalize(&directory.join(file)))
+        .collect()
+}
+
+impl SceneWatcher {
+    pub fn new() -> Self {
+        let (sender, events) = channel();
+
+        let watcher = notify::recommended_watcher(move |event| {
+            sender.send(event).ok();
+        })
+        .map_err(|e| warn!("Failed to create file watcher, hot reload is disabled: {e}"))
+        .ok();
+
+        Self {
+            watcher,
+            events,
+            sources: None,
+            files: HashSet::new(),
+            directories: HashSet::new(),
+            changed: HashSet::new(),
+            last_change: Instant::now(),
+        }
+    }
+
+    /// Watch the directories of all files of the scene, only changes if the files changed
+    fn watch(&mut self, scene: Option<&Scene>) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+
+        let sources = scene.map(|scene| {
+            let mut sources = vec![scene.path.clone()];
+            sources.extend(scene.objects.iter().map(|o| o.path.clone()));
+            if let Skybox::Image { path, .. } = &scene.settings.skybox {
+                sources.push(path.clone());
+            }
+            sources.extend(scene.material_library.clone());
+            sources
+        });
+
+        if sources == self.sources {
+            return;
+        }
+        self.sources = sources;
+
+        let files = scene
+            .map(|scene| {
+                let scene_directory = scene.path.parent().unwrap_or_else(|| Path::new(""));
+
+                let mut files = scene
+                    .objects
+                    .iter()
+                    .flat_map(|o| object_files(scene_directory, o))
+                    .collect::<HashSet<_>>();
+                files.insert(normalize(&scene.path));
+                if let Skybox::Image { path, .. } = &scene.settings.skybox {
+                    files.insert(normalize(path));
+                }
+                if let Some(library) = &scene.material_library {
+                    files.insert(normalize(&scene_directory.join(library)));
+                }
+                files
+            })
+            .unwrap_or_default();
+
+        if files == self.files {
+            return;
+        }
+
+        // watch directories instead of files to notice files replaced by editors
+        let directories = files
+            .iter()
+            .filter_map(|f| f.parent().map(Path::to_path_buf))
+            .collect::<HashSet<_>>();
+
+        for directory in self.directories.difference(&directories) {
+            watcher.unwatch(directory).ok();
+        }
+        for directory in directories.difference(&self.directories) {
+            watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .unwrap_or_else(|e| warn!("Failed to watch {}: {}", directory.display(), e));
+        }
+
+        debug!(
+            "Watching {} files in {} directories",
+            files.len(),
+            directories.len()
+        );
+
+        self.files = files;
+        self.directories = directories;
+    }
+
+    /// Collect file changes and reload the affected parts of the scene
+    pub fn update(&mut self, ctx: &egui::Context, scene: &mut Option<Scene>) {
+        self.watch(scene.as_ref());
+
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Ok(event) if event.kind.is_create() || event.kind.is_modify() => {
+                    let changed = event
+                        .paths
+                        .iter()
+                        .map(|p| normalize(p))
+                        .filter(|p| self.files.contains(p))
+                        .collect::<Vec<_>>();
+
+                    if !changed.is_empty() {
+                        self.changed.extend(changed);
+                        self.last_change = Instant::now();
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("File watcher error: {e}"),
+            }
+        }
+
+        if self.changed.is_empty() {
+            return;
+        }
+
+        if self.last_change.elapsed() < DEBOUNCE {
+            ctx.request_repaint_after(DEBOUNCE);
+            return;
+        }
+
+        let changed = std::mem::take(&mut self.changed);
+        // materials may reference other files now
+        self.sources = None;
+
+        if let Some(s) = scene.as_mut() {
+            if changed.contains(&normalize(&s.path)) {
+                Self::reload_scene(ctx, s);
+            } else {
+                Self::reload_assets(ctx, s, &changed);
+            }
+        }
+
+        ctx.request_repaint();
+    }
+
+    /// Reload the scene file unless it matches the open scene, e.g. after saving it
+    fn reload_scene(ctx: &egui::Context, scene: &mut Scene) {
+        let unchanged = std::fs::read_to_string(&scene.path).is_ok_and(|content| {
+            [scene.to_yaml(), scene.to_json()]
+                .into_iter()
+                .flatten()
+                .any(|s| s == content)
+        });
+        if unchanged {
+            return;
+        }
+
+        info!("Scene file changed, reloading {}", scene.path.display());
+        match Scene::load(&scene.path) {
+            Ok(s) => *scene = s,
+            Err(e) => {
+                warn!("Failed to reload scene: {e:?}");
+                toasts::error(
+                    ctx,
+                    t!(
+                        "reload_failed",
+                        name = scene.path.display(),
+                        error = format!("{e:#}")
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Reload changed objects and the skybox, keeping names, transforms and other edits
+    fn reload_assets(ctx: &egui::Context, scene: &mut Scene, changed: &HashSet<PathBuf>) {
+        let scene_directory = scene
+            .path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
+
+        let library = scene.library();
+
+        // models shared by several objects are only loaded once
+        let mut loaded = HashMap::<PathBuf, Option<Object>>::new();
+
+        for object in &mut scene.objects {
+            if !object_files(&scene_directory, object)
+                .iter()
+                .any(|f| changed.contains(f))
+            {
+                continue;
+            }
+
+            let model = loaded
+                .entry(object.path.clone())
+                .or_insert_with(|| {
+                    info!("Reloading {}", object.path.display());
+                    Object::load(
+                        scene_directory.join(&object.path),
+                        Import::default(),
+                        object.translation,
+                        object.rotation,
+                        object.scale,
+                    )
+                    .map_err(|e| {
+                        warn!("Failed to reload object: {e:?}");
+                        toasts::error(
+                            ctx,
+                            t!(
+                                "reload_failed",
+                                name = object.path.display(),
+                                error = format!("{e:#}")
+                            ),
+                        );
+                    })
+                    .ok()
+                })
+                .as_ref();
+
+            if let Some(model) = model {
+                let mut reloaded =
+                    model.instance(object.translation, object.rotation, object.scale);
+                reloaded.id = object.id;
+                reloaded.path.clone_from(&object.path);
+                reloaded.name.clone_from(&object.name);
+                reloaded.parent = object.parent;
+                reloaded.set_import(object.import);
+                reloaded.material_overrides = std::mem::take(&mut object.material_overrides);
+                reloaded.material = object.material.take();
+                reloaded.apply_materials(
+                    reloaded.material.as_ref().and_then(|m| library.get(m)),
+                    &scene_directory,
+                );
+                *object = reloaded;
+            }
+        }
+
+        scene.update_transforms();
+
+        let library = scene
+            .material_library
+            .as_ref()
+            .map(|l| normalize(&scene_directory.join(l)));
+
+        if let Some(library) = library.filter(|l| changed.contains(l)) {
+            info!("Reloading material library");
+            scene.load_material_library().unwrap_or_else(|e| {
+                warn!("Failed to reload material library: {e:?}");
+                toasts::error(
+                    ctx,
+                    t!(
+                        "reload_failed",
+                        name = library.display(),
+                        error = format!("{e:#}")
+                    ),
+                );
+            });
+            scene.apply_materials();
+        }
+
+        let skybox = match &scene.settings.skybox {
+            Skybox::Image { path, .. } if changed.contains(&normalize(path)) => Some(path.clone()),
+            _ => None,
+        };
+
+        if let Some(path) = skybox {
+            info!("Reloading skybox {}", path.display());
+            match Skybox::load_from_path(&path) {
+                Ok(skybox) => scene.settings.skybox = skybox,
+                Err(e) => {
+                    warn!("Failed to reload skybox: {e:?}");
+                    toasts::error(
+                        ctx,
+                        t!(
+                            "reload_failed",
+                            name = path.display(),
+                            error = format!("{e:#}")
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
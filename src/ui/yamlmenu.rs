@@ -1,35 +1,98 @@
-use crate::scene::{Camera, Scene, Settings};
-use anyhow::Context;
-use egui::{hex_color, include_image, Align, ImageButton, Layout, RichText, Ui};
+#[cfg(not(target_arch = "wasm32"))]
+use super::autosave;
+use super::toasts;
+use egui::{
+    hex_color, include_image, vec2, Align, Context, Image, ImageButton, Layout, RichText, Ui,
+};
 use egui_file::FileDialog;
 use log::{info, warn};
 use rust_i18n::t;
-use std::{fs, path::Path};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use trayracer_core::scene::{is_scene_file, Camera, Scene, Settings, Template};
+
+/// File formats the scene can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Gltf,
+    /// All objects merged into a single OBJ with MTL
+    Obj,
+    /// Scene with all referenced files in a zip
+    Bundle,
+}
+
+impl ExportFormat {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Gltf => "glb",
+            Self::Obj => "obj",
+            Self::Bundle => "zip",
+        }
+    }
+}
+
+/// Recently opened scenes, persisted between sessions
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RecentScenes {
+    /// Most recent first
+    pub paths: Vec<PathBuf>,
+    /// Open the most recent scene on launch
+    pub open_last: bool,
+}
+
+impl RecentScenes {
+    pub const STORAGE_KEY: &'static str = "recent_scenes";
+    const MAX: usize = 10;
+
+    /// Move the path to the front of the list
+    pub fn add(&mut self, path: &Path) {
+        if self.paths.first().is_some_and(|p| p == path) {
+            return;
+        }
+
+        self.paths.retain(|p| p != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(Self::MAX);
+    }
+}
 
 pub struct YamlMenu {
     pub open_yaml_dialog: Option<FileDialog>,
-    create_yaml_dialog: Option<FileDialog>,
+    /// Dialog to create a new scene, empty or from a template
+    create_yaml_dialog: Option<(Option<Template>, FileDialog)>,
+    /// Dialog to export the scene in the chosen format
+    export_dialog: Option<(ExportFormat, FileDialog)>,
+    pub recent: RecentScenes,
 }
 
 impl YamlMenu {
-    pub const fn new() -> Self {
+    pub const fn new(recent: RecentScenes) -> Self {
         Self {
             open_yaml_dialog: None,
             create_yaml_dialog: None,
+            export_dialog: None,
+            recent,
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     pub fn show(&mut self, scene: &mut Option<Scene>, ui: &mut Ui) {
         // show open yaml dialog if present
         if let Some(d) = self.open_yaml_dialog.as_mut() {
             if d.show(ui.ctx()).selected() {
                 if let Some(p) = d.path() {
                     info!("Loading scene from {}", p.display());
-                    Scene::load(p)
+                    Scene::open(p)
                         .map_err(|e| {
-                            warn!("{}", e);
+                            warn!("{e}");
+                            toasts::open_failed(ui.ctx(), &e);
                         })
                         .map(|s| {
+                            // the file that was picked, for bundles the zip and not the extraction
+                            self.recent.add(p);
                             scene.replace(s);
                         })
                         .ok();
@@ -42,20 +105,43 @@ impl YamlMenu {
         }
 
         // show create yaml dialog if present
-        if let Some(d) = self.create_yaml_dialog.as_mut() {
+        if let Some((template, d)) = self.create_yaml_dialog.as_mut() {
             if d.show(ui.ctx()).selected() {
                 match d.path() {
                     Some(p) => {
-                        info!("Created new scene at {}", p.display());
-                        scene.replace(Scene {
-                            path: p.to_path_buf(),
-                            objects: vec![],
-                            lights: vec![],
-                            camera: Camera::default(),
-                            settings: Settings::default(),
-                        });
+                        let created = template.map_or_else(
+                            || {
+                                Ok(Scene {
+                                    path: p.to_path_buf(),
+                                    objects: vec![],
+                                    groups: vec![],
+                                    lights: vec![],
+                                    cameras: vec![Camera::default()],
+                                    active_camera: 0,
+                                    settings: Settings::default(),
+                                    materials: BTreeMap::new(),
+                                    material_library: None,
+                                    library_materials: BTreeMap::new(),
+                                    solo_light: None,
+                                })
+                            },
+                            |t| t.create(p),
+                        );
 
-                        Self::save_scene(scene.as_ref());
+                        match created {
+                            Ok(s) => {
+                                info!("Created new scene at {}", p.display());
+                                scene.replace(s);
+                                self.save_scene(ui.ctx(), scene.as_ref());
+                            }
+                            Err(e) => {
+                                warn!("Failed to create scene: {e:?}");
+                                toasts::error(
+                                    ui.ctx(),
+                                    t!("create_scene_failed", error = format!("{e:#}")),
+                                );
+                            }
+                        }
                     }
                     None => {
                         warn!("Create yaml dialog selected but returned no path");
@@ -66,6 +152,34 @@ impl YamlMenu {
             }
         }
 
+        // show export dialog if present
+        if let Some((format, d)) = self.export_dialog.as_mut() {
+            if d.show(ui.ctx()).selected() {
+                match (d.path(), scene.as_ref()) {
+                    (Some(p), Some(s)) => {
+                        info!("Exporting scene to {}", p.display());
+                        match format {
+                            ExportFormat::Gltf => s.export_gltf(p),
+                            ExportFormat::Obj => s.export_obj(p),
+                            ExportFormat::Bundle => s.export_bundle(p),
+                        }
+                        .unwrap_or_else(|e| {
+                            warn!("Failed to export scene: {e:?}");
+                            toasts::error(
+                                ui.ctx(),
+                                t!("export_scene_failed", error = format!("{e:#}")),
+                            );
+                        });
+                    }
+                    _ => {
+                        warn!("Export dialog selected but returned no path");
+                    }
+                }
+
+                self.export_dialog = None;
+            }
+        }
+
         ui.horizontal(|ui| {
             ui.heading(t!("yaml"));
             self.buttons(scene, ui);
@@ -80,6 +194,49 @@ impl YamlMenu {
                 None => ui.label(t!("no_scene_loaded")),
             });
         });
+
+        self.recent_scenes(scene, ui);
+    }
+
+    fn recent_scenes(&mut self, scene: &mut Option<Scene>, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!self.recent.paths.is_empty(), |ui| {
+                ui.menu_button(t!("recent_scenes"), |ui| {
+                    let mut open = None;
+
+                    for path in &self.recent.paths {
+                        if ui.button(path.display().to_string()).clicked() {
+                            open = Some(path.clone());
+                            ui.close_menu();
+                        }
+                    }
+
+                    ui.separator();
+                    if ui.button(t!("clear_recent_scenes")).clicked() {
+                        self.recent.paths.clear();
+                        ui.close_menu();
+                    }
+
+                    if let Some(path) = open {
+                        info!("Loading recent scene from {}", path.display());
+                        match Scene::open(&path) {
+                            Ok(s) => {
+                                self.recent.add(&path);
+                                scene.replace(s);
+                            }
+                            Err(e) => {
+                                warn!("{e}");
+                                toasts::open_failed(ui.ctx(), &e);
+                                // forget scenes that can't be opened anymore
+                                self.recent.paths.retain(|p| p != &path);
+                            }
+                        }
+                    }
+                });
+            });
+
+            ui.checkbox(&mut self.recent.open_last, t!("open_last_scene"));
+        });
     }
 
     pub fn load_scene(&mut self) {
@@ -89,9 +246,10 @@ impl YamlMenu {
             .is_some_and(egui_file::FileDialog::visible)
         {
             let mut dialog = FileDialog::open_file(None).filename_filter(Box::new(|p| {
-                Path::new(p)
-                    .extension()
-                    .map_or(false, |ext| ext.eq_ignore_ascii_case("yaml"))
+                let p = Path::new(p);
+                is_scene_file(p)
+                    || p.extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
             }));
 
             dialog.open();
@@ -100,21 +258,39 @@ impl YamlMenu {
         }
     }
 
-    pub fn create_scene(&mut self) {
+    pub fn create_scene(&mut self, template: Option<Template>) {
         if !self
             .create_yaml_dialog
             .as_ref()
-            .is_some_and(egui_file::FileDialog::visible)
+            .is_some_and(|(_, d)| d.visible())
         {
-            let mut dialog = FileDialog::save_file(None).filename_filter(Box::new(|p| {
+            let mut dialog = FileDialog::save_file(None)
+                .filename_filter(Box::new(|p| is_scene_file(Path::new(p))));
+            if let Some(t) = template {
+                dialog = dialog.default_filename(format!("{}.yaml", t.name()));
+            }
+
+            dialog.open();
+            self.create_yaml_dialog = Some((template, dialog));
+        }
+    }
+
+    fn export_scene(&mut self, scene: &Scene, format: ExportFormat) {
+        let name = scene
+            .path
+            .file_stem()
+            .map_or_else(|| "scene".to_string(), |s| s.to_string_lossy().to_string());
+
+        let mut dialog = FileDialog::save_file(None)
+            .default_filename(format!("{name}.{}", format.extension()))
+            .filename_filter(Box::new(move |p| {
                 Path::new(p)
                     .extension()
-                    .map_or(false, |ext| ext.eq_ignore_ascii_case("yaml"))
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(format.extension()))
             }));
 
-            dialog.open();
-            self.create_yaml_dialog = Some(dialog);
-        }
+        dialog.open();
+        self.export_dialog = Some((format, dialog));
     }
 
     fn buttons(&mut self, scene: &mut Option<Scene>, ui: &mut Ui) {
@@ -145,17 +321,62 @@ impl YamlMenu {
                 )
                 .on_hover_text(t!("save_scene"))
                 .clicked()
-                .then(|| Self::save_scene(scene.as_ref()));
+                .then(|| self.save_scene(ui.ctx(), scene.as_ref()));
             });
 
-            // new button
-            ui.add_sized(
-                [20.0, 20.0],
-                ImageButton::new(include_image!("../../res/icons/plus-solid.svg")).tint(tint_color),
+            // export menu
+            ui.add_enabled_ui(scene.is_some(), |ui| {
+                ui.menu_image_button(
+                    Image::new(include_image!("../../res/icons/file-export-solid.svg"))
+                        .tint(tint_color)
+                        .fit_to_exact_size(vec2(14.0, 14.0)),
+                    |ui| {
+                        for (format, label) in [
+                            (ExportFormat::Gltf, t!("export_gltf")),
+                            (ExportFormat::Obj, t!("export_obj")),
+                            (ExportFormat::Bundle, t!("export_bundle")),
+                        ] {
+                            if ui.button(label).clicked() {
+                                if let Some(scene) = scene.as_ref() {
+                                    self.export_scene(scene, format);
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                    },
+                )
+                .response
+                .on_hover_text(t!("export_scene"));
+            });
+
+            // new menu
+            ui.menu_image_button(
+                Image::new(include_image!("../../res/icons/plus-solid.svg"))
+                    .tint(tint_color)
+                    .fit_to_exact_size(vec2(14.0, 14.0)),
+                |ui| {
+                    if ui.button(t!("empty_scene")).clicked() {
+                        self.create_scene(None);
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+                    ui.label(t!("new_from_template"));
+                    for template in Template::ALL {
+                        let label = match template {
+                            Template::CornellBox => t!("template_cornell_box"),
+                            Template::ThreePointSphere => t!("template_three_point"),
+                            Template::MaterialGrid => t!("template_material_grid"),
+                        };
+                        if ui.button(label).clicked() {
+                            self.create_scene(Some(template));
+                            ui.close_menu();
+                        }
+                    }
+                },
             )
-            .on_hover_text(t!("new_scene"))
-            .clicked()
-            .then(|| self.create_scene());
+            .response
+            .on_hover_text(t!("new_scene"));
 
             // reload button
             ui.add_enabled_ui(scene.is_some(), |ui| {
@@ -174,7 +395,10 @@ impl YamlMenu {
                             Ok(s) => {
                                 scene.replace(s);
                             }
-                            Err(e) => warn!("{}", e),
+                            Err(e) => {
+                                warn!("{e}");
+                                toasts::open_failed(ui.ctx(), &e.into());
+                            }
                         }
                     }
                 });
@@ -182,17 +406,22 @@ impl YamlMenu {
         });
     }
 
-    fn save_scene(scene: Option<&Scene>) {
+    /// Save the scene to its path and remember it in the recent scenes
+    pub fn save_scene(&mut self, ctx: &Context, scene: Option<&Scene>) {
         match scene {
             Some(scene) => {
-                serde_yml::to_string(scene)
-                    .context("Failed to serialize scene")
-                    .and_then(|str| {
-                        fs::write(scene.path.as_path(), str).context("Failed to save config")
-                    })
-                    .unwrap_or_else(|e| {
-                        warn!("{}", e);
-                    });
+                match scene.save() {
+                    // the saved scene supersedes the autosave
+                    Ok(()) => {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        autosave::clear();
+                        self.recent.add(&scene.path);
+                    }
+                    Err(e) => {
+                        warn!("{e:?}");
+                        toasts::error(ctx, t!("save_scene_failed", error = format!("{e:#}")));
+                    }
+                }
             }
             None => {
                 warn!("save_scene called with no scene loaded");